@@ -514,7 +514,9 @@ pub mod testbed {
     pub mod bind_groups {
         #[derive(Debug)]
         pub struct WgpuBindGroupLayout0<'a> {
+            /// `@group(0) @binding(0) var color_texture: texture_2d<f32>`
             pub color_texture: &'a wgpu::TextureView,
+            /// `@group(0) @binding(1) var color_sampler: sampler`
             pub color_sampler: &'a wgpu::Sampler,
         }
         impl<'a> WgpuBindGroupLayout0<'a> {
@@ -586,6 +588,7 @@ pub mod testbed {
         }
         #[derive(Debug)]
         pub struct WgpuBindGroupLayout1<'a> {
+            /// `@group(1) @binding(0) var<uniform> uniforms: Uniforms`
             pub uniforms: wgpu::BufferBinding<'a>,
         }
         impl<'a> WgpuBindGroupLayout1<'a> {
@@ -643,13 +646,21 @@ pub mod testbed {
         }
         #[derive(Debug)]
         pub struct WgpuBindGroupLayout2<'a> {
+            /// `@group(2) @binding(1) var<storage, read> rts: rtsStruct`
             pub rts: wgpu::BufferBinding<'a>,
+            /// `@group(2) @binding(2) var<storage, read> a: Scalars`
             pub a: wgpu::BufferBinding<'a>,
+            /// `@group(2) @binding(3) var<storage, read> b: VectorsU32`
             pub b: wgpu::BufferBinding<'a>,
+            /// `@group(2) @binding(4) var<storage, read> c: VectorsI32`
             pub c: wgpu::BufferBinding<'a>,
+            /// `@group(2) @binding(5) var<storage, read> d: VectorsF32`
             pub d: wgpu::BufferBinding<'a>,
+            /// `@group(2) @binding(6) var<storage, read> f: MatricesF32`
             pub f: wgpu::BufferBinding<'a>,
+            /// `@group(2) @binding(8) var<storage, read> h: StaticArrays`
             pub h: wgpu::BufferBinding<'a>,
+            /// `@group(2) @binding(9) var<storage, read> i: Nested`
             pub i: wgpu::BufferBinding<'a>,
         }
         impl<'a> WgpuBindGroupLayout2<'a> {
@@ -1214,7 +1225,9 @@ pub mod triangle {
     pub mod bind_groups {
         #[derive(Debug)]
         pub struct WgpuBindGroupLayout0<'a> {
+            /// `@group(0) @binding(0) var color_texture: texture_2d<f32>`
             pub color_texture: &'a wgpu::TextureView,
+            /// `@group(0) @binding(1) var color_sampler: sampler`
             pub color_sampler: &'a wgpu::Sampler,
         }
         impl<'a> WgpuBindGroupLayout0<'a> {
@@ -1286,6 +1299,7 @@ pub mod triangle {
         }
         #[derive(Debug)]
         pub struct WgpuBindGroupLayout1<'a> {
+            /// `@group(1) @binding(0) var<uniform> uniforms: Uniforms`
             pub uniforms: wgpu::BufferBinding<'a>,
         }
         impl<'a> WgpuBindGroupLayout1<'a> {
@@ -9,10 +9,11 @@ use DependencyTreeError::*;
 
 use super::parse_imports::ImportStatement;
 use super::source_file::SourceFile;
+use super::textual_include::expand_includes;
 use super::ModulePathResolver;
 use crate::{
-  AdditionalScanDirectory, FxIndexMap, FxIndexSet, ImportPathPart, SourceFilePath,
-  SourceModuleName,
+  AdditionalScanDirectory, FastIndexMap, FxIndexMap, FxIndexSet, ImportPathPart,
+  SourceFilePath, SourceModuleName,
 };
 
 #[derive(Debug, Error, Diagnostic)]
@@ -31,6 +32,18 @@ pub enum DependencyTreeError {
     #[label("Import statement")]
     import_bit: SourceSpan,
   },
+  #[error("Cannot find included file `{path}`")]
+  IncludePathNotFound {
+    path: String,
+
+    #[source_code]
+    src: NamedSource<String>,
+
+    #[label("Include directive")]
+    include_bit: SourceSpan,
+  },
+  #[error("Circular include of `{path}`: {chain}")]
+  IncludeCycle { path: SourceFilePath, chain: String },
 }
 
 #[derive(Default)]
@@ -93,6 +106,12 @@ pub struct DependencyTree {
   resolver: ModulePathResolver,
   parsed_sources: FxIndexMap<SourceFilePath, SourceFile>,
   entry_points: FxIndexSet<SourceFilePath>,
+  /// Whether `#include "file.wgsl"` directives are expanded before parsing the imports.
+  textual_includes: bool,
+  /// The files pasted in by `#include` directives.
+  included_files: FxIndexSet<SourceFilePath>,
+  /// The sources which aren't read from the file system, keyed by their virtual path.
+  in_memory_sources: FxIndexMap<SourceFilePath, String>,
 }
 
 /// Represents a dependency tree for tracking the dependencies between source files.
@@ -113,6 +132,11 @@ impl DependencyTree {
   /// * `module_prefix` - An optional module prefix to be used when generating import paths.
   /// * `entry_points` - A vector of source file paths representing the entry points of the
   ///   dependency tree.
+  /// * `textual_includes` - Whether to expand `#include "file.wgsl"` directives.
+  /// * `import_aliases` - Module names mapped to their files, eg: `pbr::lighting` to
+  ///   `shaders/lib/lighting.wgsl`, which take precedence over the directory structure.
+  /// * `in_memory_sources` - Sources used instead of reading the file at their path.
+  ///   They can be entry points and be imported like the files on disk.
   ///
   /// # Returns
   ///
@@ -123,14 +147,21 @@ impl DependencyTree {
     entry_module_prefix: Option<String>,
     entry_points: Vec<SourceFilePath>, // path to entry points
     additional_scan_dirs: Vec<AdditionalScanDirectory>,
+    textual_includes: bool,
+    import_aliases: FastIndexMap<String, PathBuf>,
+    in_memory_sources: FxIndexMap<SourceFilePath, String>,
   ) -> Result<Self, DependencyTreeError> {
     let resolver =
-      ModulePathResolver::new(workspace_root, entry_module_prefix, additional_scan_dirs);
+      ModulePathResolver::new(workspace_root, entry_module_prefix, additional_scan_dirs)
+        .with_import_aliases(import_aliases);
 
     let mut tree = Self {
       resolver,
       parsed_sources: Default::default(),
       entry_points: Default::default(),
+      textual_includes,
+      included_files: Default::default(),
+      in_memory_sources,
     };
 
     for entry_point in entry_points {
@@ -153,7 +184,7 @@ impl DependencyTree {
       .resolver
       .generate_best_possible_paths(&import_path_part, parent_source_path)
       .into_iter()
-      .find(|(_, path)| path.is_file()); // make sure this is not reimporting itself
+      .find(|(_, path)| self.in_memory_sources.contains_key(path) || path.is_file()); // make sure this is not reimporting itself
 
     let Some(parent_source) = self.parsed_sources.get_mut(parent_source_path) else {
       unreachable!("{:?} source code as not parsed", parent_source_path)
@@ -198,9 +229,17 @@ impl DependencyTree {
     match self.parsed_sources.entry(source_path.clone()) {
       Entry::Occupied(_) => {} // do nothing
       Entry::Vacant(entry) => {
-        let content = entry.key().read_contents().or(Err(SourceNotFound {
-          path: entry.key().clone(),
-        }))?;
+        let content = match self.in_memory_sources.get(entry.key()) {
+          Some(content) => content.clone(),
+          None => entry.key().read_contents().or(Err(SourceNotFound {
+            path: entry.key().clone(),
+          }))?,
+        };
+        let content = if self.textual_includes {
+          expand_includes(entry.key(), &content, &mut self.included_files)?
+        } else {
+          content
+        };
 
         let source_file =
           SourceFile::create(entry.key().clone(), module_name.clone(), content);
@@ -224,9 +263,17 @@ impl DependencyTree {
     Ok(())
   }
 
-  /// Returns all the source files including their dependencies in the dependency tree.
+  /// Returns all the source files including their dependencies in the dependency tree,
+  /// except the in-memory sources which don't exist on disk.
   pub fn all_files_including_dependencies(&self) -> FxIndexSet<SourceFilePath> {
-    self.parsed_sources.keys().cloned().collect()
+    let mut files: FxIndexSet<_> = self
+      .parsed_sources
+      .keys()
+      .filter(|path| !self.in_memory_sources.contains_key(*path))
+      .cloned()
+      .collect();
+    files.extend(self.included_files.iter().cloned());
+    files
   }
 
   pub fn parsed_files(&self) -> Vec<&SourceFile> {
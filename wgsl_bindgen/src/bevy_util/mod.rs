@@ -3,6 +3,7 @@ mod module_path_resolver;
 mod name_demangle;
 pub mod parse_imports;
 pub mod source_file;
+mod textual_include;
 
 pub use deptree::*;
 use module_path_resolver::*;
@@ -1,21 +1,64 @@
 use std::path::{Path, PathBuf};
 
-use derive_more::Constructor;
 use smallvec::SmallVec;
 
 use super::escape_os_path;
 use crate::{
-  AdditionalScanDirectory, FxIndexSet, ImportPathPart, SourceFilePath, SourceModuleName,
+  AdditionalScanDirectory, FastIndexMap, FxIndexSet, ImportPathPart, SourceFilePath,
+  SourceModuleName,
 };
 
-#[derive(Debug, Constructor, Clone, Default)]
+#[derive(Debug, Clone, Default)]
 pub(crate) struct ModulePathResolver {
   workspace_root: PathBuf,
   entry_module_prefix: Option<String>,
   additional_scan_dirs: Vec<AdditionalScanDirectory>,
+  /// Module names mapped to their files, resolved before searching the directories.
+  import_aliases: FastIndexMap<String, PathBuf>,
 }
 
 impl ModulePathResolver {
+  pub fn new(
+    workspace_root: PathBuf,
+    entry_module_prefix: Option<String>,
+    additional_scan_dirs: Vec<AdditionalScanDirectory>,
+  ) -> Self {
+    Self {
+      workspace_root,
+      entry_module_prefix,
+      additional_scan_dirs,
+      import_aliases: Default::default(),
+    }
+  }
+
+  pub fn with_import_aliases(
+    mut self,
+    import_aliases: FastIndexMap<String, PathBuf>,
+  ) -> Self {
+    self.import_aliases = import_aliases;
+    self
+  }
+
+  /// The aliased module which is the longest prefix of the import, eg: `pbr::lighting`
+  /// for `pbr::lighting::light_color`.
+  fn aliased_path(
+    &self,
+    import_path_part: &ImportPathPart,
+  ) -> Option<(SourceModuleName, SourceFilePath)> {
+    self
+      .import_aliases
+      .iter()
+      .filter(|(module_name, _)| {
+        import_path_part
+          .strip_prefix(module_name.as_str())
+          .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+      })
+      .max_by_key(|(module_name, _)| module_name.len())
+      .map(|(module_name, path)| {
+        (SourceModuleName::new(module_name.as_str()), SourceFilePath::new(path))
+      })
+  }
+
   fn create_path(
     module_prefix: &Option<String>,
     root_dir: &Path,
@@ -92,19 +135,22 @@ impl ModulePathResolver {
 
     let source_dir = source_path.parent().unwrap_or(Path::new(""));
 
-    let mut paths = Self::generate_paths_for_dir(
-      &self.entry_module_prefix,
-      import_parts.clone(),
-      &self.workspace_root,
-      source_path,
-    )
-    .chain(Self::generate_paths_for_dir(
-      &self.entry_module_prefix,
-      import_parts.clone(),
-      &source_dir,
-      source_path,
-    ))
-    .collect::<FxIndexSet<_>>();
+    let mut paths = FxIndexSet::default();
+    paths.extend(self.aliased_path(import_path_part));
+    paths.extend(
+      Self::generate_paths_for_dir(
+        &self.entry_module_prefix,
+        import_parts.clone(),
+        &self.workspace_root,
+        source_path,
+      )
+      .chain(Self::generate_paths_for_dir(
+        &self.entry_module_prefix,
+        import_parts.clone(),
+        &source_dir,
+        source_path,
+      )),
+    );
 
     for scan_dir in &self.additional_scan_dirs {
       let scan_path = Path::new(&scan_dir.directory);
@@ -126,7 +172,7 @@ mod tests {
   use pretty_assertions::assert_eq;
 
   use crate::bevy_util::ModulePathResolver;
-  use crate::{ImportPathPart, SourceFilePath, SourceModuleName};
+  use crate::{FastIndexMap, ImportPathPart, SourceFilePath, SourceModuleName};
 
   #[test]
   fn should_generate_single_import_path() {
@@ -162,6 +208,28 @@ mod tests {
     assert_eq!(result, expected);
   }
 
+  #[test]
+  fn should_resolve_import_alias_first() {
+    let source_path = SourceFilePath::new("mydir/source.wgsl");
+    let import_path_part = ImportPathPart::new("pbr::lighting::light_color");
+
+    let aliases = FastIndexMap::from_iter([
+      ("pbr".to_string(), "vendor/pbr.wgsl".into()),
+      ("pbr::lighting".to_string(), "vendor/lib/lighting.wgsl".into()),
+    ]);
+    let result = ModulePathResolver::new("mydir".into(), None, vec![])
+      .with_import_aliases(aliases)
+      .generate_best_possible_paths(&import_path_part, &source_path);
+
+    assert_eq!(
+      result.first(),
+      Some(&(
+        SourceModuleName::new("pbr::lighting"),
+        SourceFilePath::new("vendor/lib/lighting.wgsl")
+      ))
+    );
+  }
+
   // Should generate import paths with correct extensions
   #[test]
   fn should_generate_import_paths_with_correct_extensions() {
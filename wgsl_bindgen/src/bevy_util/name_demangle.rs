@@ -51,8 +51,38 @@ fn undecorate_regex() -> &'static Regex {
 }
 
 // https://github.com/bevyengine/naga_oil/blob/master/src/compose/mod.rs#L417-L419
-fn decode(from: &str) -> String {
-  String::from_utf8(data_encoding::BASE32_NOPAD.decode(from.as_bytes()).unwrap()).unwrap()
+fn decode(from: &str) -> Option<String> {
+  let bytes = data_encoding::BASE32_NOPAD.decode(from.as_bytes()).ok()?;
+  String::from_utf8(bytes).ok()
+}
+
+/// Makes each part of a demangled module path a valid Rust identifier, so the path can be used
+/// for the generated modules, eg: `"my-lib::2d"` => `"my_lib::_2d"`.
+fn make_valid_rust_module_path(path: &str) -> String {
+  path
+    .split("::")
+    .map(|part| {
+      let mut part: String = part
+        .chars()
+        .map(|c| {
+          if c.is_alphanumeric() || c == '_' {
+            c
+          } else {
+            '_'
+          }
+        })
+        .collect();
+      if part.is_empty() || part.starts_with(|c: char| c.is_ascii_digit()) {
+        part.insert(0, '_');
+      }
+      if syn::parse_str::<syn::Ident>(&part).is_err() {
+        // Keywords such as `match` or `type`.
+        part.push('_');
+      }
+      part
+    })
+    .collect::<Vec<_>>()
+    .join("::")
 }
 
 pub fn escape_os_path(path: &str) -> String {
@@ -71,13 +101,23 @@ pub fn make_valid_rust_import(value: &str) -> String {
     .to_string()
 }
 
+/// The module path of the items of an imported module in the demangled names, eg:
+/// `"\"../types\""` => `"types"`.
+pub fn demangle_module_path(module_name: &str) -> String {
+  make_valid_rust_module_path(&make_valid_rust_import(module_name))
+}
+
 // https://github.com/bevyengine/naga_oil/blob/master/src/compose/mod.rs#L421-L431
+/// Names which can't be decoded are left mangled.
 pub fn demangle_str(string: &str) -> Cow<str> {
   undecorate_regex().replace_all(string, |caps: &regex::Captures| {
+    let Some(module_path) = decode(caps.get(3).unwrap().as_str()) else {
+      return caps.get(0).unwrap().as_str().to_owned();
+    };
     format!(
       "{}{}::{}",
       caps.get(1).map(|cc| cc.as_str()).unwrap_or(""),
-      make_valid_rust_import(&decode(caps.get(3).unwrap().as_str())),
+      demangle_module_path(&module_path),
       caps.get(2).unwrap().as_str()
     )
   })
@@ -87,6 +127,7 @@ pub fn demangle_str(string: &str) -> Cow<str> {
 mod tests {
   use pretty_assertions::assert_eq;
 
+  use super::{demangle_str, make_valid_rust_module_path};
   use crate::bevy_util::make_valid_rust_import;
   use crate::quote_gen::RustItemPath;
 
@@ -114,4 +155,17 @@ mod tests {
       }
     );
   }
+
+  #[test]
+  fn test_make_valid_rust_module_path() {
+    assert_eq!(make_valid_rust_module_path("imports::lighting"), "imports::lighting");
+    assert_eq!(make_valid_rust_module_path("my-lib::2d"), "my_lib::_2d");
+    assert_eq!(make_valid_rust_module_path("utils::match"), "utils::match_");
+  }
+
+  #[test]
+  fn test_demangle_invalid_encoding() {
+    let string = "PointLightX_naga_oil_mod_X1X";
+    assert_eq!(demangle_str(string), string);
+  }
 }
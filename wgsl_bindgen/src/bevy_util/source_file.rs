@@ -10,6 +10,8 @@ pub struct SourceFile {
   pub file_path: SourceFilePath,
   pub module_name: Option<SourceModuleName>,
   pub content: String,
+  /// The hash of `content`, used to find which files changed between generations.
+  pub content_hash: blake3::Hash,
   pub imports: SmallVec<[ImportStatement; 4]>,
   pub direct_dependencies: FxIndexSet<SourceFilePath>,
 }
@@ -24,6 +26,7 @@ impl SourceFile {
     let mut source = Self {
       file_path,
       module_name,
+      content_hash: blake3::hash(normalized_content.as_bytes()),
       content: normalized_content,
       imports: SmallVec::default(),
       direct_dependencies: FxIndexSet::default(),
@@ -0,0 +1,117 @@
+//! Expands `#include "file.wgsl"` directives by pasting the included file in their place,
+//! for shaders written with textual includes instead of naga_oil `#import` modules.
+
+use std::path::{Path, PathBuf};
+
+use miette::NamedSource;
+
+use super::DependencyTreeError;
+use crate::{FxIndexSet, SourceFilePath};
+
+const INCLUDE_DIRECTIVE: &str = "#include";
+
+struct IncludeExpander<'a> {
+  /// The files being expanded, to detect circular includes.
+  stack: Vec<(PathBuf, SourceFilePath)>,
+  included_files: &'a mut FxIndexSet<SourceFilePath>,
+}
+
+impl<'a> IncludeExpander<'a> {
+  fn expand(
+    &mut self,
+    source_path: &SourceFilePath,
+    content: &str,
+  ) -> Result<String, DependencyTreeError> {
+    let mut output = String::with_capacity(content.len());
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+      let line_offset = offset;
+      offset += line.len();
+
+      let Some(include_path) = parse_include(line) else {
+        output.push_str(line);
+        continue;
+      };
+
+      let dir = source_path.parent().unwrap_or(Path::new(""));
+      let included_path = SourceFilePath::new(dir.join(include_path));
+      let included_content = included_path.read_contents().map_err(|_| {
+        DependencyTreeError::IncludePathNotFound {
+          path: include_path.to_string(),
+          src: NamedSource::new(source_path.to_string(), content.to_string()),
+          include_bit: (line_offset, line.trim_end().len()).into(),
+        }
+      })?;
+
+      let canonical_path = std::fs::canonicalize(included_path.as_path())
+        .unwrap_or_else(|_| included_path.to_path_buf());
+      if self.stack.iter().any(|(path, _)| *path == canonical_path) {
+        let chain = self
+          .stack
+          .iter()
+          .map(|(_, path)| path.to_string())
+          .chain(std::iter::once(included_path.to_string()))
+          .collect::<Vec<_>>()
+          .join(" -> ");
+        return Err(DependencyTreeError::IncludeCycle {
+          path: included_path,
+          chain,
+        });
+      }
+
+      self.stack.push((canonical_path, included_path.clone()));
+      let expanded = self.expand(&included_path, &included_content)?;
+      self.stack.pop();
+
+      output.push_str(&expanded);
+      if !expanded.ends_with('\n') && line.ends_with('\n') {
+        output.push('\n');
+      }
+      self.included_files.insert(included_path);
+    }
+
+    Ok(output)
+  }
+}
+
+/// The quoted path of an `#include "file.wgsl"` line.
+fn parse_include(line: &str) -> Option<&str> {
+  line
+    .trim()
+    .strip_prefix(INCLUDE_DIRECTIVE)?
+    .trim()
+    .strip_prefix('"')?
+    .strip_suffix('"')
+}
+
+/// Expands the `#include` directives of `content` recursively, relative to the directory of
+/// the including file. The included files are added to `included_files`.
+pub(crate) fn expand_includes(
+  source_path: &SourceFilePath,
+  content: &str,
+  included_files: &mut FxIndexSet<SourceFilePath>,
+) -> Result<String, DependencyTreeError> {
+  let canonical_path = std::fs::canonicalize(source_path.as_path())
+    .unwrap_or_else(|_| source_path.to_path_buf());
+  let mut expander = IncludeExpander {
+    stack: vec![(canonical_path, source_path.clone())],
+    included_files,
+  };
+  expander.expand(source_path, content)
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  #[test]
+  fn parse_include_directive() {
+    assert_eq!(Some("common.wgsl"), parse_include("#include \"common.wgsl\"\n"));
+    assert_eq!(Some("a/b.wgsl"), parse_include("  #include   \"a/b.wgsl\""));
+    assert_eq!(None, parse_include("// #include \"common.wgsl\""));
+    assert_eq!(None, parse_include("#import common"));
+  }
+}
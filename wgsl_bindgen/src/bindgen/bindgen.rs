@@ -1,16 +1,28 @@
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 pub use naga::valid::Capabilities as WgslShaderIRCapabilities;
 use naga_oil::compose::{
   ComposableModuleDescriptor, Composer, ComposerError, NagaModuleDescriptor,
   ShaderLanguage,
 };
+use quote::ToTokens;
+use rayon::prelude::*;
 
 use crate::bevy_util::source_file::SourceFile;
-use crate::bevy_util::DependencyTree;
+use crate::bevy_util::{DependencyTree, DependencyTreeError};
+use crate::bindgen::entry_scan::find_entry_points;
+use crate::naga_util::{module_to_naga_ir, module_to_source};
+use crate::qs::quote;
+use crate::quote_gen::MOD_REFERENCE_ROOT;
+use crate::reflection::entry_reflection;
 use crate::{
-  create_rust_bindings, SourceFilePath, SourceWithFullDependenciesResult,
-  WgslBindgenError, WgslBindgenOption, WgslEntryResult,
+  create_rust_bindings, format_tokens, FxIndexMap, OutputFormat,
+  PipelineCompilationOptions, ShaderReflection, SourceFilePath,
+  SourceWithFullDependenciesResult, WgpuVersion, WgslBindgenError, WgslBindgenOption,
+  WgslEntryResult, WgslShaderSourceType,
 };
 
 const PKG_VER: &str = env!("CARGO_PKG_VERSION");
@@ -18,72 +30,278 @@ const PKG_NAME: &str = env!("CARGO_PKG_NAME");
 
 pub struct WGSLBindgen {
   dependency_tree: DependencyTree,
+  /// The SPIR-V entries, which are parsed instead of composed.
+  spirv_entries: Vec<(SourceFile, naga::Module)>,
   options: WgslBindgenOption,
   content_hash: String,
+  /// The composed naga modules of the entries, keyed by their [`Self::entry_hash`].
+  module_cache: Mutex<HashMap<String, naga::Module>>,
 }
 
 impl WGSLBindgen {
-  pub(crate) fn new(options: WgslBindgenOption) -> Result<Self, WgslBindgenError> {
-    let entry_points = options
+  pub(crate) fn new(mut options: WgslBindgenOption) -> Result<Self, WgslBindgenError> {
+    if options.hot_reload {
+      options.shader_source_type |=
+        WgslShaderSourceType::UseEmbed | WgslShaderSourceType::UseComposerWithPath;
+    }
+
+    let in_memory_sources: FxIndexMap<_, _> = options
+      .entry_point_sources
+      .iter()
+      .map(|(name, source)| {
+        let path = SourceFilePath::new(options.workspace_root.join(name));
+        (path, source.clone())
+      })
+      .collect();
+
+    let uses_original_files = options.shader_source_type.intersects(
+      WgslShaderSourceType::UseComposerEmbed
+        | WgslShaderSourceType::UseComposerWithPath
+        | WgslShaderSourceType::IncludeStrOriginal
+        | WgslShaderSourceType::RuntimePath,
+    );
+    if !in_memory_sources.is_empty() && uses_original_files {
+      return Err(WgslBindgenError::InMemorySourceNotEmbedded);
+    }
+    if !options.spirv_entry_points.is_empty() && uses_original_files {
+      return Err(WgslBindgenError::SpirvEntryNotEmbedded);
+    }
+    if options
+      .shader_source_type
+      .contains(WgslShaderSourceType::NagaIr)
+      && options.wgpu_edition != WgpuVersion::V0_19
+    {
+      return Err(WgslBindgenError::NagaIrWgpuVersion);
+    }
+    if options.checked_shader_modules && options.wgpu_edition < WgpuVersion::V0_20 {
+      return Err(WgslBindgenError::UnsupportedWgpuVersion {
+        option: "checked_shader_modules",
+        required: WgpuVersion::V0_20,
+      });
+    }
+    if options.pipeline_compilation_options != PipelineCompilationOptions::default()
+      && options.wgpu_edition < WgpuVersion::V0_20
+    {
+      return Err(WgslBindgenError::UnsupportedWgpuVersion {
+        option: "pipeline_compilation_options",
+        required: WgpuVersion::V0_20,
+      });
+    }
+    if options.pipeline_cache_fns && options.wgpu_edition < WgpuVersion::V22 {
+      return Err(WgslBindgenError::UnsupportedWgpuVersion {
+        option: "pipeline_cache_fns",
+        required: WgpuVersion::V22,
+      });
+    }
+
+    let spirv_entries = options
+      .spirv_entry_points
+      .iter()
+      .map(|path| Self::parse_spirv_entry(SourceFilePath::new(path)))
+      .collect::<Result<Vec<_>, _>>()?;
+
+    let mut entry_points: Vec<_> = options
       .entry_points
       .iter()
       .cloned()
       .map(SourceFilePath::new)
+      .chain(in_memory_sources.keys().cloned())
       .collect();
 
+    for dir in &options.entry_point_dirs {
+      let dir_entry_points = find_entry_points(dir).map_err(|source| {
+        WgslBindgenError::EntryPointScanError {
+          dir: dir.clone(),
+          source,
+        }
+      })?;
+      entry_points.extend(dir_entry_points.into_iter().map(SourceFilePath::new));
+
+      // Makes Cargo rerun the build script when files are added to the directory.
+      if options.emit_rerun_if_change {
+        println!("cargo:rerun-if-changed={}", dir.display());
+      }
+    }
+
     let dependency_tree = DependencyTree::try_build(
       options.workspace_root.clone(),
       options.module_import_root.clone(),
       entry_points,
       options.additional_scan_dirs.clone(),
+      options.textual_includes,
+      options.import_aliases.clone(),
+      in_memory_sources,
     )?;
 
-    let content_hash = Self::get_contents_hash(&options, &dependency_tree);
+    let content_hash =
+      Self::get_contents_hash(&options, &dependency_tree, &spirv_entries);
 
     if options.emit_rerun_if_change {
-      for file in Self::iter_files_to_watch(&dependency_tree) {
+      for file in Self::iter_files_to_watch(&dependency_tree, &spirv_entries) {
         println!("cargo:rerun-if-changed={}", file);
       }
     }
 
     Ok(Self {
       dependency_tree,
+      spirv_entries,
       options,
       content_hash,
+      module_cache: Default::default(),
     })
   }
 
-  fn iter_files_to_watch(dep_tree: &DependencyTree) -> impl Iterator<Item = String> {
+  fn iter_files_to_watch<'a>(
+    dep_tree: &'a DependencyTree,
+    spirv_entries: &'a [(SourceFile, naga::Module)],
+  ) -> impl Iterator<Item = String> + 'a {
     dep_tree
       .all_files_including_dependencies()
       .into_iter()
       .map(|path| path.to_string())
+      .chain(
+        spirv_entries
+          .iter()
+          .map(|(source, _)| source.file_path.to_string()),
+      )
+  }
+
+  /// Parses a SPIR-V entry. Its source file holds the translated WGSL.
+  fn parse_spirv_entry(
+    path: SourceFilePath,
+  ) -> Result<(SourceFile, naga::Module), WgslBindgenError> {
+    let bytes = std::fs::read(path.as_path())
+      .map_err(|_| DependencyTreeError::SourceNotFound { path: path.clone() })?;
+    let map_err = |msg: String| WgslBindgenError::SpirvParseError {
+      entry: path.to_string(),
+      msg,
+    };
+
+    // The coordinate space is kept as is, so the translated shader behaves like the original.
+    let spv_options = naga::front::spv::Options {
+      adjust_coordinate_space: false,
+      ..Default::default()
+    };
+    let module = naga::front::spv::parse_u8_slice(&bytes, &spv_options)
+      .map_err(|err| map_err(err.to_string()))?;
+    let wgsl = module_to_source(&module).map_err(|err| map_err(err.to_string()))?;
+
+    Ok((SourceFile::create(path, None, wgsl), module))
   }
 
-  fn get_contents_hash(options: &WgslBindgenOption, dep_tree: &DependencyTree) -> String {
+  /// All the files read to generate the output: the entry points and every file they import,
+  /// directly or indirectly. Build scripts which set `emit_rerun_if_change` to `false` can use
+  /// this to report the files to Cargo themselves.
+  pub fn source_files(&self) -> Vec<String> {
+    Self::iter_files_to_watch(&self.dependency_tree, &self.spirv_entries).collect()
+  }
+
+  fn get_contents_hash(
+    options: &WgslBindgenOption,
+    dep_tree: &DependencyTree,
+    spirv_entries: &[(SourceFile, naga::Module)],
+  ) -> String {
     let mut hasher = blake3::Hasher::new();
 
     hasher.update(format!("{:?}", options).as_bytes());
     hasher.update(PKG_VER.as_bytes());
 
-    for SourceFile { content, .. } in dep_tree.parsed_files() {
-      hasher.update(content.as_bytes());
+    let spirv_sources = spirv_entries.iter().map(|(source, _)| source);
+    for SourceFile { content_hash, .. } in
+      dep_tree.parsed_files().into_iter().chain(spirv_sources)
+    {
+      hasher.update(content_hash.as_bytes());
     }
 
     hasher.finalize().to_string()
   }
 
+  /// The hash of everything the naga module of `entry` depends on, so the module only needs
+  /// to be composed again when the entry or one of its imports changed.
+  fn entry_hash(
+    ir_capabilities: Option<WgslShaderIRCapabilities>,
+    entry: &SourceWithFullDependenciesResult<'_>,
+  ) -> String {
+    let mut hasher = blake3::Hasher::new();
+
+    hasher.update(format!("{:?}", ir_capabilities).as_bytes());
+    hasher.update(PKG_VER.as_bytes());
+
+    let files =
+      std::iter::once(entry.source_file).chain(entry.full_dependencies.iter().copied());
+    for SourceFile {
+      file_path,
+      content_hash,
+      ..
+    } in files
+    {
+      hasher.update(file_path.to_string().as_bytes());
+      hasher.update(content_hash.as_bytes());
+    }
+
+    hasher.finalize().to_string()
+  }
+
+  /// Reuses the composed naga modules of `previous`, eg: the bindgen of the last generation in a
+  /// long running tool, so only the entries whose files changed are composed again.
+  pub fn reuse_modules_of(&mut self, previous: Self) {
+    let mut previous = previous.module_cache.into_inner().unwrap();
+    let ir_capabilities = self.options.ir_capabilities;
+    let module_cache = self.module_cache.get_mut().unwrap();
+    for entry in self
+      .dependency_tree
+      .get_source_files_with_full_dependencies()
+    {
+      let hash = Self::entry_hash(ir_capabilities, &entry);
+      if let Some(module) = previous.remove(&hash) {
+        module_cache.insert(hash, module);
+      }
+    }
+  }
+
+  /// Takes the module cache instead of `self`, as the options aren't `Sync`. The modules are
+  /// also persisted in the `cache_dir`, keyed by the hash of the entry.
+  fn entry_result<'a>(
+    module_cache: &Mutex<HashMap<String, naga::Module>>,
+    cache_dir: Option<&Path>,
+    ir_capabilities: Option<WgslShaderIRCapabilities>,
+    entry: SourceWithFullDependenciesResult<'a>,
+  ) -> Result<WgslEntryResult<'a>, WgslBindgenError> {
+    let hash = Self::entry_hash(ir_capabilities, &entry);
+
+    let cached_module = module_cache.lock().unwrap().get(&hash).cloned();
+    let cached_module = cached_module.or_else(|| {
+      let module = read_cached_module(cache_dir?, &hash)?;
+      module_cache
+        .lock()
+        .unwrap()
+        .insert(hash.clone(), module.clone());
+      Some(module)
+    });
+    if let Some(naga_module) = cached_module {
+      return Ok(WgslEntryResult {
+        mod_name: entry.source_file.file_path.file_prefix(),
+        naga_module,
+        source_including_deps: entry,
+      });
+    }
+
+    let result = Self::generate_naga_module_for_entry(ir_capabilities, entry)?;
+    if let Some(cache_dir) = cache_dir {
+      write_cached_module(cache_dir, &hash, &result.naga_module)?;
+    }
+    let naga_module = result.naga_module.clone();
+    module_cache.lock().unwrap().insert(hash, naga_module);
+    Ok(result)
+  }
+
   fn generate_naga_module_for_entry(
     ir_capabilities: Option<WgslShaderIRCapabilities>,
     entry: SourceWithFullDependenciesResult<'_>,
   ) -> Result<WgslEntryResult, WgslBindgenError> {
     let map_err = |composer: &Composer, err: ComposerError| {
-      let msg = err.emit_to_string(composer);
-      WgslBindgenError::NagaModuleComposeError {
-        entry: entry.source_file.file_path.to_string(),
-        inner: err.inner,
-        msg,
-      }
+      let entry = entry.source_file.file_path.to_string();
+      WgslBindgenError::from_compose_error(entry, composer, err)
     };
 
     let mut composer = match ir_capabilities {
@@ -131,19 +349,107 @@ impl WGSLBindgen {
       writeln!(text, "// SourceHash: {}", self.content_hash).unwrap();
       writeln!(text).unwrap();
     }
+    if let Some(header) = &self.options.header {
+      writeln!(text, "{}", header.trim_end()).unwrap();
+    }
     text
   }
 
   fn generate_output(&self) -> Result<String, WgslBindgenError> {
+    let entry_results = self.entry_results()?;
+    Ok(create_rust_bindings(entry_results, &self.options)?)
+  }
+
+  /// The composed naga modules of the entries, sorted by their module name.
+  fn entry_results(&self) -> Result<Vec<WgslEntryResult<'_>>, WgslBindgenError> {
+    // Composing and validating the naga modules dominates the generation time, so the entries
+    // are composed in parallel. The results keep the order of the entry points.
+    let module_cache = &self.module_cache;
+    let cache_dir = self.options.cache_dir.as_deref();
     let ir_capabilities = self.options.ir_capabilities;
-    let entry_results = self
+    let entries = self
       .dependency_tree
-      .get_source_files_with_full_dependencies()
-      .into_iter()
-      .map(|it| Self::generate_naga_module_for_entry(ir_capabilities, it))
+      .get_source_files_with_full_dependencies();
+    if let Some(cache_dir) = cache_dir {
+      let hashes = entries
+        .iter()
+        .map(|entry| Self::entry_hash(ir_capabilities, entry))
+        .collect();
+      prune_cache_dir(cache_dir, &hashes);
+    }
+    let mut entry_results = entries
+      .into_par_iter()
+      .map(|it| Self::entry_result(module_cache, cache_dir, ir_capabilities, it))
       .collect::<Result<Vec<_>, _>>()?;
 
-    Ok(create_rust_bindings(entry_results, &self.options)?)
+    entry_results.extend(self.spirv_entries.iter().map(|(source_file, naga_module)| {
+      WgslEntryResult {
+        mod_name: source_file.file_path.file_prefix(),
+        naga_module: naga_module.clone(),
+        source_including_deps: SourceWithFullDependenciesResult {
+          source_file,
+          full_dependencies: Default::default(),
+        },
+      }
+    }));
+
+    // The output shouldn't depend on the order the entry points were added in.
+    entry_results.sort_by_cached_key(|entry| {
+      let file_path = entry
+        .source_including_deps
+        .source_file
+        .file_path
+        .to_string();
+      (entry.mod_name.clone(), file_path)
+    });
+
+    if let Some(capabilities) = self.options.validation_capabilities {
+      for entry in &entry_results {
+        let entry_path = entry
+          .source_including_deps
+          .source_file
+          .file_path
+          .to_string();
+        WgslBindgenError::validate_module(entry_path, &entry.naga_module, capabilities)?;
+      }
+    }
+
+    // The excluded entry points were validated while composing, but get no generated code.
+    for entry in &mut entry_results {
+      entry
+        .naga_module
+        .entry_points
+        .retain(|entry_point| self.options.is_entry_point_included(&entry_point.name));
+    }
+
+    Ok(entry_results)
+  }
+
+  /// Collects the entry points, bind groups, struct layouts and vertex inputs of the shaders,
+  /// for tools which need this data without parsing the generated code.
+  pub fn reflect(&self) -> Result<ShaderReflection, WgslBindgenError> {
+    let entries = self
+      .entry_results()?
+      .iter()
+      .map(|entry| entry_reflection(entry, &self.options.for_entry(&entry.mod_name)))
+      .collect::<Result<_, _>>()?;
+    Ok(ShaderReflection { entries })
+  }
+
+  /// Writes the [`ShaderReflection`] of the shaders as JSON to `path`, for tools which aren't
+  /// written in Rust. Like `generate`, the file is only written when its content changed.
+  #[cfg(feature = "json")]
+  pub fn export_reflection_json(
+    &self,
+    path: impl AsRef<Path>,
+  ) -> Result<(), WgslBindgenError> {
+    let json = self.reflect()?.to_json();
+    let path = path.as_ref();
+    if std::fs::read_to_string(path).is_ok_and(|old| old == json) {
+      return Ok(());
+    }
+    std::fs::write(path, json)?;
+    Ok(())
   }
 
   pub fn generate_string(&self) -> Result<String, WgslBindgenError> {
@@ -152,6 +458,43 @@ impl WGSLBindgen {
     Ok(text)
   }
 
+  /// The files written by `generate` when the output is `out`, with their content.
+  fn output_files(&self, out: &Path) -> Result<Vec<(PathBuf, String)>, WgslBindgenError> {
+    if self.options.split_output {
+      let (root, modules) =
+        split_modules(&self.generate_output()?, self.options.format_output);
+      let modules_dir = split_modules_dir(out);
+      let mut files = modules
+        .into_iter()
+        .map(|(name, content)| {
+          let path = modules_dir.join(format!("{name}.rs"));
+          (path, self.header_texts() + &content)
+        })
+        .collect::<Vec<_>>();
+      files.push((out.to_path_buf(), self.header_texts() + &root));
+      Ok(files)
+    } else if self.options.include_wrapper.is_some() {
+      let output =
+        strip_inner_attributes(&self.generate_output()?, self.options.format_output);
+      Ok(vec![(out.to_path_buf(), self.header_texts() + &output)])
+    } else {
+      Ok(vec![(out.to_path_buf(), self.generate_string()?)])
+    }
+  }
+
+  /// Regenerates the output in memory and fails with a diff if the files at `out` differ,
+  /// without writing anything. Useful to check in CI that committed bindings are up to date.
+  pub fn generate_check(&self, out: impl AsRef<Path>) -> Result<(), WgslBindgenError> {
+    for (path, expected) in self.output_files(out.as_ref())? {
+      let actual = std::fs::read_to_string(&path).unwrap_or_default();
+      if actual != expected {
+        let diff = first_difference(&actual, &expected);
+        return Err(WgslBindgenError::StaleOutput { path, diff });
+      }
+    }
+    Ok(())
+  }
+
   pub fn generate(&self) -> Result<(), WgslBindgenError> {
     let out = self
       .options
@@ -159,6 +502,15 @@ impl WGSLBindgen {
       .as_ref()
       .ok_or(WgslBindgenError::OutputFileNotSpecified)?;
 
+    // The wrapper doesn't depend on the shaders, so it's written even if the output is up to date.
+    if let Some(wrapper) = &self.options.include_wrapper {
+      let content = include_wrapper_content(out);
+      let old_content = std::fs::read_to_string(wrapper).unwrap_or_default();
+      if old_content != content {
+        std::fs::File::create(wrapper)?.write_all(content.as_bytes())?
+      }
+    }
+
     let old_content = std::fs::read_to_string(out).unwrap_or_else(|_| String::new());
 
     let old_hashstr_comment = old_content
@@ -169,11 +521,348 @@ impl WGSLBindgen {
     let is_hash_changed =
       || old_hashstr_comment != format!("// SourceHash: {}", &self.content_hash);
 
-    if self.options.skip_hash_check || is_hash_changed() {
-      let content = self.generate_string()?;
-      std::fs::File::create(out)?.write_all(content.as_bytes())?
+    if !self.options.skip_hash_check && !is_hash_changed() {
+      return Ok(());
+    }
+
+    for (path, text) in self.output_files(out)? {
+      // Rewriting identical files would bump their mtime and rebuild downstream crates.
+      if std::fs::read(&path).is_ok_and(|old| old == text.as_bytes()) {
+        continue;
+      }
+      if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+      }
+      std::fs::File::create(path)?.write_all(text.as_bytes())?
     }
 
     Ok(())
   }
+
+  /// Generates the output, then regenerates it whenever one of the entry points or their
+  /// imports change, until the file watcher fails. Errors while regenerating are printed
+  /// and the previous output is kept, so a typo in a shader doesn't stop watching.
+  #[cfg(feature = "watch")]
+  pub fn watch(self) -> Result<(), WgslBindgenError> {
+    use std::collections::BTreeSet;
+    use std::time::Duration;
+
+    use notify::{RecursiveMode, Watcher};
+
+    let watched_files = |bindgen: &Self| {
+      Self::iter_files_to_watch(&bindgen.dependency_tree, &bindgen.spirv_entries)
+        .map(|file| std::fs::canonicalize(&file).unwrap_or_else(|_| PathBuf::from(file)))
+        .collect::<BTreeSet<_>>()
+    };
+
+    self.generate()?;
+    let options = self.options.clone();
+    let mut files = watched_files(&self);
+    let mut previous = self;
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+
+    loop {
+      // Editors often save by replacing the file, so the directories are watched instead.
+      let dirs = files
+        .iter()
+        .filter_map(|file| file.parent())
+        .map(Path::to_path_buf)
+        .collect::<BTreeSet<_>>();
+      for dir in &dirs {
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+      }
+
+      loop {
+        let event = match receiver.recv() {
+          Ok(event) => event?,
+          Err(_) => return Ok(()),
+        };
+        if !event.kind.is_access() && event.paths.iter().any(|path| files.contains(path))
+        {
+          break;
+        }
+      }
+
+      // Waits for the remaining events of the same save.
+      while receiver.recv_timeout(Duration::from_millis(50)).is_ok() {}
+
+      for dir in &dirs {
+        let _ = watcher.unwatch(dir);
+      }
+
+      match WGSLBindgen::new(options.clone()) {
+        Ok(mut bindgen) => {
+          bindgen.reuse_modules_of(previous);
+          if let Err(err) = bindgen.generate() {
+            eprintln!("{PKG_NAME}: {err}");
+          }
+          files = watched_files(&bindgen);
+          previous = bindgen;
+        }
+        Err(err) => eprintln!("{PKG_NAME}: {err}"),
+      }
+    }
+  }
+}
+
+/// The extension of the composed naga modules in the `cache_dir`.
+const CACHED_MODULE_EXTENSION: &str = "naga";
+
+/// The composed naga module with the given entry hash in the `cache_dir`, if any.
+fn read_cached_module(cache_dir: &Path, hash: &str) -> Option<naga::Module> {
+  let path = cache_dir.join(hash).with_extension(CACHED_MODULE_EXTENSION);
+  let bytes = std::fs::read(path).ok()?;
+  bincode::deserialize(&bytes).ok()
+}
+
+fn write_cached_module(
+  cache_dir: &Path,
+  hash: &str,
+  module: &naga::Module,
+) -> Result<(), WgslBindgenError> {
+  let bytes = module_to_naga_ir(module).map_err(|err| {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, err.to_string())
+  })?;
+  // Written to a temporary file first, so concurrent builds never read a partial entry.
+  std::fs::create_dir_all(cache_dir)?;
+  let path = cache_dir.join(hash).with_extension(CACHED_MODULE_EXTENSION);
+  let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+  std::fs::write(&temp_path, bytes)?;
+  std::fs::rename(temp_path, path)?;
+  Ok(())
+}
+
+/// Removes the cached modules of the `cache_dir` which aren't used by the current entries.
+/// Failing to remove one only costs disk space, so the errors are ignored.
+fn prune_cache_dir(cache_dir: &Path, hashes: &HashSet<String>) {
+  let Ok(dir_entries) = std::fs::read_dir(cache_dir) else {
+    return;
+  };
+  for path in dir_entries.flatten().map(|entry| entry.path()) {
+    let is_stale = path
+      .extension()
+      .is_some_and(|extension| extension == CACHED_MODULE_EXTENSION)
+      && path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|hash| !hashes.contains(hash));
+    if is_stale {
+      let _ = std::fs::remove_file(path);
+    }
+  }
+}
+
+/// Describes the first differing line between the `actual` and `expected` output,
+/// with a few lines of context.
+fn first_difference(actual: &str, expected: &str) -> String {
+  const CONTEXT: usize = 3;
+  let actual = actual.lines().collect::<Vec<_>>();
+  let expected = expected.lines().collect::<Vec<_>>();
+  let line = actual
+    .iter()
+    .zip(&expected)
+    .position(|(a, e)| a != e)
+    .unwrap_or(actual.len().min(expected.len()));
+
+  let start = line.saturating_sub(CONTEXT);
+  let mut diff = format!("@@ line {} @@\n", line + 1);
+  for context in &expected[start..line] {
+    diff += &format!(" {context}\n");
+  }
+  for removed in actual.iter().skip(line).take(CONTEXT) {
+    diff += &format!("-{removed}\n");
+  }
+  for added in expected.iter().skip(line).take(CONTEXT) {
+    diff += &format!("+{added}\n");
+  }
+  diff
+}
+
+/// Removes the inner attributes of `output`, as they aren't allowed in files included with `include!`.
+fn strip_inner_attributes(output: &str, format: OutputFormat) -> String {
+  let mut file = syn::parse_file(output).unwrap();
+  file.attrs.clear();
+  format_tokens(&file.into_token_stream(), format)
+}
+
+/// The content of a file including `output`, relative to `OUT_DIR` if the output is in it.
+fn include_wrapper_content(output: &Path) -> String {
+  let out_dir = std::env::var("OUT_DIR").ok();
+  let include = match out_dir.and_then(|dir| output.strip_prefix(dir).ok()) {
+    Some(relative_path) => {
+      let relative_path = relative_path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>();
+      let relative_path = format!("/{}", relative_path.join("/"));
+      quote!(include!(concat!(env!("OUT_DIR"), #relative_path));)
+    }
+    None => {
+      let path = output.to_string_lossy();
+      quote!(include!(#path);)
+    }
+  };
+
+  let wrapper = quote! {
+    #![allow(unused, non_snake_case, non_camel_case_types, non_upper_case_globals)]
+    #include
+  };
+  let wrapper = syn::parse_file(&wrapper.to_string()).unwrap();
+  format!(
+    "// Includes the bindings generated by {PKG_NAME}.\n{}",
+    prettyplease::unparse(&wrapper)
+  )
+}
+
+/// The directory of the module files when the output is split.
+fn split_modules_dir(output: &Path) -> PathBuf {
+  let parent = output.parent().unwrap_or(Path::new(""));
+  match output.file_name().and_then(|name| name.to_str()) {
+    Some("mod.rs" | "lib.rs" | "main.rs") => parent.to_owned(),
+    _ => parent.join(output.file_stem().unwrap_or_default()),
+  }
+}
+
+/// Moves the contents of the top level modules of `output` into their own files, except for the
+/// small `_root` module. Returns the remaining root file and the name and contents of each module file.
+fn split_modules(output: &str, format: OutputFormat) -> (String, Vec<(String, String)>) {
+  let mut file = syn::parse_file(output).unwrap();
+  let mut modules = Vec::new();
+
+  for item in file.items.iter_mut() {
+    let syn::Item::Mod(item_mod) = item else {
+      continue;
+    };
+    if item_mod.ident == MOD_REFERENCE_ROOT {
+      continue;
+    }
+    let Some((_, items)) = item_mod.content.take() else {
+      continue;
+    };
+    item_mod.semi = Some(Default::default());
+
+    let module_file = syn::File {
+      shebang: None,
+      attrs: Vec::new(),
+      items,
+    };
+    let module_file = format_tokens(&module_file.into_token_stream(), format);
+    modules.push((item_mod.ident.to_string(), module_file));
+  }
+
+  (format_tokens(&file.into_token_stream(), format), modules)
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  #[test]
+  fn split_modules_into_files() {
+    let output = indoc::indoc! {r#"
+        #![allow(unused)]
+        pub enum ShaderEntry {
+            Main,
+        }
+        mod _root {
+            pub use super::*;
+        }
+        pub mod main {
+            use super::{_root, _root::*};
+            pub const ENTRY_MAIN: &str = "main";
+        }
+    "#};
+
+    let (root, modules) = split_modules(output, OutputFormat::PrettyPlease);
+
+    assert_eq!(
+      indoc::indoc! {r#"
+          #![allow(unused)]
+          pub enum ShaderEntry {
+              Main,
+          }
+          mod _root {
+              pub use super::*;
+          }
+          pub mod main;
+      "#},
+      root
+    );
+    assert_eq!(
+      vec![(
+        "main".to_string(),
+        indoc::indoc! {r#"
+            use super::{_root, _root::*};
+            pub const ENTRY_MAIN: &str = "main";
+        "#}
+        .to_string()
+      )],
+      modules
+    );
+  }
+
+  #[test]
+  fn split_modules_dir_next_to_mod_rs() {
+    assert_eq!(
+      PathBuf::from("src/shaders"),
+      split_modules_dir(Path::new("src/shaders/mod.rs"))
+    );
+    assert_eq!(
+      PathBuf::from("src/shaders"),
+      split_modules_dir(Path::new("src/shaders.rs"))
+    );
+  }
+
+  #[test]
+  fn include_wrapper_moves_inner_attributes() {
+    let output = indoc::indoc! {r#"
+        #![allow(unused)]
+        pub const ENTRY_MAIN: &str = "main";
+    "#};
+    assert_eq!(
+      "pub const ENTRY_MAIN: &str = \"main\";\n",
+      strip_inner_attributes(output, OutputFormat::PrettyPlease)
+    );
+
+    let wrapper = include_wrapper_content(Path::new("/tmp/shaders.rs"));
+    assert!(wrapper.contains(
+      "#![allow(unused, non_snake_case, non_camel_case_types, non_upper_case_globals)]"
+    ));
+    assert!(wrapper.contains(r#"include!("/tmp/shaders.rs");"#));
+  }
+
+  #[test]
+  fn first_difference_with_context() {
+    let actual = "a\nb\nc\nd\ne\n";
+    let expected = "a\nb\nc\nd\nE\n";
+    assert_eq!("@@ line 5 @@\n b\n c\n d\n-e\n+E\n", first_difference(actual, expected));
+    assert_eq!("@@ line 2 @@\n a\n+b\n", first_difference("a\n", "a\nb\n"));
+  }
+
+  #[test]
+  fn entry_hash_changes_with_dependencies() {
+    let source = |path: &str, content: &str| {
+      SourceFile::create(SourceFilePath::new(path), None, content.to_string())
+    };
+    let entry = source("main.wgsl", "#import types\nfn main() {}");
+    let types = source("types.wgsl", "struct A { a: f32 }");
+    let changed_types = source("types.wgsl", "struct A { a: u32 }");
+
+    let hash = |dependency: &SourceFile| {
+      WGSLBindgen::entry_hash(
+        None,
+        &SourceWithFullDependenciesResult {
+          source_file: &entry,
+          full_dependencies: [dependency].into_iter().collect(),
+        },
+      )
+    };
+
+    assert_eq!(hash(&types), hash(&types.clone()));
+    assert_ne!(hash(&types), hash(&changed_types));
+  }
 }
@@ -0,0 +1,61 @@
+//! Finds the entry points of a shader directory, so its files don't need to be listed one by one.
+
+use std::path::{Path, PathBuf};
+
+/// The `.wgsl` files in `dir` and its subdirectories with a vertex, fragment or compute entry
+/// point, sorted by path. The other files are libraries which are only imported.
+pub(crate) fn find_entry_points(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+  let mut entry_points = Vec::new();
+  let mut dirs = vec![dir.to_path_buf()];
+
+  while let Some(dir) = dirs.pop() {
+    for dir_entry in std::fs::read_dir(&dir)? {
+      let path = dir_entry?.path();
+      if path.is_dir() {
+        dirs.push(path);
+      } else if path.extension().is_some_and(|ext| ext == "wgsl")
+        && has_entry_point(&std::fs::read_to_string(&path)?)
+      {
+        entry_points.push(path);
+      }
+    }
+  }
+
+  entry_points.sort();
+  Ok(entry_points)
+}
+
+fn has_entry_point(source: &str) -> bool {
+  source
+    .lines()
+    .map(|line| line.split("//").next().unwrap_or_default())
+    .any(|line| {
+      ["@vertex", "@fragment", "@compute"]
+        .iter()
+        .any(|attribute| line.contains(attribute))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+  use indoc::indoc;
+
+  use super::*;
+
+  #[test]
+  fn detect_entry_point_attributes() {
+    assert!(has_entry_point(indoc! {r#"
+        @compute @workgroup_size(64)
+        fn main() {}
+    "#}));
+    assert!(has_entry_point("@fragment fn fs_main() {}"));
+    assert!(!has_entry_point(indoc! {r#"
+        #define_import_path lighting
+
+        // Called from the @fragment entry points.
+        fn light_color() -> vec3<f32> {
+            return vec3(1.0);
+        }
+    "#}));
+  }
+}
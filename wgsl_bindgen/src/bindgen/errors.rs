@@ -1,8 +1,14 @@
-use miette::Diagnostic;
+use std::ops::Range;
+
+use miette::{Diagnostic, LabeledSpan, NamedSource, SourceSpan};
+use naga_oil::compose::{Composer, ComposerError, ComposerErrorInner, ErrSource};
 use thiserror::Error;
 
 use crate::bevy_util::DependencyTreeError;
-use crate::{CreateModuleError, WgslBindgenOptionBuilderError};
+use crate::naga_util::module_to_source;
+use crate::{
+  CreateModuleError, WgpuVersion, WgslBindgenOptionBuilderError, WgslShaderIRCapabilities,
+};
 
 /// Enum representing the possible errors that can occur in the `wgsl_bindgen` process.
 ///
@@ -18,11 +24,34 @@ pub enum WgslBindgenError {
   #[diagnostic(transparent)]
   DependencyTreeError(#[from] DependencyTreeError),
 
-  #[error("Failed to compose modules with entry `{entry}`\n{msg}")]
+  #[error("Failed to compose modules with entry `{entry}`: {inner}")]
   NagaModuleComposeError {
     entry: String,
-    msg: String,
     inner: naga_oil::compose::ComposerErrorInner,
+
+    /// The source of the module which failed to compose, as preprocessed by naga_oil.
+    #[source_code]
+    src: NamedSource<String>,
+
+    #[label(collection)]
+    labels: Vec<LabeledSpan>,
+
+    /// The error as formatted by naga_oil, for the errors without a location.
+    #[help]
+    msg: Option<String>,
+  },
+
+  #[error("Shader validation failed for entry `{entry}`: {msg}")]
+  ShaderValidationError {
+    entry: String,
+    msg: String,
+
+    /// The source of the entry as written by naga, which the labels point into.
+    #[source_code]
+    src: NamedSource<String>,
+
+    #[label(collection)]
+    labels: Vec<LabeledSpan>,
   },
 
   #[error(transparent)]
@@ -33,4 +62,138 @@ pub enum WgslBindgenError {
 
   #[error("Output file is not specified. Maybe use `generate_string` instead")]
   OutputFileNotSpecified,
+
+  #[error("Failed to scan `{}` for entry points: {source}", dir.display())]
+  EntryPointScanError {
+    dir: std::path::PathBuf,
+    source: std::io::Error,
+  },
+
+  #[error("Failed to parse SPIR-V entry `{entry}`\n{msg}")]
+  SpirvParseError { entry: String, msg: String },
+
+  #[error("SPIR-V entry points only support `WgslShaderSourceType::UseEmbed`, `WgslShaderSourceType::SpirV` and `WgslShaderSourceType::NagaIr`, as the other source types read the WGSL files")]
+  SpirvEntryNotEmbedded,
+
+  #[error("In-memory entry points only support `WgslShaderSourceType::UseEmbed`, `WgslShaderSourceType::SpirV` and `WgslShaderSourceType::NagaIr`, as the other source types read the shader files")]
+  InMemorySourceNotEmbedded,
+
+  #[error("`{option}` requires `wgpu_edition` `WgpuVersion::{required:?}` or later")]
+  UnsupportedWgpuVersion {
+    option: &'static str,
+    required: WgpuVersion,
+  },
+
+  #[error("`WgslShaderSourceType::NagaIr` embeds a module serialized by the naga of wgpu 0.19, so it requires `wgpu_edition` `WgpuVersion::V0_19`")]
+  NagaIrWgpuVersion,
+
+  #[error("Output `{}` is stale and needs to be regenerated\n{diff}", path.display())]
+  StaleOutput {
+    path: std::path::PathBuf,
+    diff: String,
+  },
+
+  #[cfg(feature = "watch")]
+  #[error("Failed to watch the shader files: {0}")]
+  WatchError(#[from] notify::Error),
+}
+
+impl WgslBindgenError {
+  /// Points the error to its location in the source of the module which failed to compose,
+  /// instead of an offset into the composed source.
+  pub(crate) fn from_compose_error(
+    entry: String,
+    composer: &Composer,
+    err: ComposerError,
+  ) -> Self {
+    // naga_oil stores the index of the module above these bits of the spans.
+    const SPAN_SHIFT: usize = 20;
+
+    let offset = match &err.source {
+      ErrSource::Module { offset, .. } | ErrSource::Constructing { offset, .. } => {
+        *offset
+      }
+    };
+    let source = err.source.source(composer).into_owned();
+    let to_span = |range: Range<usize>| {
+      let mask = (1 << SPAN_SHIFT) - 1;
+      let start = (range.start & mask)
+        .saturating_sub(offset)
+        .min(source.len());
+      let end = (range.end & mask)
+        .saturating_sub(offset)
+        .clamp(start, source.len());
+      SourceSpan::from(start..end)
+    };
+
+    let labels: Vec<_> = match &err.inner {
+      ComposerErrorInner::WgslParseError(e) => e
+        .labels()
+        .filter_map(|(span, label)| {
+          let span = to_span(span.to_range()?);
+          Some(LabeledSpan::new_with_span(Some(label.to_string()), span))
+        })
+        .collect(),
+      ComposerErrorInner::ShaderValidationError(e) => e
+        .spans()
+        .filter_map(|(span, label)| {
+          let span = to_span(span.to_range()?);
+          Some(LabeledSpan::new_with_span(Some(label.clone()), span))
+        })
+        .collect(),
+      _ => Vec::new(),
+    };
+    let msg = labels.is_empty().then(|| err.emit_to_string(composer));
+
+    Self::NagaModuleComposeError {
+      entry,
+      src: NamedSource::new(err.source.path(composer), source),
+      labels,
+      msg,
+      inner: err.inner,
+    }
+  }
+
+  /// Validates the module parsed again from the WGSL written by naga, so the spans of the errors
+  /// point into a single source instead of the sources composed by naga_oil.
+  pub(crate) fn validate_module(
+    entry: String,
+    module: &naga::Module,
+    capabilities: WgslShaderIRCapabilities,
+  ) -> Result<(), Self> {
+    let error = |msg: String, source: String, labels: Vec<LabeledSpan>| {
+      Self::ShaderValidationError {
+        src: NamedSource::new(&entry, source),
+        entry: entry.clone(),
+        msg,
+        labels,
+      }
+    };
+
+    let source = module_to_source(module)
+      .map_err(|err| error(err.to_string(), String::new(), Vec::new()))?;
+    let module = naga::front::wgsl::parse_str(&source)
+      .map_err(|err| error(err.emit_to_string(&source), source.clone(), Vec::new()))?;
+
+    let validation =
+      naga::valid::Validator::new(naga::valid::ValidationFlags::all(), capabilities)
+        .validate(&module);
+    if let Err(err) = validation {
+      let labels = err
+        .spans()
+        .filter_map(|(span, label)| {
+          let range = span.to_range()?;
+          Some(LabeledSpan::new_with_span(Some(label.clone()), range))
+        })
+        .collect();
+      let mut msg = err.as_inner().to_string();
+      let mut cause = std::error::Error::source(err.as_inner());
+      while let Some(inner) = cause {
+        msg = format!("{msg}: {inner}");
+        cause = inner.source();
+      }
+      return Err(error(msg, source, labels));
+    }
+    Ok(())
+  }
 }
@@ -1,4 +1,5 @@
 mod bindgen;
+mod entry_scan;
 mod errors;
 mod options;
 
@@ -6,6 +6,12 @@ pub enum BindResourceType {
   Buffer,
   Sampler,
   Texture,
+  /// A sized `binding_array` of buffers.
+  BufferArray,
+  /// A sized `binding_array` of samplers.
+  SamplerArray,
+  /// A sized `binding_array` of textures.
+  TextureArray,
 }
 
 #[derive(Clone)]
@@ -85,6 +91,9 @@ impl WgpuGetBindingsGeneratorConfig {
       (BindResourceType::Buffer, quote! { wgpu::BufferBinding<'a> }),
       (BindResourceType::Sampler, quote! { &'a wgpu::Sampler }),
       (BindResourceType::Texture, quote! { &'a wgpu::TextureView }),
+      (BindResourceType::BufferArray, quote! { &'a [wgpu::BufferBinding<'a>] }),
+      (BindResourceType::SamplerArray, quote! { &'a [&'a wgpu::Sampler] }),
+      (BindResourceType::TextureArray, quote! { &'a [&'a wgpu::TextureView] }),
     ]
     .into_iter()
     .collect::<FastIndexMap<_, _>>();
@@ -104,6 +113,15 @@ impl WgpuGetBindingsGeneratorConfig {
         BindResourceType::Texture => {
           quote!(wgpu::BindingResource::TextureView(#binding_var))
         }
+        BindResourceType::BufferArray => {
+          quote!(wgpu::BindingResource::BufferArray(#binding_var))
+        }
+        BindResourceType::SamplerArray => {
+          quote!(wgpu::BindingResource::SamplerArray(#binding_var))
+        }
+        BindResourceType::TextureArray => {
+          quote!(wgpu::BindingResource::TextureViewArray(#binding_var))
+        }
       };
 
       let binding = Index::from(binding);
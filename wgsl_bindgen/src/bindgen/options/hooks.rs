@@ -0,0 +1,45 @@
+use proc_macro2::TokenStream;
+
+use crate::{
+  BindGroupReflection, EntryPointReflection, ShaderEntryReflection, StructReflection,
+};
+
+/// Callbacks to emit additional items into the generated code, eg: implementations of
+/// engine specific traits for the generated bind groups. Every callback emits nothing by default.
+///
+/// The `Debug` output is part of the hash used to skip unchanged generations.
+pub trait CodegenHooks: std::fmt::Debug + Send + Sync {
+  /// Items emitted in the module of each generated struct, eg: `impl MyTrait for Camera {}`.
+  /// Structs shared by multiple entries get their items once, if they're identical.
+  fn struct_items(
+    &self,
+    _entry: &ShaderEntryReflection,
+    _item: &StructReflection,
+  ) -> TokenStream {
+    TokenStream::new()
+  }
+
+  /// Items emitted in the entry module for each bind group. The bind group itself is at
+  /// `bind_groups::WgpuBindGroup{group}`.
+  fn bind_group_items(
+    &self,
+    _entry: &ShaderEntryReflection,
+    _group: &BindGroupReflection,
+  ) -> TokenStream {
+    TokenStream::new()
+  }
+
+  /// Items emitted in the entry module for each entry point.
+  fn entry_point_items(
+    &self,
+    _entry: &ShaderEntryReflection,
+    _entry_point: &EntryPointReflection,
+  ) -> TokenStream {
+    TokenStream::new()
+  }
+
+  /// Items emitted at the end of the entry module, after everything else is generated.
+  fn module_items(&self, _entry: &ShaderEntryReflection) -> TokenStream {
+    TokenStream::new()
+  }
+}
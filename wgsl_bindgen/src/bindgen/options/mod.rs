@@ -1,19 +1,25 @@
 mod bindings;
+mod hooks;
 mod types;
 
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 pub use bindings::*;
 use derive_builder::Builder;
 use derive_more::IsVariant;
 use enumflags2::{bitflags, BitFlags};
+pub use hooks::*;
 pub use naga::valid::Capabilities as WgslShaderIRCapabilities;
 use proc_macro2::TokenStream;
 use regex::Regex;
 pub use types::*;
 
 use crate::{
-  FastIndexMap, WGSLBindgen, WgslBindgenError, WgslType, WgslTypeSerializeStrategy,
+  FastIndexMap, WGSLBindgen, WgslBindgenError, WgslScalarType, WgslType,
+  WgslTypeSerializeStrategy,
 };
 
 /// An enum representing the source type that will be generated for the output.
@@ -21,7 +27,7 @@ use crate::{
 #[repr(u8)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, IsVariant)]
 pub enum WgslShaderSourceType {
-  /// Preparse the shader modules and embed the final shader string in the output.
+  /// Preparse the shader modules and embed the final shader string, as written by naga, in the output.
   /// This option skips the naga_oil dependency in the output, and but doesn't allow shader defines.
   UseEmbed = 0b0001,
 
@@ -32,6 +38,199 @@ pub enum WgslShaderSourceType {
   /// Use Composer with absolute path to shaders, useful for hot-reloading
   /// This option allows shader defines and is useful for hot-reloading.
   UseComposerWithPath = 0b0100,
+
+  /// Embed the shader as SPIR-V produced at generation time, to skip parsing WGSL at runtime
+  /// on Vulkan targets. This requires the `spirv` feature of wgpu.
+  SpirV = 0b1000,
+
+  /// Reference the original entry file with `include_str!`, instead of embedding the source
+  /// round-tripped through naga. Only for entries without imports, as they are passed to wgpu as is.
+  IncludeStrOriginal = 0b1_0000,
+
+  /// Read the original entry file at runtime from the `SHADER_PATH` constant, with
+  /// `create_shader_module_runtime_path`. Only for entries without imports, as they are passed
+  /// to wgpu as is.
+  RuntimePath = 0b10_0000,
+
+  /// Embed the validated naga module serialized with bincode, and create the shader module with
+  /// `wgpu::ShaderSource::Naga` to skip parsing WGSL at runtime. This requires the `naga-ir` feature
+  /// of wgpu, and `naga` with the `deserialize` feature and `bincode` in the output crate. Only for
+  /// `WgpuVersion::V0_19`, as the module is serialized by the naga version of wgpu 0.19.
+  NagaIr = 0b100_0000,
+}
+
+/// How the generated Rust code is formatted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, IsVariant)]
+pub enum OutputFormat {
+  /// Skips formatting, useful for huge outputs which are formatted afterwards anyway.
+  None,
+
+  /// Formats the output with `prettyplease`.
+  #[default]
+  PrettyPlease,
+
+  /// Formats the output with `rustfmt`, or the executable in the `RUSTFMT` environment variable.
+  /// Falls back to `prettyplease` when `rustfmt` can't be run.
+  Rustfmt,
+}
+
+/// How the WGSL embedded in `SHADER_STRING` is minified, to reduce the binary size.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, IsVariant)]
+pub enum MinifyLevel {
+  /// Embeds the source as written by naga.
+  #[default]
+  Off,
+
+  /// Strips the comments and the whitespace which isn't needed.
+  Whitespace,
+
+  /// Also shortens the names of the helper functions, their arguments and local variables.
+  /// Entry points, bindings, types and constants keep their names.
+  Identifiers,
+}
+
+/// The wgpu version the generated code is compiled against, for the APIs which changed between versions.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WgpuVersion {
+  /// wgpu 0.19, which `wgsl_bindgen` itself depends on.
+  #[default]
+  V0_19,
+
+  /// wgpu 0.20 and 0.21, which add `compilation_options` to the vertex, fragment and compute stages.
+  V0_20,
+
+  /// wgpu 22, where entry points are `Option<&str>`, pipelines take a `cache` and passes don't need
+  /// the bind groups to outlive them.
+  V22,
+
+  /// wgpu 23, where devices are compared directly instead of by their `global_id`.
+  V23,
+
+  /// wgpu 24, where the ray tracing features are marked as experimental.
+  V24,
+}
+
+impl WgpuVersion {
+  /// The `entry_point` of a pipeline stage for the `entry_point` name.
+  pub(crate) fn entry_point(&self, entry_point: TokenStream) -> TokenStream {
+    if *self >= WgpuVersion::V22 {
+      quote::quote!(Some(#entry_point))
+    } else {
+      entry_point
+    }
+  }
+
+  /// The `compilation_options` field of the vertex, fragment and compute stages, if any.
+  pub(crate) fn compilation_options_field(&self) -> TokenStream {
+    if *self >= WgpuVersion::V0_20 {
+      quote::quote!(compilation_options: Default::default(),)
+    } else {
+      quote::quote!()
+    }
+  }
+
+  /// The `compilation_options` parameter of the functions taking them from the caller, and the
+  /// field passing it on to the stage, if any.
+  pub(crate) fn compilation_options_param(&self) -> (TokenStream, TokenStream) {
+    if *self >= WgpuVersion::V0_20 {
+      (
+        quote::quote!(compilation_options: wgpu::PipelineCompilationOptions<'_>,),
+        quote::quote!(compilation_options,),
+      )
+    } else {
+      (quote::quote!(), quote::quote!())
+    }
+  }
+
+  /// The `cache` parameter of the functions taking a pipeline cache from the caller, and the field
+  /// passing it on to the pipeline descriptor, if any.
+  pub(crate) fn pipeline_cache_param(&self) -> (TokenStream, TokenStream) {
+    if *self >= WgpuVersion::V22 {
+      (quote::quote!(cache: Option<&wgpu::PipelineCache>,), quote::quote!(cache,))
+    } else {
+      (quote::quote!(), quote::quote!())
+    }
+  }
+
+  /// The `cache` field of the render and compute pipeline descriptors, if any.
+  pub(crate) fn pipeline_cache_field(&self) -> TokenStream {
+    if *self >= WgpuVersion::V22 {
+      quote::quote!(cache: None,)
+    } else {
+      quote::quote!()
+    }
+  }
+
+  /// The type identifying a device, the expression storing it for `device`, and the expression
+  /// comparable to the stored one. Caches use it to check they aren't used with another device
+  /// than the one their objects were created with.
+  pub(crate) fn device_id(&self) -> (TokenStream, TokenStream, TokenStream) {
+    if *self >= WgpuVersion::V23 {
+      (
+        quote::quote!(wgpu::Device),
+        quote::quote!(device.clone()),
+        quote::quote!(*device),
+      )
+    } else {
+      (
+        quote::quote!(wgpu::Id<wgpu::Device>),
+        quote::quote!(device.global_id()),
+        quote::quote!(device.global_id()),
+      )
+    }
+  }
+
+  /// The name of the `wgpu::Features` flag with the given name in wgpu 0.19. wgpu 24 marks the ray
+  /// tracing features as experimental.
+  pub(crate) fn feature_name<'a>(&self, name: &'a str) -> std::borrow::Cow<'a, str> {
+    match name {
+      "RAY_QUERY" | "RAY_TRACING_ACCELERATION_STRUCTURE" if *self >= WgpuVersion::V24 => {
+        format!("EXPERIMENTAL_{name}").into()
+      }
+      _ => name.into(),
+    }
+  }
+
+  /// The lifetime of the passes bind groups are set on. Before wgpu 22 it's the lifetime of
+  /// the bind groups.
+  pub(crate) fn pass_lifetime(&self) -> TokenStream {
+    if *self >= WgpuVersion::V22 {
+      quote::quote!('_)
+    } else {
+      quote::quote!('a)
+    }
+  }
+}
+
+/// The `wgpu::PipelineCompilationOptions` of the stages of the generated pipelines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PipelineCompilationOptions {
+  /// Whether wgpu zero initializes the workgroup memory, which performance critical shaders
+  /// writing all of it before reading can skip.
+  pub zero_initialize_workgroup_memory: bool,
+}
+
+impl Default for PipelineCompilationOptions {
+  fn default() -> Self {
+    Self {
+      zero_initialize_workgroup_memory: true,
+    }
+  }
+}
+
+/// The stages the bindings of the generated bind group layouts are visible to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BindingVisibility {
+  /// The stages of the entry points using each binding. Bindings which aren't used by any entry
+  /// point are visible to all the stages of the module.
+  Inferred,
+
+  /// All the stages of the module's entry points.
+  #[default]
+  UnionOfModule,
+
+  /// `wgpu::ShaderStages::all()`, so the layouts can be shared by shaders with other stages.
+  All,
 }
 
 /// A struct representing a directory to scan for additional source files.
@@ -78,6 +277,15 @@ impl WgslTypeMapBuild for WgslTypeMap {
   }
 }
 
+impl WgslTypeMapBuild for HashMap<WgslType, TokenStream> {
+  fn build(&self, _: WgslTypeSerializeStrategy) -> WgslTypeMap {
+    self
+      .iter()
+      .map(|(ty, tokens)| (ty.clone(), tokens.clone()))
+      .collect()
+  }
+}
+
 /// This struct is used to create a custom mapping from the wgsl side to rust side,
 /// skipping generation of the struct and using the custom one instead.
 /// This also means skipping checks for alignment and size when using bytemuck
@@ -100,6 +308,16 @@ impl From<(&str, TokenStream)> for OverrideStruct {
   }
 }
 
+impl From<(&str, &str)> for OverrideStruct {
+  fn from((from, to): (&str, &str)) -> Self {
+    let to = syn::parse_str::<syn::Path>(to).expect("Failed to parse struct path");
+    OverrideStruct {
+      from: from.to_owned(),
+      to: quote::quote!(#to),
+    }
+  }
+}
+
 /// Struct  for overriding the field type of specific structs.
 #[derive(Clone, Debug)]
 pub struct OverrideStructFieldType {
@@ -128,6 +346,168 @@ impl From<(&str, &str, TokenStream)> for OverrideStructFieldType {
   }
 }
 
+/// Struct for overriding the scalar field types of specific structs,
+/// eg: `u32` flags fields to a `bitflags` newtype or `f32` to `ordered_float::NotNan<f32>`.
+/// Only fields whose WGSL type is exactly the given scalar are affected.
+#[derive(Clone, Debug)]
+pub struct OverrideStructScalarType {
+  pub struct_regex: Regex,
+  pub field_regex: Regex,
+  pub scalar: WgslScalarType,
+  pub override_type: TokenStream,
+}
+impl From<(Regex, Regex, WgslScalarType, TokenStream)> for OverrideStructScalarType {
+  fn from(
+    (struct_regex, field_regex, scalar, override_type): (
+      Regex,
+      Regex,
+      WgslScalarType,
+      TokenStream,
+    ),
+  ) -> Self {
+    Self {
+      struct_regex,
+      field_regex,
+      scalar,
+      override_type,
+    }
+  }
+}
+impl From<(&str, &str, WgslScalarType, TokenStream)> for OverrideStructScalarType {
+  fn from(
+    (struct_regex, field_regex, scalar, override_type): (
+      &str,
+      &str,
+      WgslScalarType,
+      TokenStream,
+    ),
+  ) -> Self {
+    Self {
+      struct_regex: Regex::new(struct_regex).expect("Failed to create struct regex"),
+      field_regex: Regex::new(field_regex).expect("Failed to create field regex"),
+      scalar,
+      override_type,
+    }
+  }
+}
+
+/// Struct for overriding the vertex format of specific vertex input struct fields,
+/// eg: storing a `vec4<f32>` color as `wgpu::VertexFormat::Unorm8x4` with a `[u8; 4]` Rust field.
+/// The Rust field type is replaced by `rust_type` in matching structs.
+#[derive(Clone, Debug)]
+pub struct OverrideVertexFormat {
+  pub struct_regex: Regex,
+  pub field_regex: Regex,
+  pub format: wgpu::VertexFormat,
+  pub rust_type: TokenStream,
+}
+impl From<(Regex, Regex, wgpu::VertexFormat, TokenStream)> for OverrideVertexFormat {
+  fn from(
+    (struct_regex, field_regex, format, rust_type): (
+      Regex,
+      Regex,
+      wgpu::VertexFormat,
+      TokenStream,
+    ),
+  ) -> Self {
+    Self {
+      struct_regex,
+      field_regex,
+      format,
+      rust_type,
+    }
+  }
+}
+impl From<(&str, &str, wgpu::VertexFormat, TokenStream)> for OverrideVertexFormat {
+  fn from(
+    (struct_regex, field_regex, format, rust_type): (
+      &str,
+      &str,
+      wgpu::VertexFormat,
+      TokenStream,
+    ),
+  ) -> Self {
+    Self {
+      struct_regex: Regex::new(struct_regex).expect("Failed to create struct regex"),
+      field_regex: Regex::new(field_regex).expect("Failed to create field regex"),
+      format,
+      rust_type,
+    }
+  }
+}
+
+/// Struct for fixing the step mode of matching vertex input structs,
+/// eg: `InstanceInput` always using `wgpu::VertexStepMode::Instance`.
+#[derive(Clone, Debug)]
+pub struct OverrideVertexStepMode {
+  pub struct_regex: Regex,
+  pub step_mode: wgpu::VertexStepMode,
+}
+impl From<(Regex, wgpu::VertexStepMode)> for OverrideVertexStepMode {
+  fn from((struct_regex, step_mode): (Regex, wgpu::VertexStepMode)) -> Self {
+    Self {
+      struct_regex,
+      step_mode,
+    }
+  }
+}
+impl From<(&str, wgpu::VertexStepMode)> for OverrideVertexStepMode {
+  fn from((struct_regex, step_mode): (&str, wgpu::VertexStepMode)) -> Self {
+    Self {
+      struct_regex: Regex::new(struct_regex).expect("Failed to create struct regex"),
+      step_mode,
+    }
+  }
+}
+
+/// Struct for fixing the `array_stride` of the vertex buffer layout of matching vertex input structs,
+/// eg: when the vertices on the CPU have fields the shader doesn't use.
+#[derive(Clone, Debug)]
+pub struct OverrideVertexStride {
+  pub struct_regex: Regex,
+  pub stride: u64,
+}
+impl From<(Regex, u64)> for OverrideVertexStride {
+  fn from((struct_regex, stride): (Regex, u64)) -> Self {
+    Self {
+      struct_regex,
+      stride,
+    }
+  }
+}
+impl From<(&str, u64)> for OverrideVertexStride {
+  fn from((struct_regex, stride): (&str, u64)) -> Self {
+    Self {
+      struct_regex: Regex::new(struct_regex).expect("Failed to create struct regex"),
+      stride,
+    }
+  }
+}
+
+/// Struct for adding or removing a derive on the generated structs matching a regex,
+/// eg: `(".*", quote!(Hash))` or `("types::Config", quote!(bevy_reflect::Reflect))`.
+#[derive(Clone, Debug)]
+pub struct CustomDerive {
+  pub struct_regex: Regex,
+  pub derive: TokenStream,
+}
+impl From<(Regex, TokenStream)> for CustomDerive {
+  fn from((struct_regex, derive): (Regex, TokenStream)) -> Self {
+    Self {
+      struct_regex,
+      derive,
+    }
+  }
+}
+impl From<(&str, TokenStream)> for CustomDerive {
+  fn from((struct_regex, derive): (&str, TokenStream)) -> Self {
+    Self {
+      struct_regex: Regex::new(struct_regex).expect("Failed to create struct regex"),
+      derive,
+    }
+  }
+}
+
 /// Struct for overriding alignment of specific structs.
 #[derive(Clone, Debug)]
 pub struct OverrideStructAlignment {
@@ -151,7 +531,119 @@ impl From<(&str, u16)> for OverrideStructAlignment {
   }
 }
 
-#[derive(Debug, Default, Builder)]
+/// Struct for configuring the padding fields generated in bytemuck mode.
+#[derive(Clone, Debug)]
+pub struct PaddingFieldConfig {
+  /// The name template for padding fields, where `{name}` is replaced by the name of the padded field.
+  /// Defaults to `_pad_{name}`.
+  pub name_template: String,
+  /// Whether padding fields are `pub`. Defaults to `true`.
+  pub is_public: bool,
+  /// Whether padding fields are marked with `#[doc(hidden)]`. Defaults to `false`.
+  pub doc_hidden: bool,
+}
+
+impl Default for PaddingFieldConfig {
+  fn default() -> Self {
+    Self {
+      name_template: "_pad_{name}".to_owned(),
+      is_public: true,
+      doc_hidden: false,
+    }
+  }
+}
+
+impl PaddingFieldConfig {
+  pub(crate) fn name_for(&self, member_name: &str) -> String {
+    self.name_template.replace("{name}", member_name)
+  }
+}
+
+/// Controls the labels of the wgpu objects created by the generated code.
+#[derive(Clone, Debug, Default)]
+pub enum LabelStyle {
+  /// Use the default labels, such as `"Compute Pipeline main"` or `"Triangle::BindGroup0"`.
+  #[default]
+  Default,
+  /// Prepend a prefix to the default labels, eg: an application name.
+  Prefix(String),
+  /// Don't emit any labels, eg: for release builds.
+  Disabled,
+  /// Compute the label from the default label. Returning `None` omits the label.
+  Custom(fn(&str) -> Option<String>),
+}
+
+impl LabelStyle {
+  pub(crate) fn label(&self, default_label: &str) -> Option<String> {
+    match self {
+      LabelStyle::Default => Some(default_label.to_owned()),
+      LabelStyle::Prefix(prefix) => Some(format!("{prefix}{default_label}")),
+      LabelStyle::Disabled => None,
+      LabelStyle::Custom(label_fn) => label_fn(default_label),
+    }
+  }
+
+  pub(crate) fn label_tokens(&self, default_label: &str) -> TokenStream {
+    match self.label(default_label) {
+      Some(label) => quote::quote!(Some(#label)),
+      None => quote::quote!(None),
+    }
+  }
+}
+
+/// Overrides some of the options for the entry points matching `entry_regex`, eg: to use
+/// bytemuck for compute shaders and encase for render shaders in the same output.
+///
+/// Structs imported by several entry points are generated once, so these entries should
+/// generate them identically.
+#[derive(Clone, Debug)]
+pub struct EntryPointOverride {
+  /// Matched against the module name of the entry, which is its file name without the extension.
+  pub entry_regex: Regex,
+  pub serialization_strategy: Option<WgslTypeSerializeStrategy>,
+  /// Replaces the base type map. The struct and type map overrides of the builder still apply.
+  pub type_map: Option<WgslTypeMap>,
+  pub custom_derives: Vec<CustomDerive>,
+  pub removed_derives: Vec<CustomDerive>,
+}
+
+impl EntryPointOverride {
+  pub fn new(entry_regex: &str) -> Self {
+    Self {
+      entry_regex: Regex::new(entry_regex).expect("Failed to create entry regex"),
+      serialization_strategy: None,
+      type_map: None,
+      custom_derives: Vec::new(),
+      removed_derives: Vec::new(),
+    }
+  }
+
+  pub fn serialization_strategy(mut self, strategy: WgslTypeSerializeStrategy) -> Self {
+    self.serialization_strategy = Some(strategy);
+    self
+  }
+
+  /// Builds the type map with the serialization strategy of this override, which must be set first.
+  pub fn type_map(mut self, map_build: impl WgslTypeMapBuild) -> Self {
+    let serialization_strategy = self
+      .serialization_strategy
+      .expect("Serialization strategy must be set before `type_map`");
+    self.type_map = Some(map_build.build(serialization_strategy));
+    self
+  }
+
+  pub fn add_custom_derive(mut self, derive: impl Into<CustomDerive>) -> Self {
+    self.custom_derives.push(derive.into());
+    self
+  }
+
+  pub fn add_removed_derive(mut self, derive: impl Into<CustomDerive>) -> Self {
+    self.removed_derives.push(derive.into());
+    self
+  }
+}
+
+#[derive(Clone, Debug, Default, Builder)]
 #[builder(
   setter(into),
   field(private),
@@ -159,9 +651,31 @@ impl From<(&str, u16)> for OverrideStructAlignment {
 )]
 pub struct WgslBindgenOption {
   /// A vector of entry points to be added. Each entry point is represented as a `String`.
-  #[builder(setter(each(name = "add_entry_point", into)))]
+  #[builder(default, setter(each(name = "add_entry_point", into)))]
   pub entry_points: Vec<String>,
 
+  /// Entry points given as source code instead of files, as `(name, source)` pairs.
+  /// Added with `add_entry_point_source`.
+  #[builder(default, setter(custom))]
+  pub entry_point_sources: Vec<(String, String)>,
+
+  /// Directories scanned for entry points: every `.wgsl` file in them or their subdirectories
+  /// with a `@vertex`, `@fragment` or `@compute` function is added as an entry point, while the
+  /// others are only imported.
+  #[builder(default, setter(each(name = "add_entry_point_dir", into)))]
+  pub entry_point_dirs: Vec<PathBuf>,
+
+  /// SPIR-V entry points, eg: shaders compiled from HLSL or Slang, which are parsed with naga's
+  /// SPIR-V frontend. Their shader source is the translated WGSL, and they can't import WGSL modules.
+  #[builder(default, setter(each(name = "add_entry_point_spirv", into)))]
+  pub spirv_entry_points: Vec<PathBuf>,
+
+  /// Whether `WgslShaderSourceType::SpirV` embeds the original files of the SPIR-V entry points,
+  /// instead of writing their parsed modules as SPIR-V again. This keeps the debug info and
+  /// optimizations of the compiler which produced them.
+  #[builder(default = "false")]
+  pub spirv_passthrough: bool,
+
   /// The root prefix/namespace if any applied to all shaders given as the entrypoints.
   #[builder(default, setter(strip_option, into))]
   pub module_import_root: Option<String>,
@@ -195,31 +709,161 @@ pub struct WgslBindgenOption {
   #[builder(default = "false")]
   pub derive_serde: bool,
 
+  /// A vector of regular expressions matching the fully qualified names of structs that derive
+  /// `serde::Serialize` and `serde::Deserialize`, eg: `"types::Config"`. Only used when `derive_serde` is `false`.
+  #[builder(default, setter(each(name = "add_serde_struct_regexp", into)))]
+  pub serde_struct_regexps: Vec<Regex>,
+
   /// The shader source type generated bitflags. Defaults to `WgslShaderSourceType::UseSingleString`.
   #[builder(default)]
   pub shader_source_type: BitFlags<WgslShaderSourceType>,
 
+  /// Whether to generate `create_shader_module_hot`, which composes the shader from the files on
+  /// disk in debug builds, so shader edits are picked up without recompiling, and uses the
+  /// embedded source in release builds or when composing fails. Adds the `UseEmbed` and
+  /// `UseComposerWithPath` source types. Defaults to `false`.
+  #[builder(default)]
+  pub hot_reload: bool,
+
+  /// Whether the output is also compiled for `wasm32` targets, where the shader files can't be read at
+  /// runtime. Gates the items of `UseComposerWithPath` and `RuntimePath` with `#[cfg(not(target_arch = "wasm32"))]`,
+  /// and `create_shader_module_hot` uses the embedded source on wasm. Combine with `checked_shader_modules`
+  /// for async shader module creation. Defaults to `false`.
+  #[builder(default)]
+  pub wasm_compatible: bool,
+
+  /// Whether the output is compiled under `#![no_std]` with `extern crate alloc`. Uses `core` and `alloc`
+  /// paths instead of `std`, imports the `alloc` types of the std prelude in every generated module,
+  /// and gates the items of `UseComposerWithPath` and `RuntimePath` with `#[cfg(feature = "std")]`.
+  /// The naga_oil composer and the `OnceLock` of `cached_bind_group_layouts` and `shader_cache` still
+  /// need `std`. Defaults to `false`.
+  #[builder(default)]
+  pub no_std: bool,
+
+  /// The wgpu version the generated code is compiled against, eg: `WgpuVersion::V22` when the crate
+  /// using the output depends on wgpu 22. Defaults to `WgpuVersion::V0_19`.
+  #[builder(default)]
+  pub wgpu_edition: WgpuVersion,
+
+  /// The compilation options of the generated compute pipelines, `vertex_state`, `fragment_state`
+  /// and `create_<vs>_<fs>_pipeline` functions. Options other than the defaults of wgpu require
+  /// `wgpu_edition` `WgpuVersion::V0_20` or later.
+  #[builder(default)]
+  pub pipeline_compilation_options: PipelineCompilationOptions,
+
+  /// How the WGSL embedded for [`WgslShaderSourceType::UseEmbed`] is minified. Defaults to
+  /// [`MinifyLevel::Off`].
+  #[builder(default)]
+  pub minify: MinifyLevel,
+
+  /// Whether to remove the functions which aren't called from any entry point of the shader,
+  /// such as unused helpers from imported modules, from the embedded WGSL and SPIR-V.
+  /// Defaults to `false`.
+  #[builder(default)]
+  pub prune_unused_functions: bool,
+
+  /// A custom header prepended to the generated file after the default header comments, eg: a license,
+  /// `//!` module docs or additional `#![allow(...)]` attributes. Defaults to `None`.
+  #[builder(default, setter(strip_option, into))]
+  pub header: Option<String>,
+
+  /// Outer attributes added to every generated top level module, eg: `quote!(#[rustfmt::skip])`.
+  #[builder(default, setter(each(name = "add_module_attribute", into)))]
+  pub module_attributes: Vec<TokenStream>,
+
+  /// `use` items added to every generated module, eg: `quote!(use crate::math::*;)` for custom
+  /// type maps referencing project local types by their short name.
+  #[builder(default, setter(each(name = "add_prelude_import", into)))]
+  pub prelude_imports: Vec<TokenStream>,
+
   /// The output file path for the generated Rust bindings. Defaults to `None`.
   #[builder(default, setter(strip_option, into))]
   pub output: Option<PathBuf>,
 
+  /// Whether to write each top level module of the output into its own file. The modules are written next to
+  /// the output when it is a `mod.rs`, eg: `src/shaders/<module>.rs` for `src/shaders/mod.rs`, or into
+  /// a directory named after the output otherwise, eg: `src/shaders/<module>.rs` for `src/shaders.rs`.
+  #[builder(default = "false")]
+  pub split_output: bool,
+
+  /// The path of a stable wrapper file which includes the output with `include!`, eg: `src/shaders.rs`
+  /// when the output is written to `OUT_DIR` with [`WgslBindgenOptionBuilder::output_in_out_dir`].
+  /// The inner attributes of the output are moved to the wrapper, as `include!` doesn't allow them.
+  #[builder(default, setter(strip_option, into))]
+  pub include_wrapper: Option<PathBuf>,
+
+  /// A directory to cache the composed naga IR of each entry in, keyed by the hash of its
+  /// resolved sources. Unchanged entries then aren't parsed and composed again, even when the
+  /// output itself isn't kept between builds, eg: in `OUT_DIR` on CI. Only the naga IR is
+  /// cached: the Rust bindings are still generated from it on every build. The modules of
+  /// entries which are no longer used are removed from it. Defaults to `None`.
+  #[builder(default, setter(strip_option, into))]
+  pub cache_dir: Option<PathBuf>,
+
+  /// How the output is formatted. Defaults to `OutputFormat::PrettyPlease`.
+  #[builder(default)]
+  pub format_output: OutputFormat,
+
+  /// Hooks which emit additional items for the generated structs, bind groups, entry points
+  /// and modules. Added with `add_codegen_hooks`.
+  #[builder(default, setter(custom))]
+  pub codegen_hooks: Vec<Arc<dyn CodegenHooks>>,
+
+  /// Hand written code appended to generated modules, where it can use their private items.
+  /// Added with `append_to_module`.
+  #[builder(default, setter(custom))]
+  pub module_additions: Vec<(String, TokenStream)>,
+
   /// The additional set of directories to scan for source files.
   #[builder(default, setter(into, each(name = "additional_scan_dir", into)))]
   pub additional_scan_dirs: Vec<AdditionalScanDirectory>,
 
+  /// Whether to expand textual `#include "file.wgsl"` directives, relative to the including file,
+  /// before resolving the naga_oil imports. Defaults to `false`.
+  #[builder(default)]
+  pub textual_includes: bool,
+
+  /// Module names mapped to their files, so imports of the module resolve without relying on
+  /// the directory structure. Added with `alias`.
+  #[builder(default, setter(custom))]
+  pub import_aliases: FastIndexMap<String, PathBuf>,
+
   /// The [wgpu::naga::valid::Capabilities](https://docs.rs/wgpu/latest/wgpu/naga/valid/struct.Capabilities.html) to support. Defaults to `None`.
   #[builder(default, setter(strip_option))]
   pub ir_capabilities: Option<WgslShaderIRCapabilities>,
 
+  /// Validates every entry module, including the SPIR-V entries, with these capabilities after
+  /// composing, and fails the generation with the validation errors. Useful to check the
+  /// shaders against the capabilities of the targeted devices. Defaults to `None`.
+  #[builder(default, setter(strip_option))]
+  pub validation_capabilities: Option<WgslShaderIRCapabilities>,
+
+  /// Fails the generation when an entry module needs more than these limits, eg: too many
+  /// bind groups or vertex attributes, or storage buffers with
+  /// `wgpu::Limits::downlevel_webgl2_defaults()`. Defaults to `None`.
+  #[builder(default, setter(strip_option))]
+  pub target_limits: Option<wgpu::Limits>,
+
   /// Whether to generate short constructor similar to enums constructors instead of `new`, if number of parameters are below the specified threshold
   /// Defaults to `None`
   #[builder(default, setter(strip_option, into))]
   pub short_constructor: Option<i32>,
 
+  /// Whether to generate a `<Struct>Builder` alongside structs that have at least the specified number of fields.
+  /// The builder allows setting fields one at a time and validates that all fields are set on `build`.
+  /// Defaults to `None`
+  #[builder(default, setter(strip_option, into))]
+  pub struct_builder_threshold: Option<usize>,
+
   /// A mapping operation for WGSL built-in types. This is used to map WGSL built-in types to their corresponding representations.
   #[builder(setter(custom))]
   pub type_map: WgslTypeMap,
 
+  /// A user supplied map layered on top of `type_map`. These entries are applied after the base map,
+  /// regardless of the order in which `type_map` and `override_type_map` are called on the builder.
+  #[builder(default, setter(custom))]
+  pub type_map_overrides: WgslTypeMap,
+
   /// A vector of custom struct mappings to be added, which will override the struct to be generated.
   #[builder(default, setter(each(name = "add_override_struct_mapping", into)))]
   pub override_struct: Vec<OverrideStruct>,
@@ -228,6 +872,58 @@ pub struct WgslBindgenOption {
   #[builder(default, setter(into))]
   pub override_struct_field_type: Vec<OverrideStructFieldType>,
 
+  /// A vector of `OverrideStructScalarType` to remap scalar fields in matching structs to custom types.
+  /// These are applied after `override_struct_field_type`.
+  #[builder(default, setter(each(name = "add_override_struct_scalar_type", into)))]
+  pub override_struct_scalar_type: Vec<OverrideStructScalarType>,
+
+  /// A vector of `OverrideVertexFormat` to override the vertex format and Rust type of vertex input fields
+  /// in matching structs. This is useful for normalized formats like `Unorm8x4` for `vec4<f32>` inputs.
+  #[builder(default, setter(each(name = "add_override_vertex_format", into)))]
+  pub override_vertex_format: Vec<OverrideVertexFormat>,
+
+  /// A vector of `OverrideVertexStepMode` to fix the step mode of matching vertex input structs.
+  /// The generated vertex entry functions don't take a step mode parameter for these structs.
+  #[builder(default, setter(each(name = "add_vertex_step_mode", into)))]
+  pub vertex_step_modes: Vec<OverrideVertexStepMode>,
+
+  /// A vector of `OverrideVertexStride` to fix the `array_stride` of the `vertex_buffer_layout` of
+  /// matching vertex input structs instead of using their size.
+  #[builder(default, setter(each(name = "add_vertex_stride", into)))]
+  pub vertex_strides: Vec<OverrideVertexStride>,
+
+  /// Whether to generate a `vertex_buffer_layout_with_stride` function for the vertex input structs, taking
+  /// the `array_stride` of the layout, eg: for CPU vertex structs with fields the shader doesn't use.
+  /// Defaults to `false`.
+  #[builder(default = "false")]
+  pub vertex_buffer_layout_with_stride: bool,
+
+  /// Whether to generate a `<FIELD>_OFFSET` constant per field and a `STRIDE` constant for the vertex
+  /// input structs, eg: for packing meshes on the CPU. Defaults to `false`.
+  #[builder(default = "false")]
+  pub vertex_layout_constants: bool,
+
+  /// Whether to generate `planar_vertex_buffer_layouts` for the vertex input structs, with a tightly
+  /// packed buffer per attribute, and a `<entry>_planar_entry` function per vertex entry taking a step
+  /// mode per buffer, for meshes storing their attributes in separate buffers. Defaults to `false`.
+  #[builder(default = "false")]
+  pub planar_vertex_layouts: bool,
+
+  /// Whether to generate `vertex_state_dynamic` taking a slice of buffer layouts, and a
+  /// `<entry>_entry_dynamic` function per vertex entry returning a `VertexEntryDynamic` with a `Vec`
+  /// of buffers, for pipelines with buffers only known at runtime. Defaults to `false`.
+  #[builder(default = "false")]
+  pub dynamic_vertex_states: bool,
+
+  /// A vector of `CustomDerive` to add derives to the generated structs matching the regex.
+  #[builder(default, setter(each(name = "add_custom_derive", into)))]
+  pub custom_derives: Vec<CustomDerive>,
+
+  /// A vector of `CustomDerive` to remove derives from the generated structs matching the regex, eg: `Debug` for structs
+  /// with huge arrays. The derives are compared by their tokens, so `quote!(Debug)` doesn't remove `std::fmt::Debug`.
+  #[builder(default, setter(each(name = "add_removed_derive", into)))]
+  pub removed_derives: Vec<CustomDerive>,
+
   /// A vector of regular expressions and alignments that override the generated alignment for matching structs.
   /// This can be used in scenarios where a specific minimum alignment is required for a uniform buffer.
   /// Refer to the [WebGPU specs](https://www.w3.org/TR/webgpu/#dom-supported-limits-minuniformbufferoffsetalignment) for more information.
@@ -239,10 +935,246 @@ pub struct WgslBindgenOption {
   #[builder(default, setter(each(name = "add_custom_padding_field_regexp", into)))]
   pub custom_padding_field_regexps: Vec<Regex>,
 
+  /// The naming and visibility of the generated padding fields. Custom padding fields keep their name.
+  #[builder(default)]
+  pub padding_field: PaddingFieldConfig,
+
+  /// The labels of the wgpu objects created by the generated code. Defaults to [`LabelStyle::Default`].
+  #[builder(default)]
+  pub label_style: LabelStyle,
+
   /// Whether to always have the init struct generated in the out. This is only applicable when using bytemuck mode.
   #[builder(default = "false")]
   pub always_generate_init_struct: bool,
 
+  /// Whether to generate const assertions comparing the `encase::ShaderSize` of host shareable structs
+  /// with the WGSL layout. This is only applicable when using encase mode, as bytemuck always has assertions.
+  #[builder(default = "false")]
+  pub encase_layout_assertions: bool,
+
+  /// Whether to generate a `validate_layouts` function per module which checks the Rust struct layouts
+  /// against the WGSL layouts at runtime. This is only applicable when using bytemuck mode.
+  #[builder(default = "false")]
+  pub generate_layout_validation: bool,
+
+  /// Whether to generate const assertions checking that the Rust field types of vertex input structs
+  /// are at least as large as the `wgpu::VertexFormat` of the corresponding attributes.
+  #[builder(default = "false")]
+  pub vertex_format_assertions: bool,
+
+  /// Whether to replace `mat2x3<f32>`, `mat3x3<f32>` and `mat4x3<f32>` with the padded matrix types
+  /// (`PaddedMat3x3f` etc.) generated in a `padded_matrices` module instead of using the type map.
+  /// This is only applicable when using bytemuck mode, and guarantees the WGSL column padding
+  /// regardless of the type map in use. Only matrices are wrapped: a `vec3<f32>` field keeps its
+  /// 12 bytes, as the next field can be placed in its padding, and the padding after it is
+  /// generated in the struct.
+  #[builder(default = "false")]
+  pub padded_matrix_wrappers: bool,
+
+  /// Whether to generate `From` conversions between vertex input structs and tuples of their field types.
+  /// Combined with a type map such as [`GlamWgslTypeMap`], this allows building vertices directly from math library types.
+  #[builder(default = "false")]
+  pub vertex_struct_conversions: bool,
+
+  /// Whether to generate a `fragment_state` function and a `<entry>_entry` function per fragment entry point,
+  /// mirroring the vertex state helpers. The number of color targets is derived from the `@location` outputs of the entry.
+  #[builder(default = "false")]
+  pub generate_fragment_states: bool,
+
+  /// Whether to generate `<ENTRY>_COLOR_TARGET_COUNT`, `<ENTRY>_COLOR_TARGET_LOCATIONS` and
+  /// `<ENTRY>_DUAL_SOURCE_BLENDING` constants per fragment entry point, with the number of color targets, the
+  /// `@location`s of its outputs and whether they use dual-source blending. Fails when the module has WGSL
+  /// constants with these names.
+  #[builder(default = "false")]
+  pub fragment_target_constants: bool,
+
+  /// Whether to generate a `create_<vs>_<fs>_pipeline` function for every pair of vertex and fragment entry points
+  /// in a module. The functions wire up the vertex entry, fragment targets, pipeline layout and shader module, and take
+  /// a `<Vs><Fs>PipelineArgs` struct for the remaining render pipeline state.
+  #[builder(default = "false")]
+  pub generate_render_pipelines: bool,
+
+  /// Whether to generate a `REQUIRED_FEATURES` constant per module, with the `wgpu::Features` its
+  /// shader needs, eg: for dual-source blending or push constants, and a `check_device` function
+  /// returning the `MissingFeatures` of a device.
+  #[builder(default = "false")]
+  pub generate_required_features: bool,
+
+  /// Whether to generate `MAX_BIND_GROUPS_USED`, `MAX_VERTEX_BUFFERS_USED`, `MAX_VERTEX_ATTRIBUTES_USED`
+  /// and `PUSH_CONSTANT_SIZE` constants per module, to aggregate the limits to request a device with.
+  /// Fails when the module has WGSL constants with these names.
+  #[builder(default = "false")]
+  pub generate_used_limits: bool,
+
+  /// Whether the generated bind group `set` methods should take any pass implementing a generated `SetBindGroup` trait,
+  /// which is implemented for `wgpu::RenderPass`, `wgpu::ComputePass` and `wgpu::RenderBundleEncoder`.
+  #[builder(default = "false")]
+  pub generic_bind_group_pass: bool,
+
+  /// Whether to generate a `set_bind_group<N>` function per bind group, and a `WgpuBindGroupsPartial`
+  /// struct with optional bind groups whose `set` only sets the bind groups present, eg: for passes
+  /// which only rebind the bind groups changing per draw. Defaults to `false`.
+  #[builder(default = "false")]
+  pub partial_bind_groups: bool,
+
+  /// Whether to generate a `set_bind_groups_from` function next to `set_bind_groups`, taking an existing
+  /// `bind_groups::WgpuBindGroups` instead of each bind group. Defaults to `false`.
+  #[builder(default = "false")]
+  pub set_bind_groups_from: bool,
+
+  /// Whether to generate a `WgpuBindGroupOwned<N>` per bind group, which creates the bind group from
+  /// the `Arc`s of a `WgpuBindGroupResources<N>` and keeps them alive along with it. Buffers are bound
+  /// entirely. Defaults to `false`.
+  #[builder(default = "false")]
+  pub owned_bind_groups: bool,
+
+  /// Whether to generate a `WgpuBindGroup<N>Builder` per bind group, created with `WgpuBindGroup<N>::builder(device)`,
+  /// with a setter per binding and a `build` failing on the bindings which aren't set. Unlike the layout
+  /// struct literal, call sites only change for the bindings they set. Defaults to `false`.
+  #[builder(default = "false")]
+  pub bind_group_builders: bool,
+
+  /// Whether to generate a `ProvideBindGroup<N>` trait per bind group with a getter per binding, and
+  /// `WgpuBindGroup<N>::from_provider` to create the bind group from any implementation, eg: the
+  /// material or scene structs of an engine. Defaults to `false`.
+  #[builder(default = "false")]
+  pub bind_group_providers: bool,
+
+  /// The stages the bindings of the bind group layouts are visible to. Defaults to
+  /// `BindingVisibility::UnionOfModule`.
+  #[builder(default)]
+  pub binding_visibility: BindingVisibility,
+
+  /// Whether to generate a `dispatch_<entry>` function and a `<entry>_workgroup_count_for` const function per compute entry,
+  /// which compute the number of workgroups needed for a total number of invocations from the workgroup size.
+  #[builder(default = "false")]
+  pub compute_dispatch_helpers: bool,
+
+  /// Whether to generate `<ENTRY>_WORKGROUP_SIZE_X`, `_Y` and `_Z` and `<ENTRY>_WORKGROUP_INVOCATIONS` constants
+  /// next to `<ENTRY>_WORKGROUP_SIZE` in the compute module, for const contexts which need scalars.
+  #[builder(default = "false")]
+  pub workgroup_size_components: bool,
+
+  /// Whether to generate a `#[repr(C)]` `DispatchIndirectArgs` struct in the compute module, along with a
+  /// `<entry>_dispatch_indirect_args` const function per compute entry converting element counts into workgroup counts.
+  /// The struct implements `bytemuck::Pod` when using bytemuck mode.
+  #[builder(default = "false")]
+  pub dispatch_indirect_args: bool,
+
+  /// Whether to generate a `create_<entry>_pipeline_with_layout` function per compute entry, which takes an existing
+  /// shader module and an optional pipeline layout instead of creating them. Passing `None` uses the automatic layout of wgpu.
+  /// With `wgpu_edition` `WgpuVersion::V0_20` or later it also takes the `compilation_options`, and with `WgpuVersion::V22`
+  /// or later an optional pipeline `cache`.
+  #[builder(default = "false")]
+  pub compute_pipeline_with_layout: bool,
+
+  /// Whether to generate a `_with_cache` variant of the compute pipeline, render pipeline and `ShaderCache` pipeline
+  /// functions, which takes an optional `wgpu::PipelineCache` for driver pipeline caching. The functions without it
+  /// pass `None`. Requires `wgpu_edition` `WgpuVersion::V22` or later.
+  #[builder(default = "false")]
+  pub pipeline_cache_fns: bool,
+
+  /// Whether to generate a `ShaderCache` struct per module, which creates the shader module, the pipeline layout
+  /// and the compute pipelines once on first use, eg: `cache.main_pipeline(device)`. The shader module is created with
+  /// the first of `UseEmbed`, `SpirV`, `NagaIr` and `IncludeStrOriginal` in `shader_source_type`, and no cache is generated without one.
+  /// Render pipelines aren't cached, as they depend on the targets and state of the caller. The cache panics when used
+  /// with another device than the one it created its objects with.
+  #[builder(default = "false")]
+  pub shader_cache: bool,
+
+  /// Whether to generate an async `create_shader_module_checked` function per module, which returns the
+  /// `CompilationMessages` of the shader module when its compilation fails. Requires `wgpu_edition`
+  /// `WgpuVersion::V0_20` or later for `wgpu::ShaderModule::get_compilation_info`. Uses the same source
+  /// type as the `ShaderCache`.
+  #[builder(default = "false")]
+  pub checked_shader_modules: bool,
+
+  /// Whether to generate a `SHADER_HASH` constant per module, with the blake3 hash of the module's resolved
+  /// source as written by naga, eg: to key pipeline caches. It doesn't depend on `minify` or the paths of the files.
+  #[builder(default = "false")]
+  pub shader_hash: bool,
+
+  /// Whether to generate an `EntryPoint` enum per module with a variant for each entry point,
+  /// along with its `name` and `stage`.
+  #[builder(default = "false")]
+  pub entry_point_enum: bool,
+
+  /// Whether to generate structs which are defined identically in more than one entry module only once,
+  /// in a `shared_structs` module which is re-exported from each entry module. The fully qualified name
+  /// of these structs, used for matching overrides, is `shared_structs::<Name>`.
+  #[builder(default = "false")]
+  pub deduplicate_structs: bool,
+
+  /// Whether to reuse the bind group types of an earlier entry module for identical bind groups,
+  /// instead of generating them again. The reused types are re-exported from the `bind_groups` module,
+  /// so a single bind group can be set for pipelines created from different shaders.
+  #[builder(default = "false")]
+  pub deduplicate_bind_groups: bool,
+
+  /// The index of the bind group shared by all the shaders, eg: the global frame group of an engine.
+  /// It's generated once in the `global_bind_group` module and re-exported from the `bind_groups`
+  /// module of each entry using it, so `create_pipeline_layout` uses the same layout. The generation
+  /// fails when its bindings differ between entries. Defaults to `None`.
+  #[builder(default, setter(strip_option))]
+  pub global_bind_group: Option<u32>,
+
+  /// Whether to generate an `is_compatible_with` function for the bind groups, which checks that the
+  /// layout entries of the bind group match the given layout, eg: the same group of another shader.
+  /// The bind groups of different shaders are also compared when generating, implementing
+  /// `CompatibleBindGroup` for each pair with the same layout entries.
+  #[builder(default = "false")]
+  pub bind_group_compatibility: bool,
+
+  /// Whether to generate a `<NAME>_USAGES` constant for each buffer and texture binding, with the
+  /// minimal `wgpu::BufferUsages` or `wgpu::TextureUsages` needed to bind the resource.
+  #[builder(default = "false")]
+  pub binding_usages: bool,
+
+  /// Whether to generate a `<NAME>_BINDING` constant with the binding index of each binding in the
+  /// `impl` of its bind group, eg: to build bind groups manually.
+  #[builder(default = "false")]
+  pub binding_index_constants: bool,
+
+  /// Whether to generate a `BindGroupLayoutCache` struct in the `bind_groups` module of each entry, which
+  /// the caller keeps per device to create the layout of each bind group once. The layouts are used by
+  /// the generated `from_bindings_with_layout` and `create_pipeline_layout_cached` functions. The cache
+  /// panics when used with another device than the one it created the layouts with.
+  #[builder(default = "false")]
+  pub cached_bind_group_layouts: bool,
+
+  /// Whether to generate a `create_pipeline_layout_with` function taking an optional layout per
+  /// bind group, which is used instead of the generated layout, eg: for the layouts an engine
+  /// manages itself. Defaults to `false`.
+  #[builder(default = "false")]
+  pub pipeline_layout_overrides: bool,
+
+  /// A vector of `EntryPointOverride` to use different options for some of the entry points.
+  #[builder(default, setter(each(name = "add_entry_point_override", into)))]
+  pub entry_point_overrides: Vec<EntryPointOverride>,
+
+  /// When not empty, only the entry point functions matching one of these regexes get pipelines
+  /// and entries generated. The other entry points are still validated.
+  #[builder(default, setter(custom))]
+  pub include_entry_points: Vec<Regex>,
+
+  /// The entry point functions matching one of these regexes don't get pipelines and entries
+  /// generated, eg: debug-only entry points. They are still validated, but aren't embedded.
+  #[builder(default, setter(custom))]
+  pub exclude_entry_points: Vec<Regex>,
+
+  /// The bindings matching one of these regexes don't generate bind group structs, eg: a group
+  /// of global samplers created by the engine. The regexes are matched against the fully
+  /// qualified name of the binding, eg: `shader::globals_group`. All the bindings of a group
+  /// must be excluded, and `create_pipeline_layout` creates the group's layout from the shader.
+  #[builder(default, setter(custom))]
+  pub excluded_bindings: Vec<Regex>,
+
+  /// Names used instead of the group index in the names generated for a bind group, eg:
+  /// `WgpuBindGroupCamera` instead of `WgpuBindGroup0`. The index is still used to set the bind
+  /// group. A `// wgsl_bindgen: group=Camera` annotation on a binding takes precedence.
+  #[builder(default, setter(custom))]
+  pub bind_group_names: Vec<(u32, String)>,
+
   /// This field can be used to provide a custom generator for extra bindings that are not covered by the default generator.
   #[builder(default, setter(custom))]
   pub extra_binding_generator: Option<BindingGenerator>,
@@ -252,14 +1184,196 @@ pub struct WgslBindgenOption {
   pub wgpu_binding_generator: BindingGenerator,
 }
 
+impl WgslBindgenOption {
+  /// Whether code is generated for the entry point function with the given name.
+  pub(crate) fn is_entry_point_included(&self, name: &str) -> bool {
+    let included = self.include_entry_points.is_empty()
+      || self.include_entry_points.iter().any(|r| r.is_match(name));
+    included && !self.exclude_entry_points.iter().any(|r| r.is_match(name))
+  }
+
+  /// The options for the entry with the given module name, with the matching overrides applied.
+  pub(crate) fn for_entry(&self, entry_mod_name: &str) -> Cow<'_, Self> {
+    let mut overrides = self
+      .entry_point_overrides
+      .iter()
+      .filter(|o| o.entry_regex.is_match(entry_mod_name))
+      .peekable();
+    if overrides.peek().is_none() {
+      return Cow::Borrowed(self);
+    }
+
+    let mut options = self.clone();
+    for o in overrides {
+      if let Some(strategy) = o.serialization_strategy {
+        options.serialization_strategy = strategy;
+      }
+      if let Some(type_map) = &o.type_map {
+        options.type_map = type_map.clone();
+        options
+          .type_map
+          .extend(self.override_struct.iter().map(|mapping| {
+            let wgsl_type = WgslType::Struct {
+              fully_qualified_name: mapping.from.clone(),
+            };
+            (wgsl_type, mapping.to.clone())
+          }));
+        options.type_map.extend(self.type_map_overrides.clone());
+      }
+      options
+        .custom_derives
+        .extend(o.custom_derives.iter().cloned());
+      options
+        .removed_derives
+        .extend(o.removed_derives.iter().cloned());
+    }
+    Cow::Owned(options)
+  }
+
+  pub(crate) fn derive_serde_for(&self, fully_qualified_struct_name: &str) -> bool {
+    self.derive_serde
+      || self
+        .serde_struct_regexps
+        .iter()
+        .any(|r| r.is_match(fully_qualified_struct_name))
+  }
+
+  pub(crate) fn vertex_format_override(
+    &self,
+    fully_qualified_struct_name: &str,
+    field_name: &str,
+  ) -> Option<&OverrideVertexFormat> {
+    self.override_vertex_format.iter().find(|o| {
+      o.struct_regex.is_match(fully_qualified_struct_name)
+        && o.field_regex.is_match(field_name)
+    })
+  }
+
+  pub(crate) fn vertex_step_mode_for(
+    &self,
+    fully_qualified_struct_name: &str,
+  ) -> Option<wgpu::VertexStepMode> {
+    self
+      .vertex_step_modes
+      .iter()
+      .find(|o| o.struct_regex.is_match(fully_qualified_struct_name))
+      .map(|o| o.step_mode)
+  }
+
+  /// The `compilation_options` field of the generated pipeline stages, if any.
+  pub(crate) fn pipeline_compilation_options_field(&self) -> TokenStream {
+    if self.pipeline_compilation_options == PipelineCompilationOptions::default() {
+      return self.wgpu_edition.compilation_options_field();
+    }
+
+    let PipelineCompilationOptions {
+      zero_initialize_workgroup_memory,
+    } = self.pipeline_compilation_options;
+    quote::quote! {
+        compilation_options: wgpu::PipelineCompilationOptions {
+            zero_initialize_workgroup_memory: #zero_initialize_workgroup_memory,
+            ..Default::default()
+        },
+    }
+  }
+
+  pub(crate) fn vertex_stride_for(
+    &self,
+    fully_qualified_struct_name: &str,
+  ) -> Option<u64> {
+    self
+      .vertex_strides
+      .iter()
+      .find(|o| o.struct_regex.is_match(fully_qualified_struct_name))
+      .map(|o| o.stride)
+  }
+}
+
 impl WgslBindgenOptionBuilder {
   pub fn build(&mut self) -> Result<WGSLBindgen, WgslBindgenError> {
     self.merge_struct_type_overrides();
+    self.merge_type_map_overrides();
 
     let options = self.fallible_build()?;
     WGSLBindgen::new(options)
   }
 
+  /// Adds an entry point from source code instead of a file, eg: a shader generated by the build
+  /// script. `name` is its virtual path relative to the `workspace_root`, eg: `"sky.wgsl"`, which
+  /// names the generated module and is used to resolve its imports. Other shaders can import it like a file.
+  /// Only `WgslShaderSourceType::UseEmbed`, `WgslShaderSourceType::SpirV` and `WgslShaderSourceType::NagaIr`
+  /// are supported, as the other source types read the shader files.
+  pub fn add_entry_point_source(
+    &mut self,
+    name: &str,
+    source: impl Into<String>,
+  ) -> &mut Self {
+    self
+      .entry_point_sources
+      .get_or_insert_with(Vec::new)
+      .push((name.to_owned(), source.into()));
+    self
+  }
+
+  /// Only generates the entry point function named `name`, and the other included ones.
+  pub fn include_entry_point(&mut self, name: &str) -> &mut Self {
+    self.include_entry_points_matching(&format!("^{}$", regex::escape(name)))
+  }
+
+  /// Only generates the entry point functions matching `regex`, and the other included ones.
+  pub fn include_entry_points_matching(&mut self, regex: &str) -> &mut Self {
+    let regex = Regex::new(regex).expect("Failed to create entry point regex");
+    self
+      .include_entry_points
+      .get_or_insert_with(Vec::new)
+      .push(regex);
+    self
+  }
+
+  /// Skips generating the entry point function named `name`, eg: `"fs_debug_wireframe"`.
+  pub fn exclude_entry_point(&mut self, name: &str) -> &mut Self {
+    self.exclude_entry_points_matching(&format!("^{}$", regex::escape(name)))
+  }
+
+  /// Skips generating the entry point functions matching `regex`, eg: `"_debug_"`.
+  pub fn exclude_entry_points_matching(&mut self, regex: &str) -> &mut Self {
+    let regex = Regex::new(regex).expect("Failed to create entry point regex");
+    self
+      .exclude_entry_points
+      .get_or_insert_with(Vec::new)
+      .push(regex);
+    self
+  }
+
+  /// Excludes the bindings matching `pattern`, where `*` matches any characters, from the
+  /// generated bind groups, eg: `"*::globals_group"`.
+  pub fn exclude_binding(&mut self, pattern: &str) -> &mut Self {
+    let regex = format!("^{}$", regex::escape(pattern).replace(r"\*", ".*"));
+    self
+      .excluded_bindings
+      .get_or_insert_with(Vec::new)
+      .push(Regex::new(&regex).unwrap());
+    self
+  }
+
+  /// Names the bind group `group_no` in the generated code, eg: `name_bind_group(0, "Camera")`
+  /// generates `WgpuBindGroupCamera`.
+  pub fn name_bind_group(&mut self, group_no: u32, name: &str) -> &mut Self {
+    self
+      .bind_group_names
+      .get_or_insert_with(Vec::new)
+      .push((group_no, name.to_owned()));
+    self
+  }
+
+  /// Writes the output to `file_name` in the `OUT_DIR` of the build script, eg: `"shaders.rs"`.
+  /// Use together with `include_wrapper`, so the generated code doesn't need to be committed.
+  pub fn output_in_out_dir(&mut self, file_name: &str) -> &mut Self {
+    let out_dir =
+      std::env::var("OUT_DIR").expect("OUT_DIR is only set when running build scripts");
+    self.output(PathBuf::from(out_dir).join(file_name))
+  }
+
   pub fn type_map(&mut self, map_build: impl WgslTypeMapBuild) -> &mut Self {
     let serialization_strategy = self
       .serialization_strategy
@@ -275,6 +1389,47 @@ impl WgslBindgenOptionBuilder {
     self
   }
 
+  /// Maps the fully qualified WGSL struct `from`, eg: `shader::Camera`, to an existing Rust type
+  /// at `to`, eg: `crate::render::Camera`. The struct will not be generated and all references
+  /// to it use the given path instead.
+  /// Resolves imports of `module_path`, eg: `#import pbr::lighting`, to `file` regardless
+  /// of where it's located, eg: `alias("pbr::lighting", "shaders/lib/lighting.wgsl")`.
+  pub fn alias(&mut self, module_path: &str, file: impl Into<PathBuf>) -> &mut Self {
+    self
+      .import_aliases
+      .get_or_insert_with(Default::default)
+      .insert(module_path.to_owned(), file.into());
+    self
+  }
+
+  pub fn override_struct_path(&mut self, from: &str, to: &str) -> &mut Self {
+    self.add_override_struct_mapping((from, to))
+  }
+
+  /// Layers the given mappings on top of the base `type_map`, replacing any existing entries.
+  /// This is useful to keep a built-in map like [`GlamWgslTypeMap`] while customizing a few types.
+  pub fn override_type_map(
+    &mut self,
+    overrides: impl IntoIterator<Item = (WgslType, TokenStream)>,
+  ) -> &mut Self {
+    self
+      .type_map_overrides
+      .get_or_insert_with(Default::default)
+      .extend(overrides);
+    self
+  }
+
+  fn merge_type_map_overrides(&mut self) {
+    let Some(overrides) = self.type_map_overrides.as_ref() else {
+      return;
+    };
+
+    self
+      .type_map
+      .get_or_insert_with(Default::default)
+      .extend(overrides.clone());
+  }
+
   fn merge_struct_type_overrides(&mut self) {
     let struct_mappings = self
       .override_struct
@@ -291,6 +1446,34 @@ impl WgslBindgenOptionBuilder {
     self.type_map(struct_mappings);
   }
 
+  /// Adds hooks which emit additional items into the generated code, see [`CodegenHooks`].
+  pub fn add_codegen_hooks(&mut self, hooks: impl CodegenHooks + 'static) -> &mut Self {
+    self
+      .codegen_hooks
+      .get_or_insert_with(Vec::new)
+      .push(Arc::new(hooks));
+    self
+  }
+
+  /// Appends hand written code, either a `TokenStream` or a string of Rust code, to the
+  /// generated module at `module_path`, eg: `"pbr"` for the entry `pbr.wgsl` or `"types"`
+  /// for an imported module.
+  pub fn append_to_module(
+    &mut self,
+    module_path: &str,
+    code: impl ToString,
+  ) -> &mut Self {
+    let code = code
+      .to_string()
+      .parse::<TokenStream>()
+      .unwrap_or_else(|err| panic!("Invalid code appended to `{module_path}`: {err}"));
+    self
+      .module_additions
+      .get_or_insert_with(Vec::new)
+      .push((module_path.to_owned(), code));
+    self
+  }
+
   pub fn extra_binding_generator(
     &mut self,
     config: impl GetBindingsGeneratorConfig,
@@ -300,3 +1483,118 @@ impl WgslBindgenOptionBuilder {
     self
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use quote::quote;
+
+  use super::*;
+  use crate::WgslVecType;
+
+  #[test]
+  fn label_style_labels() {
+    assert_eq!(
+      LabelStyle::Default.label("::BindGroup0"),
+      Some("::BindGroup0".to_owned())
+    );
+    assert_eq!(
+      LabelStyle::Prefix("App".to_owned()).label("::BindGroup0"),
+      Some("App::BindGroup0".to_owned())
+    );
+    assert_eq!(LabelStyle::Disabled.label("::BindGroup0"), None);
+    assert_eq!(
+      LabelStyle::Custom(|label| Some(label.to_lowercase())).label("::BindGroup0"),
+      Some("::bindgroup0".to_owned())
+    );
+  }
+
+  #[test]
+  fn type_map_overrides_are_applied_after_base_map() {
+    let mut builder = WgslBindgenOptionBuilder::default();
+    builder
+      .serialization_strategy(WgslTypeSerializeStrategy::Bytemuck)
+      .override_type_map([(WgslType::Vector(WgslVecType::Vec4f), quote!(MyVec4))])
+      .type_map(GlamWgslTypeMap);
+
+    builder.merge_type_map_overrides();
+
+    let type_map = builder.type_map.unwrap();
+    assert_eq!(
+      type_map[&WgslType::Vector(WgslVecType::Vec4f)].to_string(),
+      quote!(MyVec4).to_string()
+    );
+    assert_eq!(
+      type_map[&WgslType::Vector(WgslVecType::Vec3f)].to_string(),
+      quote!(glam::Vec3A).to_string()
+    );
+  }
+
+  #[test]
+  fn entry_point_overrides_apply_to_matching_entries() {
+    let options = WgslBindgenOption {
+      serialization_strategy: WgslTypeSerializeStrategy::Encase,
+      type_map: GlamWgslTypeMap.build(WgslTypeSerializeStrategy::Encase),
+      entry_point_overrides: vec![EntryPointOverride::new("^compute_.*")
+        .serialization_strategy(WgslTypeSerializeStrategy::Bytemuck)
+        .type_map(RustWgslTypeMap)
+        .add_custom_derive((".*", quote!(Hash)))],
+      ..Default::default()
+    };
+
+    let render = options.for_entry("render_main");
+    assert!(matches!(render, Cow::Borrowed(_)));
+    assert_eq!(render.serialization_strategy, WgslTypeSerializeStrategy::Encase);
+
+    let compute = options.for_entry("compute_particles");
+    assert_eq!(compute.serialization_strategy, WgslTypeSerializeStrategy::Bytemuck);
+    assert!(!compute
+      .type_map
+      .contains_key(&WgslType::Vector(WgslVecType::Vec4f)));
+    assert_eq!(compute.custom_derives.len(), 1);
+  }
+
+  #[test]
+  fn entry_point_filters() {
+    let mut builder = WgslBindgenOptionBuilder::default();
+    builder
+      .include_entry_points_matching("^fs_")
+      .exclude_entry_point("fs_debug_wireframe");
+    let options = WgslBindgenOption {
+      include_entry_points: builder.include_entry_points.take().unwrap(),
+      exclude_entry_points: builder.exclude_entry_points.take().unwrap(),
+      ..Default::default()
+    };
+
+    assert!(options.is_entry_point_included("fs_main"));
+    assert!(options.is_entry_point_included("fs_debug_wireframe_2"));
+    assert!(!options.is_entry_point_included("fs_debug_wireframe"));
+    assert!(!options.is_entry_point_included("vs_main"));
+  }
+
+  #[test]
+  fn exclude_binding_patterns() {
+    let mut builder = WgslBindgenOptionBuilder::default();
+    builder.exclude_binding("*::globals_group");
+    let patterns = builder.excluded_bindings.unwrap();
+
+    assert!(patterns[0].is_match("shader::globals_group"));
+    assert!(patterns[0].is_match("imports::globals::globals_group"));
+    assert!(!patterns[0].is_match("shader::globals_group_2"));
+  }
+
+  #[test]
+  fn override_struct_path_maps_to_user_type() {
+    let mut builder = WgslBindgenOptionBuilder::default();
+    builder
+      .serialization_strategy(WgslTypeSerializeStrategy::Bytemuck)
+      .override_struct_path("shader::Camera", "crate::render::Camera");
+
+    builder.merge_struct_type_overrides();
+
+    let type_map = builder.type_map.unwrap();
+    let camera = WgslType::Struct {
+      fully_qualified_name: "shader::Camera".into(),
+    };
+    assert_eq!(type_map[&camera].to_string(), quote!(crate::render::Camera).to_string());
+  }
+}
@@ -21,28 +21,10 @@ impl<'a> BindGroupLayoutBuilder<'a> {
       .iter()
       .map(|binding| {
         let binding_index = binding.binding_index as usize;
-        let demangled_name = RustItemPath::from_mangled(
-          binding.name.as_ref().unwrap(),
-          self.invoking_entry_module,
-        );
-        let binding_name = Ident::new(&demangled_name.item_name, Span::call_site());
+        let binding_name = Ident::new(&binding.field_name(), Span::call_site());
         let binding_var = quote!(#binding_var_name.#binding_name);
 
-        match binding.binding_type.inner {
-          naga::TypeInner::Scalar(_)
-          | naga::TypeInner::Struct { .. }
-          | naga::TypeInner::Array { .. } => {
-            entry_cons(binding_index, binding_var, BindResourceType::Buffer)
-          }
-          naga::TypeInner::Image { .. } => {
-            entry_cons(binding_index, binding_var, BindResourceType::Texture)
-          }
-          naga::TypeInner::Sampler { .. } => {
-            entry_cons(binding_index, binding_var, BindResourceType::Sampler)
-          }
-          // TODO: Better error handling.
-          _ => panic!("Failed to generate BindingType."),
-        }
+        entry_cons(binding_index, binding_var, binding.resource_type())
       })
       .collect()
   }
@@ -57,25 +39,29 @@ impl<'a> BindGroupLayoutBuilder<'a> {
           binding.name.as_ref().unwrap(),
           self.invoking_entry_module,
         );
-        let field_name = format_ident!("{}", &rust_item_path.item_name.as_str());
-
-        // TODO: Support more types.
-        let resource_type = match binding.binding_type.inner {
-          naga::TypeInner::Struct { .. } => BindResourceType::Buffer,
-          naga::TypeInner::Image { .. } => BindResourceType::Texture,
-          naga::TypeInner::Sampler { .. } => BindResourceType::Sampler,
-          naga::TypeInner::Array { .. } => BindResourceType::Buffer,
-          naga::TypeInner::Scalar(_) => BindResourceType::Buffer,
-          _ => panic!("Unsupported type for binding fields."),
-        };
+        let field_name = format_ident!("{}", binding.field_name());
 
-        let field_type = self.generator.binding_type_map[&resource_type].clone();
+        let field_type =
+          self.generator.binding_type_map[&binding.resource_type()].clone();
+        let doc = format!(
+          " `@group({}) @binding({}) var{} {}: {}`",
+          self.group_no,
+          binding.binding_index,
+          wgsl::address_space_to_wgsl(binding.address_space),
+          rust_item_path.item_name,
+          binding.binding_type_name
+        );
 
-        quote!(pub #field_name: #field_type)
+        quote! {
+          #[doc = #doc]
+          pub #field_name: #field_type
+        }
       })
       .collect();
 
-    let name = indexed_name_ident(&self.generator.layout_prefix_name, self.group_no);
+    let name = self
+      .data
+      .name_ident(&self.generator.layout_prefix_name, self.group_no);
     let entries = self.entries(format_ident!("self"));
     let entries_length = Index::from(entries.len() as usize);
     let entry_struct_type = self.generator.entry_struct_type.clone();
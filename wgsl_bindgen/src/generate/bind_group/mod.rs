@@ -1,9 +1,16 @@
-use std::collections::BTreeMap;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::num::NonZeroU32;
 
 use derive_more::Constructor;
 use quote::{format_ident, quote};
 
+use crate::quote_gen::{
+  alloc_crate, core_crate, mod_reference_root, RustItemPath, MOD_BIND_GROUP_PASS,
+  MOD_GLOBAL_BIND_GROUP,
+};
 use crate::wgsl::buffer_binding_type;
+use crate::wgsl_docs::{WgslAnnotation, WgslDocs};
 use crate::*;
 
 mod layout_builder;
@@ -11,13 +18,82 @@ use layout_builder::*;
 
 pub struct GroupData<'a> {
   pub bindings: Vec<GroupBinding<'a>>,
+  /// The name used instead of the group index in the generated names, eg: `WgpuBindGroupCamera`.
+  pub name: Option<String>,
+}
+
+impl GroupData<'_> {
+  /// The generated name of the group with the given prefix, eg: `WgpuBindGroup0` or `WgpuBindGroupCamera`.
+  pub fn name_ident(&self, prefix: &str, group_no: u32) -> Ident {
+    match &self.name {
+      Some(name) => format_ident!("{prefix}{}", sanitize_and_pascal_case(name)),
+      None => indexed_name_ident(prefix, group_no),
+    }
+  }
+
+  /// The field of the group in `WgpuBindGroups`, eg: `bind_group0` or `bind_group_camera`.
+  pub fn field_ident(&self, group_no: u32) -> Ident {
+    match &self.name {
+      Some(name) => {
+        format_ident!("bind_group_{}", sanitize_and_pascal_case(name).to_snake())
+      }
+      None => indexed_name_ident("bind_group", group_no),
+    }
+  }
 }
 
 pub struct GroupBinding<'a> {
   pub name: Option<String>,
   pub binding_index: u32,
+  /// The type bound, or the element type for a `binding_array`.
   pub binding_type: &'a naga::Type,
+  /// The number of elements of a `binding_array`.
+  pub count: Option<NonZeroU32>,
+  pub binding_type_name: String,
   pub address_space: naga::AddressSpace,
+  pub annotation: WgslAnnotation,
+  /// The stages of the entry points using the binding.
+  pub stages: wgpu::ShaderStages,
+}
+
+impl GroupBinding<'_> {
+  /// The name of the binding's fields and methods, the `name` annotation if there is one.
+  pub fn field_name(&self) -> String {
+    match &self.annotation.name {
+      Some(name) => name.clone(),
+      None => RustItemPath::from_mangled(self.name.as_ref().unwrap(), "")
+        .item_name
+        .to_string(),
+    }
+  }
+
+  /// The kind of resource bound, for bindings checked by [`check_binding_types`].
+  pub fn resource_type(&self) -> BindResourceType {
+    let resource_type = bind_resource_type(&self.binding_type.inner)
+      .expect("binding types are checked by `check_binding_types`");
+    match (self.count, resource_type) {
+      (None, _) => resource_type,
+      (Some(_), BindResourceType::Buffer) => BindResourceType::BufferArray,
+      (Some(_), BindResourceType::Sampler) => BindResourceType::SamplerArray,
+      (Some(_), BindResourceType::Texture) => BindResourceType::TextureArray,
+      (Some(_), array) => array,
+    }
+  }
+}
+
+/// The kind of resource bound for a type, or `None` when it can't be bound.
+fn bind_resource_type(ty: &naga::TypeInner) -> Option<BindResourceType> {
+  match ty {
+    naga::TypeInner::Scalar(_)
+    | naga::TypeInner::Vector { .. }
+    | naga::TypeInner::Matrix { .. }
+    | naga::TypeInner::Atomic(_)
+    | naga::TypeInner::Struct { .. }
+    | naga::TypeInner::Array { .. } => Some(BindResourceType::Buffer),
+    naga::TypeInner::Image { .. } => Some(BindResourceType::Texture),
+    naga::TypeInner::Sampler { .. } => Some(BindResourceType::Sampler),
+    _ => None,
+  }
 }
 
 #[derive(Constructor)]
@@ -26,6 +102,7 @@ struct BindGroupBuilder<'a> {
   group_no: u32,
   data: &'a GroupData<'a>,
   shader_stages: wgpu::ShaderStages,
+  options: &'a WgslBindgenOption,
   wgpu_generator: &'a BindGroupLayoutGenerator,
 }
 
@@ -35,15 +112,28 @@ impl<'a> BindGroupBuilder<'a> {
       .data
       .bindings
       .iter()
-      .map(|binding| bind_group_layout_entry(binding, self.shader_stages))
+      .map(|binding| {
+        let visibility = match self.options.binding_visibility {
+          BindingVisibility::UnionOfModule => shader_stages_tokens(self.shader_stages),
+          // Bindings which aren't used by any entry point are visible to the whole module.
+          BindingVisibility::Inferred if binding.stages.is_empty() => {
+            shader_stages_tokens(self.shader_stages)
+          }
+          BindingVisibility::Inferred => shader_stages_tokens(binding.stages),
+          BindingVisibility::All => quote!(wgpu::ShaderStages::all()),
+        };
+        bind_group_layout_entry(binding, visibility, &core_crate(self.options))
+      })
       .collect();
 
-    let bind_group_label =
-      format!("{}::BindGroup{}::LayoutDescriptor", self.entry_name, self.group_no);
+    let bind_group_label = self.options.label_style.label_tokens(&format!(
+      "{}::BindGroup{}::LayoutDescriptor",
+      self.entry_name, self.group_no
+    ));
 
     quote! {
         wgpu::BindGroupLayoutDescriptor {
-            label: Some(#bind_group_label),
+            label: #bind_group_label,
             entries: &[
                 #(#entries),*
             ],
@@ -52,30 +142,127 @@ impl<'a> BindGroupBuilder<'a> {
   }
 
   fn struct_name(&self) -> syn::Ident {
-    indexed_name_ident("WgpuBindGroup", self.group_no)
+    self.data.name_ident("WgpuBindGroup", self.group_no)
+  }
+
+  fn layout_name(&self) -> syn::Ident {
+    self
+      .data
+      .name_ident(&self.wgpu_generator.layout_prefix_name, self.group_no)
   }
 
   fn bind_group_struct_impl(&self) -> TokenStream {
-    // TODO: Support compute shader with vertex/fragment in the same module?
-    let is_compute = self.shader_stages == wgpu::ShaderStages::COMPUTE;
+    let group_no = Index::from(self.group_no as usize);
+    let dynamic_offsets = self
+      .data
+      .bindings
+      .iter()
+      .filter(|binding| binding.annotation.dynamic_offset)
+      .count();
+    let set_fns: Vec<_> = pass_types(self.shader_stages, self.options)
+      .into_iter()
+      .map(|(set_name, render_pass)| {
+        if dynamic_offsets == 0 {
+          return quote! {
+              pub fn #set_name<'a>(&'a self, render_pass: &mut #render_pass) {
+                  render_pass.set_bind_group(#group_no, &self.0, &[]);
+              }
+          };
+        }
 
-    let render_pass = if is_compute {
-      quote!(wgpu::ComputePass<'a>)
-    } else {
-      quote!(wgpu::RenderPass<'a>)
-    };
+        // The offsets of the bindings annotated with `dynamic_offset`, by binding index.
+        let offsets_len = Index::from(dynamic_offsets);
+        let set_with_offsets = format_ident!("{set_name}_with_offsets");
+        quote! {
+            pub fn #set_name<'a>(&'a self, render_pass: &mut #render_pass) {
+                self.#set_with_offsets(render_pass, &[0; #offsets_len]);
+            }
+
+            pub fn #set_with_offsets<'a>(
+                &'a self,
+                render_pass: &mut #render_pass,
+                offsets: &[wgpu::DynamicOffset; #offsets_len],
+            ) {
+                render_pass.set_bind_group(#group_no, &self.0, offsets);
+            }
+        }
+      })
+      .collect();
 
     let bind_group_name = self.struct_name();
-    let bind_group_layout_name =
-      indexed_name_ident(&self.wgpu_generator.layout_prefix_name, self.group_no);
+    let bind_group_layout_name = self.layout_name();
 
     let bind_group_layout_descriptor = self.bind_group_layout_descriptor();
+    let binding_consts: Vec<_> = self
+      .data
+      .bindings
+      .iter()
+      .map(|binding| {
+        let name = binding.field_name();
+        let index = if self.options.binding_index_constants {
+          let const_name = format_ident!("{}_BINDING", sanitized_upper_snake_case(&name));
+          let binding_index = Literal::u32_unsuffixed(binding.binding_index);
+          quote!(pub const #const_name: u32 = #binding_index;)
+        } else {
+          quote!()
+        };
+        let usages = match binding_usages(binding) {
+          Some((usages_type, usages)) if self.options.binding_usages => {
+            let usages_name =
+              format_ident!("{}_USAGES", sanitized_upper_snake_case(&name));
+            quote!(pub const #usages_name: #usages_type = #usages;)
+          }
+          _ => quote!(),
+        };
+        quote! {
+            #index
+            #usages
+        }
+      })
+      .collect();
 
-    let group_no = Index::from(self.group_no as usize);
-    let bind_group_label = format!("{}::BindGroup{}", self.entry_name, self.group_no);
+    let bind_group_label = self
+      .options
+      .label_style
+      .label_tokens(&format!("{}::BindGroup{}", self.entry_name, self.group_no));
+
+    let compatibility_fn = if self.options.bind_group_compatibility {
+      quote! {
+          /// Whether this bind group can be used in place of a bind group with the given layout,
+          /// such as the `LAYOUT_DESCRIPTOR` of a bind group from another shader.
+          pub fn is_compatible_with(layout: &wgpu::BindGroupLayoutDescriptor) -> bool {
+              Self::LAYOUT_DESCRIPTOR.entries == layout.entries
+          }
+      }
+    } else {
+      quote!()
+    };
+
+    let from_bindings_with_layout_fn = if self.options.cached_bind_group_layouts {
+      quote! {
+          /// Like `from_bindings`, with an existing layout such as the one of a `BindGroupLayoutCache`.
+          pub fn from_bindings_with_layout(
+              device: &wgpu::Device,
+              layout: &wgpu::BindGroupLayout,
+              bindings: #bind_group_layout_name,
+          ) -> Self {
+              let entries = bindings.entries();
+              let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                  label: #bind_group_label,
+                  layout,
+                  entries: &entries,
+              });
+              Self(bind_group)
+          }
+      }
+    } else {
+      quote!()
+    };
 
     quote! {
         impl #bind_group_name {
+            #(#binding_consts)*
+
             pub const LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> = #bind_group_layout_descriptor;
 
             pub fn get_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
@@ -86,15 +273,204 @@ impl<'a> BindGroupBuilder<'a> {
                 let bind_group_layout = Self::get_bind_group_layout(&device);
                 let entries = bindings.entries();
                 let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                    label: Some(#bind_group_label),
+                    label: #bind_group_label,
                     layout: &bind_group_layout,
                     entries: &entries,
                 });
                 Self(bind_group)
             }
 
-            pub fn set<'a>(&'a self, render_pass: &mut #render_pass) {
-                render_pass.set_bind_group(#group_no, &self.0, &[]);
+            #from_bindings_with_layout_fn
+
+            #compatibility_fn
+
+            #(#set_fns)*
+        }
+    }
+  }
+
+  /// The owned variant of the bind group, keeping the resources it's created from alive.
+  fn owned_bind_group(&self) -> TokenStream {
+    if !self.options.owned_bind_groups {
+      return quote!();
+    }
+
+    let alloc = alloc_crate(self.options);
+    let core = core_crate(self.options);
+    let bind_group_name = self.struct_name();
+    let bind_group_layout_name = self.layout_name();
+    let resources_name = self
+      .data
+      .name_ident("WgpuBindGroupResources", self.group_no);
+    let owned_name = self.data.name_ident("WgpuBindGroupOwned", self.group_no);
+
+    let mut fields = Vec::new();
+    let mut bindings = Vec::new();
+    // The binding arrays borrow a slice of their elements, collected before creating the bind group.
+    let mut array_slices = Vec::new();
+    for binding in &self.data.bindings {
+      let field_name = format_ident!("{}", binding.field_name());
+      let mut array_slice = |element: TokenStream| {
+        array_slices.push(quote! {
+            let #field_name: #alloc::vec::Vec<_> =
+                resources.#field_name.iter().map(#element).collect();
+        });
+        quote!(&#field_name)
+      };
+      let (resource_type, binding) = match binding.resource_type() {
+        BindResourceType::Buffer => (
+          quote!(#alloc::sync::Arc<wgpu::Buffer>),
+          quote!(resources.#field_name.as_entire_buffer_binding()),
+        ),
+        BindResourceType::Sampler => {
+          (quote!(#alloc::sync::Arc<wgpu::Sampler>), quote!(&resources.#field_name))
+        }
+        BindResourceType::Texture => {
+          (quote!(#alloc::sync::Arc<wgpu::TextureView>), quote!(&resources.#field_name))
+        }
+        BindResourceType::BufferArray => (
+          quote!(#alloc::vec::Vec<#alloc::sync::Arc<wgpu::Buffer>>),
+          array_slice(quote!(|buffer| buffer.as_entire_buffer_binding())),
+        ),
+        BindResourceType::SamplerArray => (
+          quote!(#alloc::vec::Vec<#alloc::sync::Arc<wgpu::Sampler>>),
+          array_slice(quote!(|sampler| &**sampler)),
+        ),
+        BindResourceType::TextureArray => (
+          quote!(#alloc::vec::Vec<#alloc::sync::Arc<wgpu::TextureView>>),
+          array_slice(quote!(|view| &**view)),
+        ),
+      };
+      fields.push(quote!(pub #field_name: #resource_type));
+      bindings.push(quote!(#field_name: #binding));
+    }
+
+    quote! {
+        #[derive(Debug, Clone)]
+        pub struct #resources_name {
+            #(#fields),*
+        }
+
+        #[derive(Debug)]
+        pub struct #owned_name {
+            pub bind_group: #bind_group_name,
+            pub resources: #resources_name,
+        }
+
+        impl #owned_name {
+            pub fn from_resources(device: &wgpu::Device, resources: #resources_name) -> Self {
+                #(#array_slices)*
+                let bind_group = #bind_group_name::from_bindings(
+                    device,
+                    #bind_group_layout_name {
+                        #(#bindings),*
+                    },
+                );
+                Self { bind_group, resources }
+            }
+        }
+
+        impl #core::ops::Deref for #owned_name {
+            type Target = #bind_group_name;
+
+            fn deref(&self) -> &Self::Target {
+                &self.bind_group
+            }
+        }
+    }
+  }
+
+  /// The builder of the bind group, with a setter per binding.
+  fn bind_group_builder(&self) -> TokenStream {
+    if !self.options.bind_group_builders {
+      return quote!();
+    }
+
+    let bind_group_name = self.struct_name();
+    let builder_name = format_ident!("{}Builder", bind_group_name);
+    let bind_group_layout_name = self.layout_name();
+
+    let mut member_names = Vec::new();
+    let mut builder_members = Vec::new();
+    let mut setters = Vec::new();
+    let mut member_assignments = Vec::new();
+    for binding in &self.data.bindings {
+      let name = format_ident!("{}", binding.field_name());
+      let ty = &self.wgpu_generator.binding_type_map[&binding.resource_type()];
+      let missing = format!("{}::{} is not set", bind_group_name, name);
+
+      builder_members.push(quote!(#name: Option<#ty>));
+      setters.push(quote! {
+          pub fn #name(mut self, #name: #ty) -> Self {
+              self.#name = Some(#name);
+              self
+          }
+      });
+      member_assignments.push(quote!(#name: self.#name.ok_or(#missing)?));
+      member_names.push(name);
+    }
+
+    quote! {
+        #[derive(Debug)]
+        pub struct #builder_name<'a> {
+            device: &'a wgpu::Device,
+            #(#builder_members),*
+        }
+
+        impl<'a> #builder_name<'a> {
+            #(#setters)*
+
+            pub fn build(self) -> Result<#bind_group_name, &'static str> {
+                let bindings = #bind_group_layout_name {
+                    #(#member_assignments),*
+                };
+                Ok(#bind_group_name::from_bindings(self.device, bindings))
+            }
+        }
+
+        impl #bind_group_name {
+            pub fn builder(device: &wgpu::Device) -> #builder_name<'_> {
+                #builder_name {
+                    device,
+                    #(#member_names: None),*
+                }
+            }
+        }
+    }
+  }
+
+  /// The trait providing the bindings of the bind group, and the function creating it from the trait.
+  fn bind_group_provider(&self) -> TokenStream {
+    if !self.options.bind_group_providers {
+      return quote!();
+    }
+
+    let bind_group_name = self.struct_name();
+    let provider_name = self.data.name_ident("ProvideBindGroup", self.group_no);
+    let bind_group_layout_name = self.layout_name();
+
+    let (getters, bindings): (Vec<_>, Vec<_>) = self
+      .data
+      .bindings
+      .iter()
+      .map(|binding| {
+        let name = format_ident!("{}", binding.field_name());
+        let ty = &self.wgpu_generator.binding_type_map[&binding.resource_type()];
+        (quote!(fn #name<'a>(&'a self) -> #ty;), quote!(#name: provider.#name()))
+      })
+      .unzip();
+
+    quote! {
+        pub trait #provider_name {
+            #(#getters)*
+        }
+
+        impl #bind_group_name {
+            pub fn from_provider(device: &wgpu::Device, provider: &impl #provider_name) -> Self {
+                let bindings = #bind_group_layout_name {
+                    #(#bindings),*
+                };
+                Self::from_bindings(device, bindings)
             }
         }
     }
@@ -109,12 +485,274 @@ impl<'a> BindGroupBuilder<'a> {
     };
 
     let group_impl = self.bind_group_struct_impl();
+    let owned_bind_group = self.owned_bind_group();
+    let bind_group_builder = self.bind_group_builder();
+    let bind_group_provider = self.bind_group_provider();
 
     quote! {
         #group_struct
         #group_impl
+        #owned_bind_group
+        #bind_group_builder
+        #bind_group_provider
+    }
+  }
+}
+
+/// Generates the layout structs and the bind group struct of a single bind group.
+fn bind_group_items(
+  invoking_entry_module: &str,
+  entry_name: &str,
+  group_no: u32,
+  group: &GroupData,
+  shader_stages: wgpu::ShaderStages,
+  options: &WgslBindgenOption,
+) -> TokenStream {
+  let wgpu_generator = &options.wgpu_binding_generator;
+
+  let wgpu_layout = BindGroupLayoutBuilder::new(
+    invoking_entry_module,
+    group_no,
+    group,
+    &wgpu_generator.bind_group_layout,
+  )
+  .build();
+
+  let additional_layout =
+    if let Some(additional_generator) = &options.extra_binding_generator {
+      BindGroupLayoutBuilder::new(
+        invoking_entry_module,
+        group_no,
+        group,
+        &additional_generator.bind_group_layout,
+      )
+      .build()
+    } else {
+      quote!()
+    };
+
+  let bindgroup = BindGroupBuilder::new(
+    entry_name,
+    group_no,
+    group,
+    shader_stages,
+    options,
+    &wgpu_generator.bind_group_layout,
+  )
+  .build();
+
+  quote! {
+    #additional_layout
+    #wgpu_layout
+    #bindgroup
+  }
+}
+
+/// Re-exports the types of a bind group generated in the `bind_groups` module of another entry,
+/// or in the module of the global bind group.
+fn reexported_bind_group_items(
+  owner_module: &str,
+  group_no: u32,
+  group: &GroupData,
+  options: &WgslBindgenOption,
+) -> TokenStream {
+  let root = mod_reference_root();
+  let items_module: syn::Path = if owner_module == MOD_GLOBAL_BIND_GROUP {
+    format_ident!("{MOD_GLOBAL_BIND_GROUP}").into()
+  } else {
+    syn::parse_str::<syn::Path>(&format!("{owner_module}::bind_groups")).unwrap()
+  };
+
+  let bind_group_name = group.name_ident("WgpuBindGroup", group_no);
+  let mut names = vec![
+    group.name_ident(
+      &options
+        .wgpu_binding_generator
+        .bind_group_layout
+        .layout_prefix_name,
+      group_no,
+    ),
+    bind_group_name.clone(),
+  ];
+  if options.owned_bind_groups {
+    names.push(group.name_ident("WgpuBindGroupResources", group_no));
+    names.push(group.name_ident("WgpuBindGroupOwned", group_no));
+  }
+  if options.bind_group_builders {
+    names.push(format_ident!("{bind_group_name}Builder"));
+  }
+  if options.bind_group_providers {
+    names.push(group.name_ident("ProvideBindGroup", group_no));
+  }
+  if let Some(additional_generator) = &options.extra_binding_generator {
+    names.push(
+      group
+        .name_ident(&additional_generator.bind_group_layout.layout_prefix_name, group_no),
+    );
+  }
+
+  quote! {
+    pub use #root::#items_module::{#(#names),*};
+  }
+}
+
+/// Generates the `WgslBindgenOption::global_bind_group` shared by all the entries using it, and
+/// marks it as shared in `shared_groups` so the entries re-export it. The group is visible to the
+/// stages of all these entries, and its bindings must be the same in each of them.
+pub fn global_bind_group_items(
+  entries: &[(&str, &BTreeMap<u32, GroupData>, wgpu::ShaderStages)],
+  shared_groups: &mut [BTreeMap<u32, String>],
+  options: &WgslBindgenOption,
+) -> Result<Option<TokenStream>, CreateModuleError> {
+  let Some(group_no) = options.global_bind_group else {
+    return Ok(None);
+  };
+  // The items would be merged into the module of the entry.
+  if let Some((module, ..)) = entries
+    .iter()
+    .find(|(module, ..)| *module == MOD_GLOBAL_BIND_GROUP)
+  {
+    return Err(CreateModuleError::ReservedModuleName {
+      module: module.to_string(),
+    });
+  }
+
+  // The layout entries without the visibility, which depends on the stages of each entry.
+  let layout_entries = |group: &GroupData| {
+    let mut entries: Vec<_> = group
+      .bindings
+      .iter()
+      .map(|binding| {
+        let entry = bind_group_layout_entry(binding, quote!(), &core_crate(options));
+        (binding.binding_index, binding.field_name(), entry.to_string())
+      })
+      .collect();
+    entries.sort();
+    entries
+  };
+
+  let mut global: Option<(&str, &GroupData, Vec<_>)> = None;
+  let mut shader_stages = wgpu::ShaderStages::NONE;
+  for (i, (module, bind_group_data, stages)) in entries.iter().enumerate() {
+    let Some(group) = bind_group_data.get(&group_no) else {
+      continue;
+    };
+    match &global {
+      Some((global_module, _, global_entries)) => {
+        if layout_entries(group) != *global_entries {
+          return Err(CreateModuleError::GlobalBindGroupMismatch {
+            module: module.to_string(),
+            global_module: global_module.to_string(),
+            group: group_no,
+          });
+        }
+      }
+      None => global = Some((*module, group, layout_entries(group))),
     }
+    shader_stages |= *stages;
+    shared_groups[i].insert(group_no, MOD_GLOBAL_BIND_GROUP.to_string());
+  }
+
+  Ok(global.map(|(module, group, _)| {
+    bind_group_items(module, "Global", group_no, group, shader_stages, options)
+  }))
+}
+
+/// Finds the bind groups which are identical to a bind group of an earlier entry.
+/// For each entry, this maps the group index to the module of the earliest entry with the same bind group.
+pub fn shared_bind_group_owners(
+  entries: &[(&str, &BTreeMap<u32, GroupData>, wgpu::ShaderStages, &WgslBindgenOption)],
+) -> Vec<BTreeMap<u32, String>> {
+  let mut owners = HashMap::<String, String>::new();
+
+  entries
+    .iter()
+    .map(|(invoking_entry_module, bind_group_data, shader_stages, options)| {
+      bind_group_data
+        .iter()
+        .filter_map(|(group_no, group)| {
+          // The labels are the only part that depends on the entry name, so use the same for all entries.
+          let content =
+            bind_group_items("", "Shared", *group_no, group, *shader_stages, options)
+              .to_string();
+          match owners.entry(content) {
+            Entry::Occupied(owner) => Some((*group_no, owner.get().clone())),
+            Entry::Vacant(vacant) => {
+              vacant.insert(invoking_entry_module.to_string());
+              None
+            }
+          }
+        })
+        .collect()
+    })
+    .collect()
+}
+
+/// The trait implemented for the bind groups with the same layout as a bind group of another entry.
+pub fn compatible_bind_group_trait(options: &WgslBindgenOption) -> Option<TokenStream> {
+  if !options.bind_group_compatibility {
+    return None;
   }
+
+  Some(quote! {
+      /// Implemented by the bind groups with the same layout entries as the bind group `T` of
+      /// another shader, as compared when generating. Requiring it for a pair of bind groups,
+      /// eg: in a `const` item, turns a layout mismatch between shaders into a compile error.
+      pub trait CompatibleBindGroup<T> {}
+  })
+}
+
+/// Implements `CompatibleBindGroup` for each pair of bind groups from different entries with the
+/// same layout entries. Re-exported bind groups are skipped, since they're the same type as the
+/// bind group of their owner.
+pub fn compatible_bind_group_impls(
+  entries: &[(&str, &BTreeMap<u32, GroupData>, wgpu::ShaderStages, &WgslBindgenOption)],
+  shared_groups: &[BTreeMap<u32, String>],
+) -> Vec<TokenStream> {
+  // The labels are the only part that depends on the entry name and group index, so use the
+  // same ones for all the groups.
+  let layouts: Vec<Vec<_>> = entries
+    .iter()
+    .zip(shared_groups)
+    .map(|((_, bind_group_data, shader_stages, options), shared)| {
+      bind_group_data
+        .iter()
+        .filter(|(group_no, _)| !shared.contains_key(group_no))
+        .map(|(group_no, group)| {
+          let layout =
+            bind_group_layout_descriptor("", 0, group, *shader_stages, options)
+              .to_string();
+          (group.name_ident("WgpuBindGroup", *group_no), layout)
+        })
+        .collect()
+    })
+    .collect();
+
+  let root = mod_reference_root();
+  (0..entries.len())
+    .map(|i| {
+      let impls = layouts[i].iter().flat_map(|(name, layout)| {
+        let root = &root;
+        layouts
+          .iter()
+          .enumerate()
+          .filter(move |(j, _)| *j != i)
+          .flat_map(move |(j, other_layouts)| {
+            let other_module = syn::parse_str::<syn::Path>(entries[j].0).unwrap();
+            other_layouts
+              .iter()
+              .filter(move |(_, other_layout)| other_layout == layout)
+              .map(move |(other_name, _)| {
+                quote! {
+                    impl #root::CompatibleBindGroup<#root::#other_module::bind_groups::#other_name>
+                        for bind_groups::#name {}
+                }
+              })
+          })
+      });
+      quote!(#(#impls)*)
+    })
+    .collect()
 }
 
 // TODO: Take an iterator instead?
@@ -123,94 +761,116 @@ pub fn bind_groups_module(
   options: &WgslBindgenOption,
   bind_group_data: &BTreeMap<u32, GroupData>,
   shader_stages: wgpu::ShaderStages,
+) -> TokenStream {
+  bind_groups_module_with_shared(
+    invoking_entry_module,
+    options,
+    bind_group_data,
+    shader_stages,
+    &BTreeMap::new(),
+  )
+}
+
+/// Like [`bind_groups_module`], but re-exports the bind groups in `shared_groups` from the
+/// module of the entry that generates them instead of generating them again.
+pub fn bind_groups_module_with_shared(
+  invoking_entry_module: &str,
+  options: &WgslBindgenOption,
+  bind_group_data: &BTreeMap<u32, GroupData>,
+  shader_stages: wgpu::ShaderStages,
+  shared_groups: &BTreeMap<u32, String>,
 ) -> TokenStream {
   let entry_name = sanitize_and_pascal_case(invoking_entry_module);
   let bind_groups: Vec<_> = bind_group_data
     .iter()
-    .map(|(group_no, group)| {
-      let wgpu_generator = &options.wgpu_binding_generator;
-
-      let wgpu_layout = BindGroupLayoutBuilder::new(
+    .map(|(group_no, group)| match shared_groups.get(group_no) {
+      Some(owner_module) => {
+        reexported_bind_group_items(owner_module, *group_no, group, options)
+      }
+      None => bind_group_items(
         invoking_entry_module,
-        *group_no,
-        group,
-        &wgpu_generator.bind_group_layout,
-      )
-      .build();
-
-      let additional_layout =
-        if let Some(additional_generator) = &options.extra_binding_generator {
-          BindGroupLayoutBuilder::new(
-            invoking_entry_module,
-            *group_no,
-            group,
-            &additional_generator.bind_group_layout,
-          )
-          .build()
-        } else {
-          quote!()
-        };
-
-      let bindgroup = BindGroupBuilder::new(
         &entry_name,
         *group_no,
         group,
         shader_stages,
-        &wgpu_generator.bind_group_layout,
-      )
-      .build();
-
-      quote! {
-        #additional_layout
-        #wgpu_layout
-        #bindgroup
-      }
+        options,
+      ),
     })
     .collect();
 
   let bind_group_fields: Vec<_> = bind_group_data
-    .keys()
-    .map(|group_no| {
-      let group_name = indexed_name_ident("WgpuBindGroup", *group_no);
-      let field = indexed_name_ident("bind_group", *group_no);
+    .iter()
+    .map(|(group_no, group)| {
+      let group_name = group.name_ident("WgpuBindGroup", *group_no);
+      let field = group.field_ident(*group_no);
       quote!(pub #field: &'a #group_name)
     })
     .collect();
 
-  // TODO: Support compute shader with vertex/fragment in the same module?
-  let is_compute = shader_stages == wgpu::ShaderStages::COMPUTE;
-  let render_pass = if is_compute {
-    quote!(wgpu::ComputePass<'a>)
-  } else {
-    quote!(wgpu::RenderPass<'a>)
-  };
-
   let group_parameters: Vec<_> = bind_group_data
-    .keys()
-    .map(|group_no| {
-      let group = indexed_name_ident("bind_group", *group_no);
-      let group_type = indexed_name_ident("WgpuBindGroup", *group_no);
+    .iter()
+    .map(|(group_no, group)| {
+      let group_type = group.name_ident("WgpuBindGroup", *group_no);
+      let group = group.field_ident(*group_no);
       quote!(#group: &'a bind_groups::#group_type)
     })
     .collect();
 
+  let pass_types = pass_types(shader_stages, options);
+  let layout_cache = bind_group_layout_cache(bind_group_data, options);
+  let partial_bind_groups = partial_bind_groups(bind_group_data, &pass_types, options);
+  let set_bind_group_fns = set_bind_group_fns(bind_group_data, &pass_types, options);
+
   // The set function for each bind group already sets the index.
-  let set_groups: Vec<_> = bind_group_data
-    .keys()
-    .map(|group_no| {
-      let group = indexed_name_ident("bind_group", *group_no);
-      quote!(#group.set(pass);)
-    })
-    .collect();
+  let (groups_set_fns, set_bind_groups_fns): (Vec<_>, Vec<_>) = pass_types
+    .iter()
+    .map(|(set_name, render_pass)| {
+      let set_groups: Vec<_> = bind_group_data
+        .iter()
+        .map(|(group_no, group)| {
+          let group = group.field_ident(*group_no);
+          quote!(#group.#set_name(pass);)
+        })
+        .collect();
+
+      let groups_set_fn = quote! {
+          pub fn #set_name(&self, pass: &mut #render_pass) {
+              #(self.#set_groups)*
+          }
+      };
 
-  let set_bind_groups = quote! {
-      pub fn set_bind_groups<'a>(
-          pass: &mut #render_pass,
-          #(#group_parameters),*
-      ) {
-          #(#set_groups)*
-      }
-  };
+      let set_bind_groups_name = if set_name == "set" {
+        format_ident!("set_bind_groups")
+      } else {
+        format_ident!("{set_name}_bind_groups")
+      };
+      let set_bind_groups_from = if options.set_bind_groups_from {
+        let set_bind_groups_from_name = format_ident!("{set_bind_groups_name}_from");
+        quote! {
+            pub fn #set_bind_groups_from_name<'a>(
+                pass: &mut #render_pass,
+                bind_groups: &bind_groups::WgpuBindGroups<'a>,
+            ) {
+                bind_groups.#set_name(pass);
+            }
+        }
+      } else {
+        quote!()
+      };
+      let set_bind_groups = quote! {
+          pub fn #set_bind_groups_name<'a>(
+              pass: &mut #render_pass,
+              #(#group_parameters),*
+          ) {
+              #(#set_groups)*
+          }
+
+          #set_bind_groups_from
+      };
+
+      (groups_set_fn, set_bind_groups)
+    })
+    .unzip();
 
   if bind_groups.is_empty() {
     // Don't include empty modules.
@@ -227,42 +887,246 @@ pub fn bind_groups_module(
             }
 
             impl<'a> WgpuBindGroups<'a> {
-                pub fn set(&self, pass: &mut #render_pass) {
-                    #(self.#set_groups)*
-                }
+                #(#groups_set_fns)*
             }
+
+            #layout_cache
+            #partial_bind_groups
         }
-        #set_bind_groups
+        #(#set_bind_groups_fns)*
+        #set_bind_group_fns
     }
   }
 }
 
-fn bind_group_layout_entry(
-  binding: &GroupBinding,
-  shader_stages: wgpu::ShaderStages,
+/// The `BindGroupLayoutCache` struct in the `bind_groups` module, which the caller keeps per device
+/// to create the layouts of the bind groups once.
+fn bind_group_layout_cache(
+  bind_group_data: &BTreeMap<u32, GroupData>,
+  options: &WgslBindgenOption,
 ) -> TokenStream {
-  // TODO: Assume storage is only used for compute?
-  // TODO: Support just vertex or fragment?
-  // TODO: Visible from all stages?
-  let stages = match shader_stages {
+  if !options.cached_bind_group_layouts {
+    return quote!();
+  }
+
+  let (device_id_type, device_id, current_device_id) = options.wgpu_edition.device_id();
+  let fields: Vec<_> = bind_group_data
+    .iter()
+    .map(|(group_no, group)| group.field_ident(*group_no))
+    .collect();
+  let layout_fns =
+    bind_group_data
+      .iter()
+      .zip(&fields)
+      .map(|((group_no, group), field)| {
+        let group_name = group.name_ident("WgpuBindGroup", *group_no);
+        quote! {
+            pub fn #field(&self, device: &wgpu::Device) -> &wgpu::BindGroupLayout {
+                self.check_device(device);
+                self.#field.get_or_init(|| #group_name::get_bind_group_layout(device))
+            }
+        }
+      });
+
+  quote! {
+      /// Creates the layouts of the bind groups once on first use, for a single device.
+      #[derive(Debug, Default)]
+      pub struct BindGroupLayoutCache {
+          device: std::sync::OnceLock<#device_id_type>,
+          #(#fields: std::sync::OnceLock<wgpu::BindGroupLayout>,)*
+      }
+
+      impl BindGroupLayoutCache {
+          fn check_device(&self, device: &wgpu::Device) {
+              let cached_device = self.device.get_or_init(|| #device_id);
+              assert!(
+                  *cached_device == #current_device_id,
+                  "the BindGroupLayoutCache is used with another device than the one it was created with"
+              );
+          }
+
+          #(#layout_fns)*
+      }
+  }
+}
+
+/// The `WgpuBindGroupsPartial` struct in the `bind_groups` module, for setting only some of the bind groups.
+fn partial_bind_groups(
+  bind_group_data: &BTreeMap<u32, GroupData>,
+  pass_types: &[(Ident, TokenStream)],
+  options: &WgslBindgenOption,
+) -> TokenStream {
+  if !options.partial_bind_groups {
+    return quote!();
+  }
+
+  let fields: Vec<_> = bind_group_data
+    .iter()
+    .map(|(group_no, group)| group.field_ident(*group_no))
+    .collect();
+  let field_types = bind_group_data
+    .iter()
+    .map(|(group_no, group)| group.name_ident("WgpuBindGroup", *group_no));
+  let set_fns = pass_types.iter().map(|(set_name, render_pass)| {
+    quote! {
+        pub fn #set_name(&self, pass: &mut #render_pass) {
+            #(
+                if let Some(bind_group) = self.#fields {
+                    bind_group.#set_name(pass);
+                }
+            )*
+        }
+    }
+  });
+
+  quote! {
+      #[derive(Debug, Default, Copy, Clone)]
+      pub struct WgpuBindGroupsPartial<'a> {
+          #(pub #fields: Option<&'a #field_types>),*
+      }
+
+      impl<'a> WgpuBindGroupsPartial<'a> {
+          #(#set_fns)*
+      }
+
+      impl<'a> From<WgpuBindGroups<'a>> for WgpuBindGroupsPartial<'a> {
+          fn from(bind_groups: WgpuBindGroups<'a>) -> Self {
+              Self {
+                  #(#fields: Some(bind_groups.#fields)),*
+              }
+          }
+      }
+  }
+}
+
+/// A `set_bind_group<N>` function per bind group, for setting a single bind group.
+fn set_bind_group_fns(
+  bind_group_data: &BTreeMap<u32, GroupData>,
+  pass_types: &[(Ident, TokenStream)],
+  options: &WgslBindgenOption,
+) -> TokenStream {
+  if !options.partial_bind_groups {
+    return quote!();
+  }
+
+  let set_fns = pass_types.iter().flat_map(|(set_name, render_pass)| {
+    bind_group_data.iter().map(move |(group_no, group)| {
+      let group_type = group.name_ident("WgpuBindGroup", *group_no);
+      let group = group.field_ident(*group_no);
+      let fn_name = format_ident!("{set_name}_{group}");
+      quote! {
+          pub fn #fn_name<'a>(pass: &mut #render_pass, #group: &'a bind_groups::#group_type) {
+              #group.#set_name(pass);
+          }
+      }
+    })
+  });
+
+  quote!(#(#set_fns)*)
+}
+
+// The pass types the bind groups can be set on, along with the name of the set method.
+// Modules with both compute and render entry points get a `set_compute` method for compute passes,
+// unless the generic pass trait is used.
+fn pass_types(
+  shader_stages: wgpu::ShaderStages,
+  options: &WgslBindgenOption,
+) -> Vec<(Ident, TokenStream)> {
+  let set = format_ident!("set");
+  let pass_lifetime = options.wgpu_edition.pass_lifetime();
+  if options.generic_bind_group_pass {
+    let root = mod_reference_root();
+    let pass_mod = format_ident!("{MOD_BIND_GROUP_PASS}");
+    vec![(set, quote!(impl #root::#pass_mod::SetBindGroup<'a>))]
+  } else if shader_stages == wgpu::ShaderStages::COMPUTE {
+    vec![(set, quote!(wgpu::ComputePass<#pass_lifetime>))]
+  } else if shader_stages.contains(wgpu::ShaderStages::COMPUTE) {
+    vec![
+      (set, quote!(wgpu::RenderPass<#pass_lifetime>)),
+      (format_ident!("set_compute"), quote!(wgpu::ComputePass<#pass_lifetime>)),
+    ]
+  } else {
+    vec![(set, quote!(wgpu::RenderPass<#pass_lifetime>))]
+  }
+}
+
+/// A trait for the pass types bind groups can be set on, used by the generated `set` methods
+/// when the `generic_bind_group_pass` option is enabled.
+pub fn set_bind_group_trait(options: &WgslBindgenOption) -> Option<TokenStream> {
+  if !options.generic_bind_group_pass {
+    return None;
+  }
+
+  let pass_lifetime = options.wgpu_edition.pass_lifetime();
+  let impls = [
+    quote!(wgpu::RenderPass<#pass_lifetime>),
+    quote!(wgpu::ComputePass<#pass_lifetime>),
+    quote!(wgpu::RenderBundleEncoder<'a>),
+  ]
+  .map(|pass| {
+    quote! {
+        impl<'a> SetBindGroup<'a> for #pass {
+            fn set_bind_group(
+                &mut self,
+                index: u32,
+                bind_group: &'a wgpu::BindGroup,
+                offsets: &[wgpu::DynamicOffset],
+            ) {
+                <#pass>::set_bind_group(self, index, bind_group, offsets)
+            }
+        }
+    }
+  });
+
+  Some(quote! {
+      pub trait SetBindGroup<'a> {
+          fn set_bind_group(
+              &mut self,
+              index: u32,
+              bind_group: &'a wgpu::BindGroup,
+              offsets: &[wgpu::DynamicOffset],
+          );
+      }
+
+      #(#impls)*
+  })
+}
+
+fn shader_stages_tokens(shader_stages: wgpu::ShaderStages) -> TokenStream {
+  match shader_stages {
     wgpu::ShaderStages::VERTEX_FRAGMENT => quote!(wgpu::ShaderStages::VERTEX_FRAGMENT),
     wgpu::ShaderStages::COMPUTE => quote!(wgpu::ShaderStages::COMPUTE),
     wgpu::ShaderStages::VERTEX => quote!(wgpu::ShaderStages::VERTEX),
     wgpu::ShaderStages::FRAGMENT => quote!(wgpu::ShaderStages::FRAGMENT),
-    _ => todo!(),
-  };
+    stages => {
+      let stages = stages.iter_names().map(|(name, _)| format_ident!("{name}"));
+      quote!(wgpu::ShaderStages::empty()#(.union(wgpu::ShaderStages::#stages))*)
+    }
+  }
+}
+
+fn bind_group_layout_entry(
+  binding: &GroupBinding,
+  stages: TokenStream,
+  core: &TokenStream,
+) -> TokenStream {
+  // TODO: Assume storage is only used for compute?
 
   let binding_index = Index::from(binding.binding_index as usize);
   // TODO: Support more types.
   let binding_type = match binding.binding_type.inner {
     naga::TypeInner::Scalar(_)
+    | naga::TypeInner::Vector { .. }
+    | naga::TypeInner::Matrix { .. }
+    | naga::TypeInner::Atomic(_)
     | naga::TypeInner::Struct { .. }
     | naga::TypeInner::Array { .. } => {
       let buffer_binding_type = buffer_binding_type(binding.address_space);
+      let has_dynamic_offset = binding.annotation.dynamic_offset;
 
       quote!(wgpu::BindingType::Buffer {
           ty: #buffer_binding_type,
-          has_dynamic_offset: false,
+          has_dynamic_offset: #has_dynamic_offset,
           min_binding_size: None,
       })
     }
@@ -276,9 +1140,10 @@ fn bind_group_layout_entry(
 
       match class {
         naga::ImageClass::Sampled { kind: _, multi } => {
-          // TODO: Don't assume all textures are filterable.
+          // Textures are assumed to be filterable unless annotated otherwise.
+          let filterable = binding.annotation.filterable.unwrap_or(true);
           quote!(wgpu::BindingType::Texture {
-              sample_type: wgpu::TextureSampleType::Float { filterable: true },
+              sample_type: wgpu::TextureSampleType::Float { filterable: #filterable },
               view_dimension: #view_dim,
               multisampled: #multi,
           })
@@ -312,8 +1177,15 @@ fn bind_group_layout_entry(
       };
       quote!(wgpu::BindingType::Sampler(#sampler_type))
     }
-    // TODO: Better error handling.
-    _ => panic!("Failed to generate BindingType."),
+    _ => unreachable!("binding types are checked by `check_binding_types`"),
+  };
+  // `NonZeroU32::new` is a const fn returning the `Option` expected by the const descriptor.
+  let count = match binding.count {
+    Some(count) => {
+      let count = Index::from(count.get() as usize);
+      quote!(#core::num::NonZeroU32::new(#count))
+    }
+    None => quote!(None),
   };
 
   quote! {
@@ -321,11 +1193,37 @@ fn bind_group_layout_entry(
           binding: #binding_index,
           visibility: #stages,
           ty: #binding_type,
-          count: None,
+          count: #count,
       }
   }
 }
 
+/// The type and value of the minimal usages of the resource bound, or `None` for samplers.
+fn binding_usages(binding: &GroupBinding) -> Option<(TokenStream, TokenStream)> {
+  match (binding.resource_type(), &binding.binding_type.inner) {
+    (BindResourceType::Buffer | BindResourceType::BufferArray, _) => {
+      let usages = match binding.address_space {
+        naga::AddressSpace::Uniform => quote!(wgpu::BufferUsages::UNIFORM),
+        _ => quote!(wgpu::BufferUsages::STORAGE),
+      };
+      Some((quote!(wgpu::BufferUsages), usages))
+    }
+    (
+      BindResourceType::Texture | BindResourceType::TextureArray,
+      naga::TypeInner::Image {
+        class: naga::ImageClass::Storage { .. },
+        ..
+      },
+    ) => {
+      Some((quote!(wgpu::TextureUsages), quote!(wgpu::TextureUsages::STORAGE_BINDING)))
+    }
+    (BindResourceType::Texture | BindResourceType::TextureArray, _) => {
+      Some((quote!(wgpu::TextureUsages), quote!(wgpu::TextureUsages::TEXTURE_BINDING)))
+    }
+    _ => None,
+  }
+}
+
 fn storage_access(access: naga::StorageAccess) -> TokenStream {
   let is_read = access.contains(naga::StorageAccess::LOAD);
   let is_write = access.contains(naga::StorageAccess::STORE);
@@ -337,26 +1235,83 @@ fn storage_access(access: naga::StorageAccess) -> TokenStream {
   }
 }
 
+/// Checks that the bindings have types which can be generated as layout entries, so generating
+/// the bind groups can't fail.
+pub fn check_binding_types(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+) -> Result<(), CreateModuleError> {
+  for (_, global) in module.global_variables.iter() {
+    let Some(binding) = &global.binding else {
+      continue;
+    };
+    let ty = &module.types[global.ty];
+    // Binding arrays are bound like their elements. Unbounded arrays have no count for the
+    // layout entry.
+    let bound_type = match ty.inner {
+      naga::TypeInner::BindingArray {
+        base,
+        size: naga::ArraySize::Constant(_),
+      } => Some(&module.types[base].inner),
+      naga::TypeInner::BindingArray { .. } => None,
+      ref inner => Some(inner),
+    };
+    let supported = match bound_type.and_then(bind_resource_type) {
+      Some(BindResourceType::Buffer) => matches!(
+        global.space,
+        naga::AddressSpace::Uniform | naga::AddressSpace::Storage { .. }
+      ),
+      Some(_) => true,
+      None => false,
+    };
+    if !supported {
+      let name = global.name.as_deref().unwrap_or_default();
+      return Err(CreateModuleError::UnsupportedBindingType {
+        module: invoking_entry_module.to_owned(),
+        binding: RustItemPath::from_mangled(name, invoking_entry_module)
+          .get_fully_qualified_name()
+          .to_string(),
+        group: binding.group,
+        type_name: wgsl::type_to_wgsl(module, ty),
+      });
+    }
+  }
+  Ok(())
+}
+
 pub fn get_bind_group_data(
   module: &naga::Module,
 ) -> Result<BTreeMap<u32, GroupData>, CreateModuleError> {
   // Use a BTree to sort type and field names by group index.
   // This isn't strictly necessary but makes the generated code cleaner.
   let mut groups = BTreeMap::new();
+  let global_stages = naga_util::global_stages(module);
 
   for global_handle in module.global_variables.iter() {
     let global = &module.global_variables[global_handle.0];
     if let Some(binding) = &global.binding {
       let group = groups.entry(binding.group).or_insert(GroupData {
         bindings: Vec::new(),
+        name: None,
       });
-      let binding_type = &module.types[module.global_variables[global_handle.0].ty];
+      let global_type = &module.types[module.global_variables[global_handle.0].ty];
+      let (binding_type, count) = match global_type.inner {
+        naga::TypeInner::BindingArray {
+          base,
+          size: naga::ArraySize::Constant(count),
+        } => (&module.types[base], Some(count)),
+        _ => (global_type, None),
+      };
 
       let group_binding = GroupBinding {
         name: global.name.clone(),
         binding_index: binding.binding,
         binding_type,
+        count,
+        binding_type_name: wgsl::type_to_wgsl(module, global_type),
         address_space: global.space,
+        annotation: WgslAnnotation::default(),
+        stages: global_stages[global_handle.0.index()],
       };
       // Repeated bindings will probably cause a compile error.
       // We'll still check for it here just in case.
@@ -381,6 +1336,132 @@ pub fn get_bind_group_data(
   }
 }
 
+/// Applies the `// wgsl_bindgen:` annotations written above the bindings in the WGSL source.
+pub fn annotate_bindings(
+  invoking_entry_module: &str,
+  bind_group_data: &mut BTreeMap<u32, GroupData>,
+  docs: &WgslDocs,
+) -> Result<(), CreateModuleError> {
+  for (group_no, group) in bind_group_data.iter_mut() {
+    for binding in &mut group.bindings {
+      let path =
+        RustItemPath::from_mangled(binding.name.as_deref().unwrap_or_default(), "");
+      if let Some(annotation) = docs.item_annotation(&path) {
+        binding.annotation = annotation.clone();
+      }
+      let Some(group_name) = &binding.annotation.group_name else {
+        continue;
+      };
+      match &group.name {
+        Some(name) if name != group_name => {
+          return Err(CreateModuleError::ConflictingBindGroupNames {
+            module: invoking_entry_module.to_owned(),
+            group: *group_no,
+            name: name.clone(),
+            other_name: group_name.clone(),
+          });
+        }
+        _ => group.name = Some(group_name.clone()),
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Names the bind groups from `WgslBindgenOption::bind_group_names`, unless they are already
+/// named by a `group` annotation, and checks that the generated names are unique.
+pub fn name_bind_groups(
+  invoking_entry_module: &str,
+  bind_group_data: &mut BTreeMap<u32, GroupData>,
+  options: &WgslBindgenOption,
+) -> Result<(), CreateModuleError> {
+  for (group_no, name) in &options.bind_group_names {
+    if let Some(group) = bind_group_data.get_mut(group_no) {
+      group.name.get_or_insert_with(|| name.clone());
+    }
+  }
+
+  // Both the struct and the field in `WgpuBindGroups` of a group must be unique.
+  let mut groups_by_name = HashMap::new();
+  for (group_no, group) in bind_group_data.iter() {
+    let names = [
+      group.name_ident("WgpuBindGroup", *group_no),
+      group.field_ident(*group_no),
+    ];
+    for name in names.map(|name| name.to_string()) {
+      if let Some(other_group) = groups_by_name.insert(name.clone(), *group_no) {
+        return Err(CreateModuleError::DuplicateBindGroupName {
+          module: invoking_entry_module.to_owned(),
+          group: other_group,
+          other_group: *group_no,
+          name,
+        });
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Removes the bind groups whose bindings match `WgslBindgenOption::excluded_bindings` from
+/// `bind_group_data`, and returns them.
+pub fn take_excluded_bind_groups<'a>(
+  invoking_entry_module: &str,
+  bind_group_data: &mut BTreeMap<u32, GroupData<'a>>,
+  options: &WgslBindgenOption,
+) -> Result<BTreeMap<u32, GroupData<'a>>, CreateModuleError> {
+  if options.excluded_bindings.is_empty() {
+    return Ok(BTreeMap::new());
+  }
+
+  let is_excluded = |binding: &GroupBinding| {
+    let name = binding.name.as_deref().unwrap_or_default();
+    let name = RustItemPath::from_mangled(name, invoking_entry_module)
+      .get_fully_qualified_name()
+      .to_string();
+    options.excluded_bindings.iter().any(|r| r.is_match(&name))
+  };
+
+  let mut excluded_groups = Vec::new();
+  for (group_no, group) in bind_group_data.iter() {
+    let excluded_count = group.bindings.iter().filter(|b| is_excluded(b)).count();
+    if excluded_count == group.bindings.len() {
+      excluded_groups.push(*group_no);
+    } else if excluded_count > 0 {
+      return Err(CreateModuleError::PartiallyExcludedBindGroup {
+        entry: invoking_entry_module.to_owned(),
+        group: *group_no,
+      });
+    }
+  }
+
+  Ok(
+    excluded_groups
+      .into_iter()
+      .map(|group_no| (group_no, bind_group_data.remove(&group_no).unwrap()))
+      .collect(),
+  )
+}
+
+/// The layout descriptor of a bind group, for the pipeline layout of bind groups which don't
+/// generate a bind group struct.
+pub fn bind_group_layout_descriptor(
+  entry_name: &str,
+  group_no: u32,
+  group: &GroupData,
+  shader_stages: wgpu::ShaderStages,
+  options: &WgslBindgenOption,
+) -> TokenStream {
+  BindGroupBuilder::new(
+    entry_name,
+    group_no,
+    group,
+    shader_stages,
+    options,
+    &options.wgpu_binding_generator.bind_group_layout,
+  )
+  .bind_group_layout_descriptor()
+}
+
 #[cfg(test)]
 mod tests {
   use indoc::indoc;
@@ -388,53 +1469,1479 @@ mod tests {
   use super::*;
   use crate::assert_tokens_eq;
 
+  fn group_builder<'a>(
+    group: &'a GroupData<'a>,
+    options: &'a WgslBindgenOption,
+  ) -> BindGroupBuilder<'a> {
+    BindGroupBuilder::new(
+      "Test",
+      0,
+      group,
+      wgpu::ShaderStages::FRAGMENT,
+      options,
+      &options.wgpu_binding_generator.bind_group_layout,
+    )
+  }
+
+  #[test]
+  fn bind_group_data_consecutive_bind_groups() {
+    let source = indoc! {r#"
+            @group(0) @binding(0) var<uniform> a: vec4<f32>;
+            @group(1) @binding(0) var<uniform> b: vec4<f32>;
+            @group(2) @binding(0) var<uniform> c: vec4<f32>;
+
+            @fragment
+            fn main() {}
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    assert_eq!(3, get_bind_group_data(&module).unwrap().len());
+  }
+
+  #[test]
+  fn take_excluded_bind_groups_removes_matching_groups() {
+    let source = indoc! {r#"
+            @group(0) @binding(0) var globals_sampler: sampler;
+            @group(0) @binding(1) var<uniform> globals_group: vec4<f32>;
+            @group(1) @binding(0) var<uniform> a: vec4<f32>;
+            @group(1) @binding(1) var<uniform> b: vec4<f32>;
+
+            @fragment
+            fn main() {}
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let mut data = get_bind_group_data(&module).unwrap();
+    let options = WgslBindgenOption {
+      excluded_bindings: vec![Regex::new("^.*::globals_.*$").unwrap()],
+      ..Default::default()
+    };
+
+    let excluded = take_excluded_bind_groups("shader", &mut data, &options).unwrap();
+    assert_eq!(vec![0], excluded.keys().copied().collect::<Vec<_>>());
+    assert_eq!(vec![1], data.keys().copied().collect::<Vec<_>>());
+
+    let options = WgslBindgenOption {
+      excluded_bindings: vec![Regex::new("^shader::a$").unwrap()],
+      ..Default::default()
+    };
+    assert!(matches!(
+      take_excluded_bind_groups("shader", &mut data, &options),
+      Err(CreateModuleError::PartiallyExcludedBindGroup { group: 1, .. })
+    ));
+  }
+
+  #[test]
+  fn check_binding_types_rejects_unsupported_types() {
+    let source = indoc! {r#"
+            @group(0) @binding(0) var<uniform> color: vec4<f32>;
+            @group(0) @binding(1) var textures: binding_array<texture_2d<f32>>;
+
+            @fragment
+            fn main() {}
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let Err(CreateModuleError::UnsupportedBindingType {
+      binding, type_name, ..
+    }) = check_binding_types("shader", &module)
+    else {
+      panic!("expected an unsupported binding type");
+    };
+    assert_eq!("shader::textures", binding);
+    assert_eq!("binding_array<texture_2d<f32>>", type_name);
+  }
+
+  #[test]
+  fn bind_group_data_first_group_not_zero() {
+    let source = indoc! {r#"
+            @group(1) @binding(0) var<uniform> a: vec4<f32>;
+
+            @fragment
+            fn main() {}
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    assert!(matches!(
+      get_bind_group_data(&module),
+      Err(CreateModuleError::NonConsecutiveBindGroups)
+    ));
+  }
+
+  #[test]
+  fn bind_group_data_non_consecutive_bind_groups() {
+    let source = indoc! {r#"
+            @group(0) @binding(0) var<uniform> a: vec4<f32>;
+            @group(1) @binding(0) var<uniform> b: vec4<f32>;
+            @group(3) @binding(0) var<uniform> c: vec4<f32>;
+
+            @fragment
+            fn main() {}
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    assert!(matches!(
+      get_bind_group_data(&module),
+      Err(CreateModuleError::NonConsecutiveBindGroups)
+    ));
+  }
+
+  #[test]
+  fn shared_bind_groups_between_entries() {
+    let source_a = indoc! {r#"
+            @group(0) @binding(0) var<uniform> globals: vec4<f32>;
+            @group(1) @binding(0) var<uniform> a: vec4<f32>;
+        "#};
+    let source_b = indoc! {r#"
+            @group(0) @binding(0) var<uniform> globals: vec4<f32>;
+            @group(1) @binding(0) var<uniform> b: vec4<f32>;
+        "#};
+    let source_c = indoc! {r#"
+            @group(0) @binding(0) var<uniform> other: vec4<f32>;
+        "#};
+
+    let module_a = naga::front::wgsl::parse_str(source_a).unwrap();
+    let module_b = naga::front::wgsl::parse_str(source_b).unwrap();
+    let module_c = naga::front::wgsl::parse_str(source_c).unwrap();
+    let data_a = get_bind_group_data(&module_a).unwrap();
+    let data_b = get_bind_group_data(&module_b).unwrap();
+    let data_c = get_bind_group_data(&module_c).unwrap();
+    let stages = wgpu::ShaderStages::FRAGMENT;
+    let options = WgslBindgenOption::default();
+
+    let owners = shared_bind_group_owners(&[
+      ("a", &data_a, stages, &options),
+      ("b", &data_b, stages, &options),
+      ("c", &data_c, stages, &options),
+    ]);
+    assert_eq!(
+      vec![
+        BTreeMap::new(),
+        BTreeMap::from([(0, "a".to_string())]),
+        BTreeMap::new()
+      ],
+      owners
+    );
+
+    let actual =
+      bind_groups_module_with_shared("b", &options, &data_b, stages, &owners[1]);
+    let shared_group = actual.to_string().contains(
+      &quote!(
+        pub use _root::a::bind_groups::{WgpuBindGroupLayout0, WgpuBindGroup0};
+      )
+      .to_string(),
+    );
+    assert!(shared_group);
+  }
+
+  #[test]
+  fn global_bind_group() {
+    let source_a = indoc! {r#"
+            @group(0) @binding(0) var<uniform> frame: vec4<f32>;
+            @group(1) @binding(0) var<uniform> a: vec4<f32>;
+        "#};
+    let source_b = indoc! {r#"
+            @group(0) @binding(0) var<uniform> frame: vec4<f32>;
+        "#};
+    let source_c = indoc! {r#"
+            @group(0) @binding(0) var<storage> frame: array<f32>;
+        "#};
+
+    let module_a = naga::front::wgsl::parse_str(source_a).unwrap();
+    let module_b = naga::front::wgsl::parse_str(source_b).unwrap();
+    let module_c = naga::front::wgsl::parse_str(source_c).unwrap();
+    let data_a = get_bind_group_data(&module_a).unwrap();
+    let data_b = get_bind_group_data(&module_b).unwrap();
+    let data_c = get_bind_group_data(&module_c).unwrap();
+    let options = WgslBindgenOption {
+      global_bind_group: Some(0),
+      ..Default::default()
+    };
+
+    let mut shared_groups = vec![BTreeMap::new(); 2];
+    let global = global_bind_group_items(
+      &[
+        ("a", &data_a, wgpu::ShaderStages::VERTEX_FRAGMENT),
+        ("b", &data_b, wgpu::ShaderStages::COMPUTE),
+      ],
+      &mut shared_groups,
+      &options,
+    )
+    .unwrap()
+    .unwrap();
+    // The group is visible to the stages of all the entries using it.
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug)]
+          pub struct WgpuBindGroupLayout0<'a> {
+              #[doc = " `@group(0) @binding(0) var<uniform> frame: vec4<f32>`"]
+              pub frame: wgpu::BufferBinding<'a>,
+          }
+          impl<'a> WgpuBindGroupLayout0<'a> {
+              pub fn entries(self) -> [wgpu::BindGroupEntry<'a>; 1] {
+                  [
+                      wgpu::BindGroupEntry {
+                          binding: 0,
+                          resource: wgpu::BindingResource::Buffer(self.frame),
+                      },
+                  ]
+              }
+          }
+          #[derive(Debug)]
+          pub struct WgpuBindGroup0(wgpu::BindGroup);
+          impl WgpuBindGroup0 {
+              pub const LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> = wgpu::BindGroupLayoutDescriptor {
+                  label: Some("Global::BindGroup0::LayoutDescriptor"),
+                  entries: &[
+                      wgpu::BindGroupLayoutEntry {
+                          binding: 0,
+                          visibility: wgpu::ShaderStages::empty()
+                              .union(wgpu::ShaderStages::VERTEX)
+                              .union(wgpu::ShaderStages::FRAGMENT)
+                              .union(wgpu::ShaderStages::COMPUTE),
+                          ty: wgpu::BindingType::Buffer {
+                              ty: wgpu::BufferBindingType::Uniform,
+                              has_dynamic_offset: false,
+                              min_binding_size: None,
+                          },
+                          count: None,
+                      },
+                  ],
+              };
+              pub fn get_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+                  device.create_bind_group_layout(&Self::LAYOUT_DESCRIPTOR)
+              }
+              pub fn from_bindings(device: &wgpu::Device, bindings: WgpuBindGroupLayout0) -> Self {
+                  let bind_group_layout = Self::get_bind_group_layout(&device);
+                  let entries = bindings.entries();
+                  let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                      label: Some("Global::BindGroup0"),
+                      layout: &bind_group_layout,
+                      entries: &entries,
+                  });
+                  Self(bind_group)
+              }
+              pub fn set<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+                  render_pass.set_bind_group(0, &self.0, &[]);
+              }
+              pub fn set_compute<'a>(&'a self, render_pass: &mut wgpu::ComputePass<'a>) {
+                  render_pass.set_bind_group(0, &self.0, &[]);
+              }
+          }
+      },
+      global
+    );
+    assert_eq!(
+      vec![
+        BTreeMap::from([(0, MOD_GLOBAL_BIND_GROUP.to_string())]),
+        BTreeMap::from([(0, MOD_GLOBAL_BIND_GROUP.to_string())]),
+      ],
+      shared_groups
+    );
+
+    let actual =
+      reexported_bind_group_items(&shared_groups[0][&0], 0, &data_a[&0], &options);
+    assert_tokens_eq!(
+      quote! {
+          pub use _root::global_bind_group::{WgpuBindGroupLayout0, WgpuBindGroup0};
+      },
+      actual
+    );
+
+    let mismatch = global_bind_group_items(
+      &[
+        ("a", &data_a, wgpu::ShaderStages::FRAGMENT),
+        ("c", &data_c, wgpu::ShaderStages::FRAGMENT),
+      ],
+      &mut vec![BTreeMap::new(); 2],
+      &options,
+    );
+    assert!(matches!(
+      mismatch,
+      Err(CreateModuleError::GlobalBindGroupMismatch { module, global_module, group: 0 })
+        if module == "c" && global_module == "a"
+    ));
+
+    let reserved = global_bind_group_items(
+      &[
+        ("a", &data_a, wgpu::ShaderStages::FRAGMENT),
+        (MOD_GLOBAL_BIND_GROUP, &data_b, wgpu::ShaderStages::FRAGMENT),
+      ],
+      &mut vec![BTreeMap::new(); 2],
+      &options,
+    );
+    assert!(matches!(
+      reserved,
+      Err(CreateModuleError::ReservedModuleName { module })
+        if module == MOD_GLOBAL_BIND_GROUP
+    ));
+  }
+
+  #[test]
+  fn bind_group_compatibility_fn() {
+    let source = indoc! {r#"
+            @group(0) @binding(0) var<uniform> globals: vec4<f32>;
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let bind_group_data = get_bind_group_data(&module).unwrap();
+    let options = WgslBindgenOption {
+      bind_group_compatibility: true,
+      ..Default::default()
+    };
+
+    let actual = group_builder(&bind_group_data[&0], &options).bind_group_struct_impl();
+    assert_tokens_eq!(
+      quote! {
+          impl WgpuBindGroup0 {
+              pub const LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> = wgpu::BindGroupLayoutDescriptor {
+                  label: Some("Test::BindGroup0::LayoutDescriptor"),
+                  entries: &[
+                      wgpu::BindGroupLayoutEntry {
+                          binding: 0,
+                          visibility: wgpu::ShaderStages::FRAGMENT,
+                          ty: wgpu::BindingType::Buffer {
+                              ty: wgpu::BufferBindingType::Uniform,
+                              has_dynamic_offset: false,
+                              min_binding_size: None,
+                          },
+                          count: None,
+                      },
+                  ],
+              };
+              pub fn get_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+                  device.create_bind_group_layout(&Self::LAYOUT_DESCRIPTOR)
+              }
+              pub fn from_bindings(device: &wgpu::Device, bindings: WgpuBindGroupLayout0) -> Self {
+                  let bind_group_layout = Self::get_bind_group_layout(&device);
+                  let entries = bindings.entries();
+                  let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                      label: Some("Test::BindGroup0"),
+                      layout: &bind_group_layout,
+                      entries: &entries,
+                  });
+                  Self(bind_group)
+              }
+              /// Whether this bind group can be used in place of a bind group with the given layout,
+              /// such as the `LAYOUT_DESCRIPTOR` of a bind group from another shader.
+              pub fn is_compatible_with(layout: &wgpu::BindGroupLayoutDescriptor) -> bool {
+                  Self::LAYOUT_DESCRIPTOR.entries == layout.entries
+              }
+              pub fn set<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+                  render_pass.set_bind_group(0, &self.0, &[]);
+              }
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn compatible_bind_groups() {
+    let source_a = indoc! {r#"
+            @group(0) @binding(0) var<uniform> camera: mat4x4<f32>;
+            @group(1) @binding(0) var color: texture_2d<f32>;
+        "#};
+    let source_b = indoc! {r#"
+            @group(0) @binding(0) var<uniform> view: mat4x4<f32>;
+        "#};
+    let source_c = indoc! {r#"
+            @group(0) @binding(0) var<storage> values: array<f32>;
+            @group(1) @binding(0) var<uniform> camera: mat4x4<f32>;
+        "#};
+
+    let data: Vec<_> = [source_a, source_b, source_c]
+      .into_iter()
+      .map(|source| {
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        get_bind_group_data(&module).unwrap()
+      })
+      .collect();
+    let options = WgslBindgenOption {
+      bind_group_compatibility: true,
+      ..Default::default()
+    };
+    let stages = wgpu::ShaderStages::FRAGMENT;
+
+    let actual = compatible_bind_group_impls(
+      &[
+        ("a", &data[0], stages, &options),
+        ("b", &data[1], stages, &options),
+        ("c", &data[2], stages, &options),
+      ],
+      &[BTreeMap::new(), BTreeMap::new(), BTreeMap::new()],
+    );
+
+    assert_tokens_eq!(
+      quote! {
+          impl _root::CompatibleBindGroup<_root::b::bind_groups::WgpuBindGroup0>
+              for bind_groups::WgpuBindGroup0 {}
+          impl _root::CompatibleBindGroup<_root::c::bind_groups::WgpuBindGroup1>
+              for bind_groups::WgpuBindGroup0 {}
+      },
+      actual[0]
+    );
+    assert_tokens_eq!(
+      quote! {
+          impl _root::CompatibleBindGroup<_root::a::bind_groups::WgpuBindGroup0>
+              for bind_groups::WgpuBindGroup0 {}
+          impl _root::CompatibleBindGroup<_root::c::bind_groups::WgpuBindGroup1>
+              for bind_groups::WgpuBindGroup0 {}
+      },
+      actual[1]
+    );
+    assert_tokens_eq!(
+      quote! {
+          impl _root::CompatibleBindGroup<_root::a::bind_groups::WgpuBindGroup0>
+              for bind_groups::WgpuBindGroup1 {}
+          impl _root::CompatibleBindGroup<_root::b::bind_groups::WgpuBindGroup0>
+              for bind_groups::WgpuBindGroup1 {}
+      },
+      actual[2]
+    );
+  }
+
+  #[test]
+  fn cached_bind_group_layout_fn() {
+    let source = indoc! {r#"
+            struct Transforms {};
+
+            @group(0) @binding(0) var<uniform> transforms: Transforms;
+
+            @fragment
+            fn fs_main() {}
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let bind_group_data = get_bind_group_data(&module).unwrap();
+    let options = WgslBindgenOption {
+      cached_bind_group_layouts: true,
+      ..Default::default()
+    };
+
+    let actual =
+      bind_groups_module("", &options, &bind_group_data, wgpu::ShaderStages::FRAGMENT);
+
+    assert_tokens_eq!(
+      quote! {
+          pub mod bind_groups {
+              #[derive(Debug)]
+              pub struct WgpuBindGroupLayout0<'a> {
+                  #[doc = " `@group(0) @binding(0) var<uniform> transforms: Transforms`"]
+                  pub transforms: wgpu::BufferBinding<'a>,
+              }
+              impl<'a> WgpuBindGroupLayout0<'a> {
+                pub fn entries(self) -> [wgpu::BindGroupEntry<'a>; 1] {
+                  [
+                      wgpu::BindGroupEntry {
+                          binding: 0,
+                          resource: wgpu::BindingResource::Buffer(self.transforms),
+                      },
+                  ]
+                }
+              }
+              #[derive(Debug)]
+              pub struct WgpuBindGroup0(wgpu::BindGroup);
+              impl WgpuBindGroup0 {
+                pub const LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> = wgpu::BindGroupLayoutDescriptor {
+                  label: Some("::BindGroup0::LayoutDescriptor"),
+                  entries: &[
+                      wgpu::BindGroupLayoutEntry {
+                          binding: 0,
+                          visibility: wgpu::ShaderStages::FRAGMENT,
+                          ty: wgpu::BindingType::Buffer {
+                              ty: wgpu::BufferBindingType::Uniform,
+                              has_dynamic_offset: false,
+                              min_binding_size: None,
+                          },
+                          count: None,
+                      },
+                  ],
+                };
+
+                  pub fn get_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+                      device.create_bind_group_layout(&Self::LAYOUT_DESCRIPTOR)
+                  }
+                  pub fn from_bindings(device: &wgpu::Device, bindings: WgpuBindGroupLayout0) -> Self {
+                      let bind_group_layout = Self::get_bind_group_layout(&device);
+                      let entries = bindings.entries();
+                      let bind_group = device
+                          .create_bind_group(
+                              &wgpu::BindGroupDescriptor {
+                                  label: Some("::BindGroup0"),
+                                  layout: &bind_group_layout,
+                                  entries: &entries,
+                              },
+                          );
+                      Self(bind_group)
+                  }
+                  /// Like `from_bindings`, with an existing layout such as the one of a `BindGroupLayoutCache`.
+                  pub fn from_bindings_with_layout(
+                      device: &wgpu::Device,
+                      layout: &wgpu::BindGroupLayout,
+                      bindings: WgpuBindGroupLayout0,
+                  ) -> Self {
+                      let entries = bindings.entries();
+                      let bind_group = device
+                          .create_bind_group(
+                              &wgpu::BindGroupDescriptor {
+                                  label: Some("::BindGroup0"),
+                                  layout,
+                                  entries: &entries,
+                              },
+                          );
+                      Self(bind_group)
+                  }
+                  pub fn set<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+                      render_pass.set_bind_group(0, &self.0, &[]);
+                  }
+              }
+              #[derive(Debug, Copy, Clone)]
+              pub struct WgpuBindGroups<'a> {
+                  pub bind_group0: &'a WgpuBindGroup0,
+              }
+              impl<'a> WgpuBindGroups<'a> {
+                  pub fn set(&self, pass: &mut wgpu::RenderPass<'a>) {
+                      self.bind_group0.set(pass);
+                  }
+              }
+              /// Creates the layouts of the bind groups once on first use, for a single device.
+              #[derive(Debug, Default)]
+              pub struct BindGroupLayoutCache {
+                  device: std::sync::OnceLock<wgpu::Id<wgpu::Device>>,
+                  bind_group0: std::sync::OnceLock<wgpu::BindGroupLayout>,
+              }
+              impl BindGroupLayoutCache {
+                  fn check_device(&self, device: &wgpu::Device) {
+                      let cached_device = self.device.get_or_init(|| device.global_id());
+                      assert!(
+                          *cached_device == device.global_id(),
+                          "the BindGroupLayoutCache is used with another device than the one it was created with"
+                      );
+                  }
+                  pub fn bind_group0(&self, device: &wgpu::Device) -> &wgpu::BindGroupLayout {
+                      self.check_device(device);
+                      self.bind_group0.get_or_init(|| WgpuBindGroup0::get_bind_group_layout(device))
+                  }
+              }
+          }
+          pub fn set_bind_groups<'a>(
+              pass: &mut wgpu::RenderPass<'a>,
+              bind_group0: &'a bind_groups::WgpuBindGroup0,
+          ) {
+              bind_group0.set(pass);
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn binding_usages_consts() {
+    let source = indoc! {r#"
+            @group(0) @binding(0) var<uniform> globals: vec4<f32>;
+            @group(0) @binding(1) var<storage, read_write> particleValues: array<f32>;
+            @group(0) @binding(2) var color: texture_2d<f32>;
+            @group(0) @binding(3) var output: texture_storage_2d<rgba8unorm, write>;
+            @group(0) @binding(4) var colorSampler: sampler;
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let bind_group_data = get_bind_group_data(&module).unwrap();
+    let options = WgslBindgenOption {
+      binding_usages: true,
+      ..Default::default()
+    };
+
+    let actual = group_builder(&bind_group_data[&0], &options).bind_group_struct_impl();
+    assert_tokens_eq!(
+      quote! {
+          impl WgpuBindGroup0 {
+              pub const GLOBALS_USAGES: wgpu::BufferUsages = wgpu::BufferUsages::UNIFORM;
+              pub const PARTICLE_VALUES_USAGES: wgpu::BufferUsages = wgpu::BufferUsages::STORAGE;
+              pub const COLOR_USAGES: wgpu::TextureUsages = wgpu::TextureUsages::TEXTURE_BINDING;
+              pub const OUTPUT_USAGES: wgpu::TextureUsages = wgpu::TextureUsages::STORAGE_BINDING;
+              pub const LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> = wgpu::BindGroupLayoutDescriptor {
+                  label: Some("Test::BindGroup0::LayoutDescriptor"),
+                  entries: &[
+                      wgpu::BindGroupLayoutEntry {
+                          binding: 0,
+                          visibility: wgpu::ShaderStages::FRAGMENT,
+                          ty: wgpu::BindingType::Buffer {
+                              ty: wgpu::BufferBindingType::Uniform,
+                              has_dynamic_offset: false,
+                              min_binding_size: None,
+                          },
+                          count: None,
+                      },
+                      wgpu::BindGroupLayoutEntry {
+                          binding: 1,
+                          visibility: wgpu::ShaderStages::FRAGMENT,
+                          ty: wgpu::BindingType::Buffer {
+                              ty: wgpu::BufferBindingType::Storage { read_only: false },
+                              has_dynamic_offset: false,
+                              min_binding_size: None,
+                          },
+                          count: None,
+                      },
+                      wgpu::BindGroupLayoutEntry {
+                          binding: 2,
+                          visibility: wgpu::ShaderStages::FRAGMENT,
+                          ty: wgpu::BindingType::Texture {
+                              sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                              view_dimension: wgpu::TextureViewDimension::D2,
+                              multisampled: false,
+                          },
+                          count: None,
+                      },
+                      wgpu::BindGroupLayoutEntry {
+                          binding: 3,
+                          visibility: wgpu::ShaderStages::FRAGMENT,
+                          ty: wgpu::BindingType::StorageTexture {
+                              access: wgpu::StorageTextureAccess::WriteOnly,
+                              format: wgpu::TextureFormat::Rgba8Unorm,
+                              view_dimension: wgpu::TextureViewDimension::D2,
+                          },
+                          count: None,
+                      },
+                      wgpu::BindGroupLayoutEntry {
+                          binding: 4,
+                          visibility: wgpu::ShaderStages::FRAGMENT,
+                          ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                          count: None,
+                      },
+                  ],
+              };
+              pub fn get_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+                  device.create_bind_group_layout(&Self::LAYOUT_DESCRIPTOR)
+              }
+              pub fn from_bindings(device: &wgpu::Device, bindings: WgpuBindGroupLayout0) -> Self {
+                  let bind_group_layout = Self::get_bind_group_layout(&device);
+                  let entries = bindings.entries();
+                  let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                      label: Some("Test::BindGroup0"),
+                      layout: &bind_group_layout,
+                      entries: &entries,
+                  });
+                  Self(bind_group)
+              }
+              pub fn set<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+                  render_pass.set_bind_group(0, &self.0, &[]);
+              }
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn partial_bind_groups() {
+    let source = indoc! {r#"
+            @group(0) @binding(0) var<uniform> globals: vec4<f32>;
+            @group(1) @binding(0) var<uniform> color: vec4<f32>;
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let bind_group_data = get_bind_group_data(&module).unwrap();
+    let options = WgslBindgenOption {
+      partial_bind_groups: true,
+      ..Default::default()
+    };
+    let pass_types = pass_types(wgpu::ShaderStages::FRAGMENT, &options);
+
+    let actual = partial_bind_groups(&bind_group_data, &pass_types, &options);
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug, Default, Copy, Clone)]
+          pub struct WgpuBindGroupsPartial<'a> {
+              pub bind_group0: Option<&'a WgpuBindGroup0>,
+              pub bind_group1: Option<&'a WgpuBindGroup1>
+          }
+          impl<'a> WgpuBindGroupsPartial<'a> {
+              pub fn set(&self, pass: &mut wgpu::RenderPass<'a>) {
+                  if let Some(bind_group) = self.bind_group0 {
+                      bind_group.set(pass);
+                  }
+                  if let Some(bind_group) = self.bind_group1 {
+                      bind_group.set(pass);
+                  }
+              }
+          }
+          impl<'a> From<WgpuBindGroups<'a>> for WgpuBindGroupsPartial<'a> {
+              fn from(bind_groups: WgpuBindGroups<'a>) -> Self {
+                  Self {
+                      bind_group0: Some(bind_groups.bind_group0),
+                      bind_group1: Some(bind_groups.bind_group1)
+                  }
+              }
+          }
+      },
+      actual
+    );
+
+    let actual = set_bind_group_fns(&bind_group_data, &pass_types, &options);
+    assert_tokens_eq!(
+      quote! {
+          pub fn set_bind_group0<'a>(
+              pass: &mut wgpu::RenderPass<'a>,
+              bind_group0: &'a bind_groups::WgpuBindGroup0
+          ) {
+              bind_group0.set(pass);
+          }
+          pub fn set_bind_group1<'a>(
+              pass: &mut wgpu::RenderPass<'a>,
+              bind_group1: &'a bind_groups::WgpuBindGroup1
+          ) {
+              bind_group1.set(pass);
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn owned_bind_groups() {
+    let source = indoc! {r#"
+            @group(0) @binding(0) var<uniform> globals: vec4<f32>;
+            @group(0) @binding(1) var color: texture_2d<f32>;
+            @group(0) @binding(2) var color_sampler: sampler;
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let bind_group_data = get_bind_group_data(&module).unwrap();
+    let options = WgslBindgenOption {
+      owned_bind_groups: true,
+      ..Default::default()
+    };
+
+    let actual = group_builder(&bind_group_data[&0], &options).owned_bind_group();
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug, Clone)]
+          pub struct WgpuBindGroupResources0 {
+              pub globals: std::sync::Arc<wgpu::Buffer>,
+              pub color: std::sync::Arc<wgpu::TextureView>,
+              pub color_sampler: std::sync::Arc<wgpu::Sampler>
+          }
+          #[derive(Debug)]
+          pub struct WgpuBindGroupOwned0 {
+              pub bind_group: WgpuBindGroup0,
+              pub resources: WgpuBindGroupResources0,
+          }
+          impl WgpuBindGroupOwned0 {
+              pub fn from_resources(device: &wgpu::Device, resources: WgpuBindGroupResources0) -> Self {
+                  let bind_group = WgpuBindGroup0::from_bindings(
+                      device,
+                      WgpuBindGroupLayout0 {
+                          globals: resources.globals.as_entire_buffer_binding(),
+                          color: &resources.color,
+                          color_sampler: &resources.color_sampler
+                      },
+                  );
+                  Self { bind_group, resources }
+              }
+          }
+          impl std::ops::Deref for WgpuBindGroupOwned0 {
+              type Target = WgpuBindGroup0;
+
+              fn deref(&self) -> &Self::Target {
+                  &self.bind_group
+              }
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn bind_group_builders() {
+    let source = indoc! {r#"
+            @group(0) @binding(0) var<uniform> globals: vec4<f32>;
+            @group(0) @binding(1) var color: texture_2d<f32>;
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let bind_group_data = get_bind_group_data(&module).unwrap();
+    let options = WgslBindgenOption {
+      bind_group_builders: true,
+      ..Default::default()
+    };
+
+    let actual = group_builder(&bind_group_data[&0], &options).bind_group_builder();
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug)]
+          pub struct WgpuBindGroup0Builder<'a> {
+              device: &'a wgpu::Device,
+              globals: Option<wgpu::BufferBinding<'a> >,
+              color: Option<&'a wgpu::TextureView>
+          }
+          impl<'a> WgpuBindGroup0Builder<'a> {
+              pub fn globals(mut self, globals: wgpu::BufferBinding<'a>) -> Self {
+                  self.globals = Some(globals);
+                  self
+              }
+              pub fn color(mut self, color: &'a wgpu::TextureView) -> Self {
+                  self.color = Some(color);
+                  self
+              }
+              pub fn build(self) -> Result<WgpuBindGroup0, &'static str> {
+                  let bindings = WgpuBindGroupLayout0 {
+                      globals: self.globals.ok_or("WgpuBindGroup0::globals is not set")?,
+                      color: self.color.ok_or("WgpuBindGroup0::color is not set")?
+                  };
+                  Ok(WgpuBindGroup0::from_bindings(self.device, bindings))
+              }
+          }
+          impl WgpuBindGroup0 {
+              pub fn builder(device: &wgpu::Device) -> WgpuBindGroup0Builder<'_> {
+                  WgpuBindGroup0Builder {
+                      device,
+                      globals: None,
+                      color: None
+                  }
+              }
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn bind_group_providers() {
+    let source = indoc! {r#"
+            @group(0) @binding(0) var<uniform> globals: vec4<f32>;
+            @group(0) @binding(1) var color_sampler: sampler;
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let bind_group_data = get_bind_group_data(&module).unwrap();
+    let options = WgslBindgenOption {
+      bind_group_providers: true,
+      ..Default::default()
+    };
+
+    let actual = group_builder(&bind_group_data[&0], &options).bind_group_provider();
+    assert_tokens_eq!(
+      quote! {
+          pub trait ProvideBindGroup0 {
+              fn globals<'a>(&'a self) -> wgpu::BufferBinding<'a>;
+              fn color_sampler<'a>(&'a self) -> &'a wgpu::Sampler;
+          }
+          impl WgpuBindGroup0 {
+              pub fn from_provider(device: &wgpu::Device, provider: &impl ProvideBindGroup0) -> Self {
+                  let bindings = WgpuBindGroupLayout0 {
+                      globals: provider.globals(),
+                      color_sampler: provider.color_sampler()
+                  };
+                  Self::from_bindings(device, bindings)
+              }
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn binding_arrays() {
+    let source = indoc! {r#"
+            @group(0) @binding(0) var textures: binding_array<texture_2d<f32>, 4>;
+            @group(0) @binding(1) var samplers: binding_array<sampler, 2>;
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    check_binding_types("", &module).unwrap();
+    let bind_group_data = get_bind_group_data(&module).unwrap();
+    let options = WgslBindgenOption {
+      owned_bind_groups: true,
+      bind_group_builders: true,
+      bind_group_providers: true,
+      ..Default::default()
+    };
+
+    let actual = BindGroupLayoutBuilder::new(
+      "Test",
+      0,
+      &bind_group_data[&0],
+      &options.wgpu_binding_generator.bind_group_layout,
+    )
+    .build();
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug)]
+          pub struct WgpuBindGroupLayout0<'a> {
+              #[doc = " `@group(0) @binding(0) var textures: binding_array<texture_2d<f32>, 4>`"]
+              pub textures: &'a [&'a wgpu::TextureView],
+              #[doc = " `@group(0) @binding(1) var samplers: binding_array<sampler, 2>`"]
+              pub samplers: &'a [&'a wgpu::Sampler],
+          }
+          impl<'a> WgpuBindGroupLayout0<'a> {
+              pub fn entries(self) -> [wgpu::BindGroupEntry<'a>; 2] {
+                  [
+                      wgpu::BindGroupEntry {
+                          binding: 0,
+                          resource: wgpu::BindingResource::TextureViewArray(self.textures),
+                      },
+                      wgpu::BindGroupEntry {
+                          binding: 1,
+                          resource: wgpu::BindingResource::SamplerArray(self.samplers),
+                      },
+                  ]
+              }
+          }
+      },
+      actual
+    );
+
+    let builder = group_builder(&bind_group_data[&0], &options);
+    assert_eq!(
+      quote! {
+          wgpu::BindGroupLayoutDescriptor {
+              label: Some("Test::BindGroup0::LayoutDescriptor"),
+              entries: &[
+                  wgpu::BindGroupLayoutEntry {
+                      binding: 0,
+                      visibility: wgpu::ShaderStages::FRAGMENT,
+                      ty: wgpu::BindingType::Texture {
+                          sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                          view_dimension: wgpu::TextureViewDimension::D2,
+                          multisampled: false,
+                      },
+                      count: std::num::NonZeroU32::new(4),
+                  },
+                  wgpu::BindGroupLayoutEntry {
+                      binding: 1,
+                      visibility: wgpu::ShaderStages::FRAGMENT,
+                      ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                      count: std::num::NonZeroU32::new(2),
+                  }
+              ],
+          }
+      }
+      .to_string(),
+      builder.bind_group_layout_descriptor().to_string()
+    );
+
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug, Clone)]
+          pub struct WgpuBindGroupResources0 {
+              pub textures: std::vec::Vec<std::sync::Arc<wgpu::TextureView>>,
+              pub samplers: std::vec::Vec<std::sync::Arc<wgpu::Sampler>>,
+          }
+          #[derive(Debug)]
+          pub struct WgpuBindGroupOwned0 {
+              pub bind_group: WgpuBindGroup0,
+              pub resources: WgpuBindGroupResources0,
+          }
+          impl WgpuBindGroupOwned0 {
+              pub fn from_resources(device: &wgpu::Device, resources: WgpuBindGroupResources0) -> Self {
+                  let textures: std::vec::Vec<_> = resources.textures.iter().map(|view| &**view).collect();
+                  let samplers: std::vec::Vec<_> =
+                      resources.samplers.iter().map(|sampler| &**sampler).collect();
+                  let bind_group = WgpuBindGroup0::from_bindings(
+                      device,
+                      WgpuBindGroupLayout0 {
+                          textures: &textures,
+                          samplers: &samplers,
+                      },
+                  );
+                  Self { bind_group, resources }
+              }
+          }
+          impl std::ops::Deref for WgpuBindGroupOwned0 {
+              type Target = WgpuBindGroup0;
+
+              fn deref(&self) -> &Self::Target {
+                  &self.bind_group
+              }
+          }
+      },
+      builder.owned_bind_group()
+    );
+
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug)]
+          pub struct WgpuBindGroup0Builder<'a> {
+              device: &'a wgpu::Device,
+              textures: Option<&'a [&'a wgpu::TextureView]>,
+              samplers: Option<&'a [&'a wgpu::Sampler]>,
+          }
+          impl<'a> WgpuBindGroup0Builder<'a> {
+              pub fn textures(mut self, textures: &'a [&'a wgpu::TextureView]) -> Self {
+                  self.textures = Some(textures);
+                  self
+              }
+              pub fn samplers(mut self, samplers: &'a [&'a wgpu::Sampler]) -> Self {
+                  self.samplers = Some(samplers);
+                  self
+              }
+              pub fn build(self) -> Result<WgpuBindGroup0, &'static str> {
+                  let bindings = WgpuBindGroupLayout0 {
+                      textures: self.textures.ok_or("WgpuBindGroup0::textures is not set")?,
+                      samplers: self.samplers.ok_or("WgpuBindGroup0::samplers is not set")?,
+                  };
+                  Ok(WgpuBindGroup0::from_bindings(self.device, bindings))
+              }
+          }
+          impl WgpuBindGroup0 {
+              pub fn builder(device: &wgpu::Device) -> WgpuBindGroup0Builder<'_> {
+                  WgpuBindGroup0Builder {
+                      device,
+                      textures: None,
+                      samplers: None,
+                  }
+              }
+          }
+      },
+      builder.bind_group_builder()
+    );
+
+    assert_tokens_eq!(
+      quote! {
+          pub trait ProvideBindGroup0 {
+              fn textures<'a>(&'a self) -> &'a [&'a wgpu::TextureView];
+              fn samplers<'a>(&'a self) -> &'a [&'a wgpu::Sampler];
+          }
+          impl WgpuBindGroup0 {
+              pub fn from_provider(device: &wgpu::Device, provider: &impl ProvideBindGroup0) -> Self {
+                  let bindings = WgpuBindGroupLayout0 {
+                      textures: provider.textures(),
+                      samplers: provider.samplers(),
+                  };
+                  Self::from_bindings(device, bindings)
+              }
+          }
+      },
+      builder.bind_group_provider()
+    );
+  }
+
+  #[test]
+  fn annotated_bindings() {
+    let source = indoc! {r#"
+            // wgsl_bindgen: dynamic_offset, name=camera
+            @group(0) @binding(0) var<uniform> camera_uniform: vec4<f32>;
+            // wgsl_bindgen: filterable=false
+            @group(0) @binding(1) var values: texture_2d<f32>;
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let mut bind_group_data = get_bind_group_data(&module).unwrap();
+    let docs = WgslDocs::from_sources([source]).unwrap();
+    annotate_bindings("", &mut bind_group_data, &docs).unwrap();
+
+    let actual = bind_groups_module(
+      "",
+      &WgslBindgenOption::default(),
+      &bind_group_data,
+      wgpu::ShaderStages::FRAGMENT,
+    );
+
+    assert_tokens_eq!(
+      quote! {
+          pub mod bind_groups {
+              #[derive(Debug)]
+              pub struct WgpuBindGroupLayout0<'a> {
+                  #[doc = " `@group(0) @binding(0) var<uniform> camera_uniform: vec4<f32>`"]
+                  pub camera: wgpu::BufferBinding<'a>,
+                  #[doc = " `@group(0) @binding(1) var values: texture_2d<f32>`"]
+                  pub values: &'a wgpu::TextureView,
+              }
+              impl<'a> WgpuBindGroupLayout0<'a> {
+                  pub fn entries(self) -> [wgpu::BindGroupEntry<'a>; 2] {
+                      [
+                          wgpu::BindGroupEntry {
+                              binding: 0,
+                              resource: wgpu::BindingResource::Buffer(self.camera),
+                          },
+                          wgpu::BindGroupEntry {
+                              binding: 1,
+                              resource: wgpu::BindingResource::TextureView(self.values),
+                          },
+                      ]
+                  }
+              }
+              #[derive(Debug)]
+              pub struct WgpuBindGroup0(wgpu::BindGroup);
+              impl WgpuBindGroup0 {
+                  pub const LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> = wgpu::BindGroupLayoutDescriptor {
+                      label: Some("::BindGroup0::LayoutDescriptor"),
+                      entries: &[
+                          wgpu::BindGroupLayoutEntry {
+                              binding: 0,
+                              visibility: wgpu::ShaderStages::FRAGMENT,
+                              ty: wgpu::BindingType::Buffer {
+                                  ty: wgpu::BufferBindingType::Uniform,
+                                  has_dynamic_offset: true,
+                                  min_binding_size: None,
+                              },
+                              count: None,
+                          },
+                          wgpu::BindGroupLayoutEntry {
+                              binding: 1,
+                              visibility: wgpu::ShaderStages::FRAGMENT,
+                              ty: wgpu::BindingType::Texture {
+                                  sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                                  view_dimension: wgpu::TextureViewDimension::D2,
+                                  multisampled: false,
+                              },
+                              count: None,
+                          },
+                      ],
+                  };
+                  pub fn get_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+                      device.create_bind_group_layout(&Self::LAYOUT_DESCRIPTOR)
+                  }
+                  pub fn from_bindings(device: &wgpu::Device, bindings: WgpuBindGroupLayout0) -> Self {
+                      let bind_group_layout = Self::get_bind_group_layout(&device);
+                      let entries = bindings.entries();
+                      let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                          label: Some("::BindGroup0"),
+                          layout: &bind_group_layout,
+                          entries: &entries,
+                      });
+                      Self(bind_group)
+                  }
+                  pub fn set<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+                      self.set_with_offsets(render_pass, &[0; 1]);
+                  }
+                  pub fn set_with_offsets<'a>(
+                      &'a self,
+                      render_pass: &mut wgpu::RenderPass<'a>,
+                      offsets: &[wgpu::DynamicOffset; 1],
+                  ) {
+                      render_pass.set_bind_group(0, &self.0, offsets);
+                  }
+              }
+              #[derive(Debug, Copy, Clone)]
+              pub struct WgpuBindGroups<'a> {
+                  pub bind_group0: &'a WgpuBindGroup0,
+              }
+              impl<'a> WgpuBindGroups<'a> {
+                  pub fn set(&self, pass: &mut wgpu::RenderPass<'a>) {
+                      self.bind_group0.set(pass);
+                  }
+              }
+          }
+          pub fn set_bind_groups<'a>(
+              pass: &mut wgpu::RenderPass<'a>,
+              bind_group0: &'a bind_groups::WgpuBindGroup0,
+          ) {
+              bind_group0.set(pass);
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn named_bind_groups() {
+    let source = indoc! {r#"
+            @group(0) @binding(0) var<uniform> view: vec4<f32>;
+            // wgsl_bindgen: group=material
+            @group(1) @binding(0) var color_texture: texture_2d<f32>;
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let mut bind_group_data = get_bind_group_data(&module).unwrap();
+    let options = WgslBindgenOption {
+      bind_group_names: vec![(0, "Camera".to_owned()), (1, "Unused".to_owned())],
+      ..Default::default()
+    };
+    let docs = WgslDocs::from_sources([source]).unwrap();
+    annotate_bindings("", &mut bind_group_data, &docs).unwrap();
+    name_bind_groups("", &mut bind_group_data, &options).unwrap();
+
+    let actual =
+      bind_groups_module("", &options, &bind_group_data, wgpu::ShaderStages::FRAGMENT);
+
+    // The labels and the index passed to `set_bind_group` still use the group index.
+    assert_tokens_eq!(
+      quote! {
+          pub mod bind_groups {
+              #[derive(Debug)]
+              pub struct WgpuBindGroupLayoutCamera<'a> {
+                  #[doc = " `@group(0) @binding(0) var<uniform> view: vec4<f32>`"]
+                  pub view: wgpu::BufferBinding<'a>,
+              }
+              impl<'a> WgpuBindGroupLayoutCamera<'a> {
+                  pub fn entries(self) -> [wgpu::BindGroupEntry<'a>; 1] {
+                      [
+                          wgpu::BindGroupEntry {
+                              binding: 0,
+                              resource: wgpu::BindingResource::Buffer(self.view),
+                          },
+                      ]
+                  }
+              }
+              #[derive(Debug)]
+              pub struct WgpuBindGroupCamera(wgpu::BindGroup);
+              impl WgpuBindGroupCamera {
+                  pub const LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> = wgpu::BindGroupLayoutDescriptor {
+                      label: Some("::BindGroup0::LayoutDescriptor"),
+                      entries: &[
+                          wgpu::BindGroupLayoutEntry {
+                              binding: 0,
+                              visibility: wgpu::ShaderStages::FRAGMENT,
+                              ty: wgpu::BindingType::Buffer {
+                                  ty: wgpu::BufferBindingType::Uniform,
+                                  has_dynamic_offset: false,
+                                  min_binding_size: None,
+                              },
+                              count: None,
+                          },
+                      ],
+                  };
+                  pub fn get_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+                      device.create_bind_group_layout(&Self::LAYOUT_DESCRIPTOR)
+                  }
+                  pub fn from_bindings(device: &wgpu::Device, bindings: WgpuBindGroupLayoutCamera) -> Self {
+                      let bind_group_layout = Self::get_bind_group_layout(&device);
+                      let entries = bindings.entries();
+                      let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                          label: Some("::BindGroup0"),
+                          layout: &bind_group_layout,
+                          entries: &entries,
+                      });
+                      Self(bind_group)
+                  }
+                  pub fn set<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+                      render_pass.set_bind_group(0, &self.0, &[]);
+                  }
+              }
+              #[derive(Debug)]
+              pub struct WgpuBindGroupLayoutMaterial<'a> {
+                  #[doc = " `@group(1) @binding(0) var color_texture: texture_2d<f32>`"]
+                  pub color_texture: &'a wgpu::TextureView,
+              }
+              impl<'a> WgpuBindGroupLayoutMaterial<'a> {
+                  pub fn entries(self) -> [wgpu::BindGroupEntry<'a>; 1] {
+                      [
+                          wgpu::BindGroupEntry {
+                              binding: 0,
+                              resource: wgpu::BindingResource::TextureView(self.color_texture),
+                          },
+                      ]
+                  }
+              }
+              #[derive(Debug)]
+              pub struct WgpuBindGroupMaterial(wgpu::BindGroup);
+              impl WgpuBindGroupMaterial {
+                  pub const LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> = wgpu::BindGroupLayoutDescriptor {
+                      label: Some("::BindGroup1::LayoutDescriptor"),
+                      entries: &[
+                          wgpu::BindGroupLayoutEntry {
+                              binding: 0,
+                              visibility: wgpu::ShaderStages::FRAGMENT,
+                              ty: wgpu::BindingType::Texture {
+                                  sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                                  view_dimension: wgpu::TextureViewDimension::D2,
+                                  multisampled: false,
+                              },
+                              count: None,
+                          },
+                      ],
+                  };
+                  pub fn get_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+                      device.create_bind_group_layout(&Self::LAYOUT_DESCRIPTOR)
+                  }
+                  pub fn from_bindings(device: &wgpu::Device, bindings: WgpuBindGroupLayoutMaterial) -> Self {
+                      let bind_group_layout = Self::get_bind_group_layout(&device);
+                      let entries = bindings.entries();
+                      let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                          label: Some("::BindGroup1"),
+                          layout: &bind_group_layout,
+                          entries: &entries,
+                      });
+                      Self(bind_group)
+                  }
+                  pub fn set<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+                      render_pass.set_bind_group(1, &self.0, &[]);
+                  }
+              }
+              #[derive(Debug, Copy, Clone)]
+              pub struct WgpuBindGroups<'a> {
+                  pub bind_group_camera: &'a WgpuBindGroupCamera,
+                  pub bind_group_material: &'a WgpuBindGroupMaterial,
+              }
+              impl<'a> WgpuBindGroups<'a> {
+                  pub fn set(&self, pass: &mut wgpu::RenderPass<'a>) {
+                      self.bind_group_camera.set(pass);
+                      self.bind_group_material.set(pass);
+                  }
+              }
+          }
+          pub fn set_bind_groups<'a>(
+              pass: &mut wgpu::RenderPass<'a>,
+              bind_group_camera: &'a bind_groups::WgpuBindGroupCamera,
+              bind_group_material: &'a bind_groups::WgpuBindGroupMaterial,
+          ) {
+              bind_group_camera.set(pass);
+              bind_group_material.set(pass);
+          }
+      },
+      actual
+    );
+  }
+
   #[test]
-  fn bind_group_data_consecutive_bind_groups() {
+  fn conflicting_group_annotations() {
     let source = indoc! {r#"
-            @group(0) @binding(0) var<uniform> a: vec4<f32>;
-            @group(1) @binding(0) var<uniform> b: vec4<f32>;
-            @group(2) @binding(0) var<uniform> c: vec4<f32>;
-
-            @fragment
-            fn main() {}
+            // wgsl_bindgen: group=camera
+            @group(0) @binding(0) var<uniform> view: vec4<f32>;
+            // wgsl_bindgen: group=material
+            @group(0) @binding(1) var color_texture: texture_2d<f32>;
         "#};
 
     let module = naga::front::wgsl::parse_str(source).unwrap();
-    assert_eq!(3, get_bind_group_data(&module).unwrap().len());
+    let mut bind_group_data = get_bind_group_data(&module).unwrap();
+    let docs = WgslDocs::from_sources([source]).unwrap();
+
+    assert_eq!(
+      Err(CreateModuleError::ConflictingBindGroupNames {
+        module: "test".to_owned(),
+        group: 0,
+        name: "camera".to_owned(),
+        other_name: "material".to_owned(),
+      }),
+      annotate_bindings("test", &mut bind_group_data, &docs)
+    );
   }
 
   #[test]
-  fn bind_group_data_first_group_not_zero() {
+  fn duplicate_bind_group_names() {
     let source = indoc! {r#"
-            @group(1) @binding(0) var<uniform> a: vec4<f32>;
-
-            @fragment
-            fn main() {}
+            // wgsl_bindgen: group=camera
+            @group(0) @binding(0) var<uniform> view: vec4<f32>;
+            @group(1) @binding(0) var color_texture: texture_2d<f32>;
+            @group(2) @binding(0) var color_sampler: sampler;
         "#};
 
     let module = naga::front::wgsl::parse_str(source).unwrap();
-    assert!(matches!(
-      get_bind_group_data(&module),
-      Err(CreateModuleError::NonConsecutiveBindGroups)
-    ));
+    let docs = WgslDocs::from_sources([source]).unwrap();
+    let named = |bind_group_names: Vec<(u32, &str)>| {
+      let mut bind_group_data = get_bind_group_data(&module).unwrap();
+      let options = WgslBindgenOption {
+        bind_group_names: bind_group_names
+          .into_iter()
+          .map(|(group_no, name)| (group_no, name.to_owned()))
+          .collect(),
+        ..Default::default()
+      };
+      annotate_bindings("test", &mut bind_group_data, &docs).unwrap();
+      name_bind_groups("test", &mut bind_group_data, &options)
+    };
+
+    // The same name as the annotation, once in pascal case.
+    assert_eq!(
+      Err(CreateModuleError::DuplicateBindGroupName {
+        module: "test".to_owned(),
+        group: 0,
+        other_group: 1,
+        name: "WgpuBindGroupCamera".to_owned(),
+      }),
+      named(vec![(1, "Camera")])
+    );
+    // The same name as an unnamed group.
+    assert_eq!(
+      Err(CreateModuleError::DuplicateBindGroupName {
+        module: "test".to_owned(),
+        group: 1,
+        other_group: 2,
+        name: "WgpuBindGroup1".to_owned(),
+      }),
+      named(vec![(2, "1")])
+    );
+    assert_eq!(Ok(()), named(vec![(1, "Material")]));
   }
 
   #[test]
-  fn bind_group_data_non_consecutive_bind_groups() {
+  fn binding_visibility() {
     let source = indoc! {r#"
-            @group(0) @binding(0) var<uniform> a: vec4<f32>;
-            @group(1) @binding(0) var<uniform> b: vec4<f32>;
-            @group(3) @binding(0) var<uniform> c: vec4<f32>;
+            @group(0) @binding(0) var<uniform> transform: mat4x4<f32>;
+            @group(0) @binding(1) var<uniform> color: vec4<f32>;
+
+            @vertex
+            fn vs_main() -> @builtin(position) vec4<f32> {
+                return transform * vec4(0.0);
+            }
 
             @fragment
-            fn main() {}
+            fn fs_main() -> @location(0) vec4<f32> {
+                return color;
+            }
         "#};
 
     let module = naga::front::wgsl::parse_str(source).unwrap();
-    assert!(matches!(
-      get_bind_group_data(&module),
-      Err(CreateModuleError::NonConsecutiveBindGroups)
-    ));
+    let bind_group_data = get_bind_group_data(&module).unwrap();
+    let visibilities = |binding_visibility| {
+      let options = WgslBindgenOption {
+        binding_visibility,
+        ..Default::default()
+      };
+      let group = &bind_group_data[&0];
+      BindGroupBuilder::new(
+        "Test",
+        0,
+        group,
+        wgpu::ShaderStages::VERTEX_FRAGMENT,
+        &options,
+        &options.wgpu_binding_generator.bind_group_layout,
+      )
+      .bind_group_layout_descriptor()
+      .to_string()
+    };
+    let descriptor = |transform: TokenStream, color: TokenStream| {
+      quote! {
+          wgpu::BindGroupLayoutDescriptor {
+              label: Some("Test::BindGroup0::LayoutDescriptor"),
+              entries: &[
+                  wgpu::BindGroupLayoutEntry {
+                      binding: 0,
+                      visibility: #transform,
+                      ty: wgpu::BindingType::Buffer {
+                          ty: wgpu::BufferBindingType::Uniform,
+                          has_dynamic_offset: false,
+                          min_binding_size: None,
+                      },
+                      count: None,
+                  },
+                  wgpu::BindGroupLayoutEntry {
+                      binding: 1,
+                      visibility: #color,
+                      ty: wgpu::BindingType::Buffer {
+                          ty: wgpu::BufferBindingType::Uniform,
+                          has_dynamic_offset: false,
+                          min_binding_size: None,
+                      },
+                      count: None,
+                  }
+              ],
+          }
+      }
+      .to_string()
+    };
+
+    assert_eq!(
+      descriptor(
+        quote!(wgpu::ShaderStages::VERTEX),
+        quote!(wgpu::ShaderStages::FRAGMENT)
+      ),
+      visibilities(BindingVisibility::Inferred)
+    );
+    assert_eq!(
+      descriptor(
+        quote!(wgpu::ShaderStages::VERTEX_FRAGMENT),
+        quote!(wgpu::ShaderStages::VERTEX_FRAGMENT)
+      ),
+      visibilities(BindingVisibility::UnionOfModule)
+    );
+    assert_eq!(
+      descriptor(quote!(wgpu::ShaderStages::all()), quote!(wgpu::ShaderStages::all())),
+      visibilities(BindingVisibility::All)
+    );
   }
 
   #[test]
@@ -462,7 +2969,10 @@ mod tests {
 
     let actual = bind_groups_module(
       "",
-      &WgslBindgenOption::default(),
+      &WgslBindgenOption {
+        binding_index_constants: true,
+        ..Default::default()
+      },
       &bind_group_data,
       wgpu::ShaderStages::COMPUTE,
     );
@@ -472,8 +2982,11 @@ mod tests {
           pub mod bind_groups {
               #[derive(Debug)]
               pub struct WgpuBindGroupLayout0<'a> {
+                  #[doc = " `@group(0) @binding(0) var<storage, read> src: array<vec4<f32>>`"]
                   pub src: wgpu::BufferBinding<'a>,
+                  #[doc = " `@group(0) @binding(1) var<storage, read> vertex_weights: VertexWeights`"]
                   pub vertex_weights: wgpu::BufferBinding<'a>,
+                  #[doc = " `@group(0) @binding(2) var<storage, read_write> dst: Vertices`"]
                   pub dst: wgpu::BufferBinding<'a>,
               }
               impl<'a> WgpuBindGroupLayout0<'a> {
@@ -497,6 +3010,9 @@ mod tests {
               #[derive(Debug)]
               pub struct WgpuBindGroup0(wgpu::BindGroup);
               impl WgpuBindGroup0 {
+                pub const SRC_BINDING: u32 = 0;
+                pub const VERTEX_WEIGHTS_BINDING: u32 = 1;
+                pub const DST_BINDING: u32 = 2;
                 pub const LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> = wgpu::BindGroupLayoutDescriptor {
                     label: Some("::BindGroup0::LayoutDescriptor"),
                     entries: &[
@@ -560,6 +3076,7 @@ mod tests {
               }
               #[derive(Debug)]
               pub struct WgpuBindGroupLayout1<'a> {
+                  #[doc = " `@group(1) @binding(0) var<uniform> transforms: Transforms`"]
                   pub transforms: wgpu::BufferBinding<'a>,
               }
               impl<'a> WgpuBindGroupLayout1<'a> {
@@ -575,6 +3092,7 @@ mod tests {
               #[derive(Debug)]
               pub struct WgpuBindGroup1(wgpu::BindGroup);
               impl WgpuBindGroup1 {
+                pub const TRANSFORMS_BINDING: u32 = 0;
                 pub const LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> = wgpu::BindGroupLayoutDescriptor {
                     label: Some("::BindGroup1::LayoutDescriptor"),
                     entries: &[
@@ -636,6 +3154,236 @@ mod tests {
     );
   }
 
+  #[test]
+  fn bind_groups_module_compute_and_render() {
+    let source = indoc! {r#"
+            struct Transforms {};
+
+            @group(0) @binding(0) var<uniform> transforms: Transforms;
+
+            @vertex
+            fn vs_main() {}
+
+            @compute
+            @workgroup_size(64)
+            fn cs_main() {}
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let bind_group_data = get_bind_group_data(&module).unwrap();
+
+    let actual = bind_groups_module(
+      "",
+      &WgslBindgenOption {
+        set_bind_groups_from: true,
+        ..Default::default()
+      },
+      &bind_group_data,
+      wgsl::shader_stages(&module),
+    );
+
+    assert_tokens_eq!(
+      quote! {
+          pub mod bind_groups {
+              #[derive(Debug)]
+              pub struct WgpuBindGroupLayout0<'a> {
+                  #[doc = " `@group(0) @binding(0) var<uniform> transforms: Transforms`"]
+                  pub transforms: wgpu::BufferBinding<'a>,
+              }
+              impl<'a> WgpuBindGroupLayout0<'a> {
+                pub fn entries(self) -> [wgpu::BindGroupEntry<'a>; 1] {
+                  [
+                      wgpu::BindGroupEntry {
+                          binding: 0,
+                          resource: wgpu::BindingResource::Buffer(self.transforms),
+                      },
+                  ]
+                }
+              }
+              #[derive(Debug)]
+              pub struct WgpuBindGroup0(wgpu::BindGroup);
+              impl WgpuBindGroup0 {
+                pub const LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> = wgpu::BindGroupLayoutDescriptor {
+                    label: Some("::BindGroup0::LayoutDescriptor"),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::empty()
+                                .union(wgpu::ShaderStages::VERTEX)
+                                .union(wgpu::ShaderStages::COMPUTE),
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Uniform,
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                    ],
+                };
+                  pub fn get_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+                      device.create_bind_group_layout(&Self::LAYOUT_DESCRIPTOR)
+                  }
+                  pub fn from_bindings(device: &wgpu::Device, bindings: WgpuBindGroupLayout0) -> Self {
+                      let bind_group_layout = Self::get_bind_group_layout(&device);
+                      let entries = bindings.entries();
+                      let bind_group = device
+                          .create_bind_group(
+                              &wgpu::BindGroupDescriptor {
+                                  label: Some("::BindGroup0"),
+                                  layout: &bind_group_layout,
+                                  entries: &entries,
+                              },
+                          );
+                      Self(bind_group)
+                  }
+                  pub fn set<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+                      render_pass.set_bind_group(0, &self.0, &[]);
+                  }
+                  pub fn set_compute<'a>(&'a self, render_pass: &mut wgpu::ComputePass<'a>) {
+                      render_pass.set_bind_group(0, &self.0, &[]);
+                  }
+              }
+              #[derive(Debug, Copy, Clone)]
+              pub struct WgpuBindGroups<'a> {
+                  pub bind_group0: &'a WgpuBindGroup0,
+              }
+              impl<'a> WgpuBindGroups<'a> {
+                  pub fn set(&self, pass: &mut wgpu::RenderPass<'a>) {
+                      self.bind_group0.set(pass);
+                  }
+                  pub fn set_compute(&self, pass: &mut wgpu::ComputePass<'a>) {
+                      self.bind_group0.set_compute(pass);
+                  }
+              }
+          }
+          pub fn set_bind_groups<'a>(
+              pass: &mut wgpu::RenderPass<'a>,
+              bind_group0: &'a bind_groups::WgpuBindGroup0,
+          ) {
+              bind_group0.set(pass);
+          }
+          pub fn set_bind_groups_from<'a>(
+              pass: &mut wgpu::RenderPass<'a>,
+              bind_groups: &bind_groups::WgpuBindGroups<'a>,
+          ) {
+              bind_groups.set(pass);
+          }
+          pub fn set_compute_bind_groups<'a>(
+              pass: &mut wgpu::ComputePass<'a>,
+              bind_group0: &'a bind_groups::WgpuBindGroup0,
+          ) {
+              bind_group0.set_compute(pass);
+          }
+          pub fn set_compute_bind_groups_from<'a>(
+              pass: &mut wgpu::ComputePass<'a>,
+              bind_groups: &bind_groups::WgpuBindGroups<'a>,
+          ) {
+              bind_groups.set_compute(pass);
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn bind_groups_module_generic_pass() {
+    let source = indoc! {r#"
+            struct Transforms {};
+
+            @group(0) @binding(0) var<uniform> transforms: Transforms;
+
+            @compute
+            @workgroup_size(64)
+            fn main() {}
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let bind_group_data = get_bind_group_data(&module).unwrap();
+
+    let options = WgslBindgenOption {
+      generic_bind_group_pass: true,
+      ..Default::default()
+    };
+    let actual =
+      bind_groups_module("", &options, &bind_group_data, wgpu::ShaderStages::COMPUTE);
+
+    assert_tokens_eq!(
+      quote! {
+          pub mod bind_groups {
+              #[derive(Debug)]
+              pub struct WgpuBindGroupLayout0<'a> {
+                  #[doc = " `@group(0) @binding(0) var<uniform> transforms: Transforms`"]
+                  pub transforms: wgpu::BufferBinding<'a>,
+              }
+              impl<'a> WgpuBindGroupLayout0<'a> {
+                pub fn entries(self) -> [wgpu::BindGroupEntry<'a>; 1] {
+                  [
+                      wgpu::BindGroupEntry {
+                          binding: 0,
+                          resource: wgpu::BindingResource::Buffer(self.transforms),
+                      },
+                  ]
+                }
+              }
+              #[derive(Debug)]
+              pub struct WgpuBindGroup0(wgpu::BindGroup);
+              impl WgpuBindGroup0 {
+                pub const LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> = wgpu::BindGroupLayoutDescriptor {
+                    label: Some("::BindGroup0::LayoutDescriptor"),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::COMPUTE,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Uniform,
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                    ],
+                };
+                  pub fn get_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+                      device.create_bind_group_layout(&Self::LAYOUT_DESCRIPTOR)
+                  }
+                  pub fn from_bindings(device: &wgpu::Device, bindings: WgpuBindGroupLayout0) -> Self {
+                      let bind_group_layout = Self::get_bind_group_layout(&device);
+                      let entries = bindings.entries();
+                      let bind_group = device
+                          .create_bind_group(
+                              &wgpu::BindGroupDescriptor {
+                                  label: Some("::BindGroup0"),
+                                  layout: &bind_group_layout,
+                                  entries: &entries,
+                              },
+                          );
+                      Self(bind_group)
+                  }
+                  pub fn set<'a>(&'a self, render_pass: &mut impl _root::bind_group_pass::SetBindGroup<'a>) {
+                      render_pass.set_bind_group(0, &self.0, &[]);
+                  }
+              }
+              #[derive(Debug, Copy, Clone)]
+              pub struct WgpuBindGroups<'a> {
+                  pub bind_group0: &'a WgpuBindGroup0,
+              }
+              impl<'a> WgpuBindGroups<'a> {
+                  pub fn set(&self, pass: &mut impl _root::bind_group_pass::SetBindGroup<'a>) {
+                      self.bind_group0.set(pass);
+                  }
+              }
+          }
+          pub fn set_bind_groups<'a>(
+              pass: &mut impl _root::bind_group_pass::SetBindGroup<'a>,
+              bind_group0: &'a bind_groups::WgpuBindGroup0,
+          ) {
+              bind_group0.set(pass);
+          }
+      },
+      actual
+    );
+  }
+
   #[test]
   fn bind_groups_module_vertex_fragment() {
     // Test different texture and sampler types.
@@ -690,14 +3438,23 @@ mod tests {
           pub mod bind_groups {
               #[derive(Debug)]
               pub struct WgpuBindGroupLayout0<'a> {
+                  #[doc = " `@group(0) @binding(0) var color_texture: texture_2d<f32>`"]
                   pub color_texture: &'a wgpu::TextureView,
+                  #[doc = " `@group(0) @binding(1) var color_sampler: sampler`"]
                   pub color_sampler: &'a wgpu::Sampler,
+                  #[doc = " `@group(0) @binding(2) var depth_texture: texture_depth_2d`"]
                   pub depth_texture: &'a wgpu::TextureView,
+                  #[doc = " `@group(0) @binding(3) var comparison_sampler: sampler_comparison`"]
                   pub comparison_sampler: &'a wgpu::Sampler,
+                  #[doc = " `@group(0) @binding(4) var storage_tex_read: texture_storage_2d<r32float, read>`"]
                   pub storage_tex_read: &'a wgpu::TextureView,
+                  #[doc = " `@group(0) @binding(5) var storage_tex_write: texture_storage_2d<rg32sint, write>`"]
                   pub storage_tex_write: &'a wgpu::TextureView,
+                  #[doc = " `@group(0) @binding(6) var storage_tex_read_write: texture_storage_2d<rgba8uint, read_write>`"]
                   pub storage_tex_read_write: &'a wgpu::TextureView,
+                  #[doc = " `@group(0) @binding(7) var color_texture_msaa: texture_multisampled_2d<f32>`"]
                   pub color_texture_msaa: &'a wgpu::TextureView,
+                  #[doc = " `@group(0) @binding(8) var depth_texture_msaa: texture_depth_multisampled_2d`"]
                   pub depth_texture_msaa: &'a wgpu::TextureView,
               }
               impl<'a> WgpuBindGroupLayout0<'a> {
@@ -876,7 +3633,9 @@ mod tests {
               }
               #[derive(Debug)]
               pub struct WgpuBindGroupLayout1<'a> {
+                  #[doc = " `@group(1) @binding(0) var<uniform> transforms: Transforms`"]
                   pub transforms: wgpu::BufferBinding<'a>,
+                  #[doc = " `@group(1) @binding(1) var<uniform> one: f32`"]
                   pub one: wgpu::BufferBinding<'a>,
               }
               impl<'a> WgpuBindGroupLayout1<'a> {
@@ -995,6 +3754,7 @@ mod tests {
           pub mod bind_groups {
               #[derive(Debug)]
               pub struct WgpuBindGroupLayout0<'a> {
+                  #[doc = " `@group(0) @binding(0) var<uniform> transforms: Transforms`"]
                   pub transforms: wgpu::BufferBinding<'a>,
               }
               impl<'a> WgpuBindGroupLayout0<'a> {
@@ -1094,6 +3854,7 @@ mod tests {
           pub mod bind_groups {
               #[derive(Debug)]
               pub struct WgpuBindGroupLayout0<'a> {
+                  #[doc = " `@group(0) @binding(0) var<uniform> transforms: Transforms`"]
                   pub transforms: wgpu::BufferBinding<'a>,
               }
               impl<'a> WgpuBindGroupLayout0<'a> {
@@ -3,8 +3,14 @@ use quote::quote;
 use syn::Ident;
 
 use crate::quote_gen::{RustItem, RustItemKind, RustItemPath};
+use crate::wgsl_docs::WgslDocs;
+use crate::CreateModuleError;
 
-pub fn consts_items(invoking_entry_module: &str, module: &naga::Module) -> Vec<RustItem> {
+pub fn consts_items(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  docs: &WgslDocs,
+) -> Vec<RustItem> {
   // Create matching Rust constants for WGSl constants.
   module
     .constants
@@ -15,6 +21,7 @@ pub fn consts_items(invoking_entry_module: &str, module: &naga::Module) -> Vec<R
       // we don't need full qualification here
       let rust_item_path = RustItemPath::from_mangled(name_str, invoking_entry_module);
       let name = Ident::new(&rust_item_path.item_name, Span::call_site());
+      let doc = docs.item_doc_attrs(&rust_item_path.item_name);
 
       // TODO: Add support for f64 and f16 once naga supports them.
       let type_and_value = match &module.const_expressions[t.init] {
@@ -34,12 +41,43 @@ pub fn consts_items(invoking_entry_module: &str, module: &naga::Module) -> Vec<R
       Some(RustItem::new(
         RustItemKind::ConstVarDecl,
         rust_item_path,
-        quote! { pub const #name: #type_and_value;},
+        quote! {
+          #doc
+          pub const #name: #type_and_value;
+        },
       ))
     })
     .collect()
 }
 
+/// Checks that the constants generated for the module, such as `MAX_BIND_GROUPS_USED`, don't
+/// have the name of a WGSL constant of the module, which is generated in the same Rust module.
+pub fn check_generated_const_names(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  names: impl IntoIterator<Item = impl AsRef<str>>,
+) -> Result<(), CreateModuleError> {
+  let wgsl_names: Vec<_> = module
+    .constants
+    .iter()
+    .filter_map(|(_, c)| c.name.as_deref())
+    .map(|name| RustItemPath::from_mangled(name, invoking_entry_module))
+    .filter(|path| path.parent_module_path == invoking_entry_module)
+    .map(|path| path.item_name)
+    .collect();
+
+  match names
+    .into_iter()
+    .find(|name| wgsl_names.iter().any(|n| n == name.as_ref()))
+  {
+    Some(name) => Err(CreateModuleError::GeneratedConstNameConflict {
+      module: invoking_entry_module.to_owned(),
+      name: name.as_ref().to_owned(),
+    }),
+    None => Ok(()),
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use indoc::indoc;
@@ -49,7 +87,7 @@ mod tests {
   use crate::assert_tokens_eq;
 
   pub fn consts(module: &naga::Module) -> Vec<TokenStream> {
-    consts_items("", module)
+    consts_items("", module, &WgslDocs::default())
       .into_iter()
       .map(|i| i.item)
       .collect()
@@ -1,5 +1,6 @@
 pub(crate) mod bind_group;
 pub(crate) mod consts;
 pub(crate) mod pipeline;
+pub(crate) mod shader_defs;
 pub(crate) mod shader_module;
 pub(crate) mod shader_registry;
@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 
 use derive_more::Constructor;
 
-use super::bind_group::GroupData;
+use super::bind_group::{self, GroupData};
 use crate::*;
 
 #[derive(Constructor)]
@@ -38,18 +38,74 @@ impl<'a> PipelineLayoutDataEntriesBuilder<'a> {
   }
 }
 
+/// The bind groups in `excluded_bind_groups` are created by the user, so their layouts are
+/// created from the shader instead. wgpu treats layouts with the same entries as compatible.
 pub fn create_pipeline_layout_fn(
   entry_name: &str,
   options: &WgslBindgenOption,
   bind_group_data: &BTreeMap<u32, GroupData>,
+  excluded_bind_groups: &BTreeMap<u32, GroupData>,
+  shader_stages: wgpu::ShaderStages,
 ) -> TokenStream {
-  let bind_group_layouts: Vec<_> = bind_group_data
-    .keys()
-    .map(|group_no| {
-      let group = indexed_name_ident("WgpuBindGroup", *group_no);
-      quote!(bind_groups::#group::get_bind_group_layout(device))
+  // Each layout is either borrowed from the cache or created, with `true` for created layouts.
+  let mut bind_group_layouts: BTreeMap<_, _> = bind_group_data
+    .iter()
+    .map(|(group_no, group)| {
+      let group = group.name_ident("WgpuBindGroup", *group_no);
+      let layout = (quote!(bind_groups::#group::get_bind_group_layout(device)), true);
+      (*group_no, layout)
     })
     .collect();
+  let excluded_layouts: BTreeMap<_, _> = excluded_bind_groups
+    .iter()
+    .map(|(group_no, group)| {
+      let descriptor = bind_group::bind_group_layout_descriptor(
+        entry_name,
+        *group_no,
+        group,
+        shader_stages,
+        options,
+      );
+      (*group_no, (quote!(device.create_bind_group_layout(&#descriptor)), true))
+    })
+    .collect();
+  bind_group_layouts.extend(excluded_layouts.clone());
+  let create_pipeline_layout_with =
+    create_pipeline_layout_with_fn(entry_name, options, &bind_group_layouts);
+
+  let create_pipeline_layout_cached =
+    if options.cached_bind_group_layouts && !bind_group_data.is_empty() {
+      let mut cached_layouts: BTreeMap<_, _> = bind_group_data
+        .iter()
+        .map(|(group_no, group)| {
+          let field = group.field_ident(*group_no);
+          (*group_no, (quote!(cache.#field(device)), false))
+        })
+        .collect();
+      cached_layouts.extend(excluded_layouts);
+      let layouts = layout_refs(&cached_layouts);
+      let pipeline_layout_label = options
+        .label_style
+        .label_tokens(&format!("{}::PipelineLayout", entry_name));
+      quote! {
+          /// Like `create_pipeline_layout`, with the bind group layouts of the `cache`.
+          pub fn create_pipeline_layout_cached(
+              device: &wgpu::Device,
+              cache: &bind_groups::BindGroupLayoutCache,
+          ) -> wgpu::PipelineLayout {
+              device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                  label: #pipeline_layout_label,
+                  bind_group_layouts: &[
+                      #(#layouts),*
+                  ],
+                  push_constant_ranges: &[],
+              })
+          }
+      }
+    } else {
+      quote!()
+    };
+  let bind_group_layouts = layout_refs(&bind_group_layouts);
 
   let wgpu_pipeline_gen = &options.wgpu_binding_generator.pipeline_layout;
   let wgpu_pipeline_entries_struct =
@@ -62,19 +118,751 @@ pub fn create_pipeline_layout_fn(
       quote!()
     };
 
-  let pipeline_layout_name = format!("{}::PipelineLayout", entry_name);
+  let pipeline_layout_label = options
+    .label_style
+    .label_tokens(&format!("{}::PipelineLayout", entry_name));
 
   quote! {
     #additional_pipeline_entries_struct
     #wgpu_pipeline_entries_struct
       pub fn create_pipeline_layout(device: &wgpu::Device) -> wgpu::PipelineLayout {
           device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-              label: Some(#pipeline_layout_name),
+              label: #pipeline_layout_label,
               bind_group_layouts: &[
-                  #(&#bind_group_layouts),*
+                  #(#bind_group_layouts),*
               ],
               push_constant_ranges: &[],
           })
       }
+      #create_pipeline_layout_with
+      #create_pipeline_layout_cached
+  }
+}
+
+/// The references to the layouts, which are borrowed from the created layouts.
+fn layout_refs(
+  bind_group_layouts: &BTreeMap<u32, (TokenStream, bool)>,
+) -> Vec<TokenStream> {
+  bind_group_layouts
+    .values()
+    .map(|(layout, created)| {
+      if *created {
+        quote!(&#layout)
+      } else {
+        layout.clone()
+      }
+    })
+    .collect()
+}
+
+/// `create_pipeline_layout_with`, which uses the given layouts instead of the generated ones for
+/// the groups which have one, eg: layouts managed by an engine.
+fn create_pipeline_layout_with_fn(
+  entry_name: &str,
+  options: &WgslBindgenOption,
+  bind_group_layouts: &BTreeMap<u32, (TokenStream, bool)>,
+) -> TokenStream {
+  if !options.pipeline_layout_overrides {
+    return quote!();
+  }
+
+  let group_count = Index::from(bind_group_layouts.len());
+  let mut created_layouts = Vec::new();
+  let layouts: Vec<_> = bind_group_layouts
+    .iter()
+    .enumerate()
+    .map(|(i, (group_no, (layout, created)))| {
+      let i = Index::from(i);
+      let layout = if *created {
+        // Only created when not overridden, and declared outside the array to outlive it.
+        let name = format_ident!("bind_group_layout{group_no}");
+        created_layouts.push(name.clone());
+        quote! {{
+            #name = #layout;
+            &#name
+        }}
+      } else {
+        layout.clone()
+      };
+      quote! {
+          match overrides[#i] {
+              Some(layout) => layout,
+              None => #layout,
+          }
+      }
+    })
+    .collect();
+
+  let pipeline_layout_label = options
+    .label_style
+    .label_tokens(&format!("{}::PipelineLayout", entry_name));
+
+  quote! {
+      pub fn create_pipeline_layout_with(
+          device: &wgpu::Device,
+          overrides: [Option<&wgpu::BindGroupLayout>; #group_count],
+      ) -> wgpu::PipelineLayout {
+          #(let #created_layouts;)*
+          device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+              label: #pipeline_layout_label,
+              bind_group_layouts: &[
+                  #(#layouts),*
+              ],
+              push_constant_ranges: &[],
+          })
+      }
+  }
+}
+
+pub fn create_render_pipeline_fns(
+  entry_name: &str,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+) -> TokenStream {
+  if !options.generate_render_pipelines {
+    return quote!();
+  }
+
+  let entry_points_of = |stage: naga::ShaderStage| {
+    module
+      .entry_points
+      .iter()
+      .filter(move |entry_point| entry_point.stage == stage)
+  };
+
+  let pipelines = entry_points_of(naga::ShaderStage::Vertex).flat_map(|vertex_entry| {
+    entry_points_of(naga::ShaderStage::Fragment).map(move |fragment_entry| {
+      let pair_name = format!("{}_{}", vertex_entry.name, fragment_entry.name);
+      let args_name = format_ident!("{}PipelineArgs", pair_name.to_pascal_case());
+      let fn_name = format_ident!("create_{pair_name}_pipeline");
+      let label = options
+        .label_style
+        .label_tokens(&format!("{entry_name}::{pair_name}"));
+
+      let vertex_inputs =
+        wgsl::get_entry_vertex_input_structs(entry_name, module, vertex_entry)
+          .expect("vertex inputs are checked by `check_vertex_inputs`");
+      let vertex_count = Literal::usize_unsuffixed(vertex_inputs.len());
+      let target_count = wgsl::fragment_output_locations(module, fragment_entry)
+        .last()
+        .map_or(0, |location| *location as usize + 1);
+      let target_count = Literal::usize_unsuffixed(target_count);
+      let fragment_entry_const =
+        format_ident!("ENTRY_{}", fragment_entry.name.to_uppercase());
+      let core = core_crate(options);
+      let fragment_entry_point = options
+        .wgpu_edition
+        .entry_point(quote!(#fragment_entry_const));
+      let compilation_options = options.pipeline_compilation_options_field();
+
+      let create_pipeline = |cache: TokenStream| {
+        quote! {
+            let layout = create_pipeline_layout(device);
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: #label,
+                layout: Some(&layout),
+                vertex: vertex_state(module, &args.vertex),
+                fragment: Some(wgpu::FragmentState {
+                    module,
+                    entry_point: #fragment_entry_point,
+                    targets: &args.targets,
+                    #compilation_options
+                }),
+                primitive: args.primitive,
+                depth_stencil: args.depth_stencil,
+                multisample: args.multisample,
+                multiview: args.multiview,
+                #cache
+            })
+        }
+      };
+      let create_pipeline_fns = if options.pipeline_cache_fns {
+        let fn_with_cache_name = format_ident!("{fn_name}_with_cache");
+        let create_pipeline = create_pipeline(quote!(cache,));
+        quote! {
+            pub fn #fn_name(
+                device: &wgpu::Device,
+                module: &wgpu::ShaderModule,
+                args: #args_name,
+            ) -> wgpu::RenderPipeline {
+                #fn_with_cache_name(device, module, args, None)
+            }
+
+            pub fn #fn_with_cache_name(
+                device: &wgpu::Device,
+                module: &wgpu::ShaderModule,
+                args: #args_name,
+                cache: Option<&wgpu::PipelineCache>,
+            ) -> wgpu::RenderPipeline {
+                #create_pipeline
+            }
+        }
+      } else {
+        let create_pipeline =
+          create_pipeline(options.wgpu_edition.pipeline_cache_field());
+        quote! {
+            pub fn #fn_name(
+                device: &wgpu::Device,
+                module: &wgpu::ShaderModule,
+                args: #args_name,
+            ) -> wgpu::RenderPipeline {
+                #create_pipeline
+            }
+        }
+      };
+
+      quote! {
+          #[derive(Debug)]
+          pub struct #args_name {
+              pub vertex: VertexEntry<#vertex_count>,
+              pub targets: [Option<wgpu::ColorTargetState>; #target_count],
+              pub primitive: wgpu::PrimitiveState,
+              pub depth_stencil: Option<wgpu::DepthStencilState>,
+              pub multisample: wgpu::MultisampleState,
+              pub multiview: Option<#core::num::NonZeroU32>,
+          }
+
+          #create_pipeline_fns
+      }
+    })
+  });
+
+  quote! {
+      #(#pipelines)*
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use indoc::indoc;
+
+  use super::*;
+  use crate::assert_tokens_eq;
+
+  #[test]
+  fn write_render_pipeline_fns() {
+    let source = indoc! {r#"
+            struct VertexInput {
+                @location(0) position: vec3<f32>,
+            };
+            @vertex
+            fn vs_main(in: VertexInput) -> @builtin(position) vec4<f32> {
+                return vec4<f32>(in.position, 1.0);
+            }
+            @fragment
+            fn fs_main() -> @location(0) vec4<f32> {
+                return vec4<f32>(1.0);
+            }
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      generate_render_pipelines: true,
+      ..Default::default()
+    };
+    let actual = create_render_pipeline_fns("Triangle", &module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug)]
+          pub struct VsMainFsMainPipelineArgs {
+              pub vertex: VertexEntry<1>,
+              pub targets: [Option<wgpu::ColorTargetState>; 1],
+              pub primitive: wgpu::PrimitiveState,
+              pub depth_stencil: Option<wgpu::DepthStencilState>,
+              pub multisample: wgpu::MultisampleState,
+              pub multiview: Option<std::num::NonZeroU32>,
+          }
+          pub fn create_vs_main_fs_main_pipeline(
+              device: &wgpu::Device,
+              module: &wgpu::ShaderModule,
+              args: VsMainFsMainPipelineArgs,
+          ) -> wgpu::RenderPipeline {
+              let layout = create_pipeline_layout(device);
+              device
+                  .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                      label: Some("Triangle::vs_main_fs_main"),
+                      layout: Some(&layout),
+                      vertex: vertex_state(module, &args.vertex),
+                      fragment: Some(wgpu::FragmentState {
+                          module,
+                          entry_point: ENTRY_FS_MAIN,
+                          targets: &args.targets,
+                      }),
+                      primitive: args.primitive,
+                      depth_stencil: args.depth_stencil,
+                      multisample: args.multisample,
+                      multiview: args.multiview,
+                  })
+          }
+      },
+      actual
+    )
+  }
+
+  #[test]
+  fn write_render_pipeline_fns_vertex_count_of_entry() {
+    let source = indoc! {r#"
+            struct VertexInput {
+                @location(0) position: vec3<f32>,
+            };
+            @vertex
+            fn vs_fullscreen() -> @builtin(position) vec4<f32> {
+                return vec4<f32>(0.0);
+            }
+            @vertex
+            fn vs_main(in: VertexInput) -> @builtin(position) vec4<f32> {
+                return vec4<f32>(in.position, 1.0);
+            }
+            @fragment
+            fn fs_main() -> @location(0) vec4<f32> {
+                return vec4<f32>(1.0);
+            }
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      generate_render_pipelines: true,
+      ..Default::default()
+    };
+    let actual = create_render_pipeline_fns("Triangle", &module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug)]
+          pub struct VsFullscreenFsMainPipelineArgs {
+              pub vertex: VertexEntry<0>,
+              pub targets: [Option<wgpu::ColorTargetState>; 1],
+              pub primitive: wgpu::PrimitiveState,
+              pub depth_stencil: Option<wgpu::DepthStencilState>,
+              pub multisample: wgpu::MultisampleState,
+              pub multiview: Option<std::num::NonZeroU32>,
+          }
+          pub fn create_vs_fullscreen_fs_main_pipeline(
+              device: &wgpu::Device,
+              module: &wgpu::ShaderModule,
+              args: VsFullscreenFsMainPipelineArgs,
+          ) -> wgpu::RenderPipeline {
+              let layout = create_pipeline_layout(device);
+              device
+                  .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                      label: Some("Triangle::vs_fullscreen_fs_main"),
+                      layout: Some(&layout),
+                      vertex: vertex_state(module, &args.vertex),
+                      fragment: Some(wgpu::FragmentState {
+                          module,
+                          entry_point: ENTRY_FS_MAIN,
+                          targets: &args.targets,
+                      }),
+                      primitive: args.primitive,
+                      depth_stencil: args.depth_stencil,
+                      multisample: args.multisample,
+                      multiview: args.multiview,
+                  })
+          }
+          #[derive(Debug)]
+          pub struct VsMainFsMainPipelineArgs {
+              pub vertex: VertexEntry<1>,
+              pub targets: [Option<wgpu::ColorTargetState>; 1],
+              pub primitive: wgpu::PrimitiveState,
+              pub depth_stencil: Option<wgpu::DepthStencilState>,
+              pub multisample: wgpu::MultisampleState,
+              pub multiview: Option<std::num::NonZeroU32>,
+          }
+          pub fn create_vs_main_fs_main_pipeline(
+              device: &wgpu::Device,
+              module: &wgpu::ShaderModule,
+              args: VsMainFsMainPipelineArgs,
+          ) -> wgpu::RenderPipeline {
+              let layout = create_pipeline_layout(device);
+              device
+                  .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                      label: Some("Triangle::vs_main_fs_main"),
+                      layout: Some(&layout),
+                      vertex: vertex_state(module, &args.vertex),
+                      fragment: Some(wgpu::FragmentState {
+                          module,
+                          entry_point: ENTRY_FS_MAIN,
+                          targets: &args.targets,
+                      }),
+                      primitive: args.primitive,
+                      depth_stencil: args.depth_stencil,
+                      multisample: args.multisample,
+                      multiview: args.multiview,
+                  })
+          }
+      },
+      actual
+    )
+  }
+
+  #[test]
+  fn write_render_pipeline_fns_wgpu_22() {
+    let source = indoc! {r#"
+            @vertex
+            fn vs_main() -> @builtin(position) vec4<f32> {
+                return vec4<f32>(0.0);
+            }
+            @fragment
+            fn fs_main() -> @location(0) vec4<f32> {
+                return vec4<f32>(1.0);
+            }
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      generate_render_pipelines: true,
+      wgpu_edition: WgpuVersion::V22,
+      ..Default::default()
+    };
+    let actual = create_render_pipeline_fns("Triangle", &module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug)]
+          pub struct VsMainFsMainPipelineArgs {
+              pub vertex: VertexEntry<0>,
+              pub targets: [Option<wgpu::ColorTargetState>; 1],
+              pub primitive: wgpu::PrimitiveState,
+              pub depth_stencil: Option<wgpu::DepthStencilState>,
+              pub multisample: wgpu::MultisampleState,
+              pub multiview: Option<std::num::NonZeroU32>,
+          }
+          pub fn create_vs_main_fs_main_pipeline(
+              device: &wgpu::Device,
+              module: &wgpu::ShaderModule,
+              args: VsMainFsMainPipelineArgs,
+          ) -> wgpu::RenderPipeline {
+              let layout = create_pipeline_layout(device);
+              device
+                  .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                      label: Some("Triangle::vs_main_fs_main"),
+                      layout: Some(&layout),
+                      vertex: vertex_state(module, &args.vertex),
+                      fragment: Some(wgpu::FragmentState {
+                          module,
+                          entry_point: Some(ENTRY_FS_MAIN),
+                          targets: &args.targets,
+                          compilation_options: Default::default(),
+                      }),
+                      primitive: args.primitive,
+                      depth_stencil: args.depth_stencil,
+                      multisample: args.multisample,
+                      multiview: args.multiview,
+                      cache: None,
+                  })
+          }
+      },
+      actual
+    )
+  }
+
+  #[test]
+  fn write_render_pipeline_fns_compilation_options() {
+    let source = indoc! {r#"
+            @vertex
+            fn vs_main() -> @builtin(position) vec4<f32> {
+                return vec4<f32>(0.0);
+            }
+            @fragment
+            fn fs_main() -> @location(0) vec4<f32> {
+                return vec4<f32>(1.0);
+            }
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      generate_render_pipelines: true,
+      wgpu_edition: WgpuVersion::V22,
+      pipeline_compilation_options: PipelineCompilationOptions {
+        zero_initialize_workgroup_memory: false,
+      },
+      ..Default::default()
+    };
+    let actual = create_render_pipeline_fns("Triangle", &module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug)]
+          pub struct VsMainFsMainPipelineArgs {
+              pub vertex: VertexEntry<0>,
+              pub targets: [Option<wgpu::ColorTargetState>; 1],
+              pub primitive: wgpu::PrimitiveState,
+              pub depth_stencil: Option<wgpu::DepthStencilState>,
+              pub multisample: wgpu::MultisampleState,
+              pub multiview: Option<std::num::NonZeroU32>,
+          }
+          pub fn create_vs_main_fs_main_pipeline(
+              device: &wgpu::Device,
+              module: &wgpu::ShaderModule,
+              args: VsMainFsMainPipelineArgs,
+          ) -> wgpu::RenderPipeline {
+              let layout = create_pipeline_layout(device);
+              device
+                  .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                      label: Some("Triangle::vs_main_fs_main"),
+                      layout: Some(&layout),
+                      vertex: vertex_state(module, &args.vertex),
+                      fragment: Some(wgpu::FragmentState {
+                          module,
+                          entry_point: Some(ENTRY_FS_MAIN),
+                          targets: &args.targets,
+                          compilation_options: wgpu::PipelineCompilationOptions {
+                              zero_initialize_workgroup_memory: false,
+                              ..Default::default()
+                          },
+                      }),
+                      primitive: args.primitive,
+                      depth_stencil: args.depth_stencil,
+                      multisample: args.multisample,
+                      multiview: args.multiview,
+                      cache: None,
+                  })
+          }
+      },
+      actual
+    )
+  }
+
+  #[test]
+  fn write_render_pipeline_fns_with_cache() {
+    let source = indoc! {r#"
+            @vertex
+            fn vs_main() -> @builtin(position) vec4<f32> {
+                return vec4<f32>(0.0);
+            }
+            @fragment
+            fn fs_main() -> @location(0) vec4<f32> {
+                return vec4<f32>(1.0);
+            }
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      generate_render_pipelines: true,
+      pipeline_cache_fns: true,
+      wgpu_edition: WgpuVersion::V22,
+      ..Default::default()
+    };
+    let actual = create_render_pipeline_fns("Triangle", &module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug)]
+          pub struct VsMainFsMainPipelineArgs {
+              pub vertex: VertexEntry<0>,
+              pub targets: [Option<wgpu::ColorTargetState>; 1],
+              pub primitive: wgpu::PrimitiveState,
+              pub depth_stencil: Option<wgpu::DepthStencilState>,
+              pub multisample: wgpu::MultisampleState,
+              pub multiview: Option<std::num::NonZeroU32>,
+          }
+          pub fn create_vs_main_fs_main_pipeline(
+              device: &wgpu::Device,
+              module: &wgpu::ShaderModule,
+              args: VsMainFsMainPipelineArgs,
+          ) -> wgpu::RenderPipeline {
+              create_vs_main_fs_main_pipeline_with_cache(device, module, args, None)
+          }
+          pub fn create_vs_main_fs_main_pipeline_with_cache(
+              device: &wgpu::Device,
+              module: &wgpu::ShaderModule,
+              args: VsMainFsMainPipelineArgs,
+              cache: Option<&wgpu::PipelineCache>,
+          ) -> wgpu::RenderPipeline {
+              let layout = create_pipeline_layout(device);
+              device
+                  .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                      label: Some("Triangle::vs_main_fs_main"),
+                      layout: Some(&layout),
+                      vertex: vertex_state(module, &args.vertex),
+                      fragment: Some(wgpu::FragmentState {
+                          module,
+                          entry_point: Some(ENTRY_FS_MAIN),
+                          targets: &args.targets,
+                          compilation_options: Default::default(),
+                      }),
+                      primitive: args.primitive,
+                      depth_stencil: args.depth_stencil,
+                      multisample: args.multisample,
+                      multiview: args.multiview,
+                      cache,
+                  })
+          }
+      },
+      actual
+    )
+  }
+
+  #[test]
+  fn write_create_pipeline_layout_cached() {
+    let source = indoc! {r#"
+            @group(0) @binding(0) var<uniform> frame: vec4<f32>;
+            @group(1) @binding(0) var<uniform> material: vec4<f32>;
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let mut bind_group_data = bind_group::get_bind_group_data(&module).unwrap();
+    let excluded_bind_groups = BTreeMap::from([(1, bind_group_data.remove(&1).unwrap())]);
+    let options = WgslBindgenOption {
+      cached_bind_group_layouts: true,
+      label_style: LabelStyle::Disabled,
+      ..Default::default()
+    };
+    let actual = create_pipeline_layout_fn(
+      "Test",
+      &options,
+      &bind_group_data,
+      &excluded_bind_groups,
+      wgpu::ShaderStages::FRAGMENT,
+    );
+
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug)]
+          pub struct WgpuPipelineLayout;
+          impl WgpuPipelineLayout {
+              pub fn bind_group_layout_entries(
+                  entries: [wgpu::BindGroupLayout; 1],
+              ) -> [wgpu::BindGroupLayout; 1] {
+                  entries
+              }
+          }
+          pub fn create_pipeline_layout(device: &wgpu::Device) -> wgpu::PipelineLayout {
+              device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                  label: None,
+                  bind_group_layouts: &[
+                      &bind_groups::WgpuBindGroup0::get_bind_group_layout(device),
+                      &device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                          label: None,
+                          entries: &[
+                              wgpu::BindGroupLayoutEntry {
+                                  binding: 0,
+                                  visibility: wgpu::ShaderStages::FRAGMENT,
+                                  ty: wgpu::BindingType::Buffer {
+                                      ty: wgpu::BufferBindingType::Uniform,
+                                      has_dynamic_offset: false,
+                                      min_binding_size: None,
+                                  },
+                                  count: None,
+                              },
+                          ],
+                      })
+                  ],
+                  push_constant_ranges: &[],
+              })
+          }
+          /// Like `create_pipeline_layout`, with the bind group layouts of the `cache`.
+          pub fn create_pipeline_layout_cached(
+              device: &wgpu::Device,
+              cache: &bind_groups::BindGroupLayoutCache,
+          ) -> wgpu::PipelineLayout {
+              device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                  label: None,
+                  bind_group_layouts: &[
+                      cache.bind_group0(device),
+                      &device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                          label: None,
+                          entries: &[
+                              wgpu::BindGroupLayoutEntry {
+                                  binding: 0,
+                                  visibility: wgpu::ShaderStages::FRAGMENT,
+                                  ty: wgpu::BindingType::Buffer {
+                                      ty: wgpu::BufferBindingType::Uniform,
+                                      has_dynamic_offset: false,
+                                      min_binding_size: None,
+                                  },
+                                  count: None,
+                              },
+                          ],
+                      })
+                  ],
+                  push_constant_ranges: &[],
+              })
+          }
+      },
+      actual
+    )
+  }
+
+  #[test]
+  fn write_create_pipeline_layout_with() {
+    let source = indoc! {r#"
+            @group(0) @binding(0) var<uniform> frame: vec4<f32>;
+            @group(1) @binding(0) var<uniform> material: vec4<f32>;
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let bind_group_data = bind_group::get_bind_group_data(&module).unwrap();
+    let options = WgslBindgenOption {
+      pipeline_layout_overrides: true,
+      ..Default::default()
+    };
+    let actual = create_pipeline_layout_fn(
+      "Test",
+      &options,
+      &bind_group_data,
+      &BTreeMap::new(),
+      wgpu::ShaderStages::FRAGMENT,
+    );
+
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug)]
+          pub struct WgpuPipelineLayout;
+          impl WgpuPipelineLayout {
+              pub fn bind_group_layout_entries(
+                  entries: [wgpu::BindGroupLayout; 2],
+              ) -> [wgpu::BindGroupLayout; 2] {
+                  entries
+              }
+          }
+          pub fn create_pipeline_layout(device: &wgpu::Device) -> wgpu::PipelineLayout {
+              device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                  label: Some("Test::PipelineLayout"),
+                  bind_group_layouts: &[
+                      &bind_groups::WgpuBindGroup0::get_bind_group_layout(device),
+                      &bind_groups::WgpuBindGroup1::get_bind_group_layout(device)
+                  ],
+                  push_constant_ranges: &[],
+              })
+          }
+          pub fn create_pipeline_layout_with(
+              device: &wgpu::Device,
+              overrides: [Option<&wgpu::BindGroupLayout>; 2],
+          ) -> wgpu::PipelineLayout {
+              let bind_group_layout0;
+              let bind_group_layout1;
+              device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                  label: Some("Test::PipelineLayout"),
+                  bind_group_layouts: &[
+                      match overrides[0] {
+                          Some(layout) => layout,
+                          None => {
+                              bind_group_layout0 = bind_groups::WgpuBindGroup0::get_bind_group_layout(device);
+                              &bind_group_layout0
+                          },
+                      },
+                      match overrides[1] {
+                          Some(layout) => layout,
+                          None => {
+                              bind_group_layout1 = bind_groups::WgpuBindGroup1::get_bind_group_layout(device);
+                              &bind_group_layout1
+                          },
+                      }
+                  ],
+                  push_constant_ranges: &[],
+              })
+          }
+      },
+      actual
+    )
   }
 }
@@ -0,0 +1,283 @@
+//! Generates a typed `ShaderDefs` struct for the naga_oil shader defs of an entry, found by
+//! scanning the `#ifdef`, `#ifndef` and `#if` directives of the entry and its imports.
+
+use std::collections::BTreeMap;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use regex::Regex;
+
+use crate::{
+  CreateModuleError, WgslBindgenOption, WgslEntryResult, WgslShaderSourceType,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ShaderDefKind {
+  /// Only checked with `#ifdef` or `#ifndef`, so it's inserted only when enabled.
+  Flag,
+  /// Compared against `true` or `false` with `#if`, so it's always inserted.
+  Bool,
+  /// Compared against an integer with `#if`.
+  Int,
+}
+
+/// Matches the directives the same way as naga_oil, including the `#else` variants.
+fn shader_def_directives() -> (Regex, Regex) {
+  let ifdef = Regex::new(r"^\s*#\s*(?:else\s+)?\s*ifn?def\s+([\w|\d|_]+)").unwrap();
+  let if_op =
+    Regex::new(r"^\s*#\s*(?:else\s+)?\s*if\s+([\w|\d|_]+)\s*([=!<>]*)\s*([-\w|\d]+)")
+      .unwrap();
+  (ifdef, if_op)
+}
+
+fn shader_defs_of_source(
+  module: &str,
+  source: &str,
+  defs: &mut BTreeMap<String, ShaderDefKind>,
+) -> Result<(), CreateModuleError> {
+  let (ifdef, if_op) = shader_def_directives();
+  for line in source.lines() {
+    let (name, kind) = if let Some(captures) = ifdef.captures(line) {
+      (captures[1].to_string(), ShaderDefKind::Flag)
+    } else if let Some(captures) = if_op.captures(line) {
+      let kind = match &captures[3] {
+        "true" | "false" => ShaderDefKind::Bool,
+        _ => ShaderDefKind::Int,
+      };
+      (captures[1].to_string(), kind)
+    } else {
+      continue;
+    };
+
+    // The def becomes a field of `ShaderDefs`, so it must be a valid Rust identifier.
+    if syn::parse_str::<syn::Ident>(&name.to_lowercase()).is_err() {
+      return Err(CreateModuleError::InvalidShaderDef {
+        module: module.to_string(),
+        name,
+      });
+    }
+
+    let kind_of_name = defs.entry(name).or_insert(kind);
+    *kind_of_name = (*kind_of_name).max(kind);
+  }
+  Ok(())
+}
+
+fn shader_defs_struct(defs: &BTreeMap<String, ShaderDefKind>) -> TokenStream {
+  let fields = defs.iter().map(|(name, kind)| {
+    let field = format_ident!("{}", name.to_lowercase());
+    match kind {
+      ShaderDefKind::Flag | ShaderDefKind::Bool => quote!(pub #field: bool),
+      ShaderDefKind::Int => quote!(pub #field: i32),
+    }
+  });
+
+  let inserts = defs.iter().map(|(name, kind)| {
+    let field = format_ident!("{}", name.to_lowercase());
+    match kind {
+      ShaderDefKind::Flag => quote! {
+        if self.#field {
+          shader_defs.insert(#name.to_string(), naga_oil::compose::ShaderDefValue::Bool(true));
+        }
+      },
+      ShaderDefKind::Bool => quote! {
+        shader_defs.insert(#name.to_string(), naga_oil::compose::ShaderDefValue::Bool(self.#field));
+      },
+      ShaderDefKind::Int => quote! {
+        shader_defs.insert(#name.to_string(), naga_oil::compose::ShaderDefValue::Int(self.#field));
+      },
+    }
+  });
+
+  quote! {
+    /// The shader defs checked by the `#ifdef`, `#ifndef` and `#if` directives of the shader and its imports.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct ShaderDefs {
+      #(#fields),*
+    }
+
+    impl ShaderDefs {
+      pub fn to_map(&self) -> std::collections::HashMap<String, naga_oil::compose::ShaderDefValue> {
+        let mut shader_defs = std::collections::HashMap::new();
+        #(#inserts)*
+        shader_defs
+      }
+    }
+
+    impl From<ShaderDefs> for std::collections::HashMap<String, naga_oil::compose::ShaderDefValue> {
+      fn from(shader_defs: ShaderDefs) -> Self {
+        shader_defs.to_map()
+      }
+    }
+  }
+}
+
+/// Creates the shader module with the typed defs, composing it with naga_oil at runtime.
+/// Prefers the embedded sources when both composer source types are generated.
+fn create_shader_module_with_defs_fn(
+  source_type: WgslShaderSourceType,
+  options: &WgslBindgenOption,
+) -> TokenStream {
+  let create_shader_module_fn =
+    format_ident!("{}", source_type.create_shader_module_fn_name());
+  let return_type = source_type.get_return_type(quote!(wgpu::ShaderModule));
+  let runtime_files_attribute = source_type.runtime_files_attribute(options);
+
+  quote! {
+    #runtime_files_attribute
+    pub fn create_shader_module_with_defs(device: &wgpu::Device, shader_defs: ShaderDefs) -> #return_type {
+      #create_shader_module_fn(device, shader_defs.into())
+    }
+  }
+}
+
+/// The `ShaderDefs` struct of the entry and `create_shader_module_with_defs`,
+/// or nothing if its shaders don't use any defs.
+pub(crate) fn shader_defs(
+  entry: &WgslEntryResult,
+  options: &WgslBindgenOption,
+) -> Result<TokenStream, CreateModuleError> {
+  let deps = &entry.source_including_deps;
+  let mut defs = BTreeMap::new();
+  for source_file in
+    std::iter::once(deps.source_file).chain(deps.full_dependencies.iter().copied())
+  {
+    shader_defs_of_source(&entry.mod_name, &source_file.content, &mut defs)?;
+  }
+
+  if defs.is_empty() {
+    return Ok(quote!());
+  }
+
+  let shader_defs_struct = shader_defs_struct(&defs);
+  let create_shader_module_fn = if options
+    .shader_source_type
+    .contains(WgslShaderSourceType::UseComposerEmbed)
+  {
+    create_shader_module_with_defs_fn(WgslShaderSourceType::UseComposerEmbed, options)
+  } else {
+    create_shader_module_with_defs_fn(WgslShaderSourceType::UseComposerWithPath, options)
+  };
+
+  Ok(quote! {
+    #shader_defs_struct
+    #create_shader_module_fn
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use indoc::indoc;
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+  use crate::assert_tokens_eq;
+
+  #[test]
+  fn find_shader_defs_in_directives() {
+    let source = indoc! {r#"
+        #ifdef SHADOWS
+        #else ifdef SOFT_SHADOWS
+        #endif
+        #ifndef SHADOWS
+        #endif
+        #if MAX_LIGHTS > 4
+        #endif
+        #if TONEMAP == true
+        #endif
+        #if MAX>4
+        #else if MIN_LIGHTS != 1
+        #endif
+        // #ifdef
+        fn main() {}
+    "#};
+
+    let mut defs = BTreeMap::new();
+    shader_defs_of_source("shader", source, &mut defs).unwrap();
+
+    assert_eq!(
+      BTreeMap::from([
+        ("MAX".to_string(), ShaderDefKind::Int),
+        ("MAX_LIGHTS".to_string(), ShaderDefKind::Int),
+        ("MIN_LIGHTS".to_string(), ShaderDefKind::Int),
+        ("SHADOWS".to_string(), ShaderDefKind::Flag),
+        ("SOFT_SHADOWS".to_string(), ShaderDefKind::Flag),
+        ("TONEMAP".to_string(), ShaderDefKind::Bool),
+      ]),
+      defs
+    );
+  }
+
+  #[test]
+  fn shader_defs_must_be_identifiers() {
+    let mut defs = BTreeMap::new();
+    let actual = shader_defs_of_source("shader", "#ifdef 2D\n#endif", &mut defs);
+
+    assert_eq!(
+      Err(CreateModuleError::InvalidShaderDef {
+        module: "shader".to_string(),
+        name: "2D".to_string(),
+      }),
+      actual
+    );
+  }
+
+  #[test]
+  fn generate_shader_defs_struct() {
+    let defs = BTreeMap::from([
+      ("MAX_LIGHTS".to_string(), ShaderDefKind::Int),
+      ("SHADOWS".to_string(), ShaderDefKind::Flag),
+    ]);
+
+    let actual = shader_defs_struct(&defs);
+
+    assert_tokens_eq!(
+      quote! {
+        /// The shader defs checked by the `#ifdef`, `#ifndef` and `#if` directives of the shader and its imports.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+        pub struct ShaderDefs {
+          pub max_lights: i32,
+          pub shadows: bool
+        }
+
+        impl ShaderDefs {
+          pub fn to_map(&self) -> std::collections::HashMap<String, naga_oil::compose::ShaderDefValue> {
+            let mut shader_defs = std::collections::HashMap::new();
+            shader_defs.insert("MAX_LIGHTS".to_string(), naga_oil::compose::ShaderDefValue::Int(self.max_lights));
+            if self.shadows {
+              shader_defs.insert("SHADOWS".to_string(), naga_oil::compose::ShaderDefValue::Bool(true));
+            }
+            shader_defs
+          }
+        }
+
+        impl From<ShaderDefs> for std::collections::HashMap<String, naga_oil::compose::ShaderDefValue> {
+          fn from(shader_defs: ShaderDefs) -> Self {
+            shader_defs.to_map()
+          }
+        }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn generate_create_shader_module_with_defs() {
+    let actual = create_shader_module_with_defs_fn(
+      WgslShaderSourceType::UseComposerWithPath,
+      &WgslBindgenOption::default(),
+    );
+
+    assert_tokens_eq!(
+      quote! {
+        pub fn create_shader_module_with_defs(
+          device: &wgpu::Device,
+          shader_defs: ShaderDefs
+        ) -> Result<wgpu::ShaderModule, naga_oil::compose::ComposerError> {
+          create_shader_module_from_path(device, shader_defs.into())
+        }
+      },
+      actual
+    );
+  }
+}
@@ -1,20 +1,28 @@
 //! This file is used for creating direct shader file related functions:
 //! such as `create_shader_module`, `create_compute_module`
 
+use std::borrow::Cow;
 use std::path::Path;
 
 use derive_more::Constructor;
-use enumflags2::BitFlags;
-use proc_macro2::TokenStream;
+use proc_macro2::{Literal, TokenStream};
 use quote::{format_ident, quote, TokenStreamExt};
 use syn::{Ident, Index};
 
-use crate::naga_util::module_to_source;
-use crate::quote_gen::create_shader_raw_string_literal;
-use crate::{WgslBindgenOption, WgslEntryResult, WgslShaderSourceType};
+use crate::naga_util::{
+  module_to_minified_source, module_to_naga_ir, module_to_source, module_to_spirv,
+  prune_unused_functions,
+};
+use crate::quote_gen::{
+  add_attribute_to_items, alloc_crate, cfg_attribute, create_shader_raw_string_literal,
+};
+use crate::{
+  CreateModuleError, LabelStyle, SourceFilePath, WgpuVersion, WgslBindgenOption,
+  WgslEntryResult, WgslShaderSourceType, WgslTypeSerializeStrategy,
+};
 
 impl<'a> WgslEntryResult<'a> {
-  fn get_label(&self) -> TokenStream {
+  fn get_label(&self, label_style: &LabelStyle) -> TokenStream {
     let get_label = || {
       Some(
         self
@@ -27,7 +35,7 @@ impl<'a> WgslEntryResult<'a> {
     };
 
     match get_label() {
-      Some(label) => quote!(Some(#label)),
+      Some(label) => label_style.label_tokens(label),
       None => quote!(None),
     }
   }
@@ -40,6 +48,10 @@ impl WgslShaderSourceType {
       UseEmbed => "create_shader_module_embed_source",
       UseComposerEmbed => "create_shader_module_embedded",
       UseComposerWithPath => "create_shader_module_from_path",
+      SpirV => "create_shader_module_spirv",
+      NagaIr => "create_shader_module_naga_ir",
+      IncludeStrOriginal => "create_shader_module_include_str",
+      RuntimePath => "create_shader_module_runtime_path",
     }
   }
 
@@ -49,23 +61,43 @@ impl WgslShaderSourceType {
       UseEmbed => format!("create_{}_pipeline_embed_source", name),
       UseComposerEmbed => format!("create_{}_pipeline_embedded", name),
       UseComposerWithPath => format!("create_{}_pipeline_from_path", name),
+      SpirV => format!("create_{}_pipeline_spirv", name),
+      NagaIr => format!("create_{}_pipeline_naga_ir", name),
+      IncludeStrOriginal => format!("create_{}_pipeline_include_str", name),
+      RuntimePath => format!("create_{}_pipeline_runtime_path", name),
+    }
+  }
+
+  /// Gates the items of the source types reading the shader files at runtime to the targets
+  /// with a filesystem, when the output is also compiled for wasm or `no_std`.
+  pub(crate) fn runtime_files_attribute(
+    &self,
+    options: &WgslBindgenOption,
+  ) -> TokenStream {
+    use WgslShaderSourceType::*;
+    match self {
+      UseComposerWithPath | RuntimePath => {
+        cfg_attribute(&runtime_files_predicates(options))
+      }
+      _ => quote!(),
     }
   }
 
   pub(crate) fn get_return_type(&self, type_to_return: TokenStream) -> TokenStream {
     use WgslShaderSourceType::*;
     match self {
-      UseEmbed | UseComposerEmbed => type_to_return,
+      UseEmbed | UseComposerEmbed | SpirV | NagaIr | IncludeStrOriginal => type_to_return,
       UseComposerWithPath => {
         quote!(Result<#type_to_return, naga_oil::compose::ComposerError>)
       }
+      RuntimePath => quote!(Result<#type_to_return, std::io::Error>),
     }
   }
 
   pub(crate) fn wrap_return_stmt(&self, stm: TokenStream) -> TokenStream {
     use WgslShaderSourceType::*;
     match self {
-      UseComposerWithPath => quote!(Ok(#stm)),
+      UseComposerWithPath | RuntimePath => quote!(Ok(#stm)),
       _ => stm,
     }
   }
@@ -73,7 +105,7 @@ impl WgslShaderSourceType {
   pub(crate) fn get_propagate_operator(&self) -> TokenStream {
     use WgslShaderSourceType::*;
     match self {
-      UseComposerWithPath => quote!(?),
+      UseComposerWithPath | RuntimePath => quote!(?),
       _ => quote!(),
     }
   }
@@ -139,7 +171,7 @@ impl WgslShaderSourceType {
   pub(crate) fn unwrap_result(&self) -> TokenStream {
     use WgslShaderSourceType::*;
     match self {
-      UseComposerWithPath => quote!(.unwrap()),
+      UseComposerWithPath | RuntimePath => quote!(.unwrap()),
       _ => quote!(),
     }
   }
@@ -149,7 +181,7 @@ impl WgslShaderSourceType {
   ) -> (TokenStream, TokenStream) {
     use WgslShaderSourceType::*;
     match self {
-      UseEmbed => {
+      UseEmbed | SpirV | NagaIr | IncludeStrOriginal | RuntimePath => {
         let param_defs = quote!(device: &wgpu::Device);
         let params = quote!(device);
         (param_defs, params)
@@ -169,11 +201,12 @@ impl WgslShaderSourceType {
 #[derive(Constructor)]
 struct ComputeModuleBuilder<'a> {
   module: &'a naga::Module,
-  source_type_flags: BitFlags<WgslShaderSourceType>,
+  options: &'a WgslBindgenOption,
 }
 
 impl<'a> ComputeModuleBuilder<'a> {
   fn build_compute_pipeline_fn(
+    &self,
     e: &naga::EntryPoint,
     source_type: WgslShaderSourceType,
   ) -> TokenStream {
@@ -182,9 +215,15 @@ impl<'a> ComputeModuleBuilder<'a> {
     let pipeline_name =
       format_ident!("{}", source_type.create_compute_pipeline_fn_name(&e.name));
 
-    let entry_point = &e.name;
+    let entry_name = &e.name;
+    let wgpu_edition = self.options.wgpu_edition;
+    let entry_point = wgpu_edition.entry_point(quote!(#entry_name));
+    let compilation_options = self.options.pipeline_compilation_options_field();
     // TODO: Include a user supplied module name in the label?
-    let label = format!("Compute Pipeline {}", e.name);
+    let label = self
+      .options
+      .label_style
+      .label_tokens(&format!("Compute Pipeline {}", e.name));
 
     let create_shader_module_fn_name =
       format_ident!("{}", source_type.create_shader_module_fn_name());
@@ -192,26 +231,174 @@ impl<'a> ComputeModuleBuilder<'a> {
     let unwrap_result = source_type.unwrap_result();
 
     let (param_defs, params) = source_type.shader_module_params_defs_and_params();
+    let runtime_files_attribute = source_type.runtime_files_attribute(self.options);
+
+    let create_pipeline = |cache: TokenStream| {
+      quote! {
+          let module = super::#create_shader_module_fn_name(#params) #unwrap_result;
+          let layout = super::create_pipeline_layout(device);
+          device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+              label: #label,
+              layout: Some(&layout),
+              module: &module,
+              entry_point: #entry_point,
+              #compilation_options
+              #cache
+          })
+      }
+    };
+
+    if !self.options.pipeline_cache_fns {
+      let create_pipeline = create_pipeline(wgpu_edition.pipeline_cache_field());
+      return quote! {
+          #runtime_files_attribute
+          pub fn #pipeline_name(#param_defs) -> wgpu::ComputePipeline {
+              #create_pipeline
+          }
+      };
+    }
 
+    let pipeline_with_cache_name = format_ident!("{pipeline_name}_with_cache");
+    let create_pipeline = create_pipeline(quote!(cache,));
     quote! {
+        #runtime_files_attribute
         pub fn #pipeline_name(#param_defs) -> wgpu::ComputePipeline {
-            let module = super::#create_shader_module_fn_name(#params) #unwrap_result;
-            let layout = super::create_pipeline_layout(device);
+            #pipeline_with_cache_name(#params, None)
+        }
+
+        #runtime_files_attribute
+        pub fn #pipeline_with_cache_name(
+            #param_defs,
+            cache: Option<&wgpu::PipelineCache>,
+        ) -> wgpu::ComputePipeline {
+            #create_pipeline
+        }
+    }
+  }
+
+  fn build_compute_pipeline_with_layout_fn(&self, e: &naga::EntryPoint) -> TokenStream {
+    let pipeline_name = format_ident!("create_{}_pipeline_with_layout", e.name);
+    let entry_name = &e.name;
+    let wgpu_edition = self.options.wgpu_edition;
+    let entry_point = wgpu_edition.entry_point(quote!(#entry_name));
+    let (compilation_options_param, compilation_options) =
+      wgpu_edition.compilation_options_param();
+    let (cache_param, cache) = wgpu_edition.pipeline_cache_param();
+    let label = self
+      .options
+      .label_style
+      .label_tokens(&format!("Compute Pipeline {}", e.name));
+
+    quote! {
+        pub fn #pipeline_name(
+            device: &wgpu::Device,
+            module: &wgpu::ShaderModule,
+            layout: Option<&wgpu::PipelineLayout>,
+            #compilation_options_param
+            #cache_param
+        ) -> wgpu::ComputePipeline {
             device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-                label: Some(#label),
-                layout: Some(&layout),
-                module: &module,
+                label: #label,
+                layout,
+                module,
                 entry_point: #entry_point,
+                #compilation_options
+                #cache
             })
         }
     }
   }
 
-  fn workgroup_size(e: &naga::EntryPoint) -> TokenStream {
+  fn workgroup_size(&self, e: &naga::EntryPoint) -> TokenStream {
     // Use Index to avoid specifying the type on literals.
     let name = format_ident!("{}_WORKGROUP_SIZE", e.name.to_uppercase());
     let [x, y, z] = e.workgroup_size.map(|s| Index::from(s as usize));
-    quote!(pub const #name: [u32; 3] = [#x, #y, #z];)
+    let components = if self.options.workgroup_size_components {
+      let [x_name, y_name, z_name] =
+        ["X", "Y", "Z"].map(|axis| format_ident!("{name}_{axis}"));
+      let invocations_name =
+        format_ident!("{}_WORKGROUP_INVOCATIONS", e.name.to_uppercase());
+      let invocations = Index::from(e.workgroup_size.iter().product::<u32>() as usize);
+      quote! {
+          pub const #x_name: u32 = #x;
+          pub const #y_name: u32 = #y;
+          pub const #z_name: u32 = #z;
+          pub const #invocations_name: u32 = #invocations;
+      }
+    } else {
+      quote!()
+    };
+    quote! {
+        pub const #name: [u32; 3] = [#x, #y, #z];
+        #components
+    }
+  }
+
+  fn dispatch_helpers(e: &naga::EntryPoint) -> TokenStream {
+    let workgroup_size = format_ident!("{}_WORKGROUP_SIZE", e.name.to_uppercase());
+    let workgroup_count_fn = format_ident!("{}_workgroup_count_for", e.name);
+    let dispatch_fn = format_ident!("dispatch_{}", e.name);
+
+    quote! {
+        pub const fn #workgroup_count_fn(elements: [u32; 3]) -> [u32; 3] {
+            [
+                elements[0].div_ceil(#workgroup_size[0]),
+                elements[1].div_ceil(#workgroup_size[1]),
+                elements[2].div_ceil(#workgroup_size[2]),
+            ]
+        }
+
+        pub fn #dispatch_fn(pass: &mut wgpu::ComputePass<'_>, total: [u32; 3]) {
+            let [x, y, z] = #workgroup_count_fn(total);
+            pass.dispatch_workgroups(x, y, z);
+        }
+    }
+  }
+
+  fn dispatch_indirect_args_struct(&self) -> TokenStream {
+    let bytemuck_impls =
+      if self.options.serialization_strategy == WgslTypeSerializeStrategy::Bytemuck {
+        quote! {
+            unsafe impl bytemuck::Zeroable for DispatchIndirectArgs {}
+            unsafe impl bytemuck::Pod for DispatchIndirectArgs {}
+        }
+      } else {
+        quote!()
+      };
+
+    quote! {
+        /// The layout of the arguments of `wgpu::ComputePass::dispatch_workgroups_indirect`.
+        #[repr(C)]
+        #[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+        pub struct DispatchIndirectArgs {
+            pub x: u32,
+            pub y: u32,
+            pub z: u32,
+        }
+
+        impl DispatchIndirectArgs {
+            pub const fn from_workgroup_counts([x, y, z]: [u32; 3]) -> Self {
+                Self { x, y, z }
+            }
+        }
+
+        #bytemuck_impls
+    }
+  }
+
+  fn dispatch_indirect_args_fn(e: &naga::EntryPoint) -> TokenStream {
+    let workgroup_size = format_ident!("{}_WORKGROUP_SIZE", e.name.to_uppercase());
+    let fn_name = format_ident!("{}_dispatch_indirect_args", e.name);
+
+    quote! {
+        pub const fn #fn_name(elements: [u32; 3]) -> DispatchIndirectArgs {
+            DispatchIndirectArgs {
+                x: elements[0].div_ceil(#workgroup_size[0]),
+                y: elements[1].div_ceil(#workgroup_size[1]),
+                z: elements[2].div_ceil(#workgroup_size[2]),
+            }
+        }
+    }
   }
 
   pub(crate) fn entry_points_iter(&self) -> impl Iterator<Item = &naga::EntryPoint> {
@@ -226,17 +413,40 @@ impl<'a> ComputeModuleBuilder<'a> {
     let entry_points: Vec<_> = self
       .entry_points_iter()
       .map(|e| {
-        let workgroup_size_constant = Self::workgroup_size(e);
+        let workgroup_size_constant = self.workgroup_size(e);
+
+        let dispatch_helpers = if self.options.compute_dispatch_helpers {
+          Self::dispatch_helpers(e)
+        } else {
+          quote!()
+        };
+
+        let dispatch_indirect_args = if self.options.dispatch_indirect_args {
+          Self::dispatch_indirect_args_fn(e)
+        } else {
+          quote!()
+        };
 
         let create_pipeline_fns = self
-          .source_type_flags
+          .options
+          .shader_source_type
           .iter()
-          .map(|source_type| Self::build_compute_pipeline_fn(e, source_type))
+          .map(|source_type| self.build_compute_pipeline_fn(e, source_type))
           .collect::<Vec<_>>();
 
+        let create_pipeline_with_layout_fn = if self.options.compute_pipeline_with_layout
+        {
+          self.build_compute_pipeline_with_layout_fn(e)
+        } else {
+          quote!()
+        };
+
         quote! {
             #workgroup_size_constant
+            #dispatch_helpers
+            #dispatch_indirect_args
             #(#create_pipeline_fns)*
+            #create_pipeline_with_layout_fn
         }
       })
       .collect();
@@ -245,8 +455,15 @@ impl<'a> ComputeModuleBuilder<'a> {
       // Don't include empty modules.
       quote!()
     } else {
+      let dispatch_indirect_args_struct = if self.options.dispatch_indirect_args {
+        self.dispatch_indirect_args_struct()
+      } else {
+        quote!()
+      };
+
       quote! {
           pub mod compute {
+              #dispatch_indirect_args_struct
               #(#entry_points)*
           }
       }
@@ -255,20 +472,243 @@ impl<'a> ComputeModuleBuilder<'a> {
 }
 pub(crate) fn compute_module(
   module: &naga::Module,
-  source_type_flags: BitFlags<WgslShaderSourceType>,
+  options: &WgslBindgenOption,
+) -> TokenStream {
+  ComputeModuleBuilder::new(module, options).build()
+}
+
+pub(crate) fn shader_hash(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+) -> Result<TokenStream, CreateModuleError> {
+  if !options.shader_hash {
+    return Ok(quote!());
+  }
+
+  let source =
+    module_to_source(module).map_err(|error| CreateModuleError::ShaderOutput {
+      module: invoking_entry_module.to_owned(),
+      format: "WGSL",
+      message: error.to_string(),
+    })?;
+  let bytes = blake3::hash(source.as_bytes())
+    .as_bytes()
+    .map(Literal::u8_unsuffixed);
+  Ok(quote!(pub const SHADER_HASH: [u8; 32] = [#(#bytes),*];))
+}
+
+/// The first source type whose shader module is created from just the device.
+fn device_only_source_type(options: &WgslBindgenOption) -> Option<WgslShaderSourceType> {
+  use WgslShaderSourceType::*;
+  options.shader_source_type.iter().find(|source_type| {
+    matches!(*source_type, UseEmbed | SpirV | NagaIr | IncludeStrOriginal)
+  })
+}
+
+pub(crate) fn shader_cache(
+  module: &naga::Module,
+  options: &WgslBindgenOption,
 ) -> TokenStream {
-  ComputeModuleBuilder::new(module, source_type_flags).build()
+  if !options.shader_cache {
+    return quote!();
+  }
+
+  let Some(source_type) = device_only_source_type(options) else {
+    return quote!();
+  };
+  let create_shader_module_fn =
+    format_ident!("{}", source_type.create_shader_module_fn_name());
+
+  let compute_entries: Vec<_> = module
+    .entry_points
+    .iter()
+    .filter(|e| e.stage == naga::ShaderStage::Compute)
+    .collect();
+  let pipeline_fields: Vec<_> = compute_entries
+    .iter()
+    .map(|e| format_ident!("{}_pipeline", e.name))
+    .collect();
+  let pipeline_fns = compute_entries
+    .iter()
+    .zip(&pipeline_fields)
+    .map(|(e, field)| {
+      let entry_name = &e.name;
+      let entry_point = options.wgpu_edition.entry_point(quote!(#entry_name));
+      let compilation_options = options.pipeline_compilation_options_field();
+      let label = options
+        .label_style
+        .label_tokens(&format!("Compute Pipeline {}", e.name));
+
+      let get_or_create_pipeline = |cache: TokenStream| {
+        quote! {
+            self.#field.get_or_init(|| {
+                device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: #label,
+                    layout: Some(self.pipeline_layout(device)),
+                    module: self.shader_module(device),
+                    entry_point: #entry_point,
+                    #compilation_options
+                    #cache
+                })
+            })
+        }
+      };
+
+      if !options.pipeline_cache_fns {
+        let get_or_create_pipeline =
+          get_or_create_pipeline(options.wgpu_edition.pipeline_cache_field());
+        return quote! {
+            pub fn #field(&self, device: &wgpu::Device) -> &wgpu::ComputePipeline {
+                self.check_device(device);
+                #get_or_create_pipeline
+            }
+        };
+      }
+
+      let field_with_cache = format_ident!("{field}_with_cache");
+      let get_or_create_pipeline = get_or_create_pipeline(quote!(cache,));
+      quote! {
+          pub fn #field(&self, device: &wgpu::Device) -> &wgpu::ComputePipeline {
+              self.#field_with_cache(device, None)
+          }
+
+          /// The `cache` is only used when the pipeline is created, on first use.
+          pub fn #field_with_cache(
+              &self,
+              device: &wgpu::Device,
+              cache: Option<&wgpu::PipelineCache>,
+          ) -> &wgpu::ComputePipeline {
+              self.check_device(device);
+              #get_or_create_pipeline
+          }
+      }
+    });
+
+  let (device_id_type, device_id, current_device_id) = options.wgpu_edition.device_id();
+
+  quote! {
+      /// Creates the shader module, the pipeline layout and the compute pipelines once on first use,
+      /// for a single device. Render pipelines depend on the targets and state of the caller, so
+      /// they're created from the cached `shader_module` instead.
+      #[derive(Debug, Default)]
+      pub struct ShaderCache {
+          device: std::sync::OnceLock<#device_id_type>,
+          shader_module: std::sync::OnceLock<wgpu::ShaderModule>,
+          pipeline_layout: std::sync::OnceLock<wgpu::PipelineLayout>,
+          #(#pipeline_fields: std::sync::OnceLock<wgpu::ComputePipeline>,)*
+      }
+
+      impl ShaderCache {
+          fn check_device(&self, device: &wgpu::Device) {
+              let cached_device = self.device.get_or_init(|| #device_id);
+              assert!(
+                  *cached_device == #current_device_id,
+                  "the ShaderCache is used with another device than the one it was created with"
+              );
+          }
+
+          pub fn shader_module(&self, device: &wgpu::Device) -> &wgpu::ShaderModule {
+              self.check_device(device);
+              self.shader_module.get_or_init(|| #create_shader_module_fn(device))
+          }
+
+          pub fn pipeline_layout(&self, device: &wgpu::Device) -> &wgpu::PipelineLayout {
+              self.check_device(device);
+              self.pipeline_layout.get_or_init(|| create_pipeline_layout(device))
+          }
+
+          #(#pipeline_fns)*
+      }
+  }
+}
+
+/// The error of the `create_shader_module_checked` function of each module.
+pub(crate) fn compilation_messages_struct(options: &WgslBindgenOption) -> TokenStream {
+  if !options.checked_shader_modules || options.wgpu_edition < WgpuVersion::V0_20 {
+    return quote!();
+  }
+
+  quote! {
+      #[derive(Debug)]
+      pub struct CompilationMessages {
+          pub messages: Vec<wgpu::CompilationMessage>,
+          pub error: Option<wgpu::Error>,
+      }
+  }
+}
+
+/// Only generated for wgpu 0.20 and later, which added `wgpu::ShaderModule::get_compilation_info`.
+pub(crate) fn shader_module_checked(options: &WgslBindgenOption) -> TokenStream {
+  if !options.checked_shader_modules || options.wgpu_edition < WgpuVersion::V0_20 {
+    return quote!();
+  }
+  let Some(source_type) = device_only_source_type(options) else {
+    return quote!();
+  };
+  let create_shader_module_fn =
+    format_ident!("{}", source_type.create_shader_module_fn_name());
+
+  quote! {
+      pub async fn create_shader_module_checked(
+          device: &wgpu::Device,
+      ) -> Result<wgpu::ShaderModule, _root::CompilationMessages> {
+          device.push_error_scope(wgpu::ErrorFilter::Validation);
+          let module = #create_shader_module_fn(device);
+          let error = device.pop_error_scope().await;
+          let messages = module.get_compilation_info().await.messages;
+          let has_errors = messages
+              .iter()
+              .any(|message| matches!(message.message_type, wgpu::CompilationMessageType::Error));
+          if error.is_some() || has_errors {
+              Err(_root::CompilationMessages { messages, error })
+          } else {
+              Ok(module)
+          }
+      }
+  }
+}
+
+/// The module embedded in the output, without the unused functions if configured.
+fn embedded_module<'a>(
+  entry: &'a WgslEntryResult,
+  options: &WgslBindgenOption,
+) -> Cow<'a, naga::Module> {
+  if options.prune_unused_functions {
+    let mut module = entry.naga_module.clone();
+    prune_unused_functions(&mut module);
+    Cow::Owned(module)
+  } else {
+    Cow::Borrowed(&entry.naga_module)
+  }
+}
+
+/// The `cfg` predicates of the targets which can read the shader files at runtime.
+pub(crate) fn runtime_files_predicates(options: &WgslBindgenOption) -> Vec<TokenStream> {
+  let mut predicates = Vec::new();
+  if options.wasm_compatible {
+    predicates.push(quote!(not(target_arch = "wasm32")));
+  }
+  if options.no_std {
+    predicates.push(quote!(feature = "std"));
+  }
+  predicates
 }
 
-fn generate_shader_module_embedded(entry: &WgslEntryResult) -> TokenStream {
-  let shader_content = module_to_source(&entry.naga_module).unwrap();
+fn generate_shader_module_embedded(
+  entry: &WgslEntryResult,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+) -> TokenStream {
+  let shader_content = module_to_minified_source(module, options.minify).unwrap();
   let create_shader_module_fn =
     format_ident!("{}", WgslShaderSourceType::UseEmbed.create_shader_module_fn_name());
   let shader_literal = create_shader_raw_string_literal(&shader_content);
-  let shader_label = entry.get_label();
+  let shader_label = entry.get_label(&options.label_style);
+  let alloc = alloc_crate(options);
   let create_shader_module = quote! {
       pub fn #create_shader_module_fn(device: &wgpu::Device) -> wgpu::ShaderModule {
-          let source = std::borrow::Cow::Borrowed(SHADER_STRING);
+          let source = #alloc::borrow::Cow::Borrowed(SHADER_STRING);
           device.create_shader_module(wgpu::ShaderModuleDescriptor {
               label: #shader_label,
               source: wgpu::ShaderSource::Wgsl(source)
@@ -283,11 +723,175 @@ fn generate_shader_module_embedded(entry: &WgslEntryResult) -> TokenStream {
   }
 }
 
+/// The words of a SPIR-V file, which may have been written with either endianness.
+fn spirv_file_words(path: &SourceFilePath) -> Result<Vec<u32>, String> {
+  const MAGIC_NUMBER: u32 = 0x07230203;
+  let bytes = std::fs::read(path.as_path()).map_err(|error| error.to_string())?;
+  if bytes.len() % 4 != 0 {
+    return Err(format!("`{path}` isn't a whole number of words"));
+  }
+
+  let words: Vec<_> = bytes
+    .chunks_exact(4)
+    .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+    .collect();
+  if words.first() == Some(&MAGIC_NUMBER.swap_bytes()) {
+    Ok(words.into_iter().map(u32::swap_bytes).collect())
+  } else {
+    Ok(words)
+  }
+}
+
+fn generate_shader_module_spirv(
+  entry: &WgslEntryResult,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+) -> Result<TokenStream, CreateModuleError> {
+  let map_err = |message: String| CreateModuleError::ShaderOutput {
+    module: entry.mod_name.clone(),
+    format: "SPIR-V",
+    message,
+  };
+  let file_path = &entry.source_including_deps.source_file.file_path;
+  let is_spirv_entry = options
+    .spirv_entry_points
+    .iter()
+    .any(|path| SourceFilePath::new(path) == *file_path);
+  let words = if options.spirv_passthrough && is_spirv_entry {
+    spirv_file_words(file_path).map_err(map_err)?
+  } else {
+    module_to_spirv(module).map_err(|error| map_err(error.to_string()))?
+  };
+  let words = words.into_iter().map(Literal::u32_unsuffixed);
+  let create_shader_module_fn =
+    format_ident!("{}", WgslShaderSourceType::SpirV.create_shader_module_fn_name());
+  let shader_label = entry.get_label(&options.label_style);
+  let alloc = alloc_crate(options);
+
+  Ok(quote! {
+    pub fn #create_shader_module_fn(device: &wgpu::Device) -> wgpu::ShaderModule {
+        let source = #alloc::borrow::Cow::Borrowed(SHADER_SPIRV);
+        device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: #shader_label,
+            source: wgpu::ShaderSource::SpirV(source)
+        })
+    }
+    pub const SHADER_SPIRV: &[u32] = &[#(#words),*];
+  })
+}
+
+fn generate_shader_module_naga_ir(
+  entry: &WgslEntryResult,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+) -> Result<TokenStream, CreateModuleError> {
+  let bytes =
+    module_to_naga_ir(module).map_err(|error| CreateModuleError::ShaderOutput {
+      module: entry.mod_name.clone(),
+      format: "naga IR",
+      message: error.to_string(),
+    })?;
+  let bytes = Literal::byte_string(&bytes);
+  let create_shader_module_fn =
+    format_ident!("{}", WgslShaderSourceType::NagaIr.create_shader_module_fn_name());
+  let shader_label = entry.get_label(&options.label_style);
+  let alloc = alloc_crate(options);
+
+  Ok(quote! {
+    pub fn #create_shader_module_fn(device: &wgpu::Device) -> wgpu::ShaderModule {
+        let module: naga::Module = bincode::deserialize(SHADER_NAGA_IR)
+            .expect("failed to deserialize the naga module");
+        device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: #shader_label,
+            source: wgpu::ShaderSource::Naga(#alloc::borrow::Cow::Owned(module))
+        })
+    }
+    pub const SHADER_NAGA_IR: &[u8] = #bytes;
+  })
+}
+
+/// Composes the shader from the files on disk in debug builds, falling back to the embedded
+/// source in release builds or when the edited shader fails to compose.
+fn generate_shader_module_hot(options: &WgslBindgenOption) -> TokenStream {
+  use WgslShaderSourceType::*;
+  let create_from_path_fn =
+    format_ident!("{}", UseComposerWithPath.create_shader_module_fn_name());
+  let create_embedded_fn = format_ident!("{}", UseEmbed.create_shader_module_fn_name());
+  let mut predicates = vec![quote!(debug_assertions)];
+  predicates.extend(runtime_files_predicates(options));
+  let cfg = cfg_attribute(&predicates);
+
+  quote! {
+    pub fn create_shader_module_hot(device: &wgpu::Device) -> wgpu::ShaderModule {
+        #cfg
+        match #create_from_path_fn(device, Default::default()) {
+            Ok(module) => return module,
+            Err(err) => eprintln!("Failed to reload `{}`, using the embedded source: {}", SHADER_ENTRY_PATH, err),
+        }
+        #create_embedded_fn(device)
+    }
+  }
+}
+
+/// Creates the shader module from the original entry file, read with `include_str!` or at runtime.
+fn generate_shader_module_original(
+  entry: &WgslEntryResult,
+  output_dir: &Path,
+  source_type: WgslShaderSourceType,
+  options: &WgslBindgenOption,
+) -> Result<TokenStream, CreateModuleError> {
+  let source_file = entry.source_including_deps.source_file;
+  if !entry.source_including_deps.full_dependencies.is_empty() {
+    return Err(CreateModuleError::OriginalSourceWithImports {
+      entry: source_file.file_path.to_string(),
+    });
+  }
+
+  let relative_file_path = get_path_relative_to(output_dir, &source_file.file_path);
+  let create_shader_module_fn =
+    format_ident!("{}", source_type.create_shader_module_fn_name());
+  let return_type = source_type.get_return_type(quote!(wgpu::ShaderModule));
+  let shader_label = entry.get_label(&options.label_style);
+  let alloc = alloc_crate(options);
+  let return_stmt = source_type.wrap_return_stmt(quote! {
+      device.create_shader_module(wgpu::ShaderModuleDescriptor {
+          label: #shader_label,
+          source: wgpu::ShaderSource::Wgsl(source)
+      })
+  });
+
+  let (constant, source) = if source_type.is_runtime_path() {
+    (
+      quote!(
+        pub const SHADER_PATH: &str =
+          include_file_path::include_file_path!(#relative_file_path);
+      ),
+      quote!(#alloc::borrow::Cow::Owned(std::fs::read_to_string(SHADER_PATH)?)),
+    )
+  } else {
+    (
+      quote!(
+        pub const SHADER_SOURCE: &str = include_str!(#relative_file_path);
+      ),
+      quote!(#alloc::borrow::Cow::Borrowed(SHADER_SOURCE)),
+    )
+  };
+
+  Ok(quote! {
+    pub fn #create_shader_module_fn(device: &wgpu::Device) -> #return_type {
+        let source = #source;
+        #return_stmt
+    }
+    #constant
+  })
+}
+
 struct ComposeShaderModuleBuilder<'a, 'b> {
   entry: &'a WgslEntryResult<'b>,
   entry_source_path: &'a Path,
   output_dir: &'a Path,
   source_type: WgslShaderSourceType,
+  label_style: &'a LabelStyle,
 }
 
 impl<'a, 'b> ComposeShaderModuleBuilder<'a, 'b> {
@@ -295,6 +899,7 @@ impl<'a, 'b> ComposeShaderModuleBuilder<'a, 'b> {
     entry: &'a WgslEntryResult<'b>,
     output_dir: &'a Path,
     source_type: WgslShaderSourceType,
+    label_style: &'a LabelStyle,
   ) -> Self {
     let entry_source_path = entry.source_including_deps.source_file.file_path.as_path();
 
@@ -303,6 +908,7 @@ impl<'a, 'b> ComposeShaderModuleBuilder<'a, 'b> {
       output_dir,
       source_type,
       entry_source_path,
+      label_style,
     }
   }
 
@@ -456,7 +1062,7 @@ impl<'a, 'b> ComposeShaderModuleBuilder<'a, 'b> {
     let create_shader_module_fn = self.create_shader_module_fn_name();
     let load_shader_module_fn = self.load_shader_modules_fn_name();
     let load_naga_module_fn = self.load_naga_module_fn_name();
-    let shader_label = self.entry.get_label();
+    let shader_label = self.entry.get_label(self.label_style);
     let return_type = self.source_type.get_return_type(quote!(wgpu::ShaderModule));
     let propagate_operator = self.source_type.get_propagate_operator();
     let return_stmt = self.source_type.wrap_return_stmt(quote! {
@@ -515,7 +1121,7 @@ impl<'a, 'b> ComposeShaderModuleBuilder<'a, 'b> {
 pub(crate) fn shader_module(
   entry: &WgslEntryResult,
   options: &WgslBindgenOption,
-) -> TokenStream {
+) -> Result<TokenStream, CreateModuleError> {
   use WgslShaderSourceType::*;
   let source_type = options.shader_source_type;
   let output_dir = options
@@ -529,31 +1135,71 @@ pub(crate) fn shader_module(
     });
 
   let mut token_stream = TokenStream::new();
+  let module = embedded_module(entry, options);
 
   if source_type.contains(UseEmbed) {
-    token_stream.append_all(generate_shader_module_embedded(entry));
+    token_stream.append_all(generate_shader_module_embedded(entry, &module, options));
+  }
+
+  for original_source_type in [IncludeStrOriginal, RuntimePath] {
+    if source_type.contains(original_source_type) {
+      let items = generate_shader_module_original(
+        entry,
+        &output_dir,
+        original_source_type,
+        options,
+      )?;
+      let runtime_files_attribute = original_source_type.runtime_files_attribute(options);
+      token_stream.append_all(add_attribute_to_items(&runtime_files_attribute, items));
+    }
+  }
+
+  if source_type.contains(SpirV) {
+    token_stream.append_all(generate_shader_module_spirv(entry, &module, options)?);
+  }
+
+  if source_type.contains(NagaIr) {
+    token_stream.append_all(generate_shader_module_naga_ir(entry, &module, options)?);
   }
 
   if source_type.contains(UseComposerEmbed) {
-    let builder = ComposeShaderModuleBuilder::new(entry, &output_dir, UseComposerEmbed);
+    let builder = ComposeShaderModuleBuilder::new(
+      entry,
+      &output_dir,
+      UseComposerEmbed,
+      &options.label_style,
+    );
     token_stream.append_all(builder.build());
   }
 
   if source_type.contains(UseComposerWithPath) {
-    let builder =
-      ComposeShaderModuleBuilder::new(entry, &output_dir, UseComposerWithPath);
-    token_stream.append_all(builder.build());
+    let builder = ComposeShaderModuleBuilder::new(
+      entry,
+      &output_dir,
+      UseComposerWithPath,
+      &options.label_style,
+    );
+    let runtime_files_attribute = UseComposerWithPath.runtime_files_attribute(options);
+    token_stream
+      .append_all(add_attribute_to_items(&runtime_files_attribute, builder.build()));
+  }
+
+  if options.hot_reload {
+    token_stream.append_all(generate_shader_module_hot(options));
   }
 
-  token_stream
+  Ok(token_stream)
 }
 
+/// The path of `file` relative to `relative_to`, always separated by `/` so the output is the
+/// same on every platform.
 fn get_path_relative_to(relative_to: &std::path::Path, file: &std::path::Path) -> String {
   pathdiff::diff_paths(file, relative_to)
     .expect("failed to get relative path")
-    .to_str()
-    .unwrap()
-    .to_string()
+    .components()
+    .map(|component| component.as_os_str().to_str().unwrap())
+    .collect::<Vec<_>>()
+    .join("/")
 }
 
 fn create_canonical_variable_name(name: &str, is_const: bool) -> String {
@@ -573,10 +1219,13 @@ fn create_canonical_variable_name(name: &str, is_const: bool) -> String {
 
 #[cfg(test)]
 mod tests {
+  use enumflags2::BitFlags;
   use indoc::indoc;
 
   use super::*;
   use crate::assert_tokens_eq;
+  use crate::bevy_util::source_file::SourceFile;
+  use crate::{PipelineCompilationOptions, SourceWithFullDependenciesResult};
 
   #[test]
   fn test_create_canonical_variable_name() {
@@ -596,36 +1245,497 @@ mod tests {
         "#};
 
     let module = naga::front::wgsl::parse_str(source).unwrap();
-    let actual = compute_module(&module, WgslShaderSourceType::UseEmbed.into());
+    let options = WgslBindgenOption {
+      shader_source_type: WgslShaderSourceType::UseEmbed.into(),
+      ..Default::default()
+    };
+    let actual = compute_module(&module, &options);
 
     assert_tokens_eq!(quote!(), actual);
   }
 
   #[test]
-  fn write_compute_module_multiple_entries() {
+  fn write_shader_module_spirv() {
     let source = indoc! {r#"
             @compute
-            @workgroup_size(1,2,3)
-            fn main1() {}
+            @workgroup_size(1)
+            fn main() {}
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      shader_source_type: WgslShaderSourceType::SpirV.into(),
+      ..Default::default()
+    };
+    let compute_module = compute_module(&module, &options).to_string();
+    assert!(compute_module.contains("pub fn create_main_pipeline_spirv"));
+    assert!(compute_module.contains("super :: create_shader_module_spirv (device)"));
 
+    let words = module_to_spirv(&module).unwrap();
+    assert_eq!(0x07230203, words[0]);
+  }
+
+  #[test]
+  fn write_shader_hash() {
+    let source = indoc! {r#"
             @compute
-            @workgroup_size(256)
-            fn main2() {}
-        "#
+            @workgroup_size(1)
+            fn main() {}
+        "#};
+    let formatted_source = indoc! {r#"
+            @compute   @workgroup_size(1)
+            fn main() {
+                // Comments and formatting don't change the hash.
+            }
+        "#};
+
+    let options = WgslBindgenOption {
+      shader_hash: true,
+      ..Default::default()
+    };
+    let hash = |source| {
+      let module = naga::front::wgsl::parse_str(source).unwrap();
+      shader_hash("shader", &module, &options)
+        .unwrap()
+        .to_string()
     };
+    assert!(hash(source).starts_with("pub const SHADER_HASH : [u8 ; 32] = ["));
+    assert_eq!(hash(source), hash(formatted_source));
+    assert_ne!(hash(source), hash(source.replace("main", "main2").as_str()));
+  }
+
+  #[test]
+  fn write_shader_module_naga_ir() {
+    let source = indoc! {r#"
+            @compute
+            @workgroup_size(1)
+            fn main() {}
+        "#};
 
     let module = naga::front::wgsl::parse_str(source).unwrap();
-    let actual = compute_module(&module, WgslShaderSourceType::UseEmbed.into());
+    let options = WgslBindgenOption {
+      shader_source_type: WgslShaderSourceType::NagaIr.into(),
+      ..Default::default()
+    };
+    let compute_module = compute_module(&module, &options).to_string();
+    assert!(compute_module.contains("pub fn create_main_pipeline_naga_ir"));
+    assert!(compute_module.contains("super :: create_shader_module_naga_ir (device)"));
 
+    let bytes = module_to_naga_ir(&module).unwrap();
+    let deserialized: naga::Module = bincode::deserialize(&bytes).unwrap();
+    assert_eq!("main", deserialized.entry_points[0].name);
+  }
+
+  #[test]
+  fn write_shader_module_spirv_passthrough() {
+    let path = "tests/shaders/spirv/compute.spv";
+    let source_file = SourceFile::create(SourceFilePath::new(path), None, String::new());
+    let entry = WgslEntryResult {
+      mod_name: "compute".to_string(),
+      naga_module: naga::Module::default(),
+      source_including_deps: SourceWithFullDependenciesResult {
+        source_file: &source_file,
+        full_dependencies: Default::default(),
+      },
+    };
+    let options = WgslBindgenOption {
+      spirv_entry_points: vec![path.into()],
+      spirv_passthrough: true,
+      label_style: LabelStyle::Disabled,
+      ..Default::default()
+    };
+
+    let actual =
+      generate_shader_module_spirv(&entry, &entry.naga_module, &options).unwrap();
+
+    let words = std::fs::read(path)
+      .unwrap()
+      .chunks_exact(4)
+      .map(|word| Literal::u32_unsuffixed(u32::from_le_bytes(word.try_into().unwrap())))
+      .collect::<Vec<_>>();
     assert_tokens_eq!(
       quote! {
-          pub mod compute {
-              pub const MAIN1_WORKGROUP_SIZE: [u32; 3] = [1, 2, 3];
-              pub fn create_main1_pipeline_embed_source(device: &wgpu::Device) -> wgpu::ComputePipeline {
-                  let module = super::create_shader_module_embed_source(device);
-                  let layout = super::create_pipeline_layout(device);
-                  device
-                      .create_compute_pipeline(
+        pub fn create_shader_module_spirv(device: &wgpu::Device) -> wgpu::ShaderModule {
+            let source = std::borrow::Cow::Borrowed(SHADER_SPIRV);
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::SpirV(source)
+            })
+        }
+        pub const SHADER_SPIRV: &[u32] = &[#(#words),*];
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_shader_module_include_str_original() {
+    let source = "@fragment\nfn main() {}\n";
+    let source_file = SourceFile::create(
+      SourceFilePath::new("shaders/shader.wgsl"),
+      None,
+      source.to_string(),
+    );
+    let entry = WgslEntryResult {
+      mod_name: "shader".to_string(),
+      naga_module: naga::front::wgsl::parse_str(source).unwrap(),
+      source_including_deps: SourceWithFullDependenciesResult {
+        source_file: &source_file,
+        full_dependencies: Default::default(),
+      },
+    };
+
+    let actual = generate_shader_module_original(
+      &entry,
+      Path::new("src"),
+      WgslShaderSourceType::IncludeStrOriginal,
+      &WgslBindgenOption {
+        label_style: LabelStyle::Disabled,
+        ..Default::default()
+      },
+    )
+    .unwrap();
+
+    assert_tokens_eq!(
+      quote! {
+        pub fn create_shader_module_include_str(device: &wgpu::Device) -> wgpu::ShaderModule {
+            let source = std::borrow::Cow::Borrowed(SHADER_SOURCE);
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(source)
+            })
+        }
+        pub const SHADER_SOURCE: &str = include_str!("../shaders/shader.wgsl");
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_shader_module_hot() {
+    let actual = generate_shader_module_hot(&WgslBindgenOption::default());
+
+    assert_tokens_eq!(
+      quote! {
+        pub fn create_shader_module_hot(device: &wgpu::Device) -> wgpu::ShaderModule {
+            #[cfg(debug_assertions)]
+            match create_shader_module_from_path(device, Default::default()) {
+                Ok(module) => return module,
+                Err(err) => eprintln!("Failed to reload `{}`, using the embedded source: {}", SHADER_ENTRY_PATH, err),
+            }
+            create_shader_module_embed_source(device)
+        }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_shader_module_hot_wasm_compatible() {
+    let actual = generate_shader_module_hot(&WgslBindgenOption {
+      wasm_compatible: true,
+      ..Default::default()
+    });
+
+    assert_tokens_eq!(
+      quote! {
+        pub fn create_shader_module_hot(device: &wgpu::Device) -> wgpu::ShaderModule {
+            #[cfg(all(debug_assertions, not(target_arch = "wasm32")))]
+            match create_shader_module_from_path(device, Default::default()) {
+                Ok(module) => return module,
+                Err(err) => eprintln!("Failed to reload `{}`, using the embedded source: {}", SHADER_ENTRY_PATH, err),
+            }
+            create_shader_module_embed_source(device)
+        }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn gate_runtime_path_items_for_wasm() {
+    use WgslShaderSourceType::*;
+    let wasm_options = WgslBindgenOption {
+      wasm_compatible: true,
+      ..Default::default()
+    };
+    let items = quote! {
+      pub const SHADER_ENTRY_PATH: &str = "shader.wgsl";
+      pub fn create_shader_module_relative_path() {}
+    };
+
+    assert_tokens_eq!(
+      quote! {
+        #[cfg(not(target_arch = "wasm32"))]
+        pub const SHADER_ENTRY_PATH: &str = "shader.wgsl";
+        #[cfg(not(target_arch = "wasm32"))]
+        pub fn create_shader_module_relative_path() {}
+      },
+      add_attribute_to_items(
+        &RuntimePath.runtime_files_attribute(&wasm_options),
+        items.clone()
+      )
+    );
+    assert_tokens_eq!(
+      items.clone(),
+      add_attribute_to_items(
+        &UseEmbed.runtime_files_attribute(&wasm_options),
+        items.clone()
+      )
+    );
+    assert_tokens_eq!(
+      items.clone(),
+      add_attribute_to_items(
+        &RuntimePath.runtime_files_attribute(&WgslBindgenOption::default()),
+        items
+      )
+    );
+  }
+
+  #[test]
+  fn write_shader_module_hot_no_std() {
+    let actual = generate_shader_module_hot(&WgslBindgenOption {
+      wasm_compatible: true,
+      no_std: true,
+      ..Default::default()
+    });
+
+    assert_tokens_eq!(
+      quote! {
+        pub fn create_shader_module_hot(device: &wgpu::Device) -> wgpu::ShaderModule {
+            #[cfg(all(debug_assertions, not(target_arch = "wasm32"), feature = "std"))]
+            match create_shader_module_from_path(device, Default::default()) {
+                Ok(module) => return module,
+                Err(err) => eprintln!("Failed to reload `{}`, using the embedded source: {}", SHADER_ENTRY_PATH, err),
+            }
+            create_shader_module_embed_source(device)
+        }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_shader_cache() {
+    let source = indoc! {r#"
+            @compute
+            @workgroup_size(1)
+            fn main() {}
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      shader_source_type: WgslShaderSourceType::UseComposerEmbed
+        | WgslShaderSourceType::UseEmbed,
+      shader_cache: true,
+      label_style: LabelStyle::Disabled,
+      ..Default::default()
+    };
+    let actual = shader_cache(&module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+        /// Creates the shader module, the pipeline layout and the compute pipelines once on first use,
+        /// for a single device. Render pipelines depend on the targets and state of the caller, so
+        /// they're created from the cached `shader_module` instead.
+        #[derive(Debug, Default)]
+        pub struct ShaderCache {
+            device: std::sync::OnceLock<wgpu::Id<wgpu::Device>>,
+            shader_module: std::sync::OnceLock<wgpu::ShaderModule>,
+            pipeline_layout: std::sync::OnceLock<wgpu::PipelineLayout>,
+            main_pipeline: std::sync::OnceLock<wgpu::ComputePipeline>,
+        }
+        impl ShaderCache {
+            fn check_device(&self, device: &wgpu::Device) {
+                let cached_device = self.device.get_or_init(|| device.global_id());
+                assert!(
+                    *cached_device == device.global_id(),
+                    "the ShaderCache is used with another device than the one it was created with"
+                );
+            }
+            pub fn shader_module(&self, device: &wgpu::Device) -> &wgpu::ShaderModule {
+                self.check_device(device);
+                self.shader_module.get_or_init(|| create_shader_module_embed_source(device))
+            }
+            pub fn pipeline_layout(&self, device: &wgpu::Device) -> &wgpu::PipelineLayout {
+                self.check_device(device);
+                self.pipeline_layout.get_or_init(|| create_pipeline_layout(device))
+            }
+            pub fn main_pipeline(&self, device: &wgpu::Device) -> &wgpu::ComputePipeline {
+                self.check_device(device);
+                self.main_pipeline.get_or_init(|| {
+                    device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                        label: None,
+                        layout: Some(self.pipeline_layout(device)),
+                        module: self.shader_module(device),
+                        entry_point: "main",
+                    })
+                })
+            }
+        }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_shader_cache_with_cache_v23() {
+    let source = indoc! {r#"
+            @compute
+            @workgroup_size(1)
+            fn main() {}
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      shader_source_type: WgslShaderSourceType::UseEmbed.into(),
+      shader_cache: true,
+      pipeline_cache_fns: true,
+      wgpu_edition: WgpuVersion::V23,
+      label_style: LabelStyle::Disabled,
+      ..Default::default()
+    };
+    let actual = shader_cache(&module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+        /// Creates the shader module, the pipeline layout and the compute pipelines once on first use,
+        /// for a single device. Render pipelines depend on the targets and state of the caller, so
+        /// they're created from the cached `shader_module` instead.
+        #[derive(Debug, Default)]
+        pub struct ShaderCache {
+            device: std::sync::OnceLock<wgpu::Device>,
+            shader_module: std::sync::OnceLock<wgpu::ShaderModule>,
+            pipeline_layout: std::sync::OnceLock<wgpu::PipelineLayout>,
+            main_pipeline: std::sync::OnceLock<wgpu::ComputePipeline>,
+        }
+        impl ShaderCache {
+            fn check_device(&self, device: &wgpu::Device) {
+                let cached_device = self.device.get_or_init(|| device.clone());
+                assert!(
+                    *cached_device == *device,
+                    "the ShaderCache is used with another device than the one it was created with"
+                );
+            }
+            pub fn shader_module(&self, device: &wgpu::Device) -> &wgpu::ShaderModule {
+                self.check_device(device);
+                self.shader_module.get_or_init(|| create_shader_module_embed_source(device))
+            }
+            pub fn pipeline_layout(&self, device: &wgpu::Device) -> &wgpu::PipelineLayout {
+                self.check_device(device);
+                self.pipeline_layout.get_or_init(|| create_pipeline_layout(device))
+            }
+            pub fn main_pipeline(&self, device: &wgpu::Device) -> &wgpu::ComputePipeline {
+                self.main_pipeline_with_cache(device, None)
+            }
+            /// The `cache` is only used when the pipeline is created, on first use.
+            pub fn main_pipeline_with_cache(
+                &self,
+                device: &wgpu::Device,
+                cache: Option<&wgpu::PipelineCache>,
+            ) -> &wgpu::ComputePipeline {
+                self.check_device(device);
+                self.main_pipeline.get_or_init(|| {
+                    device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                        label: None,
+                        layout: Some(self.pipeline_layout(device)),
+                        module: self.shader_module(device),
+                        entry_point: Some("main"),
+                        compilation_options: Default::default(),
+                        cache,
+                    })
+                })
+            }
+        }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_shader_module_checked() {
+    let options = WgslBindgenOption {
+      shader_source_type: WgslShaderSourceType::SpirV.into(),
+      checked_shader_modules: true,
+      wgpu_edition: WgpuVersion::V0_20,
+      ..Default::default()
+    };
+    let actual = shader_module_checked(&options);
+
+    assert_tokens_eq!(
+      quote! {
+          pub async fn create_shader_module_checked(
+              device: &wgpu::Device,
+          ) -> Result<wgpu::ShaderModule, _root::CompilationMessages> {
+              device.push_error_scope(wgpu::ErrorFilter::Validation);
+              let module = create_shader_module_spirv(device);
+              let error = device.pop_error_scope().await;
+              let messages = module.get_compilation_info().await.messages;
+              let has_errors = messages
+                  .iter()
+                  .any(|message| matches!(message.message_type, wgpu::CompilationMessageType::Error));
+              if error.is_some() || has_errors {
+                  Err(_root::CompilationMessages { messages, error })
+              } else {
+                  Ok(module)
+              }
+          }
+      },
+      actual
+    );
+    assert_tokens_eq!(
+      quote!(),
+      shader_module_checked(&WgslBindgenOption {
+        shader_source_type: WgslShaderSourceType::UseComposerWithPath.into(),
+        checked_shader_modules: true,
+        wgpu_edition: WgpuVersion::V0_20,
+        ..Default::default()
+      })
+    );
+    assert_tokens_eq!(
+      quote!(),
+      shader_module_checked(&WgslBindgenOption {
+        shader_source_type: WgslShaderSourceType::SpirV.into(),
+        checked_shader_modules: true,
+        ..Default::default()
+      })
+    );
+  }
+
+  #[test]
+  fn write_compute_module_multiple_entries() {
+    let source = indoc! {r#"
+            @compute
+            @workgroup_size(1,2,3)
+            fn main1() {}
+
+            @compute
+            @workgroup_size(256)
+            fn main2() {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      shader_source_type: WgslShaderSourceType::UseEmbed.into(),
+      workgroup_size_components: true,
+      ..Default::default()
+    };
+    let actual = compute_module(&module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+          pub mod compute {
+              pub const MAIN1_WORKGROUP_SIZE: [u32; 3] = [1, 2, 3];
+              pub const MAIN1_WORKGROUP_SIZE_X: u32 = 1;
+              pub const MAIN1_WORKGROUP_SIZE_Y: u32 = 2;
+              pub const MAIN1_WORKGROUP_SIZE_Z: u32 = 3;
+              pub const MAIN1_WORKGROUP_INVOCATIONS: u32 = 6;
+              pub fn create_main1_pipeline_embed_source(device: &wgpu::Device) -> wgpu::ComputePipeline {
+                  let module = super::create_shader_module_embed_source(device);
+                  let layout = super::create_pipeline_layout(device);
+                  device
+                      .create_compute_pipeline(
                           &wgpu::ComputePipelineDescriptor {
                               label: Some("Compute Pipeline main1"),
                               layout: Some(&layout),
@@ -635,6 +1745,10 @@ mod tests {
                       )
               }
               pub const MAIN2_WORKGROUP_SIZE: [u32; 3] = [256, 1, 1];
+              pub const MAIN2_WORKGROUP_SIZE_X: u32 = 256;
+              pub const MAIN2_WORKGROUP_SIZE_Y: u32 = 1;
+              pub const MAIN2_WORKGROUP_SIZE_Z: u32 = 1;
+              pub const MAIN2_WORKGROUP_INVOCATIONS: u32 = 256;
               pub fn create_main2_pipeline_embed_source(device: &wgpu::Device) -> wgpu::ComputePipeline {
                   let module = super::create_shader_module_embed_source(device);
                   let layout = super::create_pipeline_layout(device);
@@ -653,4 +1767,343 @@ mod tests {
       actual
     );
   }
+
+  #[test]
+  fn write_compute_module_dispatch_helpers() {
+    let source = indoc! {r#"
+            @compute
+            @workgroup_size(8, 8)
+            fn main() {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      shader_source_type: WgslShaderSourceType::UseEmbed.into(),
+      compute_dispatch_helpers: true,
+      ..Default::default()
+    };
+    let actual = compute_module(&module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+          pub mod compute {
+              pub const MAIN_WORKGROUP_SIZE: [u32; 3] = [8, 8, 1];
+              pub const fn main_workgroup_count_for(elements: [u32; 3]) -> [u32; 3] {
+                  [
+                      elements[0].div_ceil(MAIN_WORKGROUP_SIZE[0]),
+                      elements[1].div_ceil(MAIN_WORKGROUP_SIZE[1]),
+                      elements[2].div_ceil(MAIN_WORKGROUP_SIZE[2]),
+                  ]
+              }
+              pub fn dispatch_main(pass: &mut wgpu::ComputePass<'_>, total: [u32; 3]) {
+                  let [x, y, z] = main_workgroup_count_for(total);
+                  pass.dispatch_workgroups(x, y, z);
+              }
+              pub fn create_main_pipeline_embed_source(device: &wgpu::Device) -> wgpu::ComputePipeline {
+                  let module = super::create_shader_module_embed_source(device);
+                  let layout = super::create_pipeline_layout(device);
+                  device
+                      .create_compute_pipeline(
+                          &wgpu::ComputePipelineDescriptor {
+                              label: Some("Compute Pipeline main"),
+                              layout: Some(&layout),
+                              module: &module,
+                              entry_point: "main",
+                          },
+                      )
+              }
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_compute_module_dispatch_indirect_args() {
+    let source = indoc! {r#"
+            @compute
+            @workgroup_size(64)
+            fn main() {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      shader_source_type: WgslShaderSourceType::UseEmbed.into(),
+      serialization_strategy: WgslTypeSerializeStrategy::Bytemuck,
+      dispatch_indirect_args: true,
+      ..Default::default()
+    };
+    let actual = compute_module(&module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+          pub mod compute {
+              /// The layout of the arguments of `wgpu::ComputePass::dispatch_workgroups_indirect`.
+              #[repr(C)]
+              #[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+              pub struct DispatchIndirectArgs {
+                  pub x: u32,
+                  pub y: u32,
+                  pub z: u32,
+              }
+              impl DispatchIndirectArgs {
+                  pub const fn from_workgroup_counts([x, y, z]: [u32; 3]) -> Self {
+                      Self { x, y, z }
+                  }
+              }
+              unsafe impl bytemuck::Zeroable for DispatchIndirectArgs {}
+              unsafe impl bytemuck::Pod for DispatchIndirectArgs {}
+              pub const MAIN_WORKGROUP_SIZE: [u32; 3] = [64, 1, 1];
+              pub const fn main_dispatch_indirect_args(elements: [u32; 3]) -> DispatchIndirectArgs {
+                  DispatchIndirectArgs {
+                      x: elements[0].div_ceil(MAIN_WORKGROUP_SIZE[0]),
+                      y: elements[1].div_ceil(MAIN_WORKGROUP_SIZE[1]),
+                      z: elements[2].div_ceil(MAIN_WORKGROUP_SIZE[2]),
+                  }
+              }
+              pub fn create_main_pipeline_embed_source(device: &wgpu::Device) -> wgpu::ComputePipeline {
+                  let module = super::create_shader_module_embed_source(device);
+                  let layout = super::create_pipeline_layout(device);
+                  device
+                      .create_compute_pipeline(
+                          &wgpu::ComputePipelineDescriptor {
+                              label: Some("Compute Pipeline main"),
+                              layout: Some(&layout),
+                              module: &module,
+                              entry_point: "main",
+                          },
+                      )
+              }
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_compute_module_pipeline_with_layout() {
+    let source = indoc! {r#"
+            @compute
+            @workgroup_size(1)
+            fn main() {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      shader_source_type: BitFlags::empty(),
+      compute_pipeline_with_layout: true,
+      ..Default::default()
+    };
+    let actual = compute_module(&module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+          pub mod compute {
+              pub const MAIN_WORKGROUP_SIZE: [u32; 3] = [1, 1, 1];
+              pub fn create_main_pipeline_with_layout(
+                  device: &wgpu::Device,
+                  module: &wgpu::ShaderModule,
+                  layout: Option<&wgpu::PipelineLayout>,
+              ) -> wgpu::ComputePipeline {
+                  device
+                      .create_compute_pipeline(
+                          &wgpu::ComputePipelineDescriptor {
+                              label: Some("Compute Pipeline main"),
+                              layout,
+                              module,
+                              entry_point: "main",
+                          },
+                      )
+              }
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_compute_module_pipeline_with_layout_v22() {
+    let source = indoc! {r#"
+            @compute
+            @workgroup_size(1)
+            fn main() {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      shader_source_type: BitFlags::empty(),
+      compute_pipeline_with_layout: true,
+      wgpu_edition: WgpuVersion::V22,
+      ..Default::default()
+    };
+    let actual = compute_module(&module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+          pub mod compute {
+              pub const MAIN_WORKGROUP_SIZE: [u32; 3] = [1, 1, 1];
+              pub fn create_main_pipeline_with_layout(
+                  device: &wgpu::Device,
+                  module: &wgpu::ShaderModule,
+                  layout: Option<&wgpu::PipelineLayout>,
+                  compilation_options: wgpu::PipelineCompilationOptions<'_>,
+                  cache: Option<&wgpu::PipelineCache>,
+              ) -> wgpu::ComputePipeline {
+                  device
+                      .create_compute_pipeline(
+                          &wgpu::ComputePipelineDescriptor {
+                              label: Some("Compute Pipeline main"),
+                              layout,
+                              module,
+                              entry_point: Some("main"),
+                              compilation_options,
+                              cache,
+                          },
+                      )
+              }
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_compute_module_pipeline_with_cache() {
+    let source = indoc! {r#"
+            @compute
+            @workgroup_size(1)
+            fn main() {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      shader_source_type: WgslShaderSourceType::UseEmbed.into(),
+      pipeline_cache_fns: true,
+      wgpu_edition: WgpuVersion::V22,
+      ..Default::default()
+    };
+    let actual = compute_module(&module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+          pub mod compute {
+              pub const MAIN_WORKGROUP_SIZE: [u32; 3] = [1, 1, 1];
+              pub fn create_main_pipeline_embed_source(device: &wgpu::Device) -> wgpu::ComputePipeline {
+                  create_main_pipeline_embed_source_with_cache(device, None)
+              }
+              pub fn create_main_pipeline_embed_source_with_cache(
+                  device: &wgpu::Device,
+                  cache: Option<&wgpu::PipelineCache>,
+              ) -> wgpu::ComputePipeline {
+                  let module = super::create_shader_module_embed_source(device);
+                  let layout = super::create_pipeline_layout(device);
+                  device
+                      .create_compute_pipeline(
+                          &wgpu::ComputePipelineDescriptor {
+                              label: Some("Compute Pipeline main"),
+                              layout: Some(&layout),
+                              module: &module,
+                              entry_point: Some("main"),
+                              compilation_options: Default::default(),
+                              cache,
+                          },
+                      )
+              }
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_compute_module_compilation_options() {
+    let source = indoc! {r#"
+            @compute
+            @workgroup_size(1)
+            fn main() {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      shader_source_type: WgslShaderSourceType::UseEmbed.into(),
+      pipeline_compilation_options: PipelineCompilationOptions {
+        zero_initialize_workgroup_memory: false,
+      },
+      wgpu_edition: WgpuVersion::V0_20,
+      ..Default::default()
+    };
+    let actual = compute_module(&module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+          pub mod compute {
+              pub const MAIN_WORKGROUP_SIZE: [u32; 3] = [1, 1, 1];
+              pub fn create_main_pipeline_embed_source(device: &wgpu::Device) -> wgpu::ComputePipeline {
+                  let module = super::create_shader_module_embed_source(device);
+                  let layout = super::create_pipeline_layout(device);
+                  device
+                      .create_compute_pipeline(
+                          &wgpu::ComputePipelineDescriptor {
+                              label: Some("Compute Pipeline main"),
+                              layout: Some(&layout),
+                              module: &module,
+                              entry_point: "main",
+                              compilation_options: wgpu::PipelineCompilationOptions {
+                                  zero_initialize_workgroup_memory: false,
+                                  ..Default::default()
+                              },
+                          },
+                      )
+              }
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_compute_module_disabled_labels() {
+    let source = indoc! {r#"
+            @compute
+            @workgroup_size(1)
+            fn main() {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      shader_source_type: WgslShaderSourceType::UseEmbed.into(),
+      label_style: LabelStyle::Disabled,
+      ..Default::default()
+    };
+    let actual = compute_module(&module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+          pub mod compute {
+              pub const MAIN_WORKGROUP_SIZE: [u32; 3] = [1, 1, 1];
+              pub fn create_main_pipeline_embed_source(device: &wgpu::Device) -> wgpu::ComputePipeline {
+                  let module = super::create_shader_module_embed_source(device);
+                  let layout = super::create_pipeline_layout(device);
+                  device
+                      .create_compute_pipeline(
+                          &wgpu::ComputePipelineDescriptor {
+                              label: None,
+                              layout: Some(&layout),
+                              module: &module,
+                              entry_point: "main",
+                          },
+                      )
+              }
+          }
+      },
+      actual
+    );
+  }
 }
@@ -7,12 +7,15 @@ use enumflags2::BitFlags;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 
-use crate::{sanitize_and_pascal_case, WgslEntryResult, WgslShaderSourceType};
+use crate::{
+  sanitize_and_pascal_case, WgslBindgenOption, WgslEntryResult, WgslShaderSourceType,
+};
 
 #[derive(Constructor)]
 struct ShaderEntryBuilder<'a, 'b> {
   entries: &'a [WgslEntryResult<'b>],
   source_type: BitFlags<WgslShaderSourceType>,
+  options: &'a WgslBindgenOption,
 }
 
 impl<'a, 'b> ShaderEntryBuilder<'a, 'b> {
@@ -65,8 +68,10 @@ impl<'a, 'b> ShaderEntryBuilder<'a, 'b> {
     });
 
     let return_type = source_type.get_return_type(quote!(wgpu::ShaderModule));
+    let runtime_files_attribute = source_type.runtime_files_attribute(self.options);
 
     quote! {
+      #runtime_files_attribute
       pub fn #fn_name(&self, #param_defs) -> #return_type {
         match self {
           #( #match_arms, )*
@@ -124,8 +129,11 @@ impl<'a, 'b> ShaderEntryBuilder<'a, 'b> {
         Self::#enum_variant => #mod_path::SHADER_PATHS
       }
     });
+    let runtime_files_attribute =
+      WgslShaderSourceType::UseComposerWithPath.runtime_files_attribute(self.options);
 
     quote! {
+      #runtime_files_attribute
       pub fn shader_paths(&self) -> &[&str] {
         match self {
           #( #match_arms, )*
@@ -168,7 +176,7 @@ impl<'a, 'b> ShaderEntryBuilder<'a, 'b> {
 
 pub(crate) fn build_shader_registry(
   entries: &[WgslEntryResult<'_>],
-  source_type: BitFlags<WgslShaderSourceType>,
+  options: &WgslBindgenOption,
 ) -> TokenStream {
-  ShaderEntryBuilder::new(entries, source_type).build()
+  ShaderEntryBuilder::new(entries, options.shader_source_type, options).build()
 }
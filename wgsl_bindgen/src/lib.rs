@@ -42,22 +42,33 @@ extern crate wgpu_types as wgpu;
 use bevy_util::SourceWithFullDependenciesResult;
 use case::CaseExt;
 use derive_more::IsVariant;
-use generate::{bind_group, consts, pipeline, shader_module, shader_registry};
+use generate::{
+  bind_group, consts, pipeline, shader_defs, shader_module, shader_registry,
+};
 use heck::ToPascalCase;
+use limits::UsedLimits;
 use naga::ShaderStage;
 use proc_macro2::{Literal, Span, TokenStream};
 use qs::{format_ident, quote, Ident, Index};
-use quote_gen::{custom_vector_matrix_assertions, RustModBuilder, MOD_STRUCT_ASSERTIONS};
+use quote_gen::{
+  core_crate, custom_vector_matrix_assertions, padded_matrix_types, RustItemPath,
+  RustModBuilder, MOD_BIND_GROUP_PASS, MOD_GLOBAL_BIND_GROUP, MOD_PADDED_MATRICES,
+  MOD_STRUCT_ASSERTIONS,
+};
 use thiserror::Error;
+use wgsl_docs::WgslDocs;
 
 pub mod bevy_util;
 mod bindgen;
 mod generate;
+mod limits;
 mod naga_util;
 mod quote_gen;
+mod reflection;
 mod structs;
 mod types;
 mod wgsl;
+mod wgsl_docs;
 mod wgsl_type;
 
 pub mod qs {
@@ -68,6 +79,7 @@ pub mod qs {
 
 pub use bindgen::*;
 pub use naga::FastIndexMap;
+pub use reflection::*;
 pub use regex::Regex;
 pub use types::*;
 pub use wgsl_type::*;
@@ -95,6 +107,133 @@ pub enum CreateModuleError {
   /// Each binding resource must be associated with exactly one binding index.
   #[error("duplicate binding found with index `{binding}`")]
   DuplicateBinding { binding: u32 },
+
+  /// The original source of an entry with imports can't be passed to wgpu as is.
+  /// See `WgslShaderSourceType::IncludeStrOriginal` and `WgslShaderSourceType::RuntimePath`.
+  #[error("entry `{entry}` has imports, so its original source can't be used as the shader source")]
+  OriginalSourceWithImports { entry: String },
+
+  /// The engine provides the bind groups of excluded bindings, so a group can't mix
+  /// excluded and generated bindings. See `WgslBindgenOptionBuilder::exclude_binding`.
+  #[error("bind group `{group}` of `{entry}` has both excluded and generated bindings")]
+  PartiallyExcludedBindGroup { entry: String, group: u32 },
+
+  /// The binding's type can't be used in a bind group layout, such as binding arrays.
+  #[error("binding `{binding}` in group `{group}` of `{module}` has unsupported type `{type_name}`")]
+  UnsupportedBindingType {
+    module: String,
+    binding: String,
+    group: u32,
+    type_name: String,
+  },
+
+  /// The field's type doesn't match a `wgpu::VertexFormat`, such as `vec3<u8>` or a struct.
+  /// Fields with packed or normalized formats can use `override_vertex_format`.
+  #[error(
+    "vertex input `{input}.{field}` of `{module}` has unsupported type `{type_name}`"
+  )]
+  UnsupportedVertexFormat {
+    module: String,
+    input: String,
+    field: String,
+    type_name: String,
+  },
+
+  /// The struct generated for the `@location` arguments of a vertex entry would have the name
+  /// of a struct of the module.
+  #[error("the vertex inputs of `{entry}` in `{module}` would be generated as `{name}`, which is already a struct of the module")]
+  VertexInputNameConflict {
+    module: String,
+    entry: String,
+    name: String,
+  },
+
+  /// A vertex entry argument couldn't be read as vertex buffer inputs, such as a struct
+  /// member without a `@location`.
+  #[error("invalid vertex inputs of `{entry}` in `{module}`: {message}")]
+  InvalidVertexInput {
+    module: String,
+    entry: String,
+    message: String,
+  },
+
+  /// A constant generated for the module, such as `MAX_BIND_GROUPS_USED`, has the name of a
+  /// WGSL constant of the module.
+  #[error(
+    "`{name}` is generated for `{module}` but is already a constant of the module"
+  )]
+  GeneratedConstNameConflict { module: String, name: String },
+
+  /// The bindings of the global bind group must be the same in all the entries using it.
+  /// See `WgslBindgenOptionBuilder::global_bind_group`.
+  #[error("bind group `{group}` of `{module}` doesn't match the global bind group of `{global_module}`")]
+  GlobalBindGroupMismatch {
+    module: String,
+    global_module: String,
+    group: u32,
+  },
+
+  /// The entry's module has the name of a module generated for all the entries, such as the
+  /// `global_bind_group` module of `WgslBindgenOptionBuilder::global_bind_group`.
+  #[error("entry `{module}` has the name of a generated module, rename its file")]
+  ReservedModuleName { module: String },
+
+  /// The module needs more of a resource than `target_limits` allow.
+  /// See `WgslBindgenOptionBuilder::target_limits`.
+  #[error("`{module}` exceeds `{limit}` of the target limits, it uses {used} but at most {max} are allowed")]
+  LimitExceeded {
+    module: String,
+    limit: &'static str,
+    used: u32,
+    max: u32,
+  },
+
+  /// The shader defs become fields of the generated `ShaderDefs`, so their names
+  /// must be valid Rust identifiers once lowercased.
+  #[error("shader def `{name}` of `{module}` isn't a valid Rust identifier")]
+  InvalidShaderDef { module: String, name: String },
+
+  /// The bindings of a bind group have `group` annotations with different names.
+  #[error("bind group `{group}` of `{module}` is annotated with both `{name}` and `{other_name}`")]
+  ConflictingBindGroupNames {
+    module: String,
+    group: u32,
+    name: String,
+    other_name: String,
+  },
+
+  /// Two bind groups would have the same generated name, from `group` annotations or
+  /// `WgslBindgenOptionBuilder::bind_group_names`.
+  #[error(
+    "bind groups `{group}` and `{other_group}` of `{module}` are both named `{name}`"
+  )]
+  DuplicateBindGroupName {
+    module: String,
+    group: u32,
+    other_group: u32,
+    name: String,
+  },
+
+  /// A `// wgsl_bindgen:` annotation in the sources of the module is invalid, such as an unknown
+  /// key or a `name` which isn't a valid Rust identifier.
+  #[error("invalid annotation `{annotation}` in `{module}`: {message}")]
+  InvalidAnnotation {
+    module: String,
+    annotation: String,
+    message: String,
+  },
+
+  /// naga couldn't compute the size and alignment of a type in the module.
+  #[error("failed to compute the type layouts of `{module}`: {message}")]
+  InvalidTypeLayout { module: String, message: String },
+
+  /// naga failed to write the module in the format of a `WgslShaderSourceType`.
+  #[error("failed to write `{module}` as {format}: {message}")]
+  ShaderOutput {
+    module: String,
+    format: &'static str,
+    message: String,
+  },
 }
 
 pub(crate) struct WgslEntryResult<'a> {
@@ -107,68 +246,248 @@ fn create_rust_bindings(
   entries: Vec<WgslEntryResult<'_>>,
   options: &WgslBindgenOption,
 ) -> Result<String, CreateModuleError> {
-  let mut mod_builder = RustModBuilder::new(true);
+  let module_attributes = &options.module_attributes;
+  let prelude_imports = &options.prelude_imports;
+  let no_std_prelude = no_std_prelude(options);
+  let mut mod_builder = RustModBuilder::new(true)
+    .with_module_attributes(quote!(#(#module_attributes)*))
+    .with_prelude(quote!(#no_std_prelude #(#prelude_imports)*));
 
   if let Some(custom_wgsl_type_asserts) = custom_vector_matrix_assertions(options) {
     mod_builder.add(MOD_STRUCT_ASSERTIONS, custom_wgsl_type_asserts);
   }
 
-  for entry in entries.iter() {
+  if let Some(padded_types) = padded_matrix_types(options) {
+    mod_builder.add(MOD_PADDED_MATRICES, padded_types);
+  }
+
+  if let Some(pass_trait) = bind_group::set_bind_group_trait(options) {
+    mod_builder.add(MOD_BIND_GROUP_PASS, pass_trait);
+  }
+
+  let entry_options: Vec<_> = entries
+    .iter()
+    .map(|entry| options.for_entry(&entry.mod_name))
+    .collect();
+
+  for (entry, options) in entries.iter().zip(&entry_options) {
+    bind_group::check_binding_types(&entry.mod_name, &entry.naga_module)?;
+    wgsl::type_layouter(&entry.mod_name, &entry.naga_module)?;
+    wgsl::check_vertex_inputs(&entry.mod_name, &entry.naga_module, options)?;
+    if let Some(target_limits) = &options.target_limits {
+      UsedLimits::from_module(&entry.mod_name, &entry.naga_module)?
+        .check(&entry.mod_name, target_limits)?;
+    }
+  }
+  let entry_docs = entries
+    .iter()
+    .map(wgsl_docs)
+    .collect::<Result<Vec<_>, _>>()?;
+  let shared_structs = if options.deduplicate_structs {
+    let modules: Vec<_> = (0..entries.len())
+      .map(|i| (&entries[i].naga_module, &entry_docs[i], &*entry_options[i]))
+      .collect();
+    structs::shared_struct_names(&modules)?
+  } else {
+    Default::default()
+  };
+
+  let mut entry_bind_groups = entries
+    .iter()
+    .map(|entry| bind_group::get_bind_group_data(&entry.naga_module))
+    .collect::<Result<Vec<_>, _>>()?;
+  for (i, data) in entry_bind_groups.iter_mut().enumerate() {
+    let mod_name = &entries[i].mod_name;
+    bind_group::annotate_bindings(mod_name, data, &entry_docs[i])?;
+    bind_group::name_bind_groups(mod_name, data, &entry_options[i])?;
+  }
+  let entry_excluded_bind_groups = entries
+    .iter()
+    .zip(&mut entry_bind_groups)
+    .map(|(entry, data)| {
+      bind_group::take_excluded_bind_groups(&entry.mod_name, data, options)
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+  let mut shared_bind_groups = if options.deduplicate_bind_groups {
+    let bind_groups: Vec<_> = (0..entries.len())
+      .map(|i| {
+        let entry = &entries[i];
+        let shader_stages = wgsl::shader_stages(&entry.naga_module);
+        let options = &*entry_options[i];
+        (entry.mod_name.as_str(), &entry_bind_groups[i], shader_stages, options)
+      })
+      .collect();
+    bind_group::shared_bind_group_owners(&bind_groups)
+  } else {
+    vec![Default::default(); entries.len()]
+  };
+  let global_bind_group = {
+    let bind_groups: Vec<_> = (0..entries.len())
+      .map(|i| {
+        let entry = &entries[i];
+        let shader_stages = wgsl::shader_stages(&entry.naga_module);
+        (entry.mod_name.as_str(), &entry_bind_groups[i], shader_stages)
+      })
+      .collect();
+    bind_group::global_bind_group_items(&bind_groups, &mut shared_bind_groups, options)?
+  };
+  if let Some(global_bind_group) = global_bind_group {
+    mod_builder.add(MOD_GLOBAL_BIND_GROUP, global_bind_group);
+  }
+  let compatible_bind_groups = if options.bind_group_compatibility {
+    let bind_groups: Vec<_> = (0..entries.len())
+      .map(|i| {
+        let entry = &entries[i];
+        let shader_stages = wgsl::shader_stages(&entry.naga_module);
+        let options = &*entry_options[i];
+        (entry.mod_name.as_str(), &entry_bind_groups[i], shader_stages, options)
+      })
+      .collect();
+    bind_group::compatible_bind_group_impls(&bind_groups, &shared_bind_groups)
+  } else {
+    vec![quote!(); entries.len()]
+  };
+
+  for (i, entry) in entries.iter().enumerate() {
     let WgslEntryResult {
       mod_name,
       naga_module,
       ..
     } = entry;
+    let options = &*entry_options[i];
+    let docs = &entry_docs[i];
+    let bind_group_data = &entry_bind_groups[i];
+    let shared_bind_groups = &shared_bind_groups[i];
     let entry_name = sanitize_and_pascal_case(&entry.mod_name);
-    let bind_group_data = bind_group::get_bind_group_data(naga_module)?;
     let shader_stages = wgsl::shader_stages(naga_module);
 
     // Write all the structs, including uniforms and entry function inputs.
+    let struct_items = if shared_structs.is_empty() {
+      structs::structs_items(&mod_name, naga_module, options, docs)?
+    } else {
+      structs::structs_items_with_shared(
+        &mod_name,
+        naga_module,
+        options,
+        docs,
+        &shared_structs,
+      )?
+    };
+    mod_builder.add_items(struct_items).unwrap();
+
     mod_builder
-      .add_items(structs::structs_items(&mod_name, naga_module, options))
+      .add_items(consts::consts_items(&mod_name, naga_module, docs))
       .unwrap();
 
     mod_builder
-      .add_items(consts::consts_items(&mod_name, naga_module))
-      .unwrap();
+      .add(mod_name, structs::layout_validation_fn(&mod_name, naga_module, options)?);
 
-    mod_builder.add(mod_name, vertex_struct_methods(naga_module));
+    mod_builder.add(mod_name, vertex_struct_methods(mod_name, naga_module, options));
 
     mod_builder.add(
       mod_name,
-      bind_group::bind_groups_module(
+      bind_group::bind_groups_module_with_shared(
         &mod_name,
         &options,
-        &bind_group_data,
+        bind_group_data,
         shader_stages,
+        shared_bind_groups,
       ),
     );
-
+    mod_builder.add(mod_name, compatible_bind_groups[i].clone());
+
+    mod_builder.add(mod_name, shader_module::compute_module(naga_module, options));
+    mod_builder.add(mod_name, entry_point_constants(naga_module, docs));
+    mod_builder.add(mod_name, entry_point_enum(naga_module, options));
+    mod_builder.add(mod_name, fragment_target_constants(mod_name, naga_module, options)?);
+    mod_builder.add(mod_name, required_features_constant(naga_module, options));
+    mod_builder.add(mod_name, used_limits_constants(mod_name, naga_module, options)?);
+    mod_builder.add(mod_name, vertex_states(mod_name, naga_module, options, docs));
+    mod_builder.add(mod_name, fragment_states(naga_module, options));
+
+    let create_pipeline_layout = pipeline::create_pipeline_layout_fn(
+      &entry_name,
+      &options,
+      bind_group_data,
+      &entry_excluded_bind_groups[i],
+      shader_stages,
+    );
+    mod_builder.add(mod_name, create_pipeline_layout);
+    mod_builder.add(mod_name, shader_module::shader_cache(naga_module, options));
+    mod_builder.add(mod_name, shader_module::shader_module_checked(options));
+    mod_builder
+      .add(mod_name, shader_module::shader_hash(mod_name, naga_module, options)?);
     mod_builder.add(
       mod_name,
-      shader_module::compute_module(naga_module, options.shader_source_type),
+      pipeline::create_render_pipeline_fns(&entry_name, naga_module, options),
     );
-    mod_builder.add(mod_name, entry_point_constants(naga_module));
-    mod_builder.add(mod_name, vertex_states(naga_module));
+    mod_builder.add(mod_name, shader_module::shader_module(entry, options)?);
 
-    let create_pipeline_layout =
-      pipeline::create_pipeline_layout_fn(&entry_name, &options, &bind_group_data);
-    mod_builder.add(mod_name, create_pipeline_layout);
-    mod_builder.add(mod_name, shader_module::shader_module(entry, options));
+    // The defs are only used by the naga_oil composer.
+    if options.shader_source_type.intersects(
+      WgslShaderSourceType::UseComposerEmbed | WgslShaderSourceType::UseComposerWithPath,
+    ) {
+      mod_builder.add(mod_name, shader_defs::shader_defs(entry, options)?);
+    }
+
+    if !options.codegen_hooks.is_empty() {
+      let reflection = reflection::entry_reflection(entry, options)?;
+      add_codegen_hook_items(&mut mod_builder, &reflection, options);
+    }
+  }
+
+  for (module_path, code) in &options.module_additions {
+    mod_builder.add(module_path, code.clone());
   }
 
   let mod_token_stream = mod_builder.generate();
-  let shader_registry =
-    shader_registry::build_shader_registry(&entries, options.shader_source_type);
+  let shader_registry = shader_registry::build_shader_registry(&entries, options);
+  let missing_features = missing_features_error(options);
+  let compatible_bind_group_trait = bind_group::compatible_bind_group_trait(options);
+  let compilation_messages = shader_module::compilation_messages_struct(options);
 
   let output = quote! {
     #![allow(unused, non_snake_case, non_camel_case_types, non_upper_case_globals)]
 
+    #no_std_prelude
     #shader_registry
+    #missing_features
+    #compatible_bind_group_trait
+    #compilation_messages
     #mod_token_stream
   };
 
-  Ok(pretty_print(&output))
+  Ok(format_tokens(&output, options.format_output))
+}
+
+/// Adds the items emitted by the [`CodegenHooks`] of the options for an entry.
+fn add_codegen_hook_items(
+  mod_builder: &mut RustModBuilder,
+  entry: &ShaderEntryReflection,
+  options: &WgslBindgenOption,
+) {
+  let mod_name = entry.mod_name.as_str();
+  for (i, hooks) in options.codegen_hooks.iter().enumerate() {
+    for item in &entry.structs {
+      let items = hooks.struct_items(entry, item);
+      if !items.is_empty() {
+        // Structs imported by multiple entries get the same items, so they're added once.
+        let (module, name) = item
+          .name
+          .rsplit_once("::")
+          .unwrap_or((mod_name, item.name.as_str()));
+        let id = format!("{name}::codegen_hooks{i}");
+        mod_builder.add_unique(module, &id, items).unwrap();
+      }
+    }
+    for group in &entry.bind_groups {
+      mod_builder.add(mod_name, hooks.bind_group_items(entry, group));
+    }
+    for entry_point in &entry.entry_points {
+      mod_builder.add(mod_name, hooks.entry_point_items(entry, entry_point));
+    }
+    mod_builder.add(mod_name, hooks.module_items(entry));
+  }
 }
 
 fn pretty_print(tokens: &TokenStream) -> String {
@@ -176,6 +495,38 @@ fn pretty_print(tokens: &TokenStream) -> String {
   prettyplease::unparse(&file)
 }
 
+fn format_tokens(tokens: &TokenStream, format: OutputFormat) -> String {
+  match format {
+    OutputFormat::None => tokens.to_string(),
+    OutputFormat::PrettyPlease => pretty_print(tokens),
+    OutputFormat::Rustfmt => {
+      rustfmt(&tokens.to_string()).unwrap_or_else(|| pretty_print(tokens))
+    }
+  }
+}
+
+fn rustfmt(source: &str) -> Option<String> {
+  use std::io::Write;
+  use std::process::{Command, Stdio};
+
+  let rustfmt = std::env::var("RUSTFMT").unwrap_or_else(|_| "rustfmt".to_string());
+  let mut child = Command::new(rustfmt)
+    .args(["--edition", "2021"])
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::null())
+    .spawn()
+    .ok()?;
+
+  child.stdin.take()?.write_all(source.as_bytes()).ok()?;
+  let output = child.wait_with_output().ok()?;
+  output
+    .status
+    .success()
+    .then(|| String::from_utf8(output.stdout).ok())
+    .flatten()
+}
+
 fn indexed_name_ident(name: &str, index: u32) -> Ident {
   format_ident!("{name}{index}")
 }
@@ -195,12 +546,42 @@ fn sanitized_upper_snake_case(v: &str) -> String {
     .to_uppercase()
 }
 
-fn vertex_struct_methods(module: &naga::Module) -> TokenStream {
-  let structs = vertex_input_structs(module);
+fn vertex_struct_methods(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+) -> TokenStream {
+  let structs = vertex_input_structs(invoking_entry_module, module, options);
   quote!(#(#structs)*)
 }
 
-fn entry_point_constants(module: &naga::Module) -> TokenStream {
+/// Collects the WGSL doc comments and annotations of the entry source and its imports.
+fn wgsl_docs(entry: &WgslEntryResult) -> Result<WgslDocs, CreateModuleError> {
+  let sources = &entry.source_including_deps;
+  let entry_source = std::iter::once(("".to_owned(), sources.source_file));
+  let dependency_sources = sources.full_dependencies.iter().map(|dep| {
+    let module_path = dep
+      .module_name
+      .as_ref()
+      .map(|name| bevy_util::demangle_module_path(name))
+      .unwrap_or_default();
+    (module_path, *dep)
+  });
+
+  let mut docs = WgslDocs::default();
+  for (module_path, source) in entry_source.chain(dependency_sources) {
+    docs
+      .add_source(&module_path, &source.content)
+      .map_err(|err| CreateModuleError::InvalidAnnotation {
+        module: entry.mod_name.clone(),
+        annotation: err.annotation,
+        message: err.message,
+      })?;
+  }
+  Ok(docs)
+}
+
+fn entry_point_constants(module: &naga::Module, docs: &WgslDocs) -> TokenStream {
   let entry_points: Vec<TokenStream> = module
     .entry_points
     .iter()
@@ -210,7 +591,9 @@ fn entry_point_constants(module: &naga::Module) -> TokenStream {
         &format!("ENTRY_{}", &entry_point.name.to_uppercase()),
         Span::call_site(),
       );
+      let doc = docs.item_doc_attrs(&entry_point.name);
       quote! {
+          #doc
           pub const #const_name: &str = #entry_name;
       }
     })
@@ -221,47 +604,337 @@ fn entry_point_constants(module: &naga::Module) -> TokenStream {
   }
 }
 
-fn vertex_states(module: &naga::Module) -> TokenStream {
-  let vertex_inputs = wgsl::get_vertex_input_structs(module);
+fn entry_point_enum(module: &naga::Module, options: &WgslBindgenOption) -> TokenStream {
+  if !options.entry_point_enum || module.entry_points.is_empty() {
+    return quote!();
+  }
+
+  let variants: Vec<Ident> = module
+    .entry_points
+    .iter()
+    .map(|entry_point| format_ident!("{}", sanitize_and_pascal_case(&entry_point.name)))
+    .collect();
+  let names = module
+    .entry_points
+    .iter()
+    .map(|entry_point| &entry_point.name);
+  let stages = module
+    .entry_points
+    .iter()
+    .map(|entry_point| match entry_point.stage {
+      ShaderStage::Vertex => quote!(wgpu::ShaderStages::VERTEX),
+      ShaderStage::Fragment => quote!(wgpu::ShaderStages::FRAGMENT),
+      ShaderStage::Compute => quote!(wgpu::ShaderStages::COMPUTE),
+    });
+
+  quote! {
+      #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+      pub enum EntryPoint {
+          #(#variants),*
+      }
+
+      impl EntryPoint {
+          pub const ALL: &'static [Self] = &[#(Self::#variants),*];
+
+          pub const fn name(&self) -> &'static str {
+              match self {
+                  #(Self::#variants => #names),*
+              }
+          }
+
+          pub const fn stage(&self) -> wgpu::ShaderStages {
+              match self {
+                  #(Self::#variants => #stages),*
+              }
+          }
+      }
+  }
+}
+
+fn fragment_target_constants(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+) -> Result<TokenStream, CreateModuleError> {
+  if !options.fragment_target_constants {
+    return Ok(quote!());
+  }
+
+  let fragment_entries: Vec<_> = module
+    .entry_points
+    .iter()
+    .filter(|entry_point| entry_point.stage == ShaderStage::Fragment)
+    .collect();
+
+  consts::check_generated_const_names(
+    invoking_entry_module,
+    module,
+    fragment_entries.iter().flat_map(|entry_point| {
+      let entry_name = entry_point.name.to_uppercase();
+      [
+        "COLOR_TARGET_COUNT",
+        "COLOR_TARGET_LOCATIONS",
+        "DUAL_SOURCE_BLENDING",
+      ]
+      .map(|suffix| format!("{entry_name}_{suffix}"))
+    }),
+  )?;
+
+  let constants: Vec<TokenStream> = fragment_entries
+    .iter()
+    .map(|entry_point| {
+      let entry_name = entry_point.name.to_uppercase();
+      let count_name = format_ident!("{entry_name}_COLOR_TARGET_COUNT");
+      let locations_name = format_ident!("{entry_name}_COLOR_TARGET_LOCATIONS");
+
+      let locations = wgsl::fragment_output_locations(module, entry_point);
+      let count = locations
+        .last()
+        .map_or(0, |location| *location as usize + 1);
+      let count = Literal::usize_unsuffixed(count);
+      let locations = locations.into_iter().map(Literal::u32_unsuffixed);
+      let dual_source_name = format_ident!("{entry_name}_DUAL_SOURCE_BLENDING");
+      let dual_source = wgsl::uses_dual_source_blending(module, entry_point);
+      quote! {
+          pub const #count_name: usize = #count;
+          pub const #locations_name: &[u32] = &[#(#locations),*];
+          pub const #dual_source_name: bool = #dual_source;
+      }
+    })
+    .collect();
+
+  Ok(quote! {
+      #(#constants)*
+  })
+}
+
+fn required_features_constant(
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+) -> TokenStream {
+  if !options.generate_required_features {
+    return quote!();
+  }
+
+  let features =
+    wgsl::features_tokens(wgsl::required_features(module), options.wgpu_edition);
+  quote! {
+      pub const REQUIRED_FEATURES: wgpu::Features = #features;
+
+      pub fn check_device(device: &wgpu::Device) -> Result<(), _root::MissingFeatures> {
+          let missing = REQUIRED_FEATURES.difference(device.features());
+          if missing.is_empty() {
+              Ok(())
+          } else {
+              Err(_root::MissingFeatures(missing))
+          }
+      }
+  }
+}
+
+/// The limits used by the module, which can be aggregated over the modules of an application
+/// to request a device.
+fn used_limits_constants(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+) -> Result<TokenStream, CreateModuleError> {
+  if !options.generate_used_limits {
+    return Ok(quote!());
+  }
+
+  consts::check_generated_const_names(
+    invoking_entry_module,
+    module,
+    [
+      "MAX_BIND_GROUPS_USED",
+      "MAX_VERTEX_BUFFERS_USED",
+      "MAX_VERTEX_ATTRIBUTES_USED",
+      "PUSH_CONSTANT_SIZE",
+    ],
+  )?;
+  let limits = UsedLimits::from_module(invoking_entry_module, module)?;
+  let bind_groups = Literal::u32_unsuffixed(limits.bind_groups);
+  let vertex_buffers = Literal::u32_unsuffixed(limits.vertex_buffers);
+  let vertex_attributes = Literal::u32_unsuffixed(limits.vertex_attributes);
+  let push_constant_size = Literal::u32_unsuffixed(limits.push_constant_size);
+  Ok(quote! {
+      pub const MAX_BIND_GROUPS_USED: u32 = #bind_groups;
+      pub const MAX_VERTEX_BUFFERS_USED: u32 = #vertex_buffers;
+      pub const MAX_VERTEX_ATTRIBUTES_USED: u32 = #vertex_attributes;
+      pub const PUSH_CONSTANT_SIZE: u32 = #push_constant_size;
+  })
+}
+
+/// The error of the `check_device` function of each module, shared by all the modules.
+fn missing_features_error(options: &WgslBindgenOption) -> TokenStream {
+  if !options.generate_required_features {
+    return quote!();
+  }
+
+  let core = core_crate(options);
+  quote! {
+      #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+      pub struct MissingFeatures(pub wgpu::Features);
+
+      impl #core::fmt::Display for MissingFeatures {
+          fn fmt(&self, f: &mut #core::fmt::Formatter<'_>) -> #core::fmt::Result {
+              f.write_str("the device is missing the features ")?;
+              #core::fmt::Debug::fmt(&self.0, f)
+          }
+      }
+
+      impl #core::error::Error for MissingFeatures {}
+  }
+}
+
+/// The `alloc` items of the std prelude, imported in every generated module of `no_std` output.
+fn no_std_prelude(options: &WgslBindgenOption) -> TokenStream {
+  if !options.no_std {
+    return quote!();
+  }
+
+  quote! {
+      use alloc::{borrow::ToOwned, boxed::Box, format, string::{String, ToString}, vec, vec::Vec};
+  }
+}
+
+/// The `_entry` fns of a vertex entry, with a buffer layout for each of its own vertex inputs.
+fn vertex_entry_fns(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+  docs: &WgslDocs,
+  entry_point: &naga::EntryPoint,
+) -> TokenStream {
+  let vertex_inputs =
+    wgsl::get_entry_vertex_input_structs(invoking_entry_module, module, entry_point)
+      .expect("vertex inputs are checked by `check_vertex_inputs`");
   let mut step_mode_params = vec![];
+  let mut step_mode_args = vec![];
   let layout_expressions: Vec<TokenStream> = vertex_inputs
     .iter()
     .map(|input| {
       let name = Ident::new(&input.name, Span::call_site());
-      let step_mode = Ident::new(&input.name.to_snake(), Span::call_site());
-      step_mode_params.push(quote!(#step_mode: wgpu::VertexStepMode));
-      quote!(#name::vertex_buffer_layout(#step_mode))
+      let fully_qualified_name =
+        RustItemPath::from_mangled(&input.name, invoking_entry_module)
+          .get_fully_qualified_name();
+
+      // Structs with a configured step mode don't need it as a parameter. The `step_mode`
+      // annotation of the struct takes precedence over `vertex_step_modes`.
+      let annotation = docs.item_annotation(&RustItemPath::from_mangled(&input.name, ""));
+      let step_mode = annotation
+        .and_then(|annotation| annotation.step_mode)
+        .or_else(|| options.vertex_step_mode_for(&fully_qualified_name));
+      match step_mode {
+        Some(step_mode) => {
+          let step_mode = format_ident!("{step_mode:?}");
+          quote!(#name::vertex_buffer_layout(wgpu::VertexStepMode::#step_mode))
+        }
+        None => {
+          let step_mode = Ident::new(&input.name.to_snake(), Span::call_site());
+          step_mode_params.push(quote!(#step_mode: wgpu::VertexStepMode));
+          step_mode_args.push(step_mode.clone());
+          quote!(#name::vertex_buffer_layout(#step_mode))
+        }
+      }
     })
     .collect();
 
+  let fn_name = Ident::new(&format!("{}_entry", &entry_point.name), Span::call_site());
+  let const_name =
+    Ident::new(&format!("ENTRY_{}", &entry_point.name.to_uppercase()), Span::call_site());
+  let n = Literal::usize_unsuffixed(vertex_inputs.len());
+  let planar_entry = planar_vertex_entry(
+    invoking_entry_module,
+    module,
+    options,
+    &entry_point.name,
+    &vertex_inputs,
+  );
+  let dynamic_entry = if options.dynamic_vertex_states {
+    let dynamic_fn_name = format_ident!("{fn_name}_dynamic");
+    quote! {
+        pub fn #dynamic_fn_name(#(#step_mode_params),*) -> VertexEntryDynamic {
+            #fn_name(#(#step_mode_args),*).into()
+        }
+    }
+  } else {
+    quote!()
+  };
+  quote! {
+      pub fn #fn_name(#(#step_mode_params),*) -> VertexEntry<#n> {
+          VertexEntry {
+              entry_point: #const_name,
+              buffers: [
+                  #(#layout_expressions),*
+              ]
+          }
+      }
+      #planar_entry
+      #dynamic_entry
+  }
+}
+
+fn vertex_states(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+  docs: &WgslDocs,
+) -> TokenStream {
   let vertex_entries: Vec<TokenStream> = module
     .entry_points
     .iter()
-    .filter_map(|entry_point| match &entry_point.stage {
-      ShaderStage::Vertex => {
-        let fn_name =
-          Ident::new(&format!("{}_entry", &entry_point.name), Span::call_site());
-        let const_name = Ident::new(
-          &format!("ENTRY_{}", &entry_point.name.to_uppercase()),
-          Span::call_site(),
-        );
-        let n = vertex_inputs.len();
-        let n = Literal::usize_unsuffixed(n);
-        Some(quote! {
-            pub fn #fn_name(#(#step_mode_params),*) -> VertexEntry<#n> {
-                VertexEntry {
-                    entry_point: #const_name,
-                    buffers: [
-                        #(#layout_expressions),*
-                    ]
-                }
-            }
-        })
-      }
-      _ => None,
+    .filter(|entry_point| entry_point.stage == ShaderStage::Vertex)
+    .map(|entry_point| {
+      vertex_entry_fns(invoking_entry_module, module, options, docs, entry_point)
     })
     .collect();
 
+  let entry_point = options.wgpu_edition.entry_point(quote!(entry.entry_point));
+  let compilation_options = options.pipeline_compilation_options_field();
+
+  // For pipelines with buffers only known at runtime, eg: an optional tangent buffer.
+  let dynamic_vertex_state = if options.dynamic_vertex_states {
+    // The shorthand avoids a redundant field name when the name is passed as is.
+    let entry_point_field = if options.wgpu_edition >= WgpuVersion::V22 {
+      quote!(entry_point: Some(entry_point),)
+    } else {
+      quote!(entry_point,)
+    };
+    quote! {
+        #[derive(Debug, Clone)]
+        pub struct VertexEntryDynamic {
+            pub entry_point: &'static str,
+            pub buffers: Vec<wgpu::VertexBufferLayout<'static>>
+        }
+
+        impl<const N: usize> From<VertexEntry<N>> for VertexEntryDynamic {
+            fn from(entry: VertexEntry<N>) -> Self {
+                Self {
+                    entry_point: entry.entry_point,
+                    buffers: entry.buffers.into()
+                }
+            }
+        }
+
+        pub fn vertex_state_dynamic<'a>(
+            module: &'a wgpu::ShaderModule,
+            entry_point: &'a str,
+            buffers: &'a [wgpu::VertexBufferLayout<'a>],
+        ) -> wgpu::VertexState<'a> {
+            wgpu::VertexState {
+                module,
+                #entry_point_field
+                buffers,
+                #compilation_options
+            }
+        }
+    }
+  } else {
+    quote!()
+  };
+
   // Don't generate unused code.
   if vertex_entries.is_empty() {
     quote!()
@@ -279,43 +952,274 @@ fn vertex_states(module: &naga::Module) -> TokenStream {
         ) -> wgpu::VertexState<'a> {
             wgpu::VertexState {
                 module,
-                entry_point: entry.entry_point,
+                entry_point: #entry_point,
                 buffers: &entry.buffers,
+                #compilation_options
             }
         }
 
+        #dynamic_vertex_state
         #(#vertex_entries)*
     }
   }
 }
 
-fn vertex_input_structs(module: &naga::Module) -> Vec<TokenStream> {
-  let vertex_inputs = wgsl::get_vertex_input_structs(module);
+/// The `<entry>_planar_entry` function, with a buffer and a step mode per vertex attribute.
+fn planar_vertex_entry(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+  entry_point_name: &str,
+  vertex_inputs: &[wgsl::VertexInput],
+) -> TokenStream {
+  if !options.planar_vertex_layouts {
+    return quote!();
+  }
+
+  let mut buffer_count = 0;
+  let mut buffers = Vec::new();
+  let layouts: Vec<_> = vertex_inputs
+    .iter()
+    .map(|input| {
+      let name = Ident::new(&input.name, Span::call_site());
+      let fully_qualified_name =
+        RustItemPath::from_mangled(&input.name, invoking_entry_module)
+          .get_fully_qualified_name();
+      let count: usize = input
+        .fields
+        .iter()
+        .map(|(_, m)| vertex_field_formats(module, options, &fully_qualified_name, m).len())
+        .sum();
+
+      let indices: Vec<_> = (buffer_count..buffer_count + count).map(Index::from).collect();
+      let layouts: Vec<_> = (buffer_count..buffer_count + count)
+        .map(|i| format_ident!("layout{i}"))
+        .collect();
+      buffer_count += count;
+      buffers.extend(layouts.iter().cloned());
+      quote! {
+          let [#(#layouts),*] = #name::planar_vertex_buffer_layouts([#(step_modes[#indices]),*]);
+      }
+    })
+    .collect();
+
+  let fn_name = format_ident!("{entry_point_name}_planar_entry");
+  let const_name = format_ident!("ENTRY_{}", entry_point_name.to_uppercase());
+  let n = Literal::usize_unsuffixed(buffer_count);
+  quote! {
+      pub fn #fn_name(step_modes: [wgpu::VertexStepMode; #n]) -> VertexEntry<#n> {
+          #(#layouts)*
+          VertexEntry {
+              entry_point: #const_name,
+              buffers: [#(#buffers),*]
+          }
+      }
+  }
+}
+
+fn fragment_states(module: &naga::Module, options: &WgslBindgenOption) -> TokenStream {
+  if !options.generate_fragment_states {
+    return quote!();
+  }
+
+  let fragment_entries: Vec<TokenStream> = module
+    .entry_points
+    .iter()
+    .filter(|entry_point| entry_point.stage == ShaderStage::Fragment)
+    .map(|entry_point| {
+      let fn_name =
+        Ident::new(&format!("{}_entry", &entry_point.name), Span::call_site());
+      let const_name = Ident::new(
+        &format!("ENTRY_{}", &entry_point.name.to_uppercase()),
+        Span::call_site(),
+      );
+      // Targets are indexed by location, so unused locations below the highest one are left as `None`.
+      let n = wgsl::fragment_output_locations(module, entry_point)
+        .last()
+        .map_or(0, |location| *location as usize + 1);
+      let n = Literal::usize_unsuffixed(n);
+      quote! {
+          pub fn #fn_name(targets: [Option<wgpu::ColorTargetState>; #n]) -> FragmentEntry<#n> {
+              FragmentEntry {
+                  entry_point: #const_name,
+                  targets
+              }
+          }
+      }
+    })
+    .collect();
+
+  let entry_point = options.wgpu_edition.entry_point(quote!(entry.entry_point));
+  let compilation_options = options.pipeline_compilation_options_field();
+
+  // Don't generate unused code.
+  if fragment_entries.is_empty() {
+    quote!()
+  } else {
+    quote! {
+        #[derive(Debug)]
+        pub struct FragmentEntry<const N: usize> {
+            entry_point: &'static str,
+            targets: [Option<wgpu::ColorTargetState>; N]
+        }
+
+        pub fn fragment_state<'a, const N: usize>(
+            module: &'a wgpu::ShaderModule,
+            entry: &'a FragmentEntry<N>,
+        ) -> wgpu::FragmentState<'a> {
+            wgpu::FragmentState {
+                module,
+                entry_point: #entry_point,
+                targets: &entry.targets,
+                #compilation_options
+            }
+        }
+
+        #(#fragment_entries)*
+    }
+  }
+}
+
+/// The formats and column offsets of the vertex attributes of a vertex input field.
+/// Matrices have an attribute per column.
+fn vertex_field_formats(
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+  fully_qualified_struct_name: &str,
+  member: &naga::StructMember,
+) -> Vec<(wgpu::VertexFormat, u64)> {
+  let member_name = member.name.as_ref().unwrap();
+  match options.vertex_format_override(fully_qualified_struct_name, member_name) {
+    Some(o) => vec![(o.format, 0)],
+    None => wgsl::vertex_attribute_formats(&module.types[member.ty]),
+  }
+}
+
+fn vertex_input_structs(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+) -> Vec<TokenStream> {
+  let vertex_inputs = wgsl::get_vertex_input_structs(invoking_entry_module, module)
+    .expect("vertex inputs are checked by `check_vertex_inputs`");
+  let core = core_crate(options);
   vertex_inputs.iter().map(|input|  {
         let name = Ident::new(&input.name, Span::call_site());
+        let fully_qualified_name = RustItemPath::from_mangled(&input.name, invoking_entry_module)
+            .get_fully_qualified_name();
 
-        // Use index to avoid adding prefix to literals.
-        let count = Index::from(input.fields.len());
-        let attributes: Vec<_> = input
+        let (attributes, formats): (Vec<_>, Vec<_>) = input
             .fields
             .iter()
-            .map(|(location, m)| {
+            .flat_map(|(location, m)| {
                 let field_name: TokenStream = m.name.as_ref().unwrap().parse().unwrap();
-                let location = Index::from(*location as usize);
-                let format = wgsl::vertex_format(&module.types[m.ty]);
-                // TODO: Will the debug implementation always work with the macro?
-                let format = Ident::new(&format!("{format:?}"), Span::call_site());
+                let formats = vertex_field_formats(module, options, &fully_qualified_name, m);
+                let name = name.clone();
+                let core = core.clone();
+
+                // Matrices use one location per column.
+                formats.into_iter().enumerate().map(move |(i, (format, column_offset))| {
+                    let location = Index::from(*location as usize + i);
+                    // TODO: Will the debug implementation always work with the macro?
+                    let format = Ident::new(&format!("{format:?}"), Span::call_site());
+                    let offset = if column_offset == 0 {
+                        quote!(#core::mem::offset_of!(#name, #field_name) as u64)
+                    } else {
+                        let column_offset = Index::from(column_offset as usize);
+                        quote!(#core::mem::offset_of!(#name, #field_name) as u64 + #column_offset)
+                    };
+
+                    let attribute = quote! {
+                        wgpu::VertexAttribute {
+                            format: wgpu::VertexFormat::#format,
+                            offset: #offset,
+                            shader_location: #location,
+                        }
+                    };
+                    (attribute, (format, location, offset))
+                })
+            })
+            .unzip();
+
+        // Use index to avoid adding prefix to literals.
+        let count = Index::from(attributes.len());
+
+        // The CPU struct may have fields the shader doesn't use, so its stride can be configured.
+        // The field offsets are only known by the Rust compiler, so check a configured stride
+        // covers all the attributes with a const assertion.
+        let (array_stride, stride_assertion) = match options.vertex_stride_for(&fully_qualified_name) {
+            Some(stride) => {
+                let stride = Literal::u64_unsuffixed(stride);
+                let attribute_ends = formats.iter().map(|(format, _, offset)| {
+                    quote!(#stride >= #offset + wgpu::VertexFormat::#format.size())
+                });
+                let message = format!("the vertex stride of {} is smaller than its attributes", input.name);
+                let assertion = quote! {
+                    const _: () = assert!(#(#attribute_ends)&&*, #message);
+                };
+                (quote!(#stride), assertion)
+            }
+            None => (quote!(#core::mem::size_of::<#name>() as u64), quote!()),
+        };
 
+        // Each attribute in its own buffer, tightly packed.
+        let planar_layouts = if options.planar_vertex_layouts {
+            let layouts = formats.iter().enumerate().map(|(i, (format, location, _))| {
+                let i = Index::from(i);
                 quote! {
-                    wgpu::VertexAttribute {
-                        format: wgpu::VertexFormat::#format,
-                        offset: std::mem::offset_of!(#name, #field_name) as u64,
-                        shader_location: #location,
+                    wgpu::VertexBufferLayout {
+                        array_stride: wgpu::VertexFormat::#format.size(),
+                        step_mode: step_modes[#i],
+                        attributes: &[wgpu::VertexAttribute {
+                            format: wgpu::VertexFormat::#format,
+                            offset: 0,
+                            shader_location: #location,
+                        }],
                     }
                 }
-            })
-            .collect();
-
+            });
+            quote! {
+                pub const fn planar_vertex_buffer_layouts(
+                    step_modes: [wgpu::VertexStepMode; #count],
+                ) -> [wgpu::VertexBufferLayout<'static>; #count] {
+                    [#(#layouts),*]
+                }
+            }
+        } else {
+            quote!()
+        };
+
+        let layout_constants = if options.vertex_layout_constants {
+            let offsets = input.fields.iter().map(|(_, m)| {
+                let member_name = m.name.as_ref().unwrap();
+                let field_name: TokenStream = member_name.parse().unwrap();
+                let const_name = format_ident!("{}_OFFSET", sanitized_upper_snake_case(member_name));
+                quote!(pub const #const_name: u64 = #core::mem::offset_of!(#name, #field_name) as u64;)
+            });
+            quote! {
+                #(#offsets)*
+                pub const STRIDE: u64 = #core::mem::size_of::<#name>() as u64;
+            }
+        } else {
+            quote!()
+        };
+
+        let layout_with_stride = if options.vertex_buffer_layout_with_stride {
+            quote! {
+                pub const fn vertex_buffer_layout_with_stride(
+                    step_mode: wgpu::VertexStepMode,
+                    array_stride: u64,
+                ) -> wgpu::VertexBufferLayout<'static> {
+                    wgpu::VertexBufferLayout {
+                        array_stride,
+                        step_mode,
+                        attributes: &#name::VERTEX_ATTRIBUTES
+                    }
+                }
+            }
+        } else {
+            quote!()
+        };
 
         // The vertex_attr_array! macro doesn't account for field alignment.
         // Structs with glam::Vec4 and glam::Vec3 fields will not be tightly packed.
@@ -325,19 +1229,25 @@ fn vertex_input_structs(module: &naga::Module) -> Vec<TokenStream> {
         // TODO: Should this enforce WebGPU alignment requirements for compatibility?
         // https://gpuweb.github.io/gpuweb/#abstract-opdef-validating-gpuvertexbufferlayout
 
-        // TODO: Support vertex inputs that aren't in a struct.
+        // Vertex inputs that aren't in a struct use a synthesized struct per entry.
         quote! {
             impl #name {
                 pub const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; #count] = [#(#attributes),*];
+                #layout_constants
 
                 pub const fn vertex_buffer_layout(step_mode: wgpu::VertexStepMode) -> wgpu::VertexBufferLayout<'static> {
                     wgpu::VertexBufferLayout {
-                        array_stride: std::mem::size_of::<#name>() as u64,
+                        array_stride: #array_stride,
                         step_mode,
                         attributes: &#name::VERTEX_ATTRIBUTES
                     }
                 }
+
+                #layout_with_stride
+                #planar_layouts
             }
+
+            #stride_assertion
         }
     }).collect()
 }
@@ -517,7 +1427,7 @@ mod test {
         "#};
 
     let module = naga::front::wgsl::parse_str(source).unwrap();
-    let actual = vertex_struct_methods(&module);
+    let actual = vertex_struct_methods("", &module, &WgslBindgenOption::default());
 
     assert_tokens_eq!(quote!(), actual);
   }
@@ -537,7 +1447,7 @@ mod test {
         "#};
 
     let module = naga::front::wgsl::parse_str(source).unwrap();
-    let actual = vertex_struct_methods(&module);
+    let actual = vertex_struct_methods("", &module, &WgslBindgenOption::default());
 
     assert_tokens_eq!(
       quote! {
@@ -580,13 +1490,10 @@ mod test {
   }
 
   #[test]
-  fn write_vertex_module_single_input_float64() {
+  fn write_vertex_module_no_std() {
     let source = indoc! {r#"
             struct VertexInput0 {
-                @location(0) a: f64,
-                @location(1) b: vec2<f64>,
-                @location(2) c: vec3<f64>,
-                @location(3) d: vec4<f64>,
+                @location(0) a: f32,
             };
 
             @vertex
@@ -594,38 +1501,27 @@ mod test {
         "#};
 
     let module = naga::front::wgsl::parse_str(source).unwrap();
-    let actual = vertex_struct_methods(&module);
+    let options = WgslBindgenOption {
+      no_std: true,
+      ..Default::default()
+    };
+    let actual = vertex_struct_methods("", &module, &options);
 
     assert_tokens_eq!(
       quote! {
           impl VertexInput0 {
-              pub const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 4] = [
+              pub const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 1] = [
                   wgpu::VertexAttribute {
-                      format: wgpu::VertexFormat::Float64,
-                      offset: std::mem::offset_of!(VertexInput0, a) as u64,
+                      format: wgpu::VertexFormat::Float32,
+                      offset: core::mem::offset_of!(VertexInput0, a) as u64,
                       shader_location: 0,
                   },
-                  wgpu::VertexAttribute {
-                      format: wgpu::VertexFormat::Float64x2,
-                      offset: std::mem::offset_of!(VertexInput0, b) as u64,
-                      shader_location: 1,
-                  },
-                  wgpu::VertexAttribute {
-                      format: wgpu::VertexFormat::Float64x3,
-                      offset: std::mem::offset_of!(VertexInput0, c) as u64,
-                      shader_location: 2,
-                  },
-                  wgpu::VertexAttribute {
-                      format: wgpu::VertexFormat::Float64x4,
-                      offset: std::mem::offset_of!(VertexInput0, d) as u64,
-                      shader_location: 3,
-                  },
               ];
               pub const fn vertex_buffer_layout(
                   step_mode: wgpu::VertexStepMode,
               ) -> wgpu::VertexBufferLayout<'static> {
                   wgpu::VertexBufferLayout {
-                      array_stride: std::mem::size_of::<VertexInput0>() as u64,
+                      array_stride: core::mem::size_of::<VertexInput0>() as u64,
                       step_mode,
                       attributes: &VertexInput0::VERTEX_ATTRIBUTES,
                   }
@@ -637,112 +1533,108 @@ mod test {
   }
 
   #[test]
-  fn write_vertex_module_single_input_sint32() {
+  fn write_vertex_module_stride_override() {
     let source = indoc! {r#"
-            struct VertexInput0 {
-                @location(0) a: i32,
-                @location(1) a: vec2<i32>,
-                @location(2) a: vec3<i32>,
-                @location(3) a: vec4<i32>,
-
+            struct VertexInput {
+                @location(0) position: vec3<f32>,
             };
 
             @vertex
-            fn main(in0: VertexInput0) {}
+            fn main(in: VertexInput) {}
         "#};
 
     let module = naga::front::wgsl::parse_str(source).unwrap();
-    let actual = vertex_struct_methods(&module);
+    let options = WgslBindgenOption {
+      vertex_strides: vec![("VertexInput", 32).into()],
+      vertex_buffer_layout_with_stride: true,
+      ..Default::default()
+    };
+    let actual = vertex_struct_methods("", &module, &options);
 
     assert_tokens_eq!(
       quote! {
-          impl VertexInput0 {
-              pub const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 4] = [
+          impl VertexInput {
+              pub const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 1] = [
                   wgpu::VertexAttribute {
-                      format: wgpu::VertexFormat::Sint32,
-                      offset: std::mem::offset_of!(VertexInput0, a) as u64,
+                      format: wgpu::VertexFormat::Float32x3,
+                      offset: std::mem::offset_of!(VertexInput, position) as u64,
                       shader_location: 0,
                   },
-                  wgpu::VertexAttribute {
-                      format: wgpu::VertexFormat::Sint32x2,
-                      offset: std::mem::offset_of!(VertexInput0, a) as u64,
-                      shader_location: 1,
-                  },
-                  wgpu::VertexAttribute {
-                      format: wgpu::VertexFormat::Sint32x3,
-                      offset: std::mem::offset_of!(VertexInput0, a) as u64,
-                      shader_location: 2,
-                  },
-                  wgpu::VertexAttribute {
-                      format: wgpu::VertexFormat::Sint32x4,
-                      offset: std::mem::offset_of!(VertexInput0, a) as u64,
-                      shader_location: 3,
-                  },
               ];
               pub const fn vertex_buffer_layout(
                   step_mode: wgpu::VertexStepMode,
               ) -> wgpu::VertexBufferLayout<'static> {
                   wgpu::VertexBufferLayout {
-                      array_stride: std::mem::size_of::<VertexInput0>() as u64,
+                      array_stride: 32,
                       step_mode,
-                      attributes: &VertexInput0::VERTEX_ATTRIBUTES,
+                      attributes: &VertexInput::VERTEX_ATTRIBUTES,
+                  }
+              }
+              pub const fn vertex_buffer_layout_with_stride(
+                  step_mode: wgpu::VertexStepMode,
+                  array_stride: u64,
+              ) -> wgpu::VertexBufferLayout<'static> {
+                  wgpu::VertexBufferLayout {
+                      array_stride,
+                      step_mode,
+                      attributes: &VertexInput::VERTEX_ATTRIBUTES,
                   }
               }
           }
+          const _: () = assert!(
+              32 >= std::mem::offset_of!(VertexInput, position) as u64
+                  + wgpu::VertexFormat::Float32x3.size(),
+              "the vertex stride of VertexInput is smaller than its attributes"
+          );
       },
       actual
     );
   }
 
   #[test]
-  fn write_vertex_module_single_input_uint32() {
+  fn write_vertex_module_layout_constants() {
     let source = indoc! {r#"
-            struct VertexInput0 {
-                @location(0) a: u32,
-                @location(1) b: vec2<u32>,
-                @location(2) c: vec3<u32>,
-                @location(3) d: vec4<u32>,
+            struct VertexInput {
+                @location(0) position: vec3<f32>,
+                @location(1) texCoord: vec2<f32>,
             };
 
             @vertex
-            fn main(in0: VertexInput0) {}
+            fn main(in: VertexInput) {}
         "#};
 
     let module = naga::front::wgsl::parse_str(source).unwrap();
-    let actual = vertex_struct_methods(&module);
+    let options = WgslBindgenOption {
+      vertex_layout_constants: true,
+      ..Default::default()
+    };
+    let actual = vertex_struct_methods("", &module, &options);
 
     assert_tokens_eq!(
       quote! {
-          impl VertexInput0 {
-              pub const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 4] = [
+          impl VertexInput {
+              pub const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 2] = [
                   wgpu::VertexAttribute {
-                      format: wgpu::VertexFormat::Uint32,
-                      offset: std::mem::offset_of!(VertexInput0, a) as u64,
+                      format: wgpu::VertexFormat::Float32x3,
+                      offset: std::mem::offset_of!(VertexInput, position) as u64,
                       shader_location: 0,
                   },
                   wgpu::VertexAttribute {
-                      format: wgpu::VertexFormat::Uint32x2,
-                      offset: std::mem::offset_of!(VertexInput0, b) as u64,
+                      format: wgpu::VertexFormat::Float32x2,
+                      offset: std::mem::offset_of!(VertexInput, texCoord) as u64,
                       shader_location: 1,
                   },
-                  wgpu::VertexAttribute {
-                      format: wgpu::VertexFormat::Uint32x3,
-                      offset: std::mem::offset_of!(VertexInput0, c) as u64,
-                      shader_location: 2,
-                  },
-                  wgpu::VertexAttribute {
-                      format: wgpu::VertexFormat::Uint32x4,
-                      offset: std::mem::offset_of!(VertexInput0, d) as u64,
-                      shader_location: 3,
-                  },
               ];
+              pub const POSITION_OFFSET: u64 = std::mem::offset_of!(VertexInput, position) as u64;
+              pub const TEX_COORD_OFFSET: u64 = std::mem::offset_of!(VertexInput, texCoord) as u64;
+              pub const STRIDE: u64 = std::mem::size_of::<VertexInput>() as u64;
               pub const fn vertex_buffer_layout(
                   step_mode: wgpu::VertexStepMode,
               ) -> wgpu::VertexBufferLayout<'static> {
                   wgpu::VertexBufferLayout {
-                      array_stride: std::mem::size_of::<VertexInput0>() as u64,
+                      array_stride: std::mem::size_of::<VertexInput>() as u64,
                       step_mode,
-                      attributes: &VertexInput0::VERTEX_ATTRIBUTES,
+                      attributes: &VertexInput::VERTEX_ATTRIBUTES,
                   }
               }
           }
@@ -752,46 +1644,22 @@ mod test {
   }
 
   #[test]
-  fn write_entry_constants() {
+  fn write_dynamic_vertex_states() {
     let source = indoc! {r#"
-            @vertex
-            fn vs_main() {}
+            struct VertexInput {
+                @location(0) position: vec3<f32>,
+            };
 
             @vertex
-            fn another_vs() {}
-
-            @fragment
-            fn fs_main() {}
-
-            @fragment
-            fn another_fs() {}
-        "#
-    };
+            fn vs_main(in: VertexInput) {}
+        "#};
 
     let module = naga::front::wgsl::parse_str(source).unwrap();
-    let actual = entry_point_constants(&module);
-
-    assert_tokens_eq!(
-      quote! {
-          pub const ENTRY_VS_MAIN: &str = "vs_main";
-          pub const ENTRY_ANOTHER_VS: &str = "another_vs";
-          pub const ENTRY_FS_MAIN: &str = "fs_main";
-          pub const ENTRY_ANOTHER_FS: &str = "another_fs";
-      },
-      actual
-    )
-  }
-
-  #[test]
-  fn write_vertex_shader_entry_no_buffers() {
-    let source = indoc! {r#"
-            @vertex
-            fn vs_main() {}
-        "#
+    let options = WgslBindgenOption {
+      dynamic_vertex_states: true,
+      ..Default::default()
     };
-
-    let module = naga::front::wgsl::parse_str(source).unwrap();
-    let actual = vertex_states(&module);
+    let actual = vertex_states("", &module, &options, &WgslDocs::default());
 
     assert_tokens_eq!(
       quote! {
@@ -810,33 +1678,154 @@ mod test {
                   buffers: &entry.buffers,
               }
           }
-          pub fn vs_main_entry() -> VertexEntry<0> {
+          #[derive(Debug, Clone)]
+          pub struct VertexEntryDynamic {
+              pub entry_point: &'static str,
+              pub buffers: Vec<wgpu::VertexBufferLayout<'static>>,
+          }
+          impl<const N: usize> From<VertexEntry<N>> for VertexEntryDynamic {
+              fn from(entry: VertexEntry<N>) -> Self {
+                  Self {
+                      entry_point: entry.entry_point,
+                      buffers: entry.buffers.into(),
+                  }
+              }
+          }
+          pub fn vertex_state_dynamic<'a>(
+              module: &'a wgpu::ShaderModule,
+              entry_point: &'a str,
+              buffers: &'a [wgpu::VertexBufferLayout<'a>],
+          ) -> wgpu::VertexState<'a> {
+              wgpu::VertexState {
+                  module,
+                  entry_point,
+                  buffers,
+              }
+          }
+          pub fn vs_main_entry(vertex_input: wgpu::VertexStepMode) -> VertexEntry<1> {
               VertexEntry {
                   entry_point: ENTRY_VS_MAIN,
-                  buffers: [],
+                  buffers: [VertexInput::vertex_buffer_layout(vertex_input)],
               }
           }
+          pub fn vs_main_entry_dynamic(vertex_input: wgpu::VertexStepMode) -> VertexEntryDynamic {
+              vs_main_entry(vertex_input).into()
+          }
       },
       actual
-    )
+    );
   }
 
   #[test]
-  fn write_vertex_shader_multiple_entries() {
+  fn write_planar_vertex_layouts() {
     let source = indoc! {r#"
             struct VertexInput {
-                @location(0) position: vec4<f32>,
+                @location(0) position: vec3<f32>,
+                @location(1) normal: vec3<f32>,
+            };
+            struct InstanceInput {
+                @location(2) color: vec4<f32>,
             };
-            @vertex
-            fn vs_main_1(in: VertexInput) {}
 
             @vertex
-            fn vs_main_2(in: VertexInput) {}
-        "#
-    };
+            fn vs_main(in: VertexInput, instance: InstanceInput) {}
+        "#};
 
     let module = naga::front::wgsl::parse_str(source).unwrap();
-    let actual = vertex_states(&module);
+    let options = WgslBindgenOption {
+      planar_vertex_layouts: true,
+      ..Default::default()
+    };
+
+    let actual = vertex_struct_methods("", &module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+          impl VertexInput {
+              pub const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 2] = [
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Float32x3,
+                      offset: std::mem::offset_of!(VertexInput, position) as u64,
+                      shader_location: 0,
+                  },
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Float32x3,
+                      offset: std::mem::offset_of!(VertexInput, normal) as u64,
+                      shader_location: 1,
+                  },
+              ];
+              pub const fn vertex_buffer_layout(
+                  step_mode: wgpu::VertexStepMode,
+              ) -> wgpu::VertexBufferLayout<'static> {
+                  wgpu::VertexBufferLayout {
+                      array_stride: std::mem::size_of::<VertexInput>() as u64,
+                      step_mode,
+                      attributes: &VertexInput::VERTEX_ATTRIBUTES,
+                  }
+              }
+              pub const fn planar_vertex_buffer_layouts(
+                  step_modes: [wgpu::VertexStepMode; 2],
+              ) -> [wgpu::VertexBufferLayout<'static>; 2] {
+                  [
+                      wgpu::VertexBufferLayout {
+                          array_stride: wgpu::VertexFormat::Float32x3.size(),
+                          step_mode: step_modes[0],
+                          attributes: &[wgpu::VertexAttribute {
+                              format: wgpu::VertexFormat::Float32x3,
+                              offset: 0,
+                              shader_location: 0,
+                          }],
+                      },
+                      wgpu::VertexBufferLayout {
+                          array_stride: wgpu::VertexFormat::Float32x3.size(),
+                          step_mode: step_modes[1],
+                          attributes: &[wgpu::VertexAttribute {
+                              format: wgpu::VertexFormat::Float32x3,
+                              offset: 0,
+                              shader_location: 1,
+                          }],
+                      }
+                  ]
+              }
+          }
+          impl InstanceInput {
+              pub const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 1] = [
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Float32x4,
+                      offset: std::mem::offset_of!(InstanceInput, color) as u64,
+                      shader_location: 2,
+                  },
+              ];
+              pub const fn vertex_buffer_layout(
+                  step_mode: wgpu::VertexStepMode,
+              ) -> wgpu::VertexBufferLayout<'static> {
+                  wgpu::VertexBufferLayout {
+                      array_stride: std::mem::size_of::<InstanceInput>() as u64,
+                      step_mode,
+                      attributes: &InstanceInput::VERTEX_ATTRIBUTES,
+                  }
+              }
+              pub const fn planar_vertex_buffer_layouts(
+                  step_modes: [wgpu::VertexStepMode; 1],
+              ) -> [wgpu::VertexBufferLayout<'static>; 1] {
+                  [
+                      wgpu::VertexBufferLayout {
+                          array_stride: wgpu::VertexFormat::Float32x4.size(),
+                          step_mode: step_modes[0],
+                          attributes: &[wgpu::VertexAttribute {
+                              format: wgpu::VertexFormat::Float32x4,
+                              offset: 0,
+                              shader_location: 2,
+                          }],
+                      }
+                  ]
+              }
+          }
+      },
+      actual
+    );
+
+    let actual = vertex_states("", &module, &options, &WgslDocs::default());
 
     assert_tokens_eq!(
       quote! {
@@ -855,85 +1844,1042 @@ mod test {
                   buffers: &entry.buffers,
               }
           }
-          pub fn vs_main_1_entry(vertex_input: wgpu::VertexStepMode) -> VertexEntry<1> {
+          pub fn vs_main_entry(
+              vertex_input: wgpu::VertexStepMode,
+              instance_input: wgpu::VertexStepMode,
+          ) -> VertexEntry<2> {
               VertexEntry {
-                  entry_point: ENTRY_VS_MAIN_1,
-                  buffers: [VertexInput::vertex_buffer_layout(vertex_input)],
+                  entry_point: ENTRY_VS_MAIN,
+                  buffers: [
+                      VertexInput::vertex_buffer_layout(vertex_input),
+                      InstanceInput::vertex_buffer_layout(instance_input),
+                  ],
               }
           }
-          pub fn vs_main_2_entry(vertex_input: wgpu::VertexStepMode) -> VertexEntry<1> {
+          pub fn vs_main_planar_entry(step_modes: [wgpu::VertexStepMode; 3]) -> VertexEntry<3> {
+              let [layout0, layout1] =
+                  VertexInput::planar_vertex_buffer_layouts([step_modes[0], step_modes[1]]);
+              let [layout2] = InstanceInput::planar_vertex_buffer_layouts([step_modes[2]]);
               VertexEntry {
-                  entry_point: ENTRY_VS_MAIN_2,
-                  buffers: [VertexInput::vertex_buffer_layout(vertex_input)],
+                  entry_point: ENTRY_VS_MAIN,
+                  buffers: [layout0, layout1, layout2],
               }
           }
       },
       actual
-    )
+    );
   }
 
   #[test]
-  fn write_vertex_shader_entry_multiple_buffers() {
+  fn write_vertex_module_loose_inputs() {
     let source = indoc! {r#"
-            struct Input0 {
-                @location(0) position: vec4<f32>,
-            };
-            struct Input1 {
-                @location(1) some_data: vec2<f32>
-            }
             @vertex
-            fn vs_main(in0: Input0, in1: Input1) {}
-        "#
-    };
+            fn vs_main(@location(0) pos: vec3<f32>, @location(1) uv: vec2<f32>) {}
+        "#};
 
     let module = naga::front::wgsl::parse_str(source).unwrap();
-    let actual = vertex_states(&module);
+    let actual = vertex_struct_methods("", &module, &WgslBindgenOption::default());
 
     assert_tokens_eq!(
       quote! {
-          #[derive(Debug)]
-          pub struct VertexEntry<const N: usize> {
-              entry_point: &'static str,
-              buffers: [wgpu::VertexBufferLayout<'static>; N],
-          }
-          pub fn vertex_state<'a, const N: usize>(
-              module: &'a wgpu::ShaderModule,
-              entry: &'a VertexEntry<N>,
-          ) -> wgpu::VertexState<'a> {
-              wgpu::VertexState {
-                  module,
-                  entry_point: entry.entry_point,
-                  buffers: &entry.buffers,
-              }
-          }
-          pub fn vs_main_entry(input0: wgpu::VertexStepMode, input1: wgpu::VertexStepMode) -> VertexEntry<2> {
-              VertexEntry {
-                  entry_point: ENTRY_VS_MAIN,
-                  buffers: [
-                      Input0::vertex_buffer_layout(input0),
-                      Input1::vertex_buffer_layout(input1),
-                  ],
+          impl VsMainInput {
+              pub const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 2] = [
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Float32x3,
+                      offset: std::mem::offset_of!(VsMainInput, pos) as u64,
+                      shader_location: 0,
+                  },
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Float32x2,
+                      offset: std::mem::offset_of!(VsMainInput, uv) as u64,
+                      shader_location: 1,
+                  },
+              ];
+              pub const fn vertex_buffer_layout(
+                  step_mode: wgpu::VertexStepMode,
+              ) -> wgpu::VertexBufferLayout<'static> {
+                  wgpu::VertexBufferLayout {
+                      array_stride: std::mem::size_of::<VsMainInput>() as u64,
+                      step_mode,
+                      attributes: &VsMainInput::VERTEX_ATTRIBUTES,
+                  }
               }
           }
       },
       actual
-    )
+    );
   }
 
   #[test]
-  fn write_vertex_states_no_entries() {
+  fn write_vertex_module_matrix_input() {
     let source = indoc! {r#"
-            struct Input {
-                @location(0) position: vec4<f32>,
+            struct InstanceInput {
+                @location(0) color: vec4<f32>,
+                @location(1) transform: mat4x4<f32>,
             };
-            @fragment
-            fn main(in: Input) {}
-        "#
-    };
+
+            @vertex
+            fn main(instance: InstanceInput) {}
+        "#};
 
     let module = naga::front::wgsl::parse_str(source).unwrap();
-    let actual = vertex_states(&module);
+    let actual = vertex_struct_methods("", &module, &WgslBindgenOption::default());
 
-    assert_tokens_eq!(quote!(), actual)
+    assert_tokens_eq!(
+      quote! {
+          impl InstanceInput {
+              pub const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 5] = [
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Float32x4,
+                      offset: std::mem::offset_of!(InstanceInput, color) as u64,
+                      shader_location: 0,
+                  },
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Float32x4,
+                      offset: std::mem::offset_of!(InstanceInput, transform) as u64,
+                      shader_location: 1,
+                  },
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Float32x4,
+                      offset: std::mem::offset_of!(InstanceInput, transform) as u64 + 16,
+                      shader_location: 2,
+                  },
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Float32x4,
+                      offset: std::mem::offset_of!(InstanceInput, transform) as u64 + 32,
+                      shader_location: 3,
+                  },
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Float32x4,
+                      offset: std::mem::offset_of!(InstanceInput, transform) as u64 + 48,
+                      shader_location: 4,
+                  },
+              ];
+              pub const fn vertex_buffer_layout(
+                  step_mode: wgpu::VertexStepMode,
+              ) -> wgpu::VertexBufferLayout<'static> {
+                  wgpu::VertexBufferLayout {
+                      array_stride: std::mem::size_of::<InstanceInput>() as u64,
+                      step_mode,
+                      attributes: &InstanceInput::VERTEX_ATTRIBUTES,
+                  }
+              }
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_vertex_module_format_override() {
+    let source = indoc! {r#"
+            struct VertexInput0 {
+                @location(0) color: vec4<f32>,
+            };
+
+            @vertex
+            fn main(in0: VertexInput0) {}
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      override_vertex_format: vec![(
+        "VertexInput0",
+        "color",
+        wgpu::VertexFormat::Unorm8x4,
+        quote!([u8; 4]),
+      )
+        .into()],
+      ..Default::default()
+    };
+    let actual = vertex_struct_methods("", &module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+          impl VertexInput0 {
+              pub const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 1] = [
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Unorm8x4,
+                      offset: std::mem::offset_of!(VertexInput0, color) as u64,
+                      shader_location: 0,
+                  },
+              ];
+              pub const fn vertex_buffer_layout(
+                  step_mode: wgpu::VertexStepMode,
+              ) -> wgpu::VertexBufferLayout<'static> {
+                  wgpu::VertexBufferLayout {
+                      array_stride: std::mem::size_of::<VertexInput0>() as u64,
+                      step_mode,
+                      attributes: &VertexInput0::VERTEX_ATTRIBUTES,
+                  }
+              }
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_vertex_module_single_input_float64() {
+    let source = indoc! {r#"
+            struct VertexInput0 {
+                @location(0) a: f64,
+                @location(1) b: vec2<f64>,
+                @location(2) c: vec3<f64>,
+                @location(3) d: vec4<f64>,
+            };
+
+            @vertex
+            fn main(in0: VertexInput0) {}
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let actual = vertex_struct_methods("", &module, &WgslBindgenOption::default());
+
+    assert_tokens_eq!(
+      quote! {
+          impl VertexInput0 {
+              pub const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 4] = [
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Float64,
+                      offset: std::mem::offset_of!(VertexInput0, a) as u64,
+                      shader_location: 0,
+                  },
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Float64x2,
+                      offset: std::mem::offset_of!(VertexInput0, b) as u64,
+                      shader_location: 1,
+                  },
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Float64x3,
+                      offset: std::mem::offset_of!(VertexInput0, c) as u64,
+                      shader_location: 2,
+                  },
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Float64x4,
+                      offset: std::mem::offset_of!(VertexInput0, d) as u64,
+                      shader_location: 3,
+                  },
+              ];
+              pub const fn vertex_buffer_layout(
+                  step_mode: wgpu::VertexStepMode,
+              ) -> wgpu::VertexBufferLayout<'static> {
+                  wgpu::VertexBufferLayout {
+                      array_stride: std::mem::size_of::<VertexInput0>() as u64,
+                      step_mode,
+                      attributes: &VertexInput0::VERTEX_ATTRIBUTES,
+                  }
+              }
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_vertex_module_single_input_sint32() {
+    let source = indoc! {r#"
+            struct VertexInput0 {
+                @location(0) a: i32,
+                @location(1) a: vec2<i32>,
+                @location(2) a: vec3<i32>,
+                @location(3) a: vec4<i32>,
+
+            };
+
+            @vertex
+            fn main(in0: VertexInput0) {}
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let actual = vertex_struct_methods("", &module, &WgslBindgenOption::default());
+
+    assert_tokens_eq!(
+      quote! {
+          impl VertexInput0 {
+              pub const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 4] = [
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Sint32,
+                      offset: std::mem::offset_of!(VertexInput0, a) as u64,
+                      shader_location: 0,
+                  },
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Sint32x2,
+                      offset: std::mem::offset_of!(VertexInput0, a) as u64,
+                      shader_location: 1,
+                  },
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Sint32x3,
+                      offset: std::mem::offset_of!(VertexInput0, a) as u64,
+                      shader_location: 2,
+                  },
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Sint32x4,
+                      offset: std::mem::offset_of!(VertexInput0, a) as u64,
+                      shader_location: 3,
+                  },
+              ];
+              pub const fn vertex_buffer_layout(
+                  step_mode: wgpu::VertexStepMode,
+              ) -> wgpu::VertexBufferLayout<'static> {
+                  wgpu::VertexBufferLayout {
+                      array_stride: std::mem::size_of::<VertexInput0>() as u64,
+                      step_mode,
+                      attributes: &VertexInput0::VERTEX_ATTRIBUTES,
+                  }
+              }
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_vertex_module_single_input_uint32() {
+    let source = indoc! {r#"
+            struct VertexInput0 {
+                @location(0) a: u32,
+                @location(1) b: vec2<u32>,
+                @location(2) c: vec3<u32>,
+                @location(3) d: vec4<u32>,
+            };
+
+            @vertex
+            fn main(in0: VertexInput0) {}
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let actual = vertex_struct_methods("", &module, &WgslBindgenOption::default());
+
+    assert_tokens_eq!(
+      quote! {
+          impl VertexInput0 {
+              pub const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 4] = [
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Uint32,
+                      offset: std::mem::offset_of!(VertexInput0, a) as u64,
+                      shader_location: 0,
+                  },
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Uint32x2,
+                      offset: std::mem::offset_of!(VertexInput0, b) as u64,
+                      shader_location: 1,
+                  },
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Uint32x3,
+                      offset: std::mem::offset_of!(VertexInput0, c) as u64,
+                      shader_location: 2,
+                  },
+                  wgpu::VertexAttribute {
+                      format: wgpu::VertexFormat::Uint32x4,
+                      offset: std::mem::offset_of!(VertexInput0, d) as u64,
+                      shader_location: 3,
+                  },
+              ];
+              pub const fn vertex_buffer_layout(
+                  step_mode: wgpu::VertexStepMode,
+              ) -> wgpu::VertexBufferLayout<'static> {
+                  wgpu::VertexBufferLayout {
+                      array_stride: std::mem::size_of::<VertexInput0>() as u64,
+                      step_mode,
+                      attributes: &VertexInput0::VERTEX_ATTRIBUTES,
+                  }
+              }
+          }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_entry_constants() {
+    let source = indoc! {r#"
+            /// The main vertex entry.
+            @vertex
+            fn vs_main() {}
+
+            @vertex
+            fn another_vs() {}
+
+            @fragment
+            fn fs_main() {}
+
+            @fragment
+            fn another_fs() {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let docs = WgslDocs::from_sources([source]).unwrap();
+    let actual = entry_point_constants(&module, &docs);
+
+    assert_tokens_eq!(
+      quote! {
+          #[doc = " The main vertex entry."]
+          pub const ENTRY_VS_MAIN: &str = "vs_main";
+          pub const ENTRY_ANOTHER_VS: &str = "another_vs";
+          pub const ENTRY_FS_MAIN: &str = "fs_main";
+          pub const ENTRY_ANOTHER_FS: &str = "another_fs";
+      },
+      actual
+    )
+  }
+
+  #[test]
+  fn write_entry_point_enum() {
+    let source = indoc! {r#"
+            @vertex
+            fn vs_main() {}
+
+            @fragment
+            fn fs_main() {}
+
+            @compute @workgroup_size(1)
+            fn main1() {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      entry_point_enum: true,
+      ..Default::default()
+    };
+    let actual = entry_point_enum(&module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+          pub enum EntryPoint {
+              VsMain,
+              FsMain,
+              Main1
+          }
+          impl EntryPoint {
+              pub const ALL: &'static [Self] = &[Self::VsMain, Self::FsMain, Self::Main1];
+              pub const fn name(&self) -> &'static str {
+                  match self {
+                      Self::VsMain => "vs_main",
+                      Self::FsMain => "fs_main",
+                      Self::Main1 => "main1"
+                  }
+              }
+              pub const fn stage(&self) -> wgpu::ShaderStages {
+                  match self {
+                      Self::VsMain => wgpu::ShaderStages::VERTEX,
+                      Self::FsMain => wgpu::ShaderStages::FRAGMENT,
+                      Self::Main1 => wgpu::ShaderStages::COMPUTE
+                  }
+              }
+          }
+      },
+      actual
+    )
+  }
+
+  #[test]
+  fn write_vertex_shader_entry_no_buffers() {
+    let source = indoc! {r#"
+            @vertex
+            fn vs_main() {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let actual =
+      vertex_states("", &module, &WgslBindgenOption::default(), &WgslDocs::default());
+
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug)]
+          pub struct VertexEntry<const N: usize> {
+              entry_point: &'static str,
+              buffers: [wgpu::VertexBufferLayout<'static>; N],
+          }
+          pub fn vertex_state<'a, const N: usize>(
+              module: &'a wgpu::ShaderModule,
+              entry: &'a VertexEntry<N>,
+          ) -> wgpu::VertexState<'a> {
+              wgpu::VertexState {
+                  module,
+                  entry_point: entry.entry_point,
+                  buffers: &entry.buffers,
+              }
+          }
+          pub fn vs_main_entry() -> VertexEntry<0> {
+              VertexEntry {
+                  entry_point: ENTRY_VS_MAIN,
+                  buffers: [],
+              }
+          }
+      },
+      actual
+    )
+  }
+
+  #[test]
+  fn write_vertex_shader_multiple_entries() {
+    let source = indoc! {r#"
+            struct VertexInput {
+                @location(0) position: vec4<f32>,
+            };
+            @vertex
+            fn vs_main_1(in: VertexInput) {}
+
+            @vertex
+            fn vs_main_2(in: VertexInput) {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let actual =
+      vertex_states("", &module, &WgslBindgenOption::default(), &WgslDocs::default());
+
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug)]
+          pub struct VertexEntry<const N: usize> {
+              entry_point: &'static str,
+              buffers: [wgpu::VertexBufferLayout<'static>; N],
+          }
+          pub fn vertex_state<'a, const N: usize>(
+              module: &'a wgpu::ShaderModule,
+              entry: &'a VertexEntry<N>,
+          ) -> wgpu::VertexState<'a> {
+              wgpu::VertexState {
+                  module,
+                  entry_point: entry.entry_point,
+                  buffers: &entry.buffers,
+              }
+          }
+          pub fn vs_main_1_entry(vertex_input: wgpu::VertexStepMode) -> VertexEntry<1> {
+              VertexEntry {
+                  entry_point: ENTRY_VS_MAIN_1,
+                  buffers: [VertexInput::vertex_buffer_layout(vertex_input)],
+              }
+          }
+          pub fn vs_main_2_entry(vertex_input: wgpu::VertexStepMode) -> VertexEntry<1> {
+              VertexEntry {
+                  entry_point: ENTRY_VS_MAIN_2,
+                  buffers: [VertexInput::vertex_buffer_layout(vertex_input)],
+              }
+          }
+      },
+      actual
+    )
+  }
+
+  #[test]
+  fn write_vertex_shader_entry_multiple_buffers() {
+    let source = indoc! {r#"
+            struct Input0 {
+                @location(0) position: vec4<f32>,
+            };
+            struct Input1 {
+                @location(1) some_data: vec2<f32>
+            }
+            @vertex
+            fn vs_main(in0: Input0, in1: Input1) {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let actual =
+      vertex_states("", &module, &WgslBindgenOption::default(), &WgslDocs::default());
+
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug)]
+          pub struct VertexEntry<const N: usize> {
+              entry_point: &'static str,
+              buffers: [wgpu::VertexBufferLayout<'static>; N],
+          }
+          pub fn vertex_state<'a, const N: usize>(
+              module: &'a wgpu::ShaderModule,
+              entry: &'a VertexEntry<N>,
+          ) -> wgpu::VertexState<'a> {
+              wgpu::VertexState {
+                  module,
+                  entry_point: entry.entry_point,
+                  buffers: &entry.buffers,
+              }
+          }
+          pub fn vs_main_entry(input0: wgpu::VertexStepMode, input1: wgpu::VertexStepMode) -> VertexEntry<2> {
+              VertexEntry {
+                  entry_point: ENTRY_VS_MAIN,
+                  buffers: [
+                      Input0::vertex_buffer_layout(input0),
+                      Input1::vertex_buffer_layout(input1),
+                  ],
+              }
+          }
+      },
+      actual
+    )
+  }
+
+  #[test]
+  fn write_vertex_shader_entry_fixed_step_mode() {
+    let source = indoc! {r#"
+            struct VertexInput {
+                @location(0) position: vec3<f32>,
+            };
+            struct InstanceInput {
+                @location(1) offset: vec3<f32>,
+            };
+            @vertex
+            fn vs_main(vertex: VertexInput, instance: InstanceInput) {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      vertex_step_modes: vec![("InstanceInput", wgpu::VertexStepMode::Instance).into()],
+      ..Default::default()
+    };
+    let actual = vertex_states("", &module, &options, &WgslDocs::default());
+
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug)]
+          pub struct VertexEntry<const N: usize> {
+              entry_point: &'static str,
+              buffers: [wgpu::VertexBufferLayout<'static>; N],
+          }
+          pub fn vertex_state<'a, const N: usize>(
+              module: &'a wgpu::ShaderModule,
+              entry: &'a VertexEntry<N>,
+          ) -> wgpu::VertexState<'a> {
+              wgpu::VertexState {
+                  module,
+                  entry_point: entry.entry_point,
+                  buffers: &entry.buffers,
+              }
+          }
+          pub fn vs_main_entry(vertex_input: wgpu::VertexStepMode) -> VertexEntry<2> {
+              VertexEntry {
+                  entry_point: ENTRY_VS_MAIN,
+                  buffers: [
+                      VertexInput::vertex_buffer_layout(vertex_input),
+                      InstanceInput::vertex_buffer_layout(wgpu::VertexStepMode::Instance),
+                  ],
+              }
+          }
+      },
+      actual
+    )
+  }
+
+  #[test]
+  fn write_vertex_shader_entry_annotated_step_mode() {
+    let source = indoc! {r#"
+            // wgsl_bindgen: step_mode=vertex
+            struct VertexInput {
+                @location(0) position: vec3<f32>,
+            };
+            // wgsl_bindgen: step_mode=instance
+            struct InstanceInput {
+                @location(1) offset: vec3<f32>,
+            };
+            @vertex
+            fn vs_main(vertex: VertexInput, instance: InstanceInput) {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    // The annotation takes precedence over the options.
+    let options = WgslBindgenOption {
+      vertex_step_modes: vec![("VertexInput", wgpu::VertexStepMode::Instance).into()],
+      ..Default::default()
+    };
+    let docs = WgslDocs::from_sources([source]).unwrap();
+    let actual = vertex_states("", &module, &options, &docs);
+
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug)]
+          pub struct VertexEntry<const N: usize> {
+              entry_point: &'static str,
+              buffers: [wgpu::VertexBufferLayout<'static>; N],
+          }
+          pub fn vertex_state<'a, const N: usize>(
+              module: &'a wgpu::ShaderModule,
+              entry: &'a VertexEntry<N>,
+          ) -> wgpu::VertexState<'a> {
+              wgpu::VertexState {
+                  module,
+                  entry_point: entry.entry_point,
+                  buffers: &entry.buffers,
+              }
+          }
+          pub fn vs_main_entry() -> VertexEntry<2> {
+              VertexEntry {
+                  entry_point: ENTRY_VS_MAIN,
+                  buffers: [
+                      VertexInput::vertex_buffer_layout(wgpu::VertexStepMode::Vertex),
+                      InstanceInput::vertex_buffer_layout(wgpu::VertexStepMode::Instance),
+                  ],
+              }
+          }
+      },
+      actual
+    )
+  }
+
+  #[test]
+  fn write_vertex_states_no_entries() {
+    let source = indoc! {r#"
+            struct Input {
+                @location(0) position: vec4<f32>,
+            };
+            @fragment
+            fn main(in: Input) {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let actual =
+      vertex_states("", &module, &WgslBindgenOption::default(), &WgslDocs::default());
+
+    assert_tokens_eq!(quote!(), actual)
+  }
+
+  #[test]
+  fn write_fragment_states() {
+    let source = indoc! {r#"
+            struct FragmentOutput {
+                @location(0) color: vec4<f32>,
+                @location(2) normal: vec4<f32>,
+            };
+            @fragment
+            fn fs_main() -> FragmentOutput {
+                var out: FragmentOutput;
+                return out;
+            }
+            @fragment
+            fn fs_depth_only() {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      generate_fragment_states: true,
+      ..Default::default()
+    };
+    let actual = fragment_states(&module, &options);
+
+    assert_tokens_eq!(
+      quote! {
+          #[derive(Debug)]
+          pub struct FragmentEntry<const N: usize> {
+              entry_point: &'static str,
+              targets: [Option<wgpu::ColorTargetState>; N],
+          }
+          pub fn fragment_state<'a, const N: usize>(
+              module: &'a wgpu::ShaderModule,
+              entry: &'a FragmentEntry<N>,
+          ) -> wgpu::FragmentState<'a> {
+              wgpu::FragmentState {
+                  module,
+                  entry_point: entry.entry_point,
+                  targets: &entry.targets,
+              }
+          }
+          pub fn fs_main_entry(targets: [Option<wgpu::ColorTargetState>; 3]) -> FragmentEntry<3> {
+              FragmentEntry {
+                  entry_point: ENTRY_FS_MAIN,
+                  targets,
+              }
+          }
+          pub fn fs_depth_only_entry(targets: [Option<wgpu::ColorTargetState>; 0]) -> FragmentEntry<0> {
+              FragmentEntry {
+                  entry_point: ENTRY_FS_DEPTH_ONLY,
+                  targets,
+              }
+          }
+      },
+      actual
+    )
+  }
+
+  #[test]
+  fn write_fragment_states_disabled() {
+    let source = indoc! {r#"
+            @fragment
+            fn fs_main() -> @location(0) vec4<f32> {
+                return vec4<f32>(0.0);
+            }
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let actual = fragment_states(&module, &WgslBindgenOption::default());
+
+    assert_tokens_eq!(quote!(), actual)
+  }
+
+  #[test]
+  fn write_fragment_target_constants() {
+    let source = indoc! {r#"
+            struct FragmentOutput {
+                @location(0) color: vec4<f32>,
+                @builtin(frag_depth) depth: f32,
+                @location(2) normal: vec4<f32>,
+            };
+            @vertex
+            fn vs_main() -> @builtin(position) vec4<f32> {
+                return vec4<f32>(0.0);
+            }
+            @fragment
+            fn fs_main() -> FragmentOutput {
+                var out: FragmentOutput;
+                return out;
+            }
+            @fragment
+            fn fs_depth_only() {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      fragment_target_constants: true,
+      ..Default::default()
+    };
+    let actual = fragment_target_constants("test", &module, &options).unwrap();
+
+    assert_tokens_eq!(
+      quote! {
+          pub const FS_MAIN_COLOR_TARGET_COUNT: usize = 3;
+          pub const FS_MAIN_COLOR_TARGET_LOCATIONS: &[u32] = &[0, 2];
+          pub const FS_MAIN_DUAL_SOURCE_BLENDING: bool = false;
+          pub const FS_DEPTH_ONLY_COLOR_TARGET_COUNT: usize = 0;
+          pub const FS_DEPTH_ONLY_COLOR_TARGET_LOCATIONS: &[u32] = &[];
+          pub const FS_DEPTH_ONLY_DUAL_SOURCE_BLENDING: bool = false;
+      },
+      actual
+    )
+  }
+
+  #[test]
+  fn fragment_target_constants_name_conflict() {
+    let source = indoc! {r#"
+            const FS_MAIN_COLOR_TARGET_COUNT: u32 = 2u;
+
+            @fragment
+            fn fs_main() -> @location(0) vec4<f32> {
+                return vec4<f32>(1.0);
+            }
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      fragment_target_constants: true,
+      ..Default::default()
+    };
+    assert_eq!(
+      Err(CreateModuleError::GeneratedConstNameConflict {
+        module: "test".to_owned(),
+        name: "FS_MAIN_COLOR_TARGET_COUNT".to_owned(),
+      }),
+      fragment_target_constants("test", &module, &options).map(|_| ())
+    );
+  }
+
+  #[test]
+  fn write_required_features_dual_source_blending() {
+    let source = indoc! {r#"
+            struct FragmentOutput {
+                @location(0) color: vec4<f32>,
+                @location(0) @second_blend_source blend: vec4<f32>,
+            };
+            @fragment
+            fn fs_main() -> FragmentOutput {
+                var out: FragmentOutput;
+                return out;
+            }
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      fragment_target_constants: true,
+      generate_required_features: true,
+      ..Default::default()
+    };
+    let fragment_constants =
+      fragment_target_constants("test", &module, &options).unwrap();
+    let required_features = required_features_constant(&module, &options);
+    let actual = quote!(#fragment_constants #required_features);
+
+    assert_tokens_eq!(
+      quote! {
+          pub const FS_MAIN_COLOR_TARGET_COUNT: usize = 1;
+          pub const FS_MAIN_COLOR_TARGET_LOCATIONS: &[u32] = &[0];
+          pub const FS_MAIN_DUAL_SOURCE_BLENDING: bool = true;
+          pub const REQUIRED_FEATURES: wgpu::Features = wgpu::Features::DUAL_SOURCE_BLENDING;
+
+          pub fn check_device(device: &wgpu::Device) -> Result<(), _root::MissingFeatures> {
+              let missing = REQUIRED_FEATURES.difference(device.features());
+              if missing.is_empty() {
+                  Ok(())
+              } else {
+                  Err(_root::MissingFeatures(missing))
+              }
+          }
+      },
+      actual
+    )
+  }
+
+  #[test]
+  fn write_used_limits_constants() {
+    let source = indoc! {r#"
+            struct Constants {
+                transform: mat4x4<f32>,
+            };
+
+            struct VertexInput {
+                @location(0) position: vec3<f32>,
+                @location(1) normal: vec3<f32>,
+            };
+
+            var<push_constant> constants: Constants;
+            @group(1) @binding(0) var<uniform> scale: f32;
+
+            @vertex
+            fn vs_main(in: VertexInput) -> @builtin(position) vec4<f32> {
+                return constants.transform * vec4(in.position * scale, 1.0);
+            }
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      generate_used_limits: true,
+      ..Default::default()
+    };
+    assert_tokens_eq!(
+      quote! {
+          pub const MAX_BIND_GROUPS_USED: u32 = 2;
+          pub const MAX_VERTEX_BUFFERS_USED: u32 = 1;
+          pub const MAX_VERTEX_ATTRIBUTES_USED: u32 = 2;
+          pub const PUSH_CONSTANT_SIZE: u32 = 64;
+      },
+      used_limits_constants("test", &module, &options).unwrap()
+    )
+  }
+
+  #[test]
+  fn used_limits_constants_name_conflict() {
+    let source = indoc! {r#"
+            const PUSH_CONSTANT_SIZE: u32 = 128u;
+
+            @compute @workgroup_size(1)
+            fn main() {}
+        "#
+    };
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      generate_used_limits: true,
+      ..Default::default()
+    };
+    assert_eq!(
+      Err(CreateModuleError::GeneratedConstNameConflict {
+        module: "test".to_owned(),
+        name: "PUSH_CONSTANT_SIZE".to_owned(),
+      }),
+      used_limits_constants("test", &module, &options).map(|_| ())
+    );
+  }
+
+  #[test]
+  fn format_tokens_none_skips_formatting() {
+    let tokens = quote!(
+      pub struct A {
+        a: f32,
+      }
+    );
+
+    assert_eq!("pub struct A { a : f32 , }", format_tokens(&tokens, OutputFormat::None));
+    assert_eq!(
+      "pub struct A {\n    a: f32,\n}\n",
+      format_tokens(&tokens, OutputFormat::PrettyPlease)
+    );
+  }
+
+  #[test]
+  fn codegen_hooks_emit_items() {
+    #[derive(Debug)]
+    struct Hooks;
+
+    impl CodegenHooks for Hooks {
+      fn struct_items(
+        &self,
+        _entry: &ShaderEntryReflection,
+        item: &StructReflection,
+      ) -> TokenStream {
+        let name = format_ident!("{}", item.name.rsplit("::").next().unwrap());
+        quote!(impl crate::Bindable for #name {})
+      }
+
+      fn bind_group_items(
+        &self,
+        _entry: &ShaderEntryReflection,
+        group: &BindGroupReflection,
+      ) -> TokenStream {
+        let name = format_ident!("WgpuBindGroup{}", group.group);
+        quote!(impl crate::EngineBindGroup for bind_groups::#name {})
+      }
+
+      fn module_items(&self, entry: &ShaderEntryReflection) -> TokenStream {
+        let count = entry.entry_points.len();
+        quote!(pub const ENTRY_POINT_COUNT: usize = #count;)
+      }
+    }
+
+    let source = indoc! {r#"
+            struct Uniforms {
+                color: vec4<f32>,
+            };
+            @group(0) @binding(0) var<uniform> uniforms: Uniforms;
+            @fragment
+            fn fs_main() -> @location(0) vec4<f32> {
+                return uniforms.color;
+            }
+        "#};
+
+    let mut options = WgslBindgenOption::default();
+    options.codegen_hooks.push(std::sync::Arc::new(Hooks));
+    let actual = create_shader_module(source, options).unwrap();
+
+    assert!(actual.contains("impl crate::Bindable for Uniforms {}"));
+    assert!(
+      actual.contains("impl crate::EngineBindGroup for bind_groups::WgpuBindGroup0 {}")
+    );
+    assert!(actual.contains("pub const ENTRY_POINT_COUNT: usize = 1usize;"));
+  }
+
+  #[test]
+  fn append_code_to_module() {
+    let source = indoc! {r#"
+            @fragment
+            fn fs_main() {}
+        "#};
+
+    let options = WgslBindgenOption {
+      module_additions: vec![(
+        "test".to_string(),
+        quote!(
+          pub fn helper() -> &'static str {
+            ENTRY_FS_MAIN
+          }
+        ),
+      )],
+      ..Default::default()
+    };
+    let actual = create_shader_module(source, options).unwrap();
+
+    assert!(actual.contains("pub fn helper() -> &'static str {"));
   }
 }
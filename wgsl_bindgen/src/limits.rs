@@ -0,0 +1,297 @@
+use crate::{wgsl, CreateModuleError};
+
+/// The device limits a shader module needs, named after the fields of `wgpu::Limits`.
+/// Every binding is visible to all the stages of the module, so the per stage counts are
+/// the counts of the whole module.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct UsedLimits {
+  pub bind_groups: u32,
+  pub bindings_per_bind_group: u32,
+  pub sampled_textures_per_shader_stage: u32,
+  pub samplers_per_shader_stage: u32,
+  pub storage_buffers_per_shader_stage: u32,
+  pub storage_textures_per_shader_stage: u32,
+  pub uniform_buffers_per_shader_stage: u32,
+  pub uniform_buffer_binding_size: u32,
+  pub vertex_buffers: u32,
+  pub vertex_attributes: u32,
+  pub push_constant_size: u32,
+  pub compute_invocations_per_workgroup: u32,
+  pub compute_workgroup_size: [u32; 3],
+}
+
+impl UsedLimits {
+  pub fn from_module(
+    invoking_entry_module: &str,
+    module: &naga::Module,
+  ) -> Result<Self, CreateModuleError> {
+    let layouter = wgsl::type_layouter(invoking_entry_module, module)?;
+
+    let mut limits = Self::default();
+    for (_, global) in module.global_variables.iter() {
+      let ty = &module.types[global.ty];
+      if let Some(binding) = &global.binding {
+        limits.bind_groups = limits.bind_groups.max(binding.group + 1);
+        limits.bindings_per_bind_group =
+          limits.bindings_per_bind_group.max(binding.binding + 1);
+      }
+
+      match (global.space, &ty.inner) {
+        (naga::AddressSpace::Uniform, _) => {
+          limits.uniform_buffers_per_shader_stage += 1;
+          limits.uniform_buffer_binding_size = limits
+            .uniform_buffer_binding_size
+            .max(layouter[global.ty].size);
+        }
+        (naga::AddressSpace::Storage { .. }, _) => {
+          limits.storage_buffers_per_shader_stage += 1
+        }
+        (naga::AddressSpace::PushConstant, _) => {
+          limits.push_constant_size += layouter[global.ty].size
+        }
+        (
+          naga::AddressSpace::Handle,
+          naga::TypeInner::Image {
+            class: naga::ImageClass::Storage { .. },
+            ..
+          },
+        ) => limits.storage_textures_per_shader_stage += 1,
+        (naga::AddressSpace::Handle, naga::TypeInner::Image { .. }) => {
+          limits.sampled_textures_per_shader_stage += 1
+        }
+        (naga::AddressSpace::Handle, naga::TypeInner::Sampler { .. }) => {
+          limits.samplers_per_shader_stage += 1
+        }
+        _ => {}
+      }
+    }
+
+    for entry in &module.entry_points {
+      match entry.stage {
+        // Each vertex entry is used by its own pipeline, so only the largest one counts.
+        naga::ShaderStage::Vertex => {
+          let vertex_inputs =
+            wgsl::get_entry_vertex_input_structs(invoking_entry_module, module, entry)?;
+          let vertex_attributes = vertex_inputs
+            .iter()
+            .flat_map(|input| &input.fields)
+            .map(|(_, member)| match module.types[member.ty].inner {
+              naga::TypeInner::Matrix { columns, .. } => columns as u32,
+              _ => 1,
+            })
+            .sum();
+          limits.vertex_buffers = limits.vertex_buffers.max(vertex_inputs.len() as u32);
+          limits.vertex_attributes = limits.vertex_attributes.max(vertex_attributes);
+        }
+        naga::ShaderStage::Compute => {
+          let [x, y, z] = entry.workgroup_size;
+          let size = &mut limits.compute_workgroup_size;
+          *size = [size[0].max(x), size[1].max(y), size[2].max(z)];
+          // Saturate instead of overflowing, since any such size exceeds the limit anyway.
+          let invocations = u64::from(x) * u64::from(y) * u64::from(z);
+          limits.compute_invocations_per_workgroup = limits
+            .compute_invocations_per_workgroup
+            .max(u32::try_from(invocations).unwrap_or(u32::MAX));
+        }
+        naga::ShaderStage::Fragment => {}
+      }
+    }
+
+    Ok(limits)
+  }
+
+  /// Fails with the first limit of `target` exceeded by the module.
+  pub fn check(
+    &self,
+    invoking_entry_module: &str,
+    target: &wgpu::Limits,
+  ) -> Result<(), CreateModuleError> {
+    let [x, y, z] = self.compute_workgroup_size;
+    let checks = [
+      ("max_bind_groups", self.bind_groups, target.max_bind_groups),
+      (
+        "max_bindings_per_bind_group",
+        self.bindings_per_bind_group,
+        target.max_bindings_per_bind_group,
+      ),
+      (
+        "max_sampled_textures_per_shader_stage",
+        self.sampled_textures_per_shader_stage,
+        target.max_sampled_textures_per_shader_stage,
+      ),
+      (
+        "max_samplers_per_shader_stage",
+        self.samplers_per_shader_stage,
+        target.max_samplers_per_shader_stage,
+      ),
+      (
+        "max_storage_buffers_per_shader_stage",
+        self.storage_buffers_per_shader_stage,
+        target.max_storage_buffers_per_shader_stage,
+      ),
+      (
+        "max_storage_textures_per_shader_stage",
+        self.storage_textures_per_shader_stage,
+        target.max_storage_textures_per_shader_stage,
+      ),
+      (
+        "max_uniform_buffers_per_shader_stage",
+        self.uniform_buffers_per_shader_stage,
+        target.max_uniform_buffers_per_shader_stage,
+      ),
+      (
+        "max_uniform_buffer_binding_size",
+        self.uniform_buffer_binding_size,
+        target.max_uniform_buffer_binding_size,
+      ),
+      ("max_vertex_buffers", self.vertex_buffers, target.max_vertex_buffers),
+      ("max_vertex_attributes", self.vertex_attributes, target.max_vertex_attributes),
+      ("max_push_constant_size", self.push_constant_size, target.max_push_constant_size),
+      (
+        "max_compute_invocations_per_workgroup",
+        self.compute_invocations_per_workgroup,
+        target.max_compute_invocations_per_workgroup,
+      ),
+      ("max_compute_workgroup_size_x", x, target.max_compute_workgroup_size_x),
+      ("max_compute_workgroup_size_y", y, target.max_compute_workgroup_size_y),
+      ("max_compute_workgroup_size_z", z, target.max_compute_workgroup_size_z),
+    ];
+
+    match checks.into_iter().find(|(_, used, max)| used > max) {
+      Some((limit, used, max)) => Err(CreateModuleError::LimitExceeded {
+        module: invoking_entry_module.to_owned(),
+        limit,
+        used,
+        max,
+      }),
+      None => Ok(()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use indoc::indoc;
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  #[test]
+  fn used_limits() {
+    let source = indoc! {r#"
+      struct Uniforms {
+          color: vec4<f32>,
+          transform: mat4x4<f32>,
+      };
+
+      struct VertexInput {
+          @location(0) position: vec3<f32>,
+          @location(1) instance: mat4x4<f32>,
+      };
+
+      @group(0) @binding(0) var<uniform> uniforms: Uniforms;
+      @group(0) @binding(2) var<storage, read> values: array<f32>;
+      @group(1) @binding(0) var color_texture: texture_2d<f32>;
+      @group(1) @binding(1) var color_sampler: sampler;
+
+      @vertex
+      fn vs_main(in: VertexInput) -> @builtin(position) vec4<f32> {
+          return uniforms.transform * in.instance * vec4(in.position, values[0]);
+      }
+
+      @fragment
+      fn fs_main() -> @location(0) vec4<f32> {
+          return textureSample(color_texture, color_sampler, vec2(0.0)) * uniforms.color;
+      }
+    "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let limits = UsedLimits::from_module("test", &module).unwrap();
+    assert_eq!(
+      UsedLimits {
+        bind_groups: 2,
+        bindings_per_bind_group: 3,
+        sampled_textures_per_shader_stage: 1,
+        samplers_per_shader_stage: 1,
+        storage_buffers_per_shader_stage: 1,
+        uniform_buffers_per_shader_stage: 1,
+        uniform_buffer_binding_size: 80,
+        vertex_buffers: 1,
+        vertex_attributes: 5,
+        ..Default::default()
+      },
+      limits
+    );
+
+    assert_eq!(Ok(()), limits.check("test", &wgpu::Limits::default()));
+    assert_eq!(
+      Err(CreateModuleError::LimitExceeded {
+        module: "test".to_owned(),
+        limit: "max_storage_buffers_per_shader_stage",
+        used: 1,
+        max: 0,
+      }),
+      limits.check("test", &wgpu::Limits::downlevel_webgl2_defaults())
+    );
+  }
+
+  #[test]
+  fn compute_workgroup_size_limits() {
+    let source = indoc! {r#"
+      @compute @workgroup_size(512, 2)
+      fn main() {}
+    "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    assert_eq!(
+      Err(CreateModuleError::LimitExceeded {
+        module: "test".to_owned(),
+        limit: "max_compute_invocations_per_workgroup",
+        used: 1024,
+        max: 256,
+      }),
+      UsedLimits::from_module("test", &module)
+        .unwrap()
+        .check("test", &wgpu::Limits::default())
+    );
+  }
+
+  #[test]
+  fn workgroup_size_limits_dont_overflow() {
+    let source = indoc! {r#"
+      @compute @workgroup_size(65536, 65536, 2)
+      fn main() {}
+    "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let limits = UsedLimits::from_module("test", &module).unwrap();
+    assert_eq!(u32::MAX, limits.compute_invocations_per_workgroup);
+  }
+
+  #[test]
+  fn vertex_limits_of_largest_entry() {
+    let source = indoc! {r#"
+      struct VertexInput {
+          @location(0) position: vec3<f32>,
+      };
+
+      struct InstanceInput {
+          @location(1) transform: mat4x4<f32>,
+      };
+
+      @vertex
+      fn vs_main(in: VertexInput) -> @builtin(position) vec4<f32> {
+          return vec4(in.position, 1.0);
+      }
+
+      @vertex
+      fn vs_instanced(in: VertexInput, instance: InstanceInput) -> @builtin(position) vec4<f32> {
+          return instance.transform * vec4(in.position, 1.0);
+      }
+    "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let limits = UsedLimits::from_module("test", &module).unwrap();
+    assert_eq!((2, 5), (limits.vertex_buffers, limits.vertex_attributes));
+  }
+}
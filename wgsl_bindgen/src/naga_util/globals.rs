@@ -0,0 +1,88 @@
+use naga::{Expression, GlobalVariable, Handle, Module};
+
+use super::prune::collect_calls;
+
+/// The stages of the entry points using each global variable, directly or through the functions
+/// they call, indexed by the global variable's handle.
+pub fn global_stages(module: &Module) -> Vec<wgpu::ShaderStages> {
+  let mut stages = vec![wgpu::ShaderStages::NONE; module.global_variables.len()];
+  for entry_point in &module.entry_points {
+    let stage = match entry_point.stage {
+      naga::ShaderStage::Vertex => wgpu::ShaderStages::VERTEX,
+      naga::ShaderStage::Fragment => wgpu::ShaderStages::FRAGMENT,
+      naga::ShaderStage::Compute => wgpu::ShaderStages::COMPUTE,
+    };
+
+    let mut visited = vec![false; module.functions.len()];
+    let mut to_visit = Vec::new();
+    let mut globals = Vec::new();
+    collect_calls(&entry_point.function.body, &mut to_visit);
+    collect_globals(&entry_point.function.expressions, &mut globals);
+    while let Some(handle) = to_visit.pop() {
+      if !std::mem::replace(&mut visited[handle.index()], true) {
+        let function = &module.functions[handle];
+        collect_calls(&function.body, &mut to_visit);
+        collect_globals(&function.expressions, &mut globals);
+      }
+    }
+
+    for global in globals {
+      stages[global.index()] |= stage;
+    }
+  }
+  stages
+}
+
+fn collect_globals(
+  expressions: &naga::Arena<Expression>,
+  globals: &mut Vec<Handle<GlobalVariable>>,
+) {
+  globals.extend(
+    expressions
+      .iter()
+      .filter_map(|(_, expression)| match expression {
+        Expression::GlobalVariable(global) => Some(*global),
+        _ => None,
+      }),
+  );
+}
+
+#[cfg(test)]
+mod tests {
+  use indoc::indoc;
+
+  use super::*;
+
+  #[test]
+  fn stages_of_globals_used_by_entry_points() {
+    let source = indoc! {r#"
+      @group(0) @binding(0) var<uniform> transform: mat4x4<f32>;
+      @group(0) @binding(1) var<uniform> color: vec4<f32>;
+      @group(0) @binding(2) var<uniform> unused: vec4<f32>;
+
+      fn get_color() -> vec4<f32> {
+          return color;
+      }
+
+      @vertex
+      fn vs_main() -> @builtin(position) vec4<f32> {
+          return transform * get_color();
+      }
+
+      @fragment
+      fn fs_main() -> @location(0) vec4<f32> {
+          return get_color();
+      }
+    "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    assert_eq!(
+      vec![
+        wgpu::ShaderStages::VERTEX,
+        wgpu::ShaderStages::VERTEX_FRAGMENT,
+        wgpu::ShaderStages::NONE,
+      ],
+      global_stages(&module)
+    );
+  }
+}
@@ -0,0 +1,189 @@
+use crate::MinifyLevel;
+
+/// Minifies the module and the WGSL written from it as configured by `level`.
+pub(crate) fn minify_module(module: &mut naga::Module, level: MinifyLevel) {
+  if level.is_identifiers() {
+    shorten_identifiers(module);
+  }
+}
+
+pub(crate) fn minify_source(source: String, level: MinifyLevel) -> String {
+  if level.is_off() {
+    source
+  } else {
+    minify_wgsl_source(&source)
+  }
+}
+
+/// Gives the helper functions, arguments and local variables short names. Entry points,
+/// globals, types and constants keep their names, as they are looked up by name at runtime
+/// or by the generated bindings.
+fn shorten_identifiers(module: &mut naga::Module) {
+  // A single generator for the whole module avoids names shadowing each other. Collisions with
+  // the names that are kept, or with keywords, are resolved by the naga writer.
+  let mut short_names = (0..).map(short_name);
+
+  for (_, function) in module.functions.iter_mut() {
+    function.name = short_names.next();
+  }
+
+  let functions = module
+    .functions
+    .iter_mut()
+    .map(|(_, function)| function)
+    .chain(
+      module
+        .entry_points
+        .iter_mut()
+        .map(|entry| &mut entry.function),
+    );
+
+  for function in functions {
+    for argument in function.arguments.iter_mut() {
+      argument.name = short_names.next();
+    }
+    for (_, local) in function.local_variables.iter_mut() {
+      local.name = short_names.next();
+    }
+    for (_, name) in function.named_expressions.iter_mut() {
+      *name = short_names.next().unwrap();
+    }
+  }
+}
+
+/// `a`, `b`, ..., `z`, `aa`, `ab`, ...
+fn short_name(mut index: usize) -> String {
+  let mut name = Vec::new();
+  loop {
+    name.push(b'a' + (index % 26) as u8);
+    index /= 26;
+    if index == 0 {
+      break;
+    }
+    index -= 1;
+  }
+  name.reverse();
+  String::from_utf8(name).unwrap()
+}
+
+/// Strips the comments, and the whitespace which doesn't separate two words or two operators.
+pub(crate) fn minify_wgsl_source(source: &str) -> String {
+  fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+  }
+  fn is_operator(c: char) -> bool {
+    "+-*/%&|^<>=!".contains(c)
+  }
+
+  let mut output = String::with_capacity(source.len());
+  let mut chars = source.chars().peekable();
+  let mut pending_space = false;
+
+  while let Some(c) = chars.next() {
+    match (c, chars.peek()) {
+      ('/', Some('/')) => {
+        while chars.next_if(|&c| c != '\n').is_some() {}
+        pending_space = true;
+      }
+      ('/', Some('*')) => {
+        chars.next();
+        // Block comments can be nested in WGSL.
+        let mut depth = 1;
+        while depth > 0 {
+          match (chars.next(), chars.peek()) {
+            (Some('/'), Some('*')) => {
+              chars.next();
+              depth += 1;
+            }
+            (Some('*'), Some('/')) => {
+              chars.next();
+              depth -= 1;
+            }
+            (None, _) => break,
+            _ => {}
+          }
+        }
+        pending_space = true;
+      }
+      (c, _) if c.is_whitespace() => pending_space = true,
+      (c, _) => {
+        if pending_space {
+          if let Some(last) = output.chars().last() {
+            if (is_word(last) && is_word(c)) || (is_operator(last) && is_operator(c)) {
+              output.push(' ');
+            }
+          }
+          pending_space = false;
+        }
+        output.push(c);
+      }
+    }
+  }
+
+  output
+}
+
+#[cfg(test)]
+mod tests {
+  use indoc::indoc;
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  #[test]
+  fn minify_strips_comments_and_whitespace() {
+    let source = indoc! {r#"
+      // The camera
+      struct Camera {
+          view_proj: mat4x4<f32>, /* nested /* block */ comment */
+      };
+
+      fn negate(x: f32) -> f32 {
+          return 1.0 - -x;
+      }
+    "#};
+
+    assert_eq!(
+      "struct Camera{view_proj:mat4x4<f32>,};fn negate(x:f32)->f32{return 1.0- -x;}",
+      minify_wgsl_source(source)
+    );
+  }
+
+  #[test]
+  fn minify_shortens_identifiers() {
+    let source = indoc! {r#"
+      @group(0) @binding(0) var<storage, read_write> values: array<f32>;
+
+      fn square(value: f32) -> f32 {
+          let squared = value * value;
+          return squared;
+      }
+
+      @compute @workgroup_size(64)
+      fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+          var result = square(values[id.x]);
+          values[id.x] = result;
+      }
+    "#};
+
+    let mut module = naga::front::wgsl::parse_str(source).unwrap();
+    minify_module(&mut module, MinifyLevel::Identifiers);
+    let minified = minify_source(
+      crate::naga_util::module_to_source(&module).unwrap(),
+      MinifyLevel::Identifiers,
+    );
+
+    assert!(!minified.contains("square"), "{minified}");
+    assert!(minified.contains("fn main("), "{minified}");
+    assert!(minified.contains("values"), "{minified}");
+    naga::front::wgsl::parse_str(&minified).unwrap();
+  }
+
+  #[test]
+  fn short_names() {
+    assert_eq!(
+      vec!["a", "z", "aa", "az", "ba", "zz", "aaa"],
+      [0, 25, 26, 51, 52, 701, 702].map(short_name).to_vec()
+    );
+  }
+}
@@ -1,2 +1,7 @@
+mod globals;
+mod minify;
 mod module_to_source;
+mod prune;
+pub use globals::*;
 pub use module_to_source::*;
+pub use prune::*;
@@ -1,35 +1,71 @@
+use thiserror::Error;
+
+use super::minify::{minify_module, minify_source};
+use crate::MinifyLevel;
+
+/// Errors while writing a module as SPIR-V.
+#[derive(Debug, Error)]
+pub enum SpirvOutputError {
+  #[error(transparent)]
+  Validation(#[from] naga::WithSpan<naga::valid::ValidationError>),
+
+  #[error(transparent)]
+  Writer(#[from] naga::back::spv::Error),
+}
+
 // https://github.com/LucentFlux/naga-to-tokenstream/blob/main/src/lib.rs#L26
 pub fn module_to_source(
   module: &naga::Module,
 ) -> Result<String, naga::back::wgsl::Error> {
-  // Clone since we sometimes modify things
-  #[allow(unused_mut)]
-  let mut module = module.clone();
-
-  // If we minify, do the first pass before writing out
-  #[cfg(feature = "minify")]
-  {
-    wgsl_minifier::minify_module(&mut module);
-  }
-
   // Mini validation to get module info
   let info = naga::valid::Validator::new(
     naga::valid::ValidationFlags::empty(),
     naga::valid::Capabilities::all(),
   )
-  .validate(&module);
+  .validate(module);
 
   // Write to wgsl
   let info = info.unwrap();
   let src = naga::back::wgsl::write_string(
-    &module,
+    module,
     &info,
     naga::back::wgsl::WriterFlags::empty(),
   )?;
 
-  // Remove whitespace if minifying
-  #[cfg(feature = "minify")]
-  let src = wgsl_minifier::minify_wgsl_source(&src);
-
   return Ok(src);
 }
+
+/// Writes the module as WGSL, minified as configured by `level`.
+pub fn module_to_minified_source(
+  module: &naga::Module,
+  level: MinifyLevel,
+) -> Result<String, naga::back::wgsl::Error> {
+  let mut module = module.clone();
+  minify_module(&mut module, level);
+  let src = module_to_source(&module)?;
+  Ok(minify_source(src, level))
+}
+
+/// Serializes the module with bincode, to embed it as `wgpu::ShaderSource::Naga`.
+pub fn module_to_naga_ir(module: &naga::Module) -> Result<Vec<u8>, bincode::Error> {
+  bincode::serialize(module)
+}
+
+/// Writes the module as SPIR-V words. The coordinate space is adjusted like wgpu does when
+/// translating WGSL for Vulkan, so the shader behaves the same as its WGSL source.
+pub fn module_to_spirv(module: &naga::Module) -> Result<Vec<u32>, SpirvOutputError> {
+  let info = naga::valid::Validator::new(
+    naga::valid::ValidationFlags::all(),
+    naga::valid::Capabilities::all(),
+  )
+  .validate(module)?;
+
+  // The flags are set explicitly, as the defaults add debug info in debug builds.
+  let options = naga::back::spv::Options {
+    flags: naga::back::spv::WriterFlags::ADJUST_COORDINATE_SPACE
+      | naga::back::spv::WriterFlags::LABEL_VARYINGS
+      | naga::back::spv::WriterFlags::CLAMP_FRAG_DEPTH,
+    ..Default::default()
+  };
+  Ok(naga::back::spv::write_vec(module, &info, &options, None)?)
+}
@@ -0,0 +1,156 @@
+use naga::{Arena, Block, Expression, FastHashMap, Function, Handle, Module, Statement};
+
+/// Removes the functions which aren't called from any entry point, and then the types,
+/// constants and expressions which are no longer used.
+pub fn prune_unused_functions(module: &mut Module) {
+  let mut used = vec![false; module.functions.len()];
+  let mut to_visit = Vec::new();
+  for entry_point in &module.entry_points {
+    collect_calls(&entry_point.function.body, &mut to_visit);
+  }
+  while let Some(handle) = to_visit.pop() {
+    if !std::mem::replace(&mut used[handle.index()], true) {
+      collect_calls(&module.functions[handle].body, &mut to_visit);
+    }
+  }
+
+  let mut functions = Arena::new();
+  let mut new_handles = FastHashMap::default();
+  for (handle, function) in module.functions.iter() {
+    if used[handle.index()] {
+      let span = module.functions.get_span(handle);
+      new_handles.insert(handle, functions.append(function.clone(), span));
+    }
+  }
+
+  module.functions = functions;
+  let functions = module
+    .functions
+    .iter_mut()
+    .map(|(_, function)| function)
+    .chain(
+      module
+        .entry_points
+        .iter_mut()
+        .map(|entry| &mut entry.function),
+    );
+  for function in functions {
+    remap_calls(function, &new_handles);
+  }
+
+  naga::compact::compact(module);
+}
+
+pub(super) fn collect_calls(block: &Block, calls: &mut Vec<Handle<Function>>) {
+  for statement in block.iter() {
+    match statement {
+      Statement::Call { function, .. } => calls.push(*function),
+      Statement::Block(block) => collect_calls(block, calls),
+      Statement::If { accept, reject, .. } => {
+        collect_calls(accept, calls);
+        collect_calls(reject, calls);
+      }
+      Statement::Switch { cases, .. } => {
+        for case in cases {
+          collect_calls(&case.body, calls);
+        }
+      }
+      Statement::Loop {
+        body, continuing, ..
+      } => {
+        collect_calls(body, calls);
+        collect_calls(continuing, calls);
+      }
+      _ => {}
+    }
+  }
+}
+
+fn remap_calls(
+  function: &mut Function,
+  new_handles: &FastHashMap<Handle<Function>, Handle<Function>>,
+) {
+  fn remap_block(
+    block: &mut Block,
+    new_handles: &FastHashMap<Handle<Function>, Handle<Function>>,
+  ) {
+    for statement in block.iter_mut() {
+      match statement {
+        Statement::Call { function, .. } => *function = new_handles[function],
+        Statement::Block(block) => remap_block(block, new_handles),
+        Statement::If { accept, reject, .. } => {
+          remap_block(accept, new_handles);
+          remap_block(reject, new_handles);
+        }
+        Statement::Switch { cases, .. } => {
+          for case in cases {
+            remap_block(&mut case.body, new_handles);
+          }
+        }
+        Statement::Loop {
+          body, continuing, ..
+        } => {
+          remap_block(body, new_handles);
+          remap_block(continuing, new_handles);
+        }
+        _ => {}
+      }
+    }
+  }
+
+  remap_block(&mut function.body, new_handles);
+  for (_, expression) in function.expressions.iter_mut() {
+    if let Expression::CallResult(function) = expression {
+      *function = new_handles[function];
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use indoc::indoc;
+
+  use super::*;
+
+  #[test]
+  fn prune_functions_not_called_from_entry_points() {
+    let source = indoc! {r#"
+      fn unused_helper() -> f32 {
+          return 1.0;
+      }
+
+      fn inner(value: f32) -> f32 {
+          return value * 2.0;
+      }
+
+      fn outer(value: f32) -> f32 {
+          if value > 0.0 {
+              return inner(value);
+          }
+          return 0.0;
+      }
+
+      @fragment
+      fn fs_main() -> @location(0) vec4<f32> {
+          return vec4(outer(0.5));
+      }
+    "#};
+
+    let mut module = naga::front::wgsl::parse_str(source).unwrap();
+    prune_unused_functions(&mut module);
+
+    let names = module
+      .functions
+      .iter()
+      .map(|(_, function)| function.name.as_deref().unwrap())
+      .collect::<Vec<_>>();
+    assert_eq!(vec!["inner", "outer"], names);
+
+    naga::valid::Validator::new(
+      naga::valid::ValidationFlags::all(),
+      naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .unwrap();
+  }
+}
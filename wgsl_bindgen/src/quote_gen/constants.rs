@@ -4,6 +4,10 @@ use proc_macro2::Ident;
 pub(crate) const MOD_REFERENCE_ROOT: &str = "_root";
 pub(crate) const MOD_STRUCT_ASSERTIONS: &str = "layout_asserts";
 pub(crate) const MOD_BYTEMUCK_IMPLS: &str = "bytemuck_impls";
+pub(crate) const MOD_PADDED_MATRICES: &str = "padded_matrices";
+pub(crate) const MOD_BIND_GROUP_PASS: &str = "bind_group_pass";
+pub(crate) const MOD_SHARED_STRUCTS: &str = "shared_structs";
+pub(crate) const MOD_GLOBAL_BIND_GROUP: &str = "global_bind_group";
 
 pub(crate) fn mod_reference_root() -> Ident {
   unsafe { syn::parse_str(MOD_REFERENCE_ROOT).unwrap_unchecked() }
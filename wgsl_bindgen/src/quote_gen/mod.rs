@@ -12,6 +12,7 @@ pub(crate) use rust_struct_builder::*;
 pub(crate) use rust_type_info::*;
 
 use crate::bevy_util::demangle_str;
+use crate::WgslBindgenOption;
 
 /// Creates a raw string literal from the given shader content.
 ///
@@ -26,6 +27,45 @@ pub(crate) fn create_shader_raw_string_literal(shader_content: &str) -> TokenStr
   syn::parse_str::<TokenStream>(&format!("r#\"\n{}\"#", &shader_content)).unwrap()
 }
 
+/// The path of the `core` crate in the generated code, `std` unless generating `no_std` code.
+pub(crate) fn core_crate(options: &WgslBindgenOption) -> TokenStream {
+  if options.no_std {
+    quote::quote!(core)
+  } else {
+    quote::quote!(std)
+  }
+}
+
+/// The path of the `alloc` crate in the generated code, `std` unless generating `no_std` code.
+pub(crate) fn alloc_crate(options: &WgslBindgenOption) -> TokenStream {
+  if options.no_std {
+    quote::quote!(alloc)
+  } else {
+    quote::quote!(std)
+  }
+}
+
+/// Creates a `#[cfg]` attribute requiring all the `predicates`, or nothing without predicates.
+pub(crate) fn cfg_attribute(predicates: &[TokenStream]) -> TokenStream {
+  match predicates {
+    [] => TokenStream::new(),
+    [predicate] => quote::quote!(#[cfg(#predicate)]),
+    _ => quote::quote!(#[cfg(all(#(#predicates),*))]),
+  }
+}
+
+/// Adds `attribute` to each of the `items`, eg: to gate them with a `#[cfg]`.
+pub(crate) fn add_attribute_to_items(
+  attribute: &TokenStream,
+  items: TokenStream,
+) -> TokenStream {
+  if attribute.is_empty() {
+    return items;
+  }
+  let items = syn::parse2::<syn::File>(items).unwrap().items;
+  quote::quote!(#(#attribute #items)*)
+}
+
 /// Demangles the given string and qualifies it with the qualification root.
 ///
 /// # Arguments
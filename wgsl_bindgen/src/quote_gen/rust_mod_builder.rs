@@ -176,6 +176,8 @@ impl RustModBuilderConfig {
 pub(crate) struct RustModBuilder {
   modules: FastIndexMap<String, RustMod>,
   config: RustModBuilderConfig,
+  module_attributes: TokenStream,
+  prelude: TokenStream,
 }
 
 impl RustModBuilder {
@@ -185,9 +187,25 @@ impl RustModBuilder {
     Self {
       modules: config.initial_modules(),
       config,
+      module_attributes: quote!(),
+      prelude: quote!(),
     }
   }
 
+  /// Sets the outer attributes of the top level modules created after this call.
+  /// The relative root module doesn't get the attributes.
+  pub fn with_module_attributes(mut self, module_attributes: TokenStream) -> Self {
+    self.module_attributes = module_attributes;
+    self
+  }
+
+  /// Sets the items added at the start of every module created after this call,
+  /// including the nested ones. The relative root module doesn't get the prelude.
+  pub fn with_prelude(mut self, prelude: TokenStream) -> Self {
+    self.prelude = prelude;
+    self
+  }
+
   fn get_or_create_module(&mut self, path: &str) -> &mut RustMod {
     if path.is_empty() {
       panic!("path cannot be empty");
@@ -195,10 +213,16 @@ impl RustModBuilder {
 
     let modules = path.split("::").collect::<SmallVec<[_; 8]>>();
 
-    let mut current_module = self
-      .modules
-      .entry(modules[0].to_owned())
-      .or_insert_with(|| self.config.build_module(modules[0]));
+    let mut current_module =
+      self
+        .modules
+        .entry(modules[0].to_owned())
+        .or_insert_with(|| {
+          let mut module = self.config.build_module(modules[0]);
+          module.module_attributes = self.module_attributes.clone();
+          module.initial_contents.extend(self.prelude.clone());
+          module
+        });
 
     for name in &modules[1..] {
       current_module = current_module.get_or_create_submodule(name);
@@ -283,6 +307,60 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_module_attributes() {
+    let mut mod_builder =
+      RustModBuilder::new(true).with_module_attributes(quote!(#[rustfmt::skip]));
+    mod_builder.add("a::b", quote! {struct A;});
+
+    let actual = mod_builder.generate();
+
+    assert_tokens_eq!(
+      actual,
+      quote! {
+        mod _root {
+          pub use super::*;
+        }
+        #[rustfmt::skip]
+        pub mod a {
+          use super::{_root, _root::*};
+          pub mod b {
+              use super::{_root, _root::*};
+              struct A;
+          }
+        }
+      }
+    );
+  }
+
+  #[test]
+  fn test_prelude() {
+    let mut mod_builder = RustModBuilder::new(true).with_prelude(quote!(
+      use crate::math::*;
+    ));
+    mod_builder.add("a::b", quote! {struct A;});
+
+    let actual = mod_builder.generate();
+
+    assert_tokens_eq!(
+      actual,
+      quote! {
+        mod _root {
+          pub use super::*;
+        }
+        pub mod a {
+          use super::{_root, _root::*};
+          use crate::math::*;
+          pub mod b {
+              use super::{_root, _root::*};
+              use crate::math::*;
+              struct A;
+          }
+        }
+      }
+    );
+  }
+
   #[test]
   fn test_relative_root_feature() {
     let mut mod_builder = RustModBuilder::new(true);
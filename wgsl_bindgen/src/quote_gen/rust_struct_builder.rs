@@ -9,8 +9,14 @@ use syn::{Ident, Index};
 
 use super::{rust_type, RustItem, RustItemPath, RustTypeInfo};
 use crate::bevy_util::demangle_str;
-use crate::quote_gen::{RustItemKind, MOD_BYTEMUCK_IMPLS, MOD_STRUCT_ASSERTIONS};
-use crate::{sanitized_upper_snake_case, WgslBindgenOption, WgslTypeSerializeStrategy};
+use crate::quote_gen::{
+  core_crate, RustItemKind, MOD_BYTEMUCK_IMPLS, MOD_STRUCT_ASSERTIONS,
+};
+use crate::wgsl_docs::WgslDocs;
+use crate::{
+  sanitized_upper_snake_case, CustomDerive, PaddingFieldConfig, WgslBindgenOption,
+  WgslScalarType, WgslTypeSerializeStrategy,
+};
 
 #[derive(Clone)]
 pub struct Padding {
@@ -25,10 +31,20 @@ impl Padding {
     quote!(#pad_name: [0; #pad_size])
   }
 
-  fn generate_member_definition(&self) -> TokenStream {
+  fn generate_member_definition(&self, config: &PaddingFieldConfig) -> TokenStream {
     let pad_name = &self.pad_name;
     let pad_size = &self.pad_size_tokens;
-    quote!(pub #pad_name: [u8; #pad_size])
+    let visibility = if config.is_public {
+      quote!(pub)
+    } else {
+      quote!()
+    };
+    let doc_hidden = if config.doc_hidden {
+      quote!(#[doc(hidden)])
+    } else {
+      quote!()
+    };
+    quote!(#doc_hidden #visibility #pad_name: [u8; #pad_size])
   }
 }
 
@@ -39,14 +55,22 @@ struct NagaToRustStructState<'a> {
 }
 
 impl<'a> NagaToRustStructState<'a> {
-  /// This replaces the `rust_type` with a custom field map if necessary
+  /// This replaces the `rust_type` with a custom field map if necessary.
+  /// Vertex format overrides only apply to the fields of vertex inputs.
   fn get_rust_type(
     options: &WgslBindgenOption,
     fully_qualified_name: &SmolStr,
     rust_type: RustTypeInfo,
+    naga_type: &naga::Type,
     member_name: &str,
+    is_vertex_input: bool,
   ) -> proc_macro2::TokenStream {
     let fully_qualified_name = fully_qualified_name.as_str();
+    let scalar = match &naga_type.inner {
+      naga::TypeInner::Scalar(scalar) => WgslScalarType::from_naga(scalar),
+      _ => None,
+    };
+
     options
       .override_struct_field_type
       .iter()
@@ -55,6 +79,21 @@ impl<'a> NagaToRustStructState<'a> {
         let field_matches = o.field_regex.is_match(member_name);
         (struct_matches && field_matches).then_some(o.override_type.clone())
       })
+      .or_else(|| {
+        options
+          .vertex_format_override(fully_qualified_name, member_name)
+          .filter(|_| is_vertex_input)
+          .map(|o| o.rust_type.clone())
+      })
+      .or_else(|| {
+        let scalar = scalar?;
+        options.override_struct_scalar_type.iter().find_map(|o| {
+          let struct_matches = o.struct_regex.is_match(fully_qualified_name);
+          let field_matches = o.field_regex.is_match(member_name);
+          (o.scalar == scalar && struct_matches && field_matches)
+            .then_some(o.override_type.clone())
+        })
+      })
       .unwrap_or(rust_type.tokens)
   }
 
@@ -66,6 +105,7 @@ impl<'a> NagaToRustStructState<'a> {
     gctx: naga::proc::GlobalCtx<'a>,
     layout_size: usize,
     is_directly_sharable: bool,
+    is_vertex_input: bool,
   ) -> impl FnMut(NagaToRustStructState<'a>, &'a StructMember) -> NagaToRustStructState<'a>
   {
     let fold = move |mut state: NagaToRustStructState<'a>,
@@ -94,7 +134,7 @@ impl<'a> NagaToRustStructState<'a> {
         };
         let rust_type = &rust_type;
 
-        let pad_name = format!("_pad_{}", member_name);
+        let pad_name = options.padding_field.name_for(member_name);
         let required_member_size = next_offset - current_offset;
 
         match rust_type.aligned_size() {
@@ -133,8 +173,14 @@ impl<'a> NagaToRustStructState<'a> {
           pad_size_tokens,
         })
       } else {
-        let rust_type =
-          Self::get_rust_type(options, &fully_qualified_name, rust_type, member_name);
+        let rust_type = Self::get_rust_type(
+          options,
+          &fully_qualified_name,
+          rust_type,
+          naga_type,
+          member_name,
+          is_vertex_input,
+        );
 
         RustStructMemberEntry::Field(Field {
           name_ident: name_ident.clone(),
@@ -199,6 +245,7 @@ impl<'a> RustStructMemberEntry<'a> {
     naga_module: &'a naga::Module,
     layout_size: usize,
     is_directly_sharable: bool,
+    is_vertex_input: bool,
   ) -> Vec<Self> {
     let gctx = naga_module.to_ctx();
     let fully_qualified_name = item_path.get_fully_qualified_name();
@@ -213,6 +260,7 @@ impl<'a> RustStructMemberEntry<'a> {
         gctx,
         layout_size,
         is_directly_sharable,
+        is_vertex_input,
       ),
     );
     state.members
@@ -223,13 +271,35 @@ pub struct RustStructBuilder<'a> {
   item_path: &'a RustItemPath,
   members: Vec<RustStructMemberEntry<'a>>,
   is_host_sharable: bool,
+  is_vertex_input: bool,
   has_rts_array: bool,
   naga_module: &'a naga::Module,
   layout: naga::proc::TypeLayout,
   options: &'a WgslBindgenOption,
+  docs: Option<&'a WgslDocs>,
 }
 
 impl<'a> RustStructBuilder<'a> {
+  /// Adds the WGSL doc comments of the struct and its members to the generated struct.
+  pub fn with_docs(mut self, docs: &'a WgslDocs) -> Self {
+    self.docs = Some(docs);
+    self
+  }
+
+  fn struct_docs(&self) -> TokenStream {
+    self
+      .docs
+      .map(|docs| docs.item_doc_attrs(&self.item_path.item_name))
+      .unwrap_or_default()
+  }
+
+  fn field_docs(&self, member_name: &str) -> TokenStream {
+    self
+      .docs
+      .map(|docs| docs.field_doc_attrs(&self.item_path.item_name, member_name))
+      .unwrap_or_default()
+  }
+
   fn name_ident(&self) -> Ident {
     Ident::new(&self.item_path.item_name.as_ref(), Span::call_site())
   }
@@ -402,6 +472,124 @@ impl<'a> RustStructBuilder<'a> {
     }
   }
 
+  fn build_struct_builder(&self) -> TokenStream {
+    let field_count = self.members.iter().filter(|m| m.is_field()).count();
+    let should_generate = match self.options.struct_builder_threshold {
+      Some(threshold) => field_count >= threshold,
+      None => false,
+    };
+
+    if !should_generate || self.uses_generics_for_rts() {
+      return quote!();
+    }
+
+    let struct_name = self.name_ident();
+    let builder_name = format_ident!("{}Builder", self.item_path.item_name.as_str());
+
+    let mut builder_members = Vec::new();
+    let mut setters = Vec::new();
+    let mut member_assignments = Vec::new();
+
+    for entry in &self.members {
+      match entry {
+        RustStructMemberEntry::Field(field) => {
+          let name = &field.name_ident;
+          let ty = &field.rust_type;
+          let missing = format!("{}::{} is not set", struct_name, name);
+
+          builder_members.push(quote!(#name: Option<#ty>));
+          setters.push(quote! {
+            pub fn #name(mut self, #name: #ty) -> Self {
+              self.#name = Some(#name);
+              self
+            }
+          });
+          member_assignments.push(quote!(#name: self.#name.ok_or(#missing)?));
+        }
+        RustStructMemberEntry::Padding(padding) => {
+          member_assignments.push(padding.generate_member_instantiate())
+        }
+      }
+    }
+
+    // The builder only holds the fields, so it derives the `Debug` and `Clone` of the struct
+    // when the struct has them, eg: not for `Debug` removed with `removed_derives`.
+    let struct_derives: Vec<_> =
+      self.build_derives().iter().map(|d| d.to_string()).collect();
+    let derives = ["Debug", "Default", "Clone"]
+      .into_iter()
+      .filter(|d| *d == "Default" || struct_derives.iter().any(|s| s == d))
+      .map(|d| format_ident!("{d}"));
+
+    quote! {
+      #[derive(#(#derives),*)]
+      pub struct #builder_name {
+        #(#builder_members),*
+      }
+
+      impl #builder_name {
+        #(#setters)*
+
+        pub fn build(self) -> Result<#struct_name, &'static str> {
+          Ok(#struct_name {
+            #(#member_assignments),*
+          })
+        }
+      }
+
+      impl #struct_name {
+        pub fn builder() -> #builder_name {
+          #builder_name::default()
+        }
+      }
+    }
+  }
+
+  fn build_vertex_conversions(&self) -> TokenStream {
+    if !self.options.vertex_struct_conversions
+      || !self.is_vertex_input
+      || self.has_rts_array
+    {
+      return quote!();
+    }
+
+    let struct_name = self.name_ident();
+
+    let mut names = Vec::new();
+    let mut types = Vec::new();
+    let mut member_assignments = Vec::new();
+
+    for entry in &self.members {
+      match entry {
+        RustStructMemberEntry::Field(field) => {
+          let name = &field.name_ident;
+          names.push(name.clone());
+          types.push(field.rust_type.clone());
+          member_assignments.push(quote!(#name));
+        }
+        RustStructMemberEntry::Padding(padding) => {
+          member_assignments.push(padding.generate_member_instantiate())
+        }
+      }
+    }
+
+    quote! {
+      impl From<(#(#types,)*)> for #struct_name {
+        fn from((#(#names,)*): (#(#types,)*)) -> Self {
+          Self {
+            #(#member_assignments),*
+          }
+        }
+      }
+
+      impl From<#struct_name> for (#(#types,)*) {
+        fn from(value: #struct_name) -> Self {
+          (#(value.#names,)*)
+        }
+      }
+    }
+  }
+
   fn build_fields(&self) -> Vec<TokenStream> {
     let gctx = self.naga_module.to_ctx();
     let members = self
@@ -417,6 +605,7 @@ impl<'a> RustStructBuilder<'a> {
             naga_type,
           } = field;
 
+          let wgsl_docs = self.field_docs(&name.to_string());
           let doc_comment = if self.is_directly_shareable() {
             let offset = member.offset;
             let size = naga_type.inner.size(gctx);
@@ -440,12 +629,15 @@ impl<'a> RustStructBuilder<'a> {
           };
 
           quote! {
+            #wgsl_docs
             #doc_comment
             #runtime_size_attribute
             pub #name: #rust_type
           }
         }
-        RustStructMemberEntry::Padding(padding) => padding.generate_member_definition(),
+        RustStructMemberEntry::Padding(padding) => {
+          padding.generate_member_definition(&self.options.padding_field)
+        }
       })
       .collect::<Vec<_>>();
 
@@ -469,10 +661,27 @@ impl<'a> RustStructBuilder<'a> {
         derives.push(quote!(encase::ShaderType));
       }
     }
-    if self.options.derive_serde {
+    let fully_qualified_name = self.item_path.get_fully_qualified_name();
+    if self.options.derive_serde_for(&fully_qualified_name) {
       derives.push(quote!(serde::Serialize));
       derives.push(quote!(serde::Deserialize));
     }
+
+    let matching_derives = |custom_derives: &[CustomDerive]| {
+      custom_derives
+        .iter()
+        .filter(|d| d.struct_regex.is_match(&fully_qualified_name))
+        .map(|d| d.derive.clone())
+        .collect::<Vec<_>>()
+    };
+
+    derives.extend(matching_derives(&self.options.custom_derives));
+
+    let removed_derives: Vec<_> = matching_derives(&self.options.removed_derives)
+      .iter()
+      .map(ToString::to_string)
+      .collect();
+    derives.retain(|derive| !removed_derives.contains(&derive.to_string()));
     derives
   }
 
@@ -490,6 +699,7 @@ impl<'a> RustStructBuilder<'a> {
       quote!(#fully_qualified_name)
     };
 
+    let core = core_crate(self.options);
     let assert_member_offsets: Vec<_> = self
       .members
       .iter()
@@ -500,7 +710,7 @@ impl<'a> RustStructBuilder<'a> {
       .map(|m| {
         let m = m.naga_member;
         let name = Ident::new(m.name.as_ref().unwrap(), Span::call_site());
-        let rust_offset = quote!(std::mem::offset_of!(#struct_name, #name));
+        let rust_offset = quote!(#core::mem::offset_of!(#struct_name, #name));
         let wgsl_offset = Index::from(m.offset as usize);
         quote!(assert!(#rust_offset == #wgsl_offset);)
       })
@@ -523,7 +733,21 @@ impl<'a> RustStructBuilder<'a> {
       quote! {
         const #assertion_name: () = {
           #(#assert_member_offsets)*
-          assert!(std::mem::size_of::<#struct_name>() == #struct_size);
+          assert!(#core::mem::size_of::<#struct_name>() == #struct_size);
+        };
+      }
+    } else if self.uses_encase_layout_assertions() {
+      // Encase computes the WGSL layout itself, so compare its shader size with naga's.
+      let struct_size = Index::from(self.layout.size as usize);
+
+      let assertion_name = format_ident!(
+        "{}_ASSERTS",
+        sanitized_upper_snake_case(&fully_qualified_name_str)
+      );
+
+      quote! {
+        const #assertion_name: () = {
+          assert!(<#struct_name as encase::ShaderSize>::SHADER_SIZE.get() == #struct_size);
         };
       }
     } else {
@@ -531,6 +755,63 @@ impl<'a> RustStructBuilder<'a> {
     }
   }
 
+  fn build_vertex_format_assertion(&self) -> TokenStream {
+    if !self.options.vertex_format_assertions || !self.is_vertex_input {
+      return quote!();
+    }
+
+    let fully_qualified_name_str = self.item_path.get_fully_qualified_name();
+    let core = core_crate(self.options);
+    let assert_format_sizes: Vec<_> = self
+      .members
+      .iter()
+      .filter_map(|m| match m {
+        RustStructMemberEntry::Field(field) => Some(field),
+        RustStructMemberEntry::Padding(_) => None,
+      })
+      .filter(|field| {
+        matches!(
+          field.naga_member.binding,
+          Some(naga::Binding::Location { .. })
+        )
+      })
+      .map(|field| {
+        let rust_type = &field.rust_type;
+        let member_name = field.name_ident.to_string();
+        let formats = match self
+          .options
+          .vertex_format_override(&fully_qualified_name_str, &member_name)
+        {
+          Some(o) => vec![(o.format, 0)],
+          None => crate::wgsl::vertex_attribute_formats(field.naga_type),
+        }
+        .into_iter()
+        .map(|(format, _)| format_ident!("{format:?}"));
+        quote! {
+          assert!(#core::mem::size_of::<#rust_type>() as u64 >= #(wgpu::VertexFormat::#formats.size())+*);
+        }
+      })
+      .collect();
+
+    let assertion_name = format_ident!(
+      "{}_VERTEX_FORMAT_ASSERTS",
+      sanitized_upper_snake_case(&fully_qualified_name_str)
+    );
+
+    quote! {
+      const #assertion_name: () = {
+        #(#assert_format_sizes)*
+      };
+    }
+  }
+
+  fn uses_encase_layout_assertions(&self) -> bool {
+    self.options.encase_layout_assertions
+      && self.options.serialization_strategy == WgslTypeSerializeStrategy::Encase
+      && self.is_host_sharable
+      && !self.has_rts_array
+  }
+
   pub fn build_bytemuck_impls(&self) -> TokenStream {
     let struct_name_in_usage = self.fully_qualified_struct_name_in_usage_fragment();
     let impl_fragment = self.impl_trait_for_fragment();
@@ -545,6 +826,79 @@ impl<'a> RustStructBuilder<'a> {
     }
   }
 
+  fn custom_alignment(&self) -> Option<naga::proc::Alignment> {
+    let fully_qualified_name = self.item_path.get_fully_qualified_name();
+    let fully_qualified_name = fully_qualified_name.as_str();
+    self
+      .options
+      .override_struct_alignment
+      .iter()
+      .find_map(|struct_align| {
+        struct_align
+          .struct_regex
+          .is_match(fully_qualified_name)
+          .then_some(struct_align.alignment as u32)
+      })
+      .map(|align| naga::proc::Alignment::new(align))
+      .flatten()
+  }
+
+  /// Builds runtime checks of the Rust layout against the WGSL layout, pushing a
+  /// message to `errors` for each mismatch. Used for the `validate_layouts` function.
+  pub fn build_layout_validation(&self) -> TokenStream {
+    if !self.is_directly_shareable() {
+      return quote!();
+    }
+
+    let fully_qualified_name_str = self.item_path.get_fully_qualified_name();
+    let fully_qualified_name =
+      syn::parse_str::<TokenStream>(&fully_qualified_name_str).unwrap();
+    let struct_name = if self.uses_generics_for_rts() {
+      quote!(#fully_qualified_name<1>)
+    } else {
+      quote!(#fully_qualified_name)
+    };
+
+    let core = core_crate(self.options);
+    let offset_checks = self
+      .members
+      .iter()
+      .filter_map(|m| match m {
+        RustStructMemberEntry::Field(field) => Some(field),
+        RustStructMemberEntry::Padding(_) => None,
+      })
+      .map(|field| {
+        let name = &field.name_ident;
+        let wgsl_offset = Index::from(field.naga_member.offset as usize);
+        let message = format!(
+          "{}::{} has offset {{}} but WGSL expects {{}}",
+          fully_qualified_name_str, name
+        );
+        quote! {
+          let offset = #core::mem::offset_of!(#struct_name, #name);
+          if offset != #wgsl_offset {
+            errors.push(format!(#message, offset, #wgsl_offset));
+          }
+        }
+      });
+
+    let struct_size = self
+      .custom_alignment()
+      .map(|alignment| alignment.round_up(self.layout.size))
+      .unwrap_or(self.layout.size) as usize;
+    let struct_size = Index::from(struct_size);
+    let size_message =
+      format!("{} has size {{}} but WGSL expects {{}}", fully_qualified_name_str);
+
+    quote! {
+      #(#offset_checks)*
+      let size = #core::mem::size_of::<#struct_name>();
+      if size != #struct_size {
+        errors.push(format!(#size_message, size, #struct_size));
+      }
+    }
+  }
+
   pub fn build(&self) -> Vec<RustItem> {
     let struct_name_def = self.struct_name_in_definition_fragment();
 
@@ -562,20 +916,7 @@ impl<'a> RustStructBuilder<'a> {
 
     let derives = self.build_derives();
 
-    let fully_qualified_name = self.item_path.get_fully_qualified_name();
-    let fully_qualified_name = fully_qualified_name.as_str();
-    let custom_alignment = self
-      .options
-      .override_struct_alignment
-      .iter()
-      .find_map(|struct_align| {
-        struct_align
-          .struct_regex
-          .is_match(fully_qualified_name)
-          .then_some(struct_align.alignment as u32)
-      })
-      .map(|align| naga::proc::Alignment::new(align))
-      .flatten();
+    let custom_alignment = self.custom_alignment();
 
     let alignment = custom_alignment.unwrap_or(self.layout.alignment) * 1u32;
     let alignment = Index::from(alignment as usize);
@@ -589,10 +930,14 @@ impl<'a> RustStructBuilder<'a> {
       quote!()
     };
 
+    let struct_docs = self.struct_docs();
     let fields = self.build_fields();
     let struct_new_fn = self.build_fn_new();
     let init_struct = self.build_init_struct();
+    let struct_builder = self.build_struct_builder();
+    let vertex_conversions = self.build_vertex_conversions();
     let assert_layout = self.build_layout_assertion(custom_alignment);
+    let assert_vertex_formats = self.build_vertex_format_assertion();
     let unsafe_bytemuck_pod_impl = self.build_bytemuck_impls();
     let fully_qualified_name = self.item_path.get_fully_qualified_name();
 
@@ -601,6 +946,7 @@ impl<'a> RustStructBuilder<'a> {
         RustItemKind::Any,
         self.item_path.clone(),
         quote! {
+          #struct_docs
           #repr_c
           #[derive(#(#derives),*)]
           pub struct #struct_name_def {
@@ -609,12 +955,17 @@ impl<'a> RustStructBuilder<'a> {
 
           #struct_new_fn
           #init_struct
+          #struct_builder
+          #vertex_conversions
         },
       ),
       RustItem::new(
         RustItemKind::Any,
         RustItemPath::new(MOD_STRUCT_ASSERTIONS.into(), fully_qualified_name.clone()),
-        assert_layout,
+        quote! {
+          #assert_layout
+          #assert_vertex_formats
+        },
       ),
       RustItem::new(
         RustItemKind::Any,
@@ -632,6 +983,7 @@ impl<'a> RustStructBuilder<'a> {
     layout: naga::proc::TypeLayout,
     is_directly_sharable: bool,
     is_host_sharable: bool,
+    is_vertex_input: bool,
     has_rts_array: bool,
   ) -> Self {
     let members = RustStructMemberEntry::from_naga(
@@ -641,16 +993,19 @@ impl<'a> RustStructBuilder<'a> {
       naga_module,
       layout.size as usize,
       is_directly_sharable,
+      is_vertex_input,
     );
 
     RustStructBuilder {
       item_path,
       members,
       is_host_sharable,
+      is_vertex_input,
       naga_module,
       options: &options,
       has_rts_array,
       layout,
+      docs: None,
     }
   }
 }
@@ -1,11 +1,13 @@
 use naga::{Scalar, ScalarKind, VectorSize};
 use proc_macro2::TokenStream;
-use quote::{quote, ToTokens};
+use quote::{format_ident, quote, ToTokens};
 use strum::IntoEnumIterator;
-use syn::Index;
+use syn::{Ident, Index};
 
 use crate::bevy_util::demangle_str;
-use crate::quote_gen::demangle_and_qualify;
+use crate::quote_gen::{
+  core_crate, demangle_and_qualify, MOD_PADDED_MATRICES, MOD_REFERENCE_ROOT,
+};
 use crate::wgsl_type::WgslBuiltInMappedType;
 use crate::{
   WgslBindgenOption, WgslMatType, WgslType, WgslTypeAlignmentAndSize,
@@ -52,10 +54,11 @@ pub(crate) fn custom_vector_matrix_assertions(
 
     let alignment = Index::from(ty.alignment_value());
     let aligned_size = Index::from(ty.aligned_size()?);
+    let core = core_crate(options);
 
     Some(quote! {
-      assert!(std::mem::size_of::<#ty>() == #aligned_size);
-      assert!(std::mem::align_of::<#ty>() == #alignment);
+      assert!(#core::mem::size_of::<#ty>() == #aligned_size);
+      assert!(#core::mem::align_of::<#ty>() == #alignment);
     })
   }
 
@@ -69,6 +72,81 @@ pub(crate) fn custom_vector_matrix_assertions(
   })
 }
 
+fn uses_padded_matrix_wrappers(options: &WgslBindgenOption) -> bool {
+  options.padded_matrix_wrappers
+    && options.serialization_strategy == WgslTypeSerializeStrategy::Bytemuck
+}
+
+fn padded_matrix_ident(columns: usize) -> Ident {
+  format_ident!("PaddedMat{}x3f", columns)
+}
+
+/// Generates the padded `matCx3<f32>` wrapper types of the `padded_matrices` module, used in place
+/// of the type map when `padded_matrix_wrappers` is enabled. Each column is padded to 16 bytes as
+/// in WGSL.
+pub(crate) fn padded_matrix_types(options: &WgslBindgenOption) -> Option<TokenStream> {
+  if !uses_padded_matrix_wrappers(options) {
+    return None;
+  }
+
+  let core = core_crate(options);
+  let types = [2usize, 3, 4].map(|columns| {
+    let name = padded_matrix_ident(columns);
+    let cols = Index::from(columns);
+    let size = Index::from(columns * 16);
+    let assertion_name = format_ident!("PADDED_MAT{}X3F_ASSERTS", columns);
+
+    let padded_cols = (0..columns)
+      .map(Index::from)
+      .map(|i| quote!([cols[#i][0], cols[#i][1], cols[#i][2], 0.0]));
+    let unpadded_cols = (0..columns)
+      .map(Index::from)
+      .map(|i| quote!([self.cols[#i][0], self.cols[#i][1], self.cols[#i][2]]));
+
+    quote! {
+      #[repr(C, align(16))]
+      #[derive(Debug, Default, PartialEq, Clone, Copy)]
+      pub struct #name {
+        pub cols: [[f32; 4]; #cols],
+      }
+
+      impl #name {
+        pub const fn from_cols(cols: [[f32; 3]; #cols]) -> Self {
+          Self {
+            cols: [#(#padded_cols),*],
+          }
+        }
+
+        pub const fn to_cols(&self) -> [[f32; 3]; #cols] {
+          [#(#unpadded_cols),*]
+        }
+      }
+
+      impl From<[[f32; 3]; #cols]> for #name {
+        fn from(cols: [[f32; 3]; #cols]) -> Self {
+          Self::from_cols(cols)
+        }
+      }
+
+      impl From<#name> for [[f32; 3]; #cols] {
+        fn from(value: #name) -> Self {
+          value.to_cols()
+        }
+      }
+
+      unsafe impl bytemuck::Zeroable for #name {}
+      unsafe impl bytemuck::Pod for #name {}
+
+      const #assertion_name: () = {
+        assert!(#core::mem::size_of::<#name>() == #size);
+        assert!(#core::mem::align_of::<#name>() == 16);
+      };
+    }
+  });
+
+  Some(quote!(#(#types)*))
+}
+
 #[allow(non_snake_case)]
 pub(crate) const fn RustTypeInfo(
   tokens: TokenStream,
@@ -224,7 +302,9 @@ pub(crate) fn rust_type(
 ) -> RustTypeInfo {
   let t_handle = module.types.get(ty).unwrap();
   let mut layouter = naga::proc::Layouter::default();
-  layouter.update(module.to_ctx()).unwrap();
+  layouter
+    .update(module.to_ctx())
+    .expect("type layouts are checked by `wgsl::type_layouter`");
 
   let type_layout = layouter[t_handle];
 
@@ -251,6 +331,20 @@ pub(crate) fn rust_type(
         RustTypeInfo(quote!([#inner_type; #len]), stride as usize, alignment)
       }
     }
+    naga::TypeInner::Matrix {
+      columns,
+      rows: VectorSize::Tri,
+      scalar: Scalar {
+        kind: ScalarKind::Float,
+        width: 4,
+      },
+    } if uses_padded_matrix_wrappers(options) => {
+      let columns = *columns as usize;
+      let name = padded_matrix_ident(columns);
+      let root = Ident::new(MOD_REFERENCE_ROOT, proc_macro2::Span::call_site());
+      let module = Ident::new(MOD_PADDED_MATRICES, proc_macro2::Span::call_site());
+      RustTypeInfo(quote!(#root::#module::#name), columns * 16, alignment)
+    }
     naga::TypeInner::Matrix {
       columns,
       rows,
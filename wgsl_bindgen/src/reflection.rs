@@ -0,0 +1,420 @@
+//! The data collected from the shaders to generate the bindings, for tooling like editors
+//! and asset pipelines which would otherwise need to parse the generated Rust code.
+
+use crate::generate::bind_group;
+use crate::quote_gen::RustItemPath;
+use crate::{wgsl, CreateModuleError, WgslBindgenOption, WgslEntryResult};
+
+/// The reflection data of all the entry shaders, returned by [`crate::WGSLBindgen::reflect`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct ShaderReflection {
+  pub entries: Vec<ShaderEntryReflection>,
+}
+
+/// The reflection data of a single entry shader and its imports.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct ShaderEntryReflection {
+  /// The name of the generated module, eg: `triangle` for `shaders/triangle.wgsl`.
+  pub mod_name: String,
+  pub source_path: String,
+  pub entry_points: Vec<EntryPointReflection>,
+  pub bind_groups: Vec<BindGroupReflection>,
+  pub structs: Vec<StructReflection>,
+  pub vertex_inputs: Vec<VertexInputReflection>,
+  #[cfg_attr(feature = "json", serde(serialize_with = "json::serialize_features"))]
+  pub required_features: wgpu::Features,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct EntryPointReflection {
+  pub name: String,
+  #[cfg_attr(feature = "json", serde(serialize_with = "json::serialize_stage"))]
+  pub stage: wgpu::ShaderStages,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct BindGroupReflection {
+  pub group: u32,
+  pub bindings: Vec<BindingReflection>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct BindingReflection {
+  pub binding: u32,
+  pub name: String,
+  /// The address space as written in WGSL, eg: `<uniform>` or `<storage, read>`.
+  /// Empty for handles like textures and samplers.
+  pub address_space: String,
+  /// The type as written in WGSL, eg: `Camera` or `texture_2d<f32>`.
+  pub type_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct StructReflection {
+  /// The fully qualified name, eg: `types::Camera`.
+  pub name: String,
+  pub size: u32,
+  pub alignment: u32,
+  pub members: Vec<StructMemberReflection>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct StructMemberReflection {
+  pub name: String,
+  pub offset: u32,
+  pub size: u32,
+  pub type_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct VertexInputReflection {
+  pub name: String,
+  pub attributes: Vec<VertexAttributeReflection>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct VertexAttributeReflection {
+  pub location: u32,
+  pub name: String,
+  #[cfg_attr(
+    feature = "json",
+    serde(serialize_with = "json::serialize_vertex_format")
+  )]
+  pub format: wgpu::VertexFormat,
+  pub offset: u32,
+}
+
+pub(crate) fn entry_reflection(
+  entry: &WgslEntryResult,
+  options: &WgslBindgenOption,
+) -> Result<ShaderEntryReflection, CreateModuleError> {
+  let module = &entry.naga_module;
+  let mod_name = &entry.mod_name;
+
+  let entry_points = module
+    .entry_points
+    .iter()
+    .map(|entry_point| EntryPointReflection {
+      name: entry_point.name.clone(),
+      stage: match entry_point.stage {
+        naga::ShaderStage::Vertex => wgpu::ShaderStages::VERTEX,
+        naga::ShaderStage::Fragment => wgpu::ShaderStages::FRAGMENT,
+        naga::ShaderStage::Compute => wgpu::ShaderStages::COMPUTE,
+      },
+    })
+    .collect();
+
+  let bind_groups = bind_group::get_bind_group_data(module)?
+    .into_iter()
+    .map(|(group, data)| BindGroupReflection {
+      group,
+      bindings: data
+        .bindings
+        .iter()
+        .map(|binding| BindingReflection {
+          binding: binding.binding_index,
+          name: demangled_name(binding.name.as_deref().unwrap_or_default(), mod_name),
+          address_space: wgsl::address_space_to_wgsl(binding.address_space),
+          type_name: binding.binding_type_name.clone(),
+        })
+        .collect(),
+    })
+    .collect();
+
+  let layouter = wgsl::type_layouter(mod_name, module)?;
+
+  let structs = module
+    .types
+    .iter()
+    .filter_map(|(handle, ty)| match (&ty.name, &ty.inner) {
+      (Some(name), naga::TypeInner::Struct { members, .. }) => Some(StructReflection {
+        name: demangled_name(name, mod_name),
+        size: layouter[handle].size,
+        alignment: layouter[handle].alignment.round_up(1),
+        members: members
+          .iter()
+          .map(|member| StructMemberReflection {
+            name: member.name.clone().unwrap_or_default(),
+            offset: member.offset,
+            size: layouter[member.ty].size,
+            type_name: wgsl::type_to_wgsl(module, &module.types[member.ty]),
+          })
+          .collect(),
+      }),
+      _ => None,
+    })
+    .collect();
+
+  wgsl::check_vertex_inputs(mod_name, module, options)?;
+  let vertex_inputs = wgsl::get_vertex_input_structs(mod_name, module)?
+    .into_iter()
+    .map(|input| {
+      let input_name =
+        RustItemPath::from_mangled(&input.name, mod_name).get_fully_qualified_name();
+      VertexInputReflection {
+        name: demangled_name(&input.name, mod_name),
+        attributes: input
+          .fields
+          .iter()
+          .flat_map(|(location, member)| {
+            // Matrices use one attribute per column at consecutive locations.
+            crate::vertex_field_formats(module, options, &input_name, member)
+              .into_iter()
+              .enumerate()
+              .map(|(i, (format, column_offset))| VertexAttributeReflection {
+                location: *location + i as u32,
+                name: member.name.clone().unwrap_or_default(),
+                format,
+                offset: member.offset + column_offset as u32,
+              })
+          })
+          .collect(),
+      }
+    })
+    .collect();
+
+  Ok(ShaderEntryReflection {
+    mod_name: mod_name.clone(),
+    source_path: entry
+      .source_including_deps
+      .source_file
+      .file_path
+      .to_string(),
+    entry_points,
+    bind_groups,
+    structs,
+    vertex_inputs,
+    required_features: wgsl::required_features(module),
+  })
+}
+
+impl ShaderReflection {
+  /// Serializes the reflection data as pretty printed JSON, for tools which aren't written in Rust.
+  #[cfg(feature = "json")]
+  pub fn to_json(&self) -> String {
+    serde_json::to_string_pretty(self).unwrap()
+  }
+}
+
+/// The `wgpu` types are serialized with the names used in WGSL and WebGPU.
+#[cfg(feature = "json")]
+mod json {
+  use serde::ser::SerializeSeq;
+  use serde::Serializer;
+
+  /// Serializes the features as a list of their lowercase names, eg: `["dual_source_blending"]`.
+  pub fn serialize_features<S: Serializer>(
+    features: &wgpu::Features,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(None)?;
+    for (name, _) in features.iter_names() {
+      seq.serialize_element(&name.to_lowercase())?;
+    }
+    seq.end()
+  }
+
+  /// Serializes the stage by its lowercase name, eg: `"vertex"`.
+  pub fn serialize_stage<S: Serializer>(
+    stage: &wgpu::ShaderStages,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    let name = stage
+      .iter_names()
+      .map(|(name, _)| name)
+      .next()
+      .unwrap_or_default();
+    serializer.serialize_str(&name.to_lowercase())
+  }
+
+  pub fn serialize_vertex_format<S: Serializer>(
+    format: &wgpu::VertexFormat,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("{format:?}").to_lowercase())
+  }
+}
+
+fn demangled_name(name: &str, mod_name: &str) -> String {
+  RustItemPath::from_mangled(name, mod_name)
+    .get_fully_qualified_name()
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use indoc::indoc;
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+  use crate::bevy_util::source_file::SourceFile;
+  use crate::{SourceFilePath, SourceWithFullDependenciesResult};
+
+  #[test]
+  fn reflect_bind_groups_structs_and_vertex_inputs() {
+    let source = indoc! {r#"
+        struct Camera {
+            view_proj: mat4x4<f32>,
+            position: vec3<f32>,
+        };
+
+        struct VertexInput {
+            @location(0) position: vec3<f32>,
+            @location(1) uv: vec2<f32>,
+        };
+
+        @group(0) @binding(0) var<uniform> camera: Camera;
+        @group(0) @binding(1) var color_texture: texture_2d<f32>;
+
+        @vertex
+        fn vs_main(in: VertexInput) -> @builtin(position) vec4<f32> {
+            return camera.view_proj * vec4(in.position, 1.0);
+        }
+    "#};
+
+    let source_file =
+      SourceFile::create(SourceFilePath::new("shader.wgsl"), None, source.to_string());
+    let entry = WgslEntryResult {
+      mod_name: "shader".to_string(),
+      naga_module: naga::front::wgsl::parse_str(source).unwrap(),
+      source_including_deps: SourceWithFullDependenciesResult {
+        source_file: &source_file,
+        full_dependencies: Default::default(),
+      },
+    };
+
+    let reflection = entry_reflection(&entry, &WgslBindgenOption::default()).unwrap();
+
+    assert_eq!(
+      vec![EntryPointReflection {
+        name: "vs_main".to_string(),
+        stage: wgpu::ShaderStages::VERTEX,
+      }],
+      reflection.entry_points
+    );
+    assert_eq!(
+      vec![BindGroupReflection {
+        group: 0,
+        bindings: vec![
+          BindingReflection {
+            binding: 0,
+            name: "shader::camera".to_string(),
+            address_space: "<uniform>".to_string(),
+            type_name: "Camera".to_string(),
+          },
+          BindingReflection {
+            binding: 1,
+            name: "shader::color_texture".to_string(),
+            address_space: String::new(),
+            type_name: "texture_2d<f32>".to_string(),
+          },
+        ],
+      }],
+      reflection.bind_groups
+    );
+
+    let camera = &reflection.structs[0];
+    assert_eq!("shader::Camera", camera.name);
+    assert_eq!((80, 16), (camera.size, camera.alignment));
+    assert_eq!(64, camera.members[1].offset);
+
+    let vertex_input = &reflection.vertex_inputs[0];
+    assert_eq!("shader::VertexInput", vertex_input.name);
+    assert_eq!(
+      VertexAttributeReflection {
+        location: 1,
+        name: "uv".to_string(),
+        format: wgpu::VertexFormat::Float32x2,
+        offset: 16,
+      },
+      vertex_input.attributes[1]
+    );
+    assert_eq!(wgpu::Features::empty(), reflection.required_features);
+  }
+
+  #[test]
+  fn reflect_matrix_vertex_inputs() {
+    let source = indoc! {r#"
+        struct InstanceInput {
+            @location(0) model_0: vec4<f32>,
+            @location(1) transform: mat4x4<f32>,
+        };
+
+        @vertex
+        fn vs_main(instance: InstanceInput) -> @builtin(position) vec4<f32> {
+            return instance.transform * instance.model_0;
+        }
+    "#};
+
+    let source_file =
+      SourceFile::create(SourceFilePath::new("shader.wgsl"), None, source.to_string());
+    let entry = WgslEntryResult {
+      mod_name: "shader".to_string(),
+      naga_module: naga::front::wgsl::parse_str(source).unwrap(),
+      source_including_deps: SourceWithFullDependenciesResult {
+        source_file: &source_file,
+        full_dependencies: Default::default(),
+      },
+    };
+
+    let reflection = entry_reflection(&entry, &WgslBindgenOption::default()).unwrap();
+
+    let attribute = |location, name: &str, offset| VertexAttributeReflection {
+      location,
+      name: name.to_string(),
+      format: wgpu::VertexFormat::Float32x4,
+      offset,
+    };
+    assert_eq!(
+      vec![
+        attribute(0, "model_0", 0),
+        attribute(1, "transform", 16),
+        attribute(2, "transform", 32),
+        attribute(3, "transform", 48),
+        attribute(4, "transform", 64),
+      ],
+      reflection.vertex_inputs[0].attributes
+    );
+  }
+
+  #[cfg(feature = "json")]
+  #[test]
+  fn reflection_to_json() {
+    let reflection = ShaderReflection {
+      entries: vec![ShaderEntryReflection {
+        mod_name: "shader".to_string(),
+        source_path: "shader.wgsl".to_string(),
+        entry_points: vec![EntryPointReflection {
+          name: "fs_main".to_string(),
+          stage: wgpu::ShaderStages::FRAGMENT,
+        }],
+        bind_groups: Vec::new(),
+        structs: Vec::new(),
+        vertex_inputs: vec![VertexInputReflection {
+          name: "shader::VertexInput".to_string(),
+          attributes: vec![VertexAttributeReflection {
+            location: 0,
+            name: "position".to_string(),
+            format: wgpu::VertexFormat::Float32x3,
+            offset: 0,
+          }],
+        }],
+        required_features: wgpu::Features::DUAL_SOURCE_BLENDING,
+      }],
+    };
+
+    let json = reflection.to_json();
+    assert!(json.contains(r#""stage": "fragment""#));
+    assert!(json.contains(r#""format": "float32x3""#));
+    assert!(json.contains(r#""dual_source_blending""#));
+  }
+}
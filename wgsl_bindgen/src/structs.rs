@@ -1,18 +1,135 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use naga::{Handle, Type};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use smol_str::SmolStr;
 
-use crate::quote_gen::{RustItem, RustItemPath, RustStructBuilder};
-use crate::{WgslBindgenOption, WgslTypeSerializeStrategy};
+use crate::quote_gen::{
+  mod_reference_root, RustItem, RustItemKind, RustItemPath, RustStructBuilder,
+  MOD_BYTEMUCK_IMPLS, MOD_SHARED_STRUCTS, MOD_STRUCT_ASSERTIONS,
+};
+use crate::wgsl_docs::WgslDocs;
+use crate::{wgsl, CreateModuleError, WgslBindgenOption, WgslTypeSerializeStrategy};
 
 pub fn structs_items(
   invoking_entry_module: &str,
   module: &naga::Module,
   options: &WgslBindgenOption,
+  docs: &WgslDocs,
+) -> Result<Vec<RustItem>, CreateModuleError> {
+  let items = visit_structs(invoking_entry_module, module, options, |builder| {
+    builder.with_docs(docs).build()
+  })?
+  .into_iter()
+  .flatten()
+  .chain(loose_vertex_input_items(invoking_entry_module, module, options)?)
+  .collect();
+  Ok(items)
+}
+
+/// Generates a struct per vertex entry for the vertex inputs that are entry point arguments
+/// instead of struct members.
+fn loose_vertex_input_items(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+) -> Result<Vec<RustItem>, CreateModuleError> {
+  let layouter = wgsl::type_layouter(invoking_entry_module, module)?;
+
+  let mut items = Vec::new();
+  for vertex_entry in module
+    .entry_points
+    .iter()
+    .filter(|e| e.stage == naga::ShaderStage::Vertex)
+  {
+    if let Some(input) =
+      wgsl::get_loose_vertex_input(invoking_entry_module, module, vertex_entry)?
+    {
+      items.extend(loose_vertex_input_struct(
+        invoking_entry_module,
+        module,
+        options,
+        &layouter,
+        input,
+      ));
+    }
+  }
+  Ok(items)
+}
+
+fn loose_vertex_input_struct(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+  layouter: &naga::proc::Layouter,
+  input: wgsl::VertexInput,
 ) -> Vec<RustItem> {
+  let members = input
+    .fields
+    .into_iter()
+    .map(|(_, member)| member)
+    .collect::<Vec<_>>();
+
+  let alignment = members
+    .iter()
+    .map(|m| layouter[m.ty].alignment)
+    .max()
+    .unwrap_or(naga::proc::Alignment::ONE);
+  let size = members
+    .last()
+    .map(|m| m.offset + layouter[m.ty].size)
+    .unwrap_or_default();
+  let layout = naga::proc::TypeLayout {
+    size: alignment.round_up(size),
+    alignment,
+  };
+
+  let item_path = RustItemPath::new(invoking_entry_module.into(), input.name.into());
+  let builder = RustStructBuilder::from_naga(
+    &item_path, &members, module, options, layout, false, false, true, false,
+  );
+  builder.build()
+}
+
+/// Generates a `validate_layouts` function that checks the Rust layout of all
+/// directly shareable structs against the WGSL layout at runtime.
+pub fn layout_validation_fn(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+) -> Result<TokenStream, CreateModuleError> {
+  if !options.generate_layout_validation
+    || options.serialization_strategy != WgslTypeSerializeStrategy::Bytemuck
+  {
+    return Ok(quote!());
+  }
+
+  let checks = visit_structs(invoking_entry_module, module, options, |builder| {
+    builder.build_layout_validation()
+  })?;
+
+  Ok(quote! {
+    pub fn validate_layouts() -> Result<(), Vec<String>> {
+      let mut errors = Vec::new();
+      #(#checks)*
+      if errors.is_empty() {
+        Ok(())
+      } else {
+        Err(errors)
+      }
+    }
+  })
+}
+
+fn visit_structs<T>(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+  visit: impl Fn(RustStructBuilder) -> T,
+) -> Result<Vec<T>, CreateModuleError> {
   // Initialize the layout calculator provided by naga.
-  let mut layouter = naga::proc::Layouter::default();
-  layouter.update(module.to_ctx()).unwrap();
+  let layouter = wgsl::type_layouter(invoking_entry_module, module)?;
 
   let mut global_variable_types = HashSet::new();
   for g in module.global_variables.iter() {
@@ -21,7 +138,7 @@ pub fn structs_items(
 
   // Create matching Rust structs for WGSL structs.
   // This is a UniqueArena, so each struct will only be generated once.
-  module
+  let structs = module
     .types
     .iter()
     .filter(|(h, _)| {
@@ -39,7 +156,7 @@ pub fn structs_items(
           .any(|e| e.function.arguments.iter().any(|a| a.ty == *h))
         || global_variable_types.contains(h)
     })
-    .flat_map(|(t_handle, ty)| {
+    .filter_map(|(t_handle, ty)| {
       if let naga::TypeInner::Struct { members, .. } = &ty.inner {
         let rust_item_path =
           RustItemPath::from_mangled(ty.name.as_ref().unwrap(), invoking_entry_module);
@@ -48,9 +165,9 @@ pub fn structs_items(
         if options.type_map.contains_key(&crate::WgslType::Struct {
           fully_qualified_name: rust_item_path.get_fully_qualified_name().into(),
         }) {
-          Vec::new()
+          None
         } else {
-          rust_struct(
+          let builder = rust_struct_builder(
             &rust_item_path,
             members,
             &layouter,
@@ -58,24 +175,26 @@ pub fn structs_items(
             module,
             options,
             &global_variable_types,
-          )
+          );
+          Some(visit(builder))
         }
       } else {
-        Vec::new()
+        None
       }
     })
-    .collect()
+    .collect();
+  Ok(structs)
 }
 
-fn rust_struct(
-  rust_item_path: &RustItemPath,
-  naga_members: &[naga::StructMember],
+fn rust_struct_builder<'a>(
+  rust_item_path: &'a RustItemPath,
+  naga_members: &'a [naga::StructMember],
   layouter: &naga::proc::Layouter,
   t_handle: naga::Handle<naga::Type>,
-  naga_module: &naga::Module,
-  options: &WgslBindgenOption,
+  naga_module: &'a naga::Module,
+  options: &'a WgslBindgenOption,
   global_variable_types: &HashSet<Handle<Type>>,
-) -> Vec<RustItem> {
+) -> RustStructBuilder<'a> {
   let layout = layouter[t_handle];
 
   // Assume types used in global variables are host shareable and require validation.
@@ -86,12 +205,17 @@ fn rust_struct(
   // This allows vertex input field types without padding like vec3 for positions.
   let is_host_sharable = global_variable_types.contains(&t_handle);
 
+  let is_vertex_input = naga_module.entry_points.iter().any(|e| {
+    e.stage == naga::ShaderStage::Vertex
+      && e.function.arguments.iter().any(|a| a.ty == t_handle)
+  });
+
   let has_rts_array = struct_has_rts_array_member(naga_members, naga_module);
   let is_directly_sharable = options.serialization_strategy
     == WgslTypeSerializeStrategy::Bytemuck
     && is_host_sharable;
 
-  let builder = RustStructBuilder::from_naga(
+  RustStructBuilder::from_naga(
     rust_item_path,
     naga_members,
     naga_module,
@@ -99,9 +223,137 @@ fn rust_struct(
     layout,
     is_directly_sharable,
     is_host_sharable,
+    is_vertex_input,
     has_rts_array,
-  );
-  builder.build()
+  )
+}
+
+/// Finds the structs that are defined with the same generated contents in more than one
+/// entry module. Structs imported from other modules are already generated only once.
+pub fn shared_struct_names(
+  modules: &[(&naga::Module, &WgslDocs, &WgslBindgenOption)],
+) -> Result<HashSet<SmolStr>, CreateModuleError> {
+  let mut contents = HashMap::<SmolStr, Vec<String>>::new();
+  let mut nested_structs = HashMap::<SmolStr, HashSet<SmolStr>>::new();
+
+  for (module, docs, options) in modules {
+    for item in structs_items(MOD_SHARED_STRUCTS, module, options, docs)? {
+      if item.path.parent_module_path == MOD_SHARED_STRUCTS {
+        let content = item.item.to_string();
+        contents
+          .entry(item.path.item_name)
+          .or_default()
+          .push(content);
+      }
+    }
+
+    for (handle, ty) in module.types.iter() {
+      let (Some(name), naga::TypeInner::Struct { .. }) = (&ty.name, &ty.inner) else {
+        continue;
+      };
+      let mut types = HashSet::new();
+      add_types_recursive(&mut types, module, handle);
+      types.remove(&handle);
+
+      let nested = nested_structs.entry(name.into()).or_default();
+      for nested_ty in types {
+        let nested_ty = &module.types[nested_ty];
+        if let (Some(name), naga::TypeInner::Struct { .. }) =
+          (&nested_ty.name, &nested_ty.inner)
+        {
+          let path = RustItemPath::from_mangled(name, MOD_SHARED_STRUCTS);
+          if path.parent_module_path == MOD_SHARED_STRUCTS {
+            nested.insert(path.item_name);
+          }
+        }
+      }
+    }
+  }
+
+  let mut shared: HashSet<SmolStr> = contents
+    .into_iter()
+    .filter(|(_, contents)| {
+      contents.len() > 1 && contents.iter().all(|c| *c == contents[0])
+    })
+    .map(|(name, _)| name)
+    .collect();
+
+  // A shared struct can only reference structs which are shared as well.
+  loop {
+    let unshareable: Vec<SmolStr> = shared
+      .iter()
+      .filter(|name| {
+        nested_structs
+          .get(*name)
+          .is_some_and(|nested| !nested.is_subset(&shared))
+      })
+      .cloned()
+      .collect();
+    if unshareable.is_empty() {
+      return Ok(shared);
+    }
+    for name in unshareable {
+      shared.remove(&name);
+    }
+  }
+}
+
+/// Like [`structs_items`], but generates the `shared` structs in the shared structs module
+/// and re-exports them from the entry module.
+pub fn structs_items_with_shared(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+  docs: &WgslDocs,
+  shared: &HashSet<SmolStr>,
+) -> Result<Vec<RustItem>, CreateModuleError> {
+  let is_shared_item = |item: &RustItem, entry_module: &str| {
+    let path = &item.path;
+    if path.parent_module_path == entry_module {
+      shared.contains(&path.item_name)
+    } else if path.parent_module_path == MOD_STRUCT_ASSERTIONS
+      || path.parent_module_path == MOD_BYTEMUCK_IMPLS
+    {
+      path
+        .item_name
+        .strip_prefix(entry_module)
+        .and_then(|name| name.strip_prefix("::"))
+        .is_some_and(|name| shared.contains(name))
+    } else {
+      false
+    }
+  };
+
+  let (shared_items, local_items): (Vec<_>, Vec<_>) =
+    structs_items(invoking_entry_module, module, options, docs)?
+      .into_iter()
+      .partition(|item| is_shared_item(item, invoking_entry_module));
+
+  let root = mod_reference_root();
+  let shared_mod = format_ident!("{MOD_SHARED_STRUCTS}");
+  let reexports = shared_items
+    .iter()
+    .filter(|item| item.path.parent_module_path == invoking_entry_module)
+    .map(|item| {
+      let name = format_ident!("{}", item.path.item_name.as_str());
+      RustItem::new(
+        RustItemKind::Any,
+        item.path.clone(),
+        quote!(pub use #root::#shared_mod::#name;),
+      )
+    });
+
+  let shared_items = structs_items(MOD_SHARED_STRUCTS, module, options, docs)?
+    .into_iter()
+    .filter(|item| is_shared_item(item, MOD_SHARED_STRUCTS));
+
+  Ok(
+    local_items
+      .into_iter()
+      .chain(reexports.collect::<Vec<_>>())
+      .chain(shared_items)
+      .collect(),
+  )
 }
 
 fn add_types_recursive(
@@ -150,7 +402,8 @@ mod tests {
   use crate::*;
 
   pub fn structs(module: &naga::Module, options: &WgslBindgenOption) -> Vec<TokenStream> {
-    structs_items("", module, options)
+    structs_items("", module, options, &WgslDocs::default())
+      .unwrap()
       .into_iter()
       .map(|s| s.item)
       .collect()
@@ -1506,4 +1759,1084 @@ mod tests {
       actual
     );
   }
+
+  #[test]
+  fn write_struct_builder_above_threshold() {
+    let source = indoc! {r#"
+        struct Uniforms {
+            a: u32,
+            @size(8)
+            b: i32,
+            c: f32,
+        };
+        @group(0) @binding(0) var<uniform> u: Uniforms;
+      "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    let structs = structs(
+      &module,
+      &WgslBindgenOption {
+        serialization_strategy: WgslTypeSerializeStrategy::Bytemuck,
+        type_map: RustWgslTypeMap.build(WgslTypeSerializeStrategy::Bytemuck),
+        struct_builder_threshold: Some(3),
+        ..Default::default()
+      },
+    );
+    let actual = quote!(#(#structs)*);
+
+    assert_tokens_eq!(
+      quote! {
+        #[repr(C, align(4))]
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        pub struct Uniforms {
+            /// size: 4, offset: 0x0, type: `u32`
+            pub a: u32,
+            /// size: 4, offset: 0x4, type: `i32`
+            pub b: i32,
+            pub _pad_b: [u8; 0x8 - core::mem::size_of::<i32>()],
+            /// size: 4, offset: 0xC, type: `f32`
+            pub c: f32,
+        }
+        impl Uniforms {
+            pub const fn new(a: u32, b: i32, c: f32) -> Self {
+                Self {
+                    a,
+                    b,
+                    _pad_b: [0; 0x8 - core::mem::size_of::<i32>()],
+                    c,
+                }
+            }
+        }
+        #[repr(C)]
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        pub struct UniformsInit {
+            pub a: u32,
+            pub b: i32,
+            pub c: f32,
+        }
+        impl UniformsInit {
+            pub const fn build(&self) -> Uniforms {
+                Uniforms {
+                    a: self.a,
+                    b: self.b,
+                    _pad_b: [0; 0x8 - core::mem::size_of::<i32>()],
+                    c: self.c,
+                }
+            }
+        }
+        impl From<UniformsInit> for Uniforms {
+            fn from(data: UniformsInit) -> Self {
+                data.build()
+            }
+        }
+        #[derive(Debug, Default, Clone)]
+        pub struct UniformsBuilder {
+            a: Option<u32>,
+            b: Option<i32>,
+            c: Option<f32>,
+        }
+        impl UniformsBuilder {
+            pub fn a(mut self, a: u32) -> Self {
+                self.a = Some(a);
+                self
+            }
+            pub fn b(mut self, b: i32) -> Self {
+                self.b = Some(b);
+                self
+            }
+            pub fn c(mut self, c: f32) -> Self {
+                self.c = Some(c);
+                self
+            }
+            pub fn build(self) -> Result<Uniforms, &'static str> {
+                Ok(Uniforms {
+                    a: self.a.ok_or("Uniforms::a is not set")?,
+                    b: self.b.ok_or("Uniforms::b is not set")?,
+                    _pad_b: [0; 0x8 - core::mem::size_of::<i32>()],
+                    c: self.c.ok_or("Uniforms::c is not set")?,
+                })
+            }
+        }
+        impl Uniforms {
+            pub fn builder() -> UniformsBuilder {
+                UniformsBuilder::default()
+            }
+        }
+        const UNIFORMS_ASSERTS: () = {
+            assert!(std::mem::offset_of!(Uniforms, a) == 0);
+            assert!(std::mem::offset_of!(Uniforms, b) == 4);
+            assert!(std::mem::offset_of!(Uniforms, c) == 12);
+            assert!(std::mem::size_of::<Uniforms>() == 16);
+        };
+        unsafe impl bytemuck::Zeroable for Uniforms {}
+        unsafe impl bytemuck::Pod for Uniforms {}
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_vertex_struct_conversions() {
+    let source = indoc! {r#"
+        struct VertexInput {
+            @location(0) position: vec3<f32>,
+            @location(1) uv: vec2<f32>,
+        };
+
+        @vertex
+        fn main(in: VertexInput) -> @builtin(position) vec4<f32> {
+            return vec4(0.0);
+        }
+      "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    let structs = structs(
+      &module,
+      &WgslBindgenOption {
+        serialization_strategy: WgslTypeSerializeStrategy::Encase,
+        type_map: GlamWgslTypeMap.build(WgslTypeSerializeStrategy::Encase),
+        vertex_struct_conversions: true,
+        ..Default::default()
+      },
+    );
+    let actual = quote!(#(#structs)*);
+
+    assert_tokens_eq!(
+      quote! {
+        #[repr(C)]
+        #[derive(Debug, PartialEq, Clone, Copy, encase::ShaderType)]
+        pub struct VertexInput {
+            pub position: glam::Vec3A,
+            pub uv: glam::Vec2,
+        }
+        impl VertexInput {
+            pub const fn new(position: glam::Vec3A, uv: glam::Vec2) -> Self {
+                Self { position, uv }
+            }
+        }
+        impl From<(glam::Vec3A, glam::Vec2)> for VertexInput {
+            fn from((position, uv): (glam::Vec3A, glam::Vec2)) -> Self {
+                Self { position, uv }
+            }
+        }
+        impl From<VertexInput> for (glam::Vec3A, glam::Vec2) {
+            fn from(value: VertexInput) -> Self {
+                (value.position, value.uv)
+            }
+        }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_struct_custom_derives() {
+    let source = indoc! {r#"
+        struct VertexInput {
+            @location(0) position: vec3<f32>,
+        };
+
+        @vertex
+        fn main(in: VertexInput) -> @builtin(position) vec4<f32> {
+            return vec4(0.0);
+        }
+      "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    let structs = structs(
+      &module,
+      &WgslBindgenOption {
+        serialization_strategy: WgslTypeSerializeStrategy::Bytemuck,
+        type_map: RustWgslTypeMap.build(WgslTypeSerializeStrategy::Bytemuck),
+        custom_derives: vec![
+          (".*", quote!(Default)).into(),
+          ("Other", quote!(Hash)).into(),
+        ],
+        removed_derives: vec![("VertexInput", quote!(Debug)).into()],
+        ..Default::default()
+      },
+    );
+    let actual = quote!(#(#structs)*);
+
+    assert_tokens_eq!(
+      quote! {
+        #[repr(C)]
+        #[derive(PartialEq, Clone, Copy, Default)]
+        pub struct VertexInput {
+            pub position: [f32; 3],
+        }
+        impl VertexInput {
+            pub const fn new(position: [f32; 3]) -> Self {
+                Self { position }
+            }
+        }
+        unsafe impl bytemuck::Zeroable for VertexInput {}
+        unsafe impl bytemuck::Pod for VertexInput {}
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_struct_builder_removed_derives() {
+    let source = indoc! {r#"
+        struct VertexInput {
+            @location(0) position: vec3<f32>,
+        };
+
+        @vertex
+        fn main(in: VertexInput) -> @builtin(position) vec4<f32> {
+            return vec4(0.0);
+        }
+      "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    let structs = structs(
+      &module,
+      &WgslBindgenOption {
+        serialization_strategy: WgslTypeSerializeStrategy::Bytemuck,
+        type_map: RustWgslTypeMap.build(WgslTypeSerializeStrategy::Bytemuck),
+        struct_builder_threshold: Some(1),
+        removed_derives: vec![("VertexInput", quote!(Debug)).into()],
+        ..Default::default()
+      },
+    );
+    let actual = quote!(#(#structs)*);
+
+    assert_tokens_eq!(
+      quote! {
+        #[repr(C)]
+        #[derive(PartialEq, Clone, Copy)]
+        pub struct VertexInput {
+            pub position: [f32; 3],
+        }
+        impl VertexInput {
+            pub const fn new(position: [f32; 3]) -> Self {
+                Self { position }
+            }
+        }
+        #[derive(Default, Clone)]
+        pub struct VertexInputBuilder {
+            position: Option<[f32; 3]>,
+        }
+        impl VertexInputBuilder {
+            pub fn position(mut self, position: [f32; 3]) -> Self {
+                self.position = Some(position);
+                self
+            }
+            pub fn build(self) -> Result<VertexInput, &'static str> {
+                Ok(VertexInput {
+                    position: self.position.ok_or("VertexInput::position is not set")?,
+                })
+            }
+        }
+        impl VertexInput {
+            pub fn builder() -> VertexInputBuilder {
+                VertexInputBuilder::default()
+            }
+        }
+        unsafe impl bytemuck::Zeroable for VertexInput {}
+        unsafe impl bytemuck::Pod for VertexInput {}
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_struct_serde_regexps() {
+    let source = indoc! {r#"
+        struct Config {
+            a: u32,
+        };
+        struct Other {
+            b: u32,
+        };
+        @vertex
+        fn main(config: Config, other: Other) -> @builtin(position) vec4<f32> {
+            return vec4(0.0);
+        }
+      "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    let structs = structs(
+      &module,
+      &WgslBindgenOption {
+        serialization_strategy: WgslTypeSerializeStrategy::Encase,
+        serde_struct_regexps: vec![Regex::new("^Config$").unwrap()],
+        ..Default::default()
+      },
+    );
+    let actual = quote!(#(#structs)*);
+
+    assert_tokens_eq!(
+      quote! {
+        #[repr(C)]
+        #[derive(
+            Debug,
+            PartialEq,
+            Clone,
+            Copy,
+            encase::ShaderType,
+            serde::Serialize,
+            serde::Deserialize
+        )]
+        pub struct Config {
+            pub a: u32,
+        }
+        impl Config {
+            pub const fn new(a: u32) -> Self {
+                Self { a }
+            }
+        }
+        #[repr(C)]
+        #[derive(Debug, PartialEq, Clone, Copy, encase::ShaderType)]
+        pub struct Other {
+            pub b: u32,
+        }
+        impl Other {
+            pub const fn new(b: u32) -> Self {
+                Self { b }
+            }
+        }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn shared_structs_between_modules() {
+    let source_a = indoc! {r#"
+        struct Camera {
+            view_proj: mat4x4<f32>,
+        };
+        struct Light {
+            color: vec4<f32>,
+        };
+        @group(0) @binding(0) var<uniform> camera: Camera;
+        @group(0) @binding(1) var<uniform> light: Light;
+      "#};
+    let source_b = indoc! {r#"
+        struct Camera {
+            view_proj: mat4x4<f32>,
+        };
+        struct Light {
+            intensity: f32,
+        };
+        @group(0) @binding(0) var<uniform> camera: Camera;
+        @group(0) @binding(1) var<uniform> light: Light;
+      "#};
+
+    let module_a = naga::front::wgsl::parse_str(source_a).unwrap();
+    let module_b = naga::front::wgsl::parse_str(source_b).unwrap();
+    let docs = WgslDocs::default();
+    let options = WgslBindgenOption {
+      serialization_strategy: WgslTypeSerializeStrategy::Bytemuck,
+      ..Default::default()
+    };
+
+    let shared =
+      shared_struct_names(&[(&module_a, &docs, &options), (&module_b, &docs, &options)])
+        .unwrap();
+    assert_eq!(HashSet::from([SmolStr::new("Camera")]), shared);
+
+    let items =
+      structs_items_with_shared("a", &module_a, &options, &docs, &shared).unwrap();
+    let paths: Vec<_> = items
+      .iter()
+      .map(|item| item.path.get_fully_qualified_name())
+      .collect();
+    assert_eq!(
+      vec![
+        "a::Light",
+        "layout_asserts::a::Light",
+        "bytemuck_impls::a::Light",
+        "a::Camera",
+        "shared_structs::Camera",
+        "layout_asserts::shared_structs::Camera",
+        "bytemuck_impls::shared_structs::Camera",
+      ],
+      paths
+    );
+    assert_tokens_eq!(
+      quote!(
+        pub use _root::shared_structs::Camera;
+      ),
+      items[3].item.clone()
+    );
+  }
+
+  #[test]
+  fn write_struct_wgsl_docs() {
+    let source = indoc! {r#"
+        /// Per instance data.
+        struct Instance {
+            /// The instance color.
+            color: vec4<f32>,
+            scale: f32,
+        };
+        @vertex
+        fn main(instance: Instance) -> @builtin(position) vec4<f32> {
+            return vec4(0.0);
+        }
+      "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let options = WgslBindgenOption {
+      serialization_strategy: WgslTypeSerializeStrategy::Encase,
+      ..Default::default()
+    };
+    let docs = WgslDocs::from_sources([source]).unwrap();
+    let structs = structs_items("", &module, &options, &docs)
+      .unwrap()
+      .into_iter()
+      .map(|s| s.item);
+    let actual = quote!(#(#structs)*);
+
+    assert_tokens_eq!(
+      quote! {
+        #[doc = " Per instance data."]
+        #[repr(C)]
+        #[derive(Debug, PartialEq, Clone, Copy, encase::ShaderType)]
+        pub struct Instance {
+            #[doc = " The instance color."]
+            pub color: [f32; 4],
+            pub scale: f32,
+        }
+        impl Instance {
+            pub const fn new(color: [f32; 4], scale: f32) -> Self {
+                Self { color, scale }
+            }
+        }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_struct_scalar_type_overrides() {
+    let source = indoc! {r#"
+        struct Material {
+            flags: u32,
+            count: u32,
+            roughness: f32,
+        };
+        @group(0) @binding(0) var<uniform> material: Material;
+      "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    let structs = structs(
+      &module,
+      &WgslBindgenOption {
+        serialization_strategy: WgslTypeSerializeStrategy::Encase,
+        type_map: RustWgslTypeMap.build(WgslTypeSerializeStrategy::Encase),
+        override_struct_scalar_type: vec![
+          ("Material", "flags", WgslScalarType::U32, quote!(MaterialFlags)).into(),
+          ("Material", ".*", WgslScalarType::F32, quote!(NotNan<f32>)).into(),
+        ],
+        ..Default::default()
+      },
+    );
+    let actual = quote!(#(#structs)*);
+
+    assert_tokens_eq!(
+      quote! {
+        #[repr(C)]
+        #[derive(Debug, PartialEq, Clone, Copy, encase::ShaderType)]
+        pub struct Material {
+            pub flags: MaterialFlags,
+            pub count: u32,
+            pub roughness: NotNan<f32>,
+        }
+        impl Material {
+            pub const fn new(flags: MaterialFlags, count: u32, roughness: NotNan<f32>) -> Self {
+                Self { flags, count, roughness }
+            }
+        }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_padded_matrix_wrappers() {
+    let source = indoc! {r#"
+        struct Transforms {
+            normal: mat3x3<f32>,
+            scale: f32,
+        };
+        @group(0) @binding(0) var<uniform> transforms: Transforms;
+      "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    let structs = structs(
+      &module,
+      &WgslBindgenOption {
+        serialization_strategy: WgslTypeSerializeStrategy::Bytemuck,
+        type_map: NalgebraWgslTypeMap.build(WgslTypeSerializeStrategy::Bytemuck),
+        padded_matrix_wrappers: true,
+        ..Default::default()
+      },
+    );
+    let actual = quote!(#(#structs)*);
+
+    assert_tokens_eq!(
+      quote! {
+        #[repr(C, align(16))]
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        pub struct Transforms {
+            /// size: 48, offset: 0x0, type: `mat3x3<f32>`
+            pub normal: _root::padded_matrices::PaddedMat3x3f,
+            /// size: 4, offset: 0x30, type: `f32`
+            pub scale: f32,
+            pub _pad_scale: [u8; 0x10 - core::mem::size_of::<f32>()],
+        }
+        impl Transforms {
+            pub const fn new(normal: _root::padded_matrices::PaddedMat3x3f, scale: f32) -> Self {
+                Self {
+                    normal,
+                    scale,
+                    _pad_scale: [0; 0x10 - core::mem::size_of::<f32>()],
+                }
+            }
+        }
+        #[repr(C)]
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        pub struct TransformsInit {
+            pub normal: _root::padded_matrices::PaddedMat3x3f,
+            pub scale: f32,
+        }
+        impl TransformsInit {
+            pub const fn build(&self) -> Transforms {
+                Transforms {
+                    normal: self.normal,
+                    scale: self.scale,
+                    _pad_scale: [0; 0x10 - core::mem::size_of::<f32>()],
+                }
+            }
+        }
+        impl From<TransformsInit> for Transforms {
+            fn from(data: TransformsInit) -> Self {
+                data.build()
+            }
+        }
+        const TRANSFORMS_ASSERTS: () = {
+            assert!(std::mem::offset_of!(Transforms, normal) == 0);
+            assert!(std::mem::offset_of!(Transforms, scale) == 48);
+            assert!(std::mem::size_of::<Transforms>() == 64);
+        };
+        unsafe impl bytemuck::Zeroable for Transforms {}
+        unsafe impl bytemuck::Pod for Transforms {}
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_custom_padding_field_config() {
+    let source = indoc! {r#"
+        struct Data {
+            @size(8)
+            a: u32,
+            b: u32,
+        };
+        @group(0) @binding(0) var<uniform> data: Data;
+      "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    let structs = structs(
+      &module,
+      &WgslBindgenOption {
+        serialization_strategy: WgslTypeSerializeStrategy::Bytemuck,
+        type_map: RustWgslTypeMap.build(WgslTypeSerializeStrategy::Bytemuck),
+        padding_field: PaddingFieldConfig {
+          name_template: "__padding_{name}".into(),
+          is_public: false,
+          doc_hidden: true,
+        },
+        ..Default::default()
+      },
+    );
+    let actual = quote!(#(#structs)*);
+
+    assert_tokens_eq!(
+      quote! {
+        #[repr(C, align(4))]
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        pub struct Data {
+            /// size: 4, offset: 0x0, type: `u32`
+            pub a: u32,
+            #[doc(hidden)]
+            __padding_a: [u8; 0x8 - core::mem::size_of::<u32>()],
+            /// size: 4, offset: 0x8, type: `u32`
+            pub b: u32,
+        }
+        impl Data {
+            pub const fn new(a: u32, b: u32) -> Self {
+                Self {
+                    a,
+                    __padding_a: [0; 0x8 - core::mem::size_of::<u32>()],
+                    b,
+                }
+            }
+        }
+        #[repr(C)]
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        pub struct DataInit {
+            pub a: u32,
+            pub b: u32,
+        }
+        impl DataInit {
+            pub const fn build(&self) -> Data {
+                Data {
+                    a: self.a,
+                    __padding_a: [0; 0x8 - core::mem::size_of::<u32>()],
+                    b: self.b,
+                }
+            }
+        }
+        impl From<DataInit> for Data {
+            fn from(data: DataInit) -> Self {
+                data.build()
+            }
+        }
+        const DATA_ASSERTS: () = {
+            assert!(std::mem::offset_of!(Data, a) == 0);
+            assert!(std::mem::offset_of!(Data, b) == 8);
+            assert!(std::mem::size_of::<Data>() == 12);
+        };
+        unsafe impl bytemuck::Zeroable for Data {}
+        unsafe impl bytemuck::Pod for Data {}
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_encase_layout_assertions() {
+    let source = indoc! {r#"
+        struct Uniforms {
+            color: vec4<f32>,
+            scale: f32,
+        };
+        @group(0) @binding(0) var<uniform> u: Uniforms;
+      "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    let structs = structs(
+      &module,
+      &WgslBindgenOption {
+        serialization_strategy: WgslTypeSerializeStrategy::Encase,
+        type_map: GlamWgslTypeMap.build(WgslTypeSerializeStrategy::Encase),
+        encase_layout_assertions: true,
+        ..Default::default()
+      },
+    );
+    let actual = quote!(#(#structs)*);
+
+    assert_tokens_eq!(
+      quote! {
+        #[repr(C)]
+        #[derive(Debug, PartialEq, Clone, Copy, encase::ShaderType)]
+        pub struct Uniforms {
+            pub color: glam::Vec4,
+            pub scale: f32,
+        }
+        impl Uniforms {
+            pub const fn new(color: glam::Vec4, scale: f32) -> Self {
+                Self { color, scale }
+            }
+        }
+        const UNIFORMS_ASSERTS: () = {
+            assert!(<Uniforms as encase::ShaderSize>::SHADER_SIZE.get() == 32);
+        };
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_layout_validation_fn() {
+    let source = indoc! {r#"
+        struct Uniforms {
+            color: vec4<f32>,
+            scale: f32,
+        };
+        @group(0) @binding(0) var<uniform> u: Uniforms;
+      "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    let actual = layout_validation_fn(
+      "",
+      &module,
+      &WgslBindgenOption {
+        serialization_strategy: WgslTypeSerializeStrategy::Bytemuck,
+        type_map: GlamWgslTypeMap.build(WgslTypeSerializeStrategy::Bytemuck),
+        generate_layout_validation: true,
+        ..Default::default()
+      },
+    )
+    .unwrap();
+
+    assert_tokens_eq!(
+      quote! {
+        pub fn validate_layouts() -> Result<(), Vec<String>> {
+            let mut errors = Vec::new();
+            let offset = std::mem::offset_of!(Uniforms, color);
+            if offset != 0 {
+                errors.push(format!("Uniforms::color has offset {} but WGSL expects {}", offset, 0));
+            }
+            let offset = std::mem::offset_of!(Uniforms, scale);
+            if offset != 16 {
+                errors.push(format!("Uniforms::scale has offset {} but WGSL expects {}", offset, 16));
+            }
+            let size = std::mem::size_of::<Uniforms>();
+            if size != 32 {
+                errors.push(format!("Uniforms has size {} but WGSL expects {}", size, 32));
+            }
+            if errors.is_empty() {
+                Ok(())
+            } else {
+                Err(errors)
+            }
+        }
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn structs_invalid_type_layout() {
+    // WGSL can't spell a 3 byte float, so build the module by hand.
+    let mut module = naga::Module::default();
+    module.types.insert(
+      naga::Type {
+        name: Some("Odd".into()),
+        inner: naga::TypeInner::Scalar(naga::Scalar {
+          kind: naga::ScalarKind::Float,
+          width: 3,
+        }),
+      },
+      naga::Span::UNDEFINED,
+    );
+
+    let options = WgslBindgenOption::default();
+    assert!(matches!(
+      structs_items("shader", &module, &options, &WgslDocs::default()),
+      Err(CreateModuleError::InvalidTypeLayout { module, .. }) if module == "shader"
+    ));
+    assert!(matches!(
+      layout_validation_fn(
+        "shader",
+        &module,
+        &WgslBindgenOption {
+          serialization_strategy: WgslTypeSerializeStrategy::Bytemuck,
+          generate_layout_validation: true,
+          ..Default::default()
+        },
+      ),
+      Err(CreateModuleError::InvalidTypeLayout { .. })
+    ));
+  }
+
+  #[test]
+  fn write_vertex_format_assertions() {
+    let source = indoc! {r#"
+        struct VertexInput {
+            @location(0) position: vec3<f32>,
+            @location(1) color: vec4<f32>,
+            @builtin(vertex_index) index: u32,
+        };
+
+        @vertex
+        fn main(in: VertexInput) -> @builtin(position) vec4<f32> {
+            return vec4(0.0);
+        }
+      "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    let mut type_map = GlamWgslTypeMap.build(WgslTypeSerializeStrategy::Bytemuck);
+    type_map.insert(WgslType::Vector(WgslVecType::Vec4f), quote!([u16; 4]));
+
+    let options = WgslBindgenOption {
+      serialization_strategy: WgslTypeSerializeStrategy::Bytemuck,
+      type_map,
+      vertex_format_assertions: true,
+      ..Default::default()
+    };
+    let actual = structs_items("", &module, &options, &WgslDocs::default())
+      .unwrap()
+      .into_iter()
+      .find(|item| item.path.parent_module_path == MOD_STRUCT_ASSERTIONS)
+      .unwrap()
+      .item;
+
+    assert_tokens_eq!(
+      quote! {
+        const VERTEX_INPUT_VERTEX_FORMAT_ASSERTS: () = {
+            assert!(std::mem::size_of::<glam::Vec3A>() as u64 >= wgpu::VertexFormat::Float32x3.size());
+            assert!(std::mem::size_of::<[u16; 4]>() as u64 >= wgpu::VertexFormat::Float32x4.size());
+        };
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_loose_vertex_input_struct() {
+    let source = indoc! {r#"
+        @vertex
+        fn vs_main(
+            @location(0) position: vec3<f32>,
+            @builtin(vertex_index) index: u32,
+            @location(1) uv: vec2<f32>,
+        ) -> @builtin(position) vec4<f32> {
+            return vec4(position, 1.0);
+        }
+      "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    let structs = structs(
+      &module,
+      &WgslBindgenOption {
+        serialization_strategy: WgslTypeSerializeStrategy::Bytemuck,
+        type_map: GlamWgslTypeMap.build(WgslTypeSerializeStrategy::Bytemuck),
+        ..Default::default()
+      },
+    );
+    let actual = quote!(#(#structs)*);
+
+    assert_tokens_eq!(
+      quote! {
+        #[repr(C)]
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        pub struct VsMainInput {
+            pub position: glam::Vec3A,
+            pub uv: [f32; 2],
+        }
+        impl VsMainInput {
+            pub const fn new(position: glam::Vec3A, uv: [f32; 2]) -> Self {
+                Self { position, uv }
+            }
+        }
+        unsafe impl bytemuck::Zeroable for VsMainInput {}
+        unsafe impl bytemuck::Pod for VsMainInput {}
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_loose_vertex_input_structs_of_all_entries() {
+    let source = indoc! {r#"
+        struct VertexInput {
+            @location(0) position: vec4<f32>,
+        };
+
+        @vertex
+        fn vs_main(@location(0) position: vec4<f32>) -> @builtin(position) vec4<f32> {
+            return position;
+        }
+
+        @vertex
+        fn vs_textured(in: VertexInput, @location(1) uv: vec2<f32>) -> @builtin(position) vec4<f32> {
+            return in.position;
+        }
+      "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    let structs = structs(
+      &module,
+      &WgslBindgenOption {
+        serialization_strategy: WgslTypeSerializeStrategy::Bytemuck,
+        type_map: GlamWgslTypeMap.build(WgslTypeSerializeStrategy::Bytemuck),
+        ..Default::default()
+      },
+    );
+    let actual = quote!(#(#structs)*);
+
+    assert_tokens_eq!(
+      quote! {
+        #[repr(C)]
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        pub struct VertexInput {
+            pub position: glam::Vec4,
+        }
+        impl VertexInput {
+            pub const fn new(position: glam::Vec4) -> Self {
+                Self { position }
+            }
+        }
+        unsafe impl bytemuck::Zeroable for VertexInput {}
+        unsafe impl bytemuck::Pod for VertexInput {}
+        #[repr(C)]
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        pub struct VsMainInput {
+            pub position: glam::Vec4,
+        }
+        impl VsMainInput {
+            pub const fn new(position: glam::Vec4) -> Self {
+                Self { position }
+            }
+        }
+        unsafe impl bytemuck::Zeroable for VsMainInput {}
+        unsafe impl bytemuck::Pod for VsMainInput {}
+        #[repr(C)]
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        pub struct VsTexturedInput {
+            pub uv: [f32; 2],
+        }
+        impl VsTexturedInput {
+            pub const fn new(uv: [f32; 2]) -> Self {
+                Self { uv }
+            }
+        }
+        unsafe impl bytemuck::Zeroable for VsTexturedInput {}
+        unsafe impl bytemuck::Pod for VsTexturedInput {}
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_vertex_format_overrides() {
+    let source = indoc! {r#"
+        struct VertexInput {
+            @location(0) position: vec3<f32>,
+            @location(1) color: vec4<f32>,
+        };
+
+        @vertex
+        fn main(in: VertexInput) -> @builtin(position) vec4<f32> {
+            return vec4(0.0);
+        }
+      "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    let structs = structs(
+      &module,
+      &WgslBindgenOption {
+        serialization_strategy: WgslTypeSerializeStrategy::Bytemuck,
+        type_map: RustWgslTypeMap.build(WgslTypeSerializeStrategy::Bytemuck),
+        override_vertex_format: vec![(
+          "VertexInput",
+          "color",
+          wgpu::VertexFormat::Unorm8x4,
+          quote!([u8; 4]),
+        )
+          .into()],
+        vertex_format_assertions: true,
+        ..Default::default()
+      },
+    );
+    let actual = quote!(#(#structs)*);
+
+    assert_tokens_eq!(
+      quote! {
+        #[repr(C)]
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        pub struct VertexInput {
+            pub position: [f32; 3],
+            pub color: [u8; 4],
+        }
+        impl VertexInput {
+            pub const fn new(position: [f32; 3], color: [u8; 4]) -> Self {
+                Self { position, color }
+            }
+        }
+        const VERTEX_INPUT_VERTEX_FORMAT_ASSERTS: () = {
+            assert!(std::mem::size_of::<[f32; 3]>() as u64 >= wgpu::VertexFormat::Float32x3.size());
+            assert!(std::mem::size_of::<[u8; 4]>() as u64 >= wgpu::VertexFormat::Unorm8x4.size());
+        };
+        unsafe impl bytemuck::Zeroable for VertexInput {}
+        unsafe impl bytemuck::Pod for VertexInput {}
+      },
+      actual
+    );
+  }
+
+  #[test]
+  fn write_vertex_format_overrides_only_for_vertex_inputs() {
+    let source = indoc! {r#"
+        struct VertexInput {
+            @location(0) color: vec4<f32>,
+        };
+
+        struct FragmentInput {
+            @location(0) color: vec4<f32>,
+        };
+
+        @vertex
+        fn vs_main(in: VertexInput) -> @builtin(position) vec4<f32> {
+            return vec4(0.0);
+        }
+
+        @fragment
+        fn fs_main(in: FragmentInput) -> @location(0) vec4<f32> {
+            return in.color;
+        }
+      "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    let structs = structs(
+      &module,
+      &WgslBindgenOption {
+        serialization_strategy: WgslTypeSerializeStrategy::Bytemuck,
+        type_map: RustWgslTypeMap.build(WgslTypeSerializeStrategy::Bytemuck),
+        override_vertex_format: vec![(
+          ".*Input",
+          "color",
+          wgpu::VertexFormat::Unorm8x4,
+          quote!([u8; 4]),
+        )
+          .into()],
+        ..Default::default()
+      },
+    );
+    let actual = quote!(#(#structs)*);
+
+    assert_tokens_eq!(
+      quote! {
+        #[repr(C)]
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        pub struct VertexInput {
+            pub color: [u8; 4],
+        }
+        impl VertexInput {
+            pub const fn new(color: [u8; 4]) -> Self {
+                Self { color }
+            }
+        }
+        unsafe impl bytemuck::Zeroable for VertexInput {}
+        unsafe impl bytemuck::Pod for VertexInput {}
+        #[repr(C)]
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        pub struct FragmentInput {
+            pub color: [f32; 4],
+        }
+        impl FragmentInput {
+            pub const fn new(color: [f32; 4]) -> Self {
+                Self { color }
+            }
+        }
+        unsafe impl bytemuck::Zeroable for FragmentInput {}
+        unsafe impl bytemuck::Pod for FragmentInput {}
+      },
+      actual
+    );
+  }
 }
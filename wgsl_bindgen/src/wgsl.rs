@@ -1,7 +1,26 @@
+use heck::ToPascalCase;
 use naga::StructMember;
-use proc_macro2::TokenStream;
+use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
 
+use crate::quote_gen::RustItemPath;
+use crate::{CreateModuleError, WgpuVersion, WgslBindgenOption};
+
+/// Computes the size and alignment of the types of the module.
+pub fn type_layouter(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+) -> Result<naga::proc::Layouter, CreateModuleError> {
+  let mut layouter = naga::proc::Layouter::default();
+  layouter.update(module.to_ctx()).map_err(|error| {
+    CreateModuleError::InvalidTypeLayout {
+      module: invoking_entry_module.to_owned(),
+      message: error.to_string(),
+    }
+  })?;
+  Ok(layouter)
+}
+
 pub fn shader_stages(module: &naga::Module) -> wgpu::ShaderStages {
   module
     .entry_points
@@ -32,49 +51,249 @@ pub fn buffer_binding_type(storage: naga::AddressSpace) -> TokenStream {
   }
 }
 
-pub fn vertex_format(ty: &naga::Type) -> wgpu::VertexFormat {
+/// The address space of a global variable as written in its WGSL declaration.
+pub fn address_space_to_wgsl(space: naga::AddressSpace) -> String {
+  match space {
+    naga::AddressSpace::Uniform => "<uniform>".to_owned(),
+    naga::AddressSpace::Storage { access } => {
+      format!("<storage, {}>", storage_access_to_wgsl(access))
+    }
+    naga::AddressSpace::Workgroup => "<workgroup>".to_owned(),
+    naga::AddressSpace::Private => "<private>".to_owned(),
+    naga::AddressSpace::PushConstant => "<push_constant>".to_owned(),
+    naga::AddressSpace::Function => "<function>".to_owned(),
+    naga::AddressSpace::Handle => String::new(),
+  }
+}
+
+fn storage_access_to_wgsl(access: naga::StorageAccess) -> &'static str {
+  if access.contains(naga::StorageAccess::STORE) {
+    if access.contains(naga::StorageAccess::LOAD) {
+      "read_write"
+    } else {
+      "write"
+    }
+  } else {
+    "read"
+  }
+}
+
+fn scalar_to_wgsl(scalar: naga::Scalar) -> String {
+  let prefix = match scalar.kind {
+    naga::ScalarKind::Bool => return "bool".to_owned(),
+    naga::ScalarKind::Sint | naga::ScalarKind::AbstractInt => "i",
+    naga::ScalarKind::Uint => "u",
+    naga::ScalarKind::Float | naga::ScalarKind::AbstractFloat => "f",
+  };
+  format!("{prefix}{}", scalar.width as u32 * 8)
+}
+
+/// The WGSL spelling of a type, using the demangled names for structs.
+pub fn type_to_wgsl(module: &naga::Module, ty: &naga::Type) -> String {
+  use naga::{ImageClass, ImageDimension, TypeInner};
+
+  match &ty.inner {
+    TypeInner::Scalar(scalar) => scalar_to_wgsl(*scalar),
+    TypeInner::Vector { size, scalar } => {
+      format!("vec{}<{}>", *size as u8, scalar_to_wgsl(*scalar))
+    }
+    TypeInner::Matrix {
+      columns,
+      rows,
+      scalar,
+    } => format!("mat{}x{}<{}>", *columns as u8, *rows as u8, scalar_to_wgsl(*scalar)),
+    TypeInner::Atomic(scalar) => format!("atomic<{}>", scalar_to_wgsl(*scalar)),
+    TypeInner::Struct { .. } => ty
+      .name
+      .as_ref()
+      .map(|name| RustItemPath::from_mangled(name, "").item_name.to_string())
+      .unwrap_or_else(|| "struct".to_owned()),
+    TypeInner::Array { base, size, .. } | TypeInner::BindingArray { base, size } => {
+      let keyword = match &ty.inner {
+        TypeInner::BindingArray { .. } => "binding_array",
+        _ => "array",
+      };
+      let base = type_to_wgsl(module, &module.types[*base]);
+      match size {
+        naga::ArraySize::Constant(size) => format!("{keyword}<{base}, {size}>"),
+        naga::ArraySize::Dynamic => format!("{keyword}<{base}>"),
+      }
+    }
+    TypeInner::Image {
+      dim,
+      arrayed,
+      class,
+    } => {
+      let dim = match dim {
+        ImageDimension::D1 => "1d",
+        ImageDimension::D2 => "2d",
+        ImageDimension::D3 => "3d",
+        ImageDimension::Cube => "cube",
+      };
+      let array = if *arrayed { "_array" } else { "" };
+      match class {
+        ImageClass::Sampled { kind, multi } => {
+          let multi = if *multi { "multisampled_" } else { "" };
+          let scalar = scalar_to_wgsl(naga::Scalar {
+            kind: *kind,
+            width: 4,
+          });
+          format!("texture_{multi}{dim}{array}<{scalar}>")
+        }
+        ImageClass::Depth { multi } => {
+          let multi = if *multi { "multisampled_" } else { "" };
+          format!("texture_depth_{multi}{dim}{array}")
+        }
+        ImageClass::Storage { format, access } => format!(
+          "texture_storage_{dim}{array}<{}, {}>",
+          format!("{format:?}").to_lowercase(),
+          storage_access_to_wgsl(*access)
+        ),
+      }
+    }
+    TypeInner::Sampler { comparison: false } => "sampler".to_owned(),
+    TypeInner::Sampler { comparison: true } => "sampler_comparison".to_owned(),
+    inner => inner.to_wgsl(&module.to_ctx()),
+  }
+}
+
+pub fn try_vertex_format(ty: &naga::Type) -> Option<wgpu::VertexFormat> {
   // Not all wgsl types work as vertex attributes in wgpu.
   match &ty.inner {
     naga::TypeInner::Scalar(scalar) => match (scalar.kind, scalar.width) {
-      (naga::ScalarKind::Sint, 4) => wgpu::VertexFormat::Sint32,
-      (naga::ScalarKind::Uint, 4) => wgpu::VertexFormat::Uint32,
-      (naga::ScalarKind::Float, 4) => wgpu::VertexFormat::Float32,
-      (naga::ScalarKind::Float, 8) => wgpu::VertexFormat::Float64,
-      _ => todo!(),
+      (naga::ScalarKind::Sint, 4) => Some(wgpu::VertexFormat::Sint32),
+      (naga::ScalarKind::Uint, 4) => Some(wgpu::VertexFormat::Uint32),
+      (naga::ScalarKind::Float, 4) => Some(wgpu::VertexFormat::Float32),
+      (naga::ScalarKind::Float, 8) => Some(wgpu::VertexFormat::Float64),
+      _ => None,
+    },
+    naga::TypeInner::Vector { size, scalar } => vector_vertex_format(*size, scalar),
+    _ => None,
+  }
+}
+
+fn vector_vertex_format(
+  size: naga::VectorSize,
+  scalar: &naga::Scalar,
+) -> Option<wgpu::VertexFormat> {
+  let format = match size {
+    naga::VectorSize::Bi => match (scalar.kind, scalar.width) {
+      (naga::ScalarKind::Sint, 1) => wgpu::VertexFormat::Sint8x2,
+      (naga::ScalarKind::Uint, 1) => wgpu::VertexFormat::Uint8x2,
+      (naga::ScalarKind::Sint, 2) => wgpu::VertexFormat::Sint16x2,
+      (naga::ScalarKind::Uint, 2) => wgpu::VertexFormat::Uint16x2,
+      (naga::ScalarKind::Uint, 4) => wgpu::VertexFormat::Uint32x2,
+      (naga::ScalarKind::Sint, 4) => wgpu::VertexFormat::Sint32x2,
+      (naga::ScalarKind::Float, 4) => wgpu::VertexFormat::Float32x2,
+      (naga::ScalarKind::Float, 8) => wgpu::VertexFormat::Float64x2,
+      _ => return None,
     },
-    naga::TypeInner::Vector { size, scalar } => match size {
-      naga::VectorSize::Bi => match (scalar.kind, scalar.width) {
-        (naga::ScalarKind::Sint, 1) => wgpu::VertexFormat::Sint8x2,
-        (naga::ScalarKind::Uint, 1) => wgpu::VertexFormat::Uint8x2,
-        (naga::ScalarKind::Sint, 2) => wgpu::VertexFormat::Sint16x2,
-        (naga::ScalarKind::Uint, 2) => wgpu::VertexFormat::Uint16x2,
-        (naga::ScalarKind::Uint, 4) => wgpu::VertexFormat::Uint32x2,
-        (naga::ScalarKind::Sint, 4) => wgpu::VertexFormat::Sint32x2,
-        (naga::ScalarKind::Float, 4) => wgpu::VertexFormat::Float32x2,
-        (naga::ScalarKind::Float, 8) => wgpu::VertexFormat::Float64x2,
-        _ => todo!(),
-      },
-      naga::VectorSize::Tri => match (scalar.kind, scalar.width) {
-        (naga::ScalarKind::Uint, 4) => wgpu::VertexFormat::Uint32x3,
-        (naga::ScalarKind::Sint, 4) => wgpu::VertexFormat::Sint32x3,
-        (naga::ScalarKind::Float, 4) => wgpu::VertexFormat::Float32x3,
-        (naga::ScalarKind::Float, 8) => wgpu::VertexFormat::Float64x3,
-        _ => todo!(),
-      },
-      naga::VectorSize::Quad => match (scalar.kind, scalar.width) {
-        (naga::ScalarKind::Sint, 1) => wgpu::VertexFormat::Sint8x4,
-        (naga::ScalarKind::Uint, 1) => wgpu::VertexFormat::Uint8x4,
-        (naga::ScalarKind::Sint, 2) => wgpu::VertexFormat::Sint16x4,
-        (naga::ScalarKind::Uint, 2) => wgpu::VertexFormat::Uint16x4,
-        (naga::ScalarKind::Uint, 4) => wgpu::VertexFormat::Uint32x4,
-        (naga::ScalarKind::Sint, 4) => wgpu::VertexFormat::Sint32x4,
-        (naga::ScalarKind::Float, 4) => wgpu::VertexFormat::Float32x4,
-        (naga::ScalarKind::Float, 8) => wgpu::VertexFormat::Float64x4,
-        _ => todo!(),
-      },
+    naga::VectorSize::Tri => match (scalar.kind, scalar.width) {
+      (naga::ScalarKind::Uint, 4) => wgpu::VertexFormat::Uint32x3,
+      (naga::ScalarKind::Sint, 4) => wgpu::VertexFormat::Sint32x3,
+      (naga::ScalarKind::Float, 4) => wgpu::VertexFormat::Float32x3,
+      (naga::ScalarKind::Float, 8) => wgpu::VertexFormat::Float64x3,
+      _ => return None,
     },
-    _ => todo!(), // are these types even valid as attributes?
+    naga::VectorSize::Quad => match (scalar.kind, scalar.width) {
+      (naga::ScalarKind::Sint, 1) => wgpu::VertexFormat::Sint8x4,
+      (naga::ScalarKind::Uint, 1) => wgpu::VertexFormat::Uint8x4,
+      (naga::ScalarKind::Sint, 2) => wgpu::VertexFormat::Sint16x4,
+      (naga::ScalarKind::Uint, 2) => wgpu::VertexFormat::Uint16x4,
+      (naga::ScalarKind::Uint, 4) => wgpu::VertexFormat::Uint32x4,
+      (naga::ScalarKind::Sint, 4) => wgpu::VertexFormat::Sint32x4,
+      (naga::ScalarKind::Float, 4) => wgpu::VertexFormat::Float32x4,
+      (naga::ScalarKind::Float, 8) => wgpu::VertexFormat::Float64x4,
+      _ => return None,
+    },
+  };
+  Some(format)
+}
+
+/// The vertex attributes needed for a type, as formats and byte offsets relative to the field.
+/// Matrices are expanded into one attribute per column at consecutive locations,
+/// assuming the Rust type uses the same column stride as WGSL.
+pub fn vertex_attribute_formats(ty: &naga::Type) -> Vec<(wgpu::VertexFormat, u64)> {
+  try_vertex_attribute_formats(ty)
+    .expect("vertex input types are checked before generating")
+}
+
+fn try_vertex_attribute_formats(
+  ty: &naga::Type,
+) -> Option<Vec<(wgpu::VertexFormat, u64)>> {
+  match &ty.inner {
+    naga::TypeInner::Matrix {
+      columns,
+      rows,
+      scalar,
+    } => {
+      let format = vector_vertex_format(*rows, scalar)?;
+      let column_stride = match rows {
+        naga::VectorSize::Bi => 2,
+        naga::VectorSize::Tri | naga::VectorSize::Quad => 4,
+      } * scalar.width as u64;
+
+      let formats = (0..*columns as u64)
+        .map(|column| (format, column * column_stride))
+        .collect();
+      Some(formats)
+    }
+    _ => Some(vec![(try_vertex_format(ty)?, 0)]),
+  }
+}
+
+/// Checks that the vertex input fields have types usable as vertex attributes, unless their
+/// format is overridden, so generating their vertex attributes can't fail.
+pub fn check_vertex_inputs(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  options: &WgslBindgenOption,
+) -> Result<(), CreateModuleError> {
+  for vertex_entry in module
+    .entry_points
+    .iter()
+    .filter(|e| e.stage == naga::ShaderStage::Vertex)
+  {
+    let Some(input) =
+      get_loose_vertex_input(invoking_entry_module, module, vertex_entry)?
+    else {
+      continue;
+    };
+    if module
+      .types
+      .iter()
+      .any(|(_, ty)| ty.name.as_deref() == Some(input.name.as_str()))
+    {
+      return Err(CreateModuleError::VertexInputNameConflict {
+        module: invoking_entry_module.to_owned(),
+        entry: vertex_entry.name.clone(),
+        name: input.name,
+      });
+    }
   }
+
+  for input in get_vertex_input_structs(invoking_entry_module, module)? {
+    let input_name = RustItemPath::from_mangled(&input.name, invoking_entry_module)
+      .get_fully_qualified_name()
+      .to_string();
+    for (_, member) in &input.fields {
+      let field = member.name.clone().unwrap_or_default();
+      let ty = &module.types[member.ty];
+      if options
+        .vertex_format_override(&input_name, &field)
+        .is_none()
+        && try_vertex_attribute_formats(ty).is_none()
+      {
+        return Err(CreateModuleError::UnsupportedVertexFormat {
+          module: invoking_entry_module.to_owned(),
+          input: input_name,
+          field,
+          type_name: type_to_wgsl(module, ty),
+        });
+      }
+    }
+  }
+  Ok(())
 }
 
 pub struct VertexInput {
@@ -82,49 +301,267 @@ pub struct VertexInput {
   pub fields: Vec<(u32, StructMember)>,
 }
 
-// TODO: Handle errors.
-// Collect the necessary data to generate an equivalent Rust struct.
-pub fn get_vertex_input_structs(module: &naga::Module) -> Vec<VertexInput> {
-  // TODO: Handle multiple entries?
-  module
+/// Collects the vertex inputs of all the vertex entries to generate equivalent Rust structs.
+/// Inputs shared by several entries are only included once.
+pub fn get_vertex_input_structs(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+) -> Result<Vec<VertexInput>, CreateModuleError> {
+  let mut inputs: Vec<VertexInput> = Vec::new();
+  for vertex_entry in module
     .entry_points
     .iter()
-    .find(|e| e.stage == naga::ShaderStage::Vertex)
-    .map(|vertex_entry| {
-      vertex_entry
-        .function
-        .arguments
-        .iter()
-        .filter(|a| a.binding.is_none())
-        .filter_map(|argument| {
-          let arg_type = &module.types[argument.ty];
-          match &arg_type.inner {
-            naga::TypeInner::Struct { members, span: _ } => {
-              let input = VertexInput {
-                name: arg_type.name.as_ref().unwrap().clone(),
-                fields: members
-                  .iter()
-                  .filter_map(|member| {
-                    // Skip builtins since they have no location binding.
-                    let location = match member.binding.as_ref().unwrap() {
-                      naga::Binding::BuiltIn(_) => None,
-                      naga::Binding::Location { location, .. } => Some(*location),
-                    }?;
-
-                    Some((location, member.clone()))
-                  })
-                  .collect(),
-              };
-
-              Some(input)
-            }
-            // An argument has to have a binding unless it is a structure.
-            _ => None,
-          }
-        })
-        .collect()
+    .filter(|e| e.stage == naga::ShaderStage::Vertex)
+  {
+    for input in
+      get_entry_vertex_input_structs(invoking_entry_module, module, vertex_entry)?
+    {
+      if !inputs.iter().any(|i| i.name == input.name) {
+        inputs.push(input);
+      }
+    }
+  }
+  Ok(inputs)
+}
+
+/// The vertex inputs of a single vertex entry, in the order of its vertex buffers.
+pub fn get_entry_vertex_input_structs(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  vertex_entry: &naga::EntryPoint,
+) -> Result<Vec<VertexInput>, CreateModuleError> {
+  let invalid_input = |message: String| CreateModuleError::InvalidVertexInput {
+    module: invoking_entry_module.to_owned(),
+    entry: vertex_entry.name.clone(),
+    message,
+  };
+
+  let mut inputs = Vec::new();
+  // An argument has to have a binding unless it is a structure.
+  for argument in vertex_entry
+    .function
+    .arguments
+    .iter()
+    .filter(|a| a.binding.is_none())
+  {
+    let arg_type = &module.types[argument.ty];
+    let naga::TypeInner::Struct { members, span: _ } = &arg_type.inner else {
+      continue;
+    };
+    let name = arg_type
+      .name
+      .clone()
+      .ok_or_else(|| invalid_input("the struct of an argument has no name".to_owned()))?;
+
+    let mut fields = Vec::new();
+    for member in members {
+      let binding = member.binding.as_ref().ok_or_else(|| {
+        invalid_input(format!(
+          "member `{}` of `{name}` has no binding",
+          member.name.as_deref().unwrap_or_default()
+        ))
+      })?;
+      // Skip builtins since they have no location binding.
+      if let naga::Binding::Location { location, .. } = binding {
+        fields.push((*location, member.clone()));
+      }
+    }
+    inputs.push(VertexInput { name, fields });
+  }
+  inputs.extend(get_loose_vertex_input(invoking_entry_module, module, vertex_entry)?);
+  Ok(inputs)
+}
+
+/// The name of the struct synthesized for the loose `@location` arguments of a vertex entry.
+pub fn loose_vertex_input_name(vertex_entry: &naga::EntryPoint) -> String {
+  format!("{}Input", vertex_entry.name.to_pascal_case())
+}
+
+// Vertex inputs that aren't in a struct are collected into a single synthesized struct,
+// which is placed after the struct inputs of the entry.
+pub fn get_loose_vertex_input(
+  invoking_entry_module: &str,
+  module: &naga::Module,
+  vertex_entry: &naga::EntryPoint,
+) -> Result<Option<VertexInput>, CreateModuleError> {
+  let layouter = type_layouter(invoking_entry_module, module)?;
+
+  let mut offset = 0;
+  let fields: Vec<_> = vertex_entry
+    .function
+    .arguments
+    .iter()
+    .filter_map(|argument| match argument.binding.as_ref()? {
+      naga::Binding::Location { location, .. } => {
+        let layout = layouter[argument.ty];
+        let member_offset = layout.alignment.round_up(offset);
+        offset = member_offset + layout.size;
+
+        let member = StructMember {
+          name: argument.name.clone(),
+          ty: argument.ty,
+          binding: argument.binding.clone(),
+          offset: member_offset,
+        };
+        Some((*location, member))
+      }
+      naga::Binding::BuiltIn(_) => None,
+    })
+    .collect();
+
+  Ok((!fields.is_empty()).then(|| VertexInput {
+    name: loose_vertex_input_name(vertex_entry),
+    fields,
+  }))
+}
+
+// The bindings of the outputs of a fragment entry point, either directly or through the members
+// of its result struct.
+fn fragment_output_bindings<'a>(
+  module: &'a naga::Module,
+  fragment_entry: &'a naga::EntryPoint,
+) -> Vec<&'a naga::Binding> {
+  let Some(result) = fragment_entry.function.result.as_ref() else {
+    return Vec::new();
+  };
+
+  match (&result.binding, &module.types[result.ty].inner) {
+    (Some(binding), _) => vec![binding],
+    (None, naga::TypeInner::Struct { members, .. }) => members
+      .iter()
+      .filter_map(|member| member.binding.as_ref())
+      .collect(),
+    _ => Vec::new(),
+  }
+}
+
+/// The `@location` indices written by a fragment entry point, either directly or through the members
+/// of its result struct. The locations are sorted and deduplicated.
+pub fn fragment_output_locations(
+  module: &naga::Module,
+  fragment_entry: &naga::EntryPoint,
+) -> Vec<u32> {
+  let mut locations: Vec<u32> = fragment_output_bindings(module, fragment_entry)
+    .into_iter()
+    .filter_map(|binding| match binding {
+      naga::Binding::Location { location, .. } => Some(*location),
+      naga::Binding::BuiltIn(_) => None,
+    })
+    .collect();
+  locations.sort_unstable();
+  locations.dedup();
+  locations
+}
+
+/// Whether a fragment entry point writes a second blend source, which requires dual-source blending.
+pub fn uses_dual_source_blending(
+  module: &naga::Module,
+  fragment_entry: &naga::EntryPoint,
+) -> bool {
+  fragment_output_bindings(module, fragment_entry)
+    .into_iter()
+    .any(|binding| {
+      matches!(
+        binding,
+        naga::Binding::Location {
+          second_blend_source: true,
+          ..
+        }
+      )
+    })
+}
+
+/// The device features required to create pipelines from the entry points of a module.
+pub fn required_features(module: &naga::Module) -> wgpu::Features {
+  let mut features = wgpu::Features::empty();
+  for entry_point in &module.entry_points {
+    if entry_point.stage == naga::ShaderStage::Fragment
+      && uses_dual_source_blending(module, entry_point)
+    {
+      features |= wgpu::Features::DUAL_SOURCE_BLENDING;
+    }
+    for argument in &entry_point.function.arguments {
+      features |= argument
+        .binding
+        .as_ref()
+        .map_or_else(wgpu::Features::empty, builtin_features);
+    }
+  }
+
+  for (_, ty) in module.types.iter() {
+    features |= type_features(&ty.inner);
+    if let naga::TypeInner::Struct { members, .. } = &ty.inner {
+      for member in members {
+        features |= member
+          .binding
+          .as_ref()
+          .map_or_else(wgpu::Features::empty, builtin_features);
+      }
+    }
+  }
+
+  for (_, global) in module.global_variables.iter() {
+    if global.space == naga::AddressSpace::PushConstant {
+      features |= wgpu::Features::PUSH_CONSTANTS;
+    }
+  }
+  features
+}
+
+fn type_features(inner: &naga::TypeInner) -> wgpu::Features {
+  use naga::TypeInner as Ti;
+  match inner {
+    Ti::Scalar(scalar)
+    | Ti::Atomic(scalar)
+    | Ti::Vector { scalar, .. }
+    | Ti::Matrix { scalar, .. }
+    | Ti::ValuePointer { scalar, .. } => match (scalar.kind, scalar.width) {
+      (naga::ScalarKind::Float, 2) => wgpu::Features::SHADER_F16,
+      (naga::ScalarKind::Float, 8) => wgpu::Features::SHADER_F64,
+      (naga::ScalarKind::Sint | naga::ScalarKind::Uint, 2) => wgpu::Features::SHADER_I16,
+      _ => wgpu::Features::empty(),
+    },
+    // Storage textures which can be read from aren't supported by every format.
+    Ti::Image {
+      class: naga::ImageClass::Storage { access, .. },
+      ..
+    } if access.contains(naga::StorageAccess::LOAD) => {
+      wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+    }
+    Ti::AccelerationStructure => {
+      wgpu::Features::RAY_TRACING_ACCELERATION_STRUCTURE | wgpu::Features::RAY_QUERY
+    }
+    Ti::RayQuery => wgpu::Features::RAY_QUERY,
+    _ => wgpu::Features::empty(),
+  }
+}
+
+fn builtin_features(binding: &naga::Binding) -> wgpu::Features {
+  match binding {
+    naga::Binding::BuiltIn(naga::BuiltIn::PrimitiveIndex) => {
+      wgpu::Features::SHADER_PRIMITIVE_INDEX
+    }
+    naga::Binding::BuiltIn(naga::BuiltIn::ViewIndex) => wgpu::Features::MULTIVIEW,
+    _ => wgpu::Features::empty(),
+  }
+}
+
+pub fn features_tokens(
+  features: wgpu::Features,
+  wgpu_edition: WgpuVersion,
+) -> TokenStream {
+  let flags: Vec<TokenStream> = features
+    .iter_names()
+    .map(|(name, _)| {
+      let name = Ident::new(&wgpu_edition.feature_name(name), Span::call_site());
+      quote!(wgpu::Features::#name)
     })
-    .unwrap_or_default()
+    .collect();
+
+  match flags.split_first() {
+    None => quote!(wgpu::Features::empty()),
+    Some((first, rest)) => quote!(#first #(.union(#rest))*),
+  }
 }
 
 #[cfg(test)]
@@ -210,6 +647,111 @@ mod tests {
     assert_eq!(wgpu::ShaderStages::all(), shader_stages(&module));
   }
 
+  #[test]
+  fn vertex_input_structs_multiple_entries() {
+    let source = indoc! {r#"
+            struct VertexInput {
+                @location(0) position: vec4<f32>,
+            };
+
+            struct InstanceInput {
+                @location(1) offset: vec4<f32>,
+            };
+
+            @vertex
+            fn vs_main(in: VertexInput) -> @builtin(position) vec4<f32> {
+                return in.position;
+            }
+
+            @vertex
+            fn vs_instanced(in: VertexInput, instance: InstanceInput) -> @builtin(position) vec4<f32> {
+                return in.position + instance.offset;
+            }
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    let names: Vec<_> = get_vertex_input_structs("", &module)
+      .unwrap()
+      .into_iter()
+      .map(|input| input.name)
+      .collect();
+    assert_eq!(vec!["VertexInput", "InstanceInput"], names);
+
+    let names: Vec<_> =
+      get_entry_vertex_input_structs("", &module, &module.entry_points[0])
+        .unwrap()
+        .into_iter()
+        .map(|input| input.name)
+        .collect();
+    assert_eq!(vec!["VertexInput"], names);
+  }
+
+  #[test]
+  fn check_vertex_inputs_of_all_entries() {
+    let source = indoc! {r#"
+            struct VertexInput {
+                @location(0) position: vec4<f32>,
+            };
+
+            struct FlagInput {
+                @location(1) flag: bool,
+            };
+
+            @vertex
+            fn vs_main(in: VertexInput) -> @builtin(position) vec4<f32> {
+                return in.position;
+            }
+
+            @vertex
+            fn vs_flag(in: VertexInput, flag: FlagInput) -> @builtin(position) vec4<f32> {
+                return in.position;
+            }
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    assert_eq!(
+      Err(CreateModuleError::UnsupportedVertexFormat {
+        module: "shader".to_string(),
+        input: "shader::FlagInput".to_string(),
+        field: "flag".to_string(),
+        type_name: "bool".to_string(),
+      }),
+      check_vertex_inputs("shader", &module, &WgslBindgenOption::default())
+    );
+  }
+
+  #[test]
+  fn check_vertex_inputs_name_conflict() {
+    let source = indoc! {r#"
+            struct VsMainInput {
+                @location(0) position: vec4<f32>,
+            };
+
+            @vertex
+            fn vs_main(@location(0) position: vec4<f32>) -> @builtin(position) vec4<f32> {
+                return position;
+            }
+
+            @vertex
+            fn vs_other(in: VsMainInput) -> @builtin(position) vec4<f32> {
+                return in.position;
+            }
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    assert_eq!(
+      Err(CreateModuleError::VertexInputNameConflict {
+        module: "shader".to_string(),
+        entry: "vs_main".to_string(),
+        name: "VsMainInput".to_string(),
+      }),
+      check_vertex_inputs("shader", &module, &WgslBindgenOption::default())
+    );
+  }
+
   #[test]
   fn vertex_input_structs_two_structs() {
     let source = indoc! {r#"
@@ -240,9 +782,9 @@ mod tests {
 
     let module = naga::front::wgsl::parse_str(source).unwrap();
 
-    let vertex_inputs = get_vertex_input_structs(&module);
-    // Only structures should be included.
-    assert_eq!(2, vertex_inputs.len());
+    let vertex_inputs = get_vertex_input_structs("", &module).unwrap();
+    // Loose location arguments are collected into a synthesized struct.
+    assert_eq!(3, vertex_inputs.len());
 
     assert_eq!("VertexInput0", vertex_inputs[0].name);
     assert_eq!(3, vertex_inputs[0].fields.len());
@@ -253,5 +795,155 @@ mod tests {
     assert_eq!(4, vertex_inputs[1].fields.len());
     assert_eq!("in5", vertex_inputs[1].fields[2].1.name.as_ref().unwrap());
     assert_eq!(5, vertex_inputs[1].fields[2].0);
+
+    assert_eq!("MainInput", vertex_inputs[2].name);
+    assert_eq!(1, vertex_inputs[2].fields.len());
+    assert_eq!("in3", vertex_inputs[2].fields[0].1.name.as_ref().unwrap());
+    assert_eq!(7, vertex_inputs[2].fields[0].0);
+  }
+
+  #[test]
+  fn fragment_output_locations_struct() {
+    let source = indoc! {r#"
+            struct FragmentOutput {
+                @location(0) color: vec4<f32>,
+                @builtin(frag_depth) depth: f32,
+                @location(2) normal: vec4<f32>,
+            };
+
+            @fragment
+            fn fs_main() -> FragmentOutput {
+                var out: FragmentOutput;
+                return out;
+            }
+
+            @fragment
+            fn fs_single() -> @location(0) vec4<f32> {
+                return vec4<f32>(0.0);
+            }
+
+            @fragment
+            fn fs_none() {}
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    let locations: Vec<_> = module
+      .entry_points
+      .iter()
+      .map(|entry| fragment_output_locations(&module, entry))
+      .collect();
+    assert_eq!(vec![vec![0, 2], vec![0], vec![]], locations);
+  }
+
+  #[test]
+  fn dual_source_blending_features() {
+    let source = indoc! {r#"
+            struct FragmentOutput {
+                @location(0) color: vec4<f32>,
+                @location(0) @second_blend_source blend: vec4<f32>,
+            };
+
+            @fragment
+            fn fs_dual() -> FragmentOutput {
+                var out: FragmentOutput;
+                return out;
+            }
+
+            @fragment
+            fn fs_single() -> @location(0) vec4<f32> {
+                return vec4<f32>(0.0);
+            }
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+
+    assert!(uses_dual_source_blending(&module, &module.entry_points[0]));
+    assert!(!uses_dual_source_blending(&module, &module.entry_points[1]));
+    assert_eq!(vec![0], fragment_output_locations(&module, &module.entry_points[0]));
+    assert_eq!(wgpu::Features::DUAL_SOURCE_BLENDING, required_features(&module));
+  }
+
+  #[test]
+  fn required_features_from_types_and_builtins() {
+    let source = indoc! {r#"
+            struct Constants {
+                scale: f32,
+            };
+
+            var<push_constant> constants: Constants;
+            @group(0) @binding(0) var image: texture_storage_2d<r32float, read_write>;
+
+            @fragment
+            fn fs_main(@builtin(primitive_index) primitive: u32) -> @location(0) vec4<f32> {
+                let value = textureLoad(image, vec2(0));
+                return value * constants.scale * f32(primitive);
+            }
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    assert_eq!(
+      wgpu::Features::PUSH_CONSTANTS
+        | wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+        | wgpu::Features::SHADER_PRIMITIVE_INDEX,
+      required_features(&module)
+    );
+  }
+
+  #[test]
+  fn features_tokens_of_wgpu_edition() {
+    let features = wgpu::Features::RAY_QUERY | wgpu::Features::PUSH_CONSTANTS;
+
+    assert_eq!(
+      quote!(wgpu::Features::PUSH_CONSTANTS.union(wgpu::Features::RAY_QUERY)).to_string(),
+      features_tokens(features, WgpuVersion::V23).to_string()
+    );
+    assert_eq!(
+      quote!(wgpu::Features::PUSH_CONSTANTS.union(wgpu::Features::EXPERIMENTAL_RAY_QUERY))
+        .to_string(),
+      features_tokens(features, WgpuVersion::V24).to_string()
+    );
+  }
+
+  #[test]
+  fn binding_types_to_wgsl() {
+    let source = indoc! {r#"
+            struct Light {
+                color: vec4<f32>,
+            };
+
+            @group(0) @binding(0) var<uniform> light: Light;
+            @group(0) @binding(1) var<storage, read_write> values: array<vec2<u32>>;
+            @group(0) @binding(2) var<storage> lights: array<Light, 4>;
+            @group(0) @binding(3) var shadow_maps: texture_depth_2d_array;
+            @group(0) @binding(4) var output: texture_storage_2d<rgba8unorm, write>;
+            @group(0) @binding(5) var shadow_sampler: sampler_comparison;
+        "#};
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let declarations: Vec<_> = module
+      .global_variables
+      .iter()
+      .map(|(_, global)| {
+        format!(
+          "var{} {}: {}",
+          address_space_to_wgsl(global.space),
+          global.name.as_ref().unwrap(),
+          type_to_wgsl(&module, &module.types[global.ty])
+        )
+      })
+      .collect();
+
+    assert_eq!(
+      vec![
+        "var<uniform> light: Light",
+        "var<storage, read_write> values: array<vec2<u32>>",
+        "var<storage, read> lights: array<Light, 4>",
+        "var shadow_maps: texture_depth_2d_array",
+        "var output: texture_storage_2d<rgba8unorm, write>",
+        "var shadow_sampler: sampler_comparison",
+      ],
+      declarations
+    );
   }
 }
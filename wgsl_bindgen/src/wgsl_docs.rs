@@ -0,0 +1,440 @@
+//! Collects the `///` doc comments and `// wgsl_bindgen:` annotations of WGSL items, since
+//! naga doesn't keep comments. The doc comments are matched to the generated Rust items by their
+//! demangled names, the annotations by their full item path.
+
+use std::collections::HashMap;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use regex::Regex;
+
+use crate::quote_gen::RustItemPath;
+
+/// The codegen settings of a binding or a struct, written as a comment above its declaration
+/// like `// wgsl_bindgen: filterable=false, dynamic_offset, name=camera, group=Frame` or
+/// `// wgsl_bindgen: step_mode=instance`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct WgslAnnotation {
+  /// Whether a float texture can be sampled with a filtering sampler, `true` when unset.
+  pub filterable: Option<bool>,
+  /// Whether a buffer is bound with a dynamic offset.
+  pub dynamic_offset: bool,
+  /// The name of the binding in the generated Rust code.
+  pub name: Option<String>,
+  /// The name of the binding's bind group in the generated Rust code, eg: `WgpuBindGroupFrame`.
+  pub group_name: Option<String>,
+  /// The step mode of a vertex input struct, instead of a parameter of the `_entry` fns.
+  pub step_mode: Option<wgpu::VertexStepMode>,
+}
+
+impl WgslAnnotation {
+  /// Parses the comma separated `key` or `key=value` settings.
+  fn parse(settings: &str) -> Result<Self, String> {
+    let mut annotation = Self::default();
+    for setting in settings
+      .split(',')
+      .map(str::trim)
+      .filter(|it| !it.is_empty())
+    {
+      let (key, value) = match setting.split_once('=') {
+        Some((key, value)) => (key.trim(), Some(value.trim())),
+        None => (setting, None),
+      };
+      match key {
+        "filterable" => annotation.filterable = Some(parse_flag(key, value)?),
+        "dynamic_offset" => annotation.dynamic_offset = parse_flag(key, value)?,
+        "name" => annotation.name = Some(parse_ident(key, value)?),
+        "group" => annotation.group_name = Some(parse_ident(key, value)?),
+        "step_mode" => {
+          annotation.step_mode = Some(match value {
+            Some("vertex") => wgpu::VertexStepMode::Vertex,
+            Some("instance") => wgpu::VertexStepMode::Instance,
+            _ => return Err("`step_mode` must be `vertex` or `instance`".to_owned()),
+          })
+        }
+        _ => return Err(format!("unknown key `{key}`")),
+      }
+    }
+    Ok(annotation)
+  }
+
+  /// Checks that only the settings of structs are used.
+  fn check_struct(&self) -> Result<(), String> {
+    let binding_settings = Self {
+      step_mode: None,
+      ..self.clone()
+    };
+    if binding_settings != Self::default() {
+      return Err("only `step_mode` can be used on structs".to_owned());
+    }
+    Ok(())
+  }
+
+  /// Checks that only the settings of bindings are used.
+  fn check_binding(&self) -> Result<(), String> {
+    if self.step_mode.is_some() {
+      return Err("`step_mode` can only be used on structs".to_owned());
+    }
+    Ok(())
+  }
+}
+
+/// The value of a `key` or `key=true|false` setting.
+fn parse_flag(key: &str, value: Option<&str>) -> Result<bool, String> {
+  match value {
+    None | Some("true") => Ok(true),
+    Some("false") => Ok(false),
+    Some(_) => Err(format!("`{key}` must be `true` or `false`")),
+  }
+}
+
+/// The value of a `key=value` setting used in the generated names, which must be a valid Rust
+/// identifier other than a keyword.
+fn parse_ident(key: &str, value: Option<&str>) -> Result<String, String> {
+  match value {
+    Some(value)
+      if syn::parse_str::<syn::Ident>(value).is_ok() && !value.starts_with("r#") =>
+    {
+      Ok(value.to_owned())
+    }
+    Some(value) => Err(format!("`{value}` of `{key}` isn't a valid Rust identifier")),
+    None => Err(format!("`{key}` needs a value")),
+  }
+}
+
+const MISPLACED_ANNOTATION: &str =
+  "annotations can only be written above bindings and structs";
+
+/// A `// wgsl_bindgen:` annotation which couldn't be parsed, or which isn't above a binding or a
+/// struct it can be used on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidAnnotation {
+  pub annotation: String,
+  pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct WgslDocs {
+  items: HashMap<String, Vec<String>>,
+  fields: HashMap<(String, String), Vec<String>>,
+  annotations: HashMap<RustItemPath, WgslAnnotation>,
+}
+
+impl WgslDocs {
+  /// Collects the docs of all the given sources of the entry module. Items documented in an
+  /// earlier source take precedence.
+  pub fn from_sources<'a>(
+    sources: impl IntoIterator<Item = &'a str>,
+  ) -> Result<Self, InvalidAnnotation> {
+    let mut docs = Self::default();
+    for source in sources {
+      docs.add_source("", source)?;
+    }
+    Ok(docs)
+  }
+
+  /// Collects the docs of a source, whose items have the demangled `module_path`, or `""` for the
+  /// entry module.
+  pub fn add_source(
+    &mut self,
+    module_path: &str,
+    source: &str,
+  ) -> Result<(), InvalidAnnotation> {
+    let attributes = r"(?:@\w+(?:\([^)]*\))?\s*)*";
+    let attribute_line = Regex::new(r"^(?:@\w+(?:\([^)]*\))?\s*)+$").unwrap();
+    let struct_decl = Regex::new(r"^struct\s+(\w+)").unwrap();
+    let item_decl = Regex::new(&format!(
+      r"^{attributes}(const|override|fn|alias|var)(?:<[^>]*>)?\s+(\w+)"
+    ))
+    .unwrap();
+    let field_decl = Regex::new(&format!(r"^{attributes}(\w+)\s*:")).unwrap();
+    let item_path = |name: &str| RustItemPath::new(module_path.into(), name.into());
+
+    let mut pending_docs: Vec<String> = Vec::new();
+    let mut pending_annotation: Option<(&str, WgslAnnotation)> = None;
+    let mut current_struct: Option<String> = None;
+
+    for line in source.lines().map(str::trim) {
+      if let Some(doc) = line.strip_prefix("///") {
+        pending_docs.push(doc.to_owned());
+        continue;
+      }
+      if let Some(settings) = line
+        .strip_prefix("//")
+        .and_then(|comment| comment.trim_start().strip_prefix("wgsl_bindgen:"))
+      {
+        let settings = settings.trim();
+        let annotation =
+          WgslAnnotation::parse(settings).map_err(|message| InvalidAnnotation {
+            annotation: settings.to_owned(),
+            message,
+          })?;
+        pending_annotation = Some((settings, annotation));
+        continue;
+      }
+      // Attributes on their own line belong to the next declaration.
+      if line.is_empty() || attribute_line.is_match(line) {
+        continue;
+      }
+
+      let docs = std::mem::take(&mut pending_docs);
+      // The annotated item and the check of the settings which can be used on it.
+      let mut annotated: Option<(String, fn(&WgslAnnotation) -> Result<(), String>)> =
+        None;
+      if let Some(struct_name) = &current_struct {
+        if let Some(captures) = field_decl.captures(line) {
+          if !docs.is_empty() {
+            let key = (struct_name.clone(), captures[1].to_owned());
+            self.fields.entry(key).or_insert(docs);
+          }
+        }
+        if line.contains('}') {
+          current_struct = None;
+        }
+      } else if let Some(captures) = struct_decl.captures(line) {
+        let struct_name = captures[1].to_owned();
+        if !docs.is_empty() {
+          self.items.entry(struct_name.clone()).or_insert(docs);
+        }
+        if !line.contains('}') {
+          current_struct = Some(struct_name.clone());
+        }
+        annotated = Some((struct_name, WgslAnnotation::check_struct));
+      } else if let Some(captures) = item_decl.captures(line) {
+        let item_name = captures[2].to_owned();
+        if !docs.is_empty() {
+          self.items.entry(item_name.clone()).or_insert(docs);
+        }
+        if &captures[1] == "var" {
+          annotated = Some((item_name, WgslAnnotation::check_binding));
+        }
+      }
+
+      if let Some((settings, annotation)) = pending_annotation.take() {
+        let invalid = |message| InvalidAnnotation {
+          annotation: settings.to_owned(),
+          message,
+        };
+        let Some((name, check)) = annotated else {
+          return Err(invalid(MISPLACED_ANNOTATION.to_owned()));
+        };
+        check(&annotation).map_err(invalid)?;
+        self.annotations.insert(item_path(&name), annotation);
+      }
+    }
+
+    match pending_annotation {
+      Some((settings, _)) => Err(InvalidAnnotation {
+        annotation: settings.to_owned(),
+        message: MISPLACED_ANNOTATION.to_owned(),
+      }),
+      None => Ok(()),
+    }
+  }
+
+  /// The `#[doc]` attributes of a struct, constant, function or global variable.
+  pub fn item_doc_attrs(&self, name: &str) -> TokenStream {
+    doc_attrs(self.items.get(name))
+  }
+
+  /// The `// wgsl_bindgen:` annotation of a binding or a struct.
+  pub fn item_annotation(&self, path: &RustItemPath) -> Option<&WgslAnnotation> {
+    self.annotations.get(path)
+  }
+
+  /// The `#[doc]` attributes of a struct member.
+  pub fn field_doc_attrs(&self, struct_name: &str, field_name: &str) -> TokenStream {
+    doc_attrs(
+      self
+        .fields
+        .get(&(struct_name.to_owned(), field_name.to_owned())),
+    )
+  }
+}
+
+fn doc_attrs(docs: Option<&Vec<String>>) -> TokenStream {
+  let docs = docs.into_iter().flatten();
+  quote!(#(#[doc = #docs])*)
+}
+
+#[cfg(test)]
+mod tests {
+  use indoc::indoc;
+
+  use super::*;
+
+  #[test]
+  fn collects_item_and_field_docs() {
+    let source = indoc! {r#"
+        /// Camera data.
+        /// Updated once per frame.
+        struct Camera {
+            /// The view projection matrix.
+            view_proj: mat4x4<f32>,
+            // Not a doc comment.
+            position: vec4<f32>,
+            /// Near plane.
+            @size(16) near: f32,
+        };
+
+        /// The number of lights.
+        const LIGHT_COUNT: u32 = 4u;
+
+        @group(0) @binding(0)
+        /// The camera uniform.
+        var<uniform> camera: Camera;
+
+        /// The vertex entry.
+        @vertex
+        fn vs_main() {}
+    "#};
+
+    let docs = WgslDocs::from_sources([source]).unwrap();
+
+    assert_eq!(
+      quote!(#[doc = " Camera data."] #[doc = " Updated once per frame."]).to_string(),
+      docs.item_doc_attrs("Camera").to_string()
+    );
+    assert_eq!(
+      quote!(#[doc = " The view projection matrix."]).to_string(),
+      docs.field_doc_attrs("Camera", "view_proj").to_string()
+    );
+    assert!(docs.field_doc_attrs("Camera", "position").is_empty());
+    assert_eq!(
+      quote!(#[doc = " Near plane."]).to_string(),
+      docs.field_doc_attrs("Camera", "near").to_string()
+    );
+    assert_eq!(
+      quote!(#[doc = " The number of lights."]).to_string(),
+      docs.item_doc_attrs("LIGHT_COUNT").to_string()
+    );
+    assert_eq!(
+      quote!(#[doc = " The camera uniform."]).to_string(),
+      docs.item_doc_attrs("camera").to_string()
+    );
+    assert_eq!(
+      quote!(#[doc = " The vertex entry."]).to_string(),
+      docs.item_doc_attrs("vs_main").to_string()
+    );
+  }
+
+  #[test]
+  fn collects_binding_annotations() {
+    let source = indoc! {r#"
+        // wgsl_bindgen: dynamic_offset, name=camera
+        /// The camera uniform.
+        @group(0) @binding(0) var<uniform> camera_uniform: Camera;
+
+        // wgsl_bindgen: filterable=false
+        @group(0) @binding(1)
+        var depth_values: texture_2d<f32>;
+
+        // A regular comment.
+        @group(0) @binding(2) var color_sampler: sampler;
+    "#};
+
+    let docs = WgslDocs::from_sources([source]).unwrap();
+    let path = |name: &str| RustItemPath::new("".into(), name.into());
+
+    assert_eq!(
+      Some(&WgslAnnotation {
+        filterable: None,
+        dynamic_offset: true,
+        name: Some("camera".to_owned()),
+        group_name: None,
+        step_mode: None,
+      }),
+      docs.item_annotation(&path("camera_uniform"))
+    );
+    assert_eq!(
+      quote!(#[doc = " The camera uniform."]).to_string(),
+      docs.item_doc_attrs("camera_uniform").to_string()
+    );
+    assert_eq!(
+      Some(&WgslAnnotation {
+        filterable: Some(false),
+        ..Default::default()
+      }),
+      docs.item_annotation(&path("depth_values"))
+    );
+    assert_eq!(None, docs.item_annotation(&path("color_sampler")));
+  }
+
+  #[test]
+  fn collects_struct_annotations_by_module_path() {
+    let source = indoc! {r#"
+        // wgsl_bindgen: step_mode=instance
+        struct InstanceInput {
+            @location(0) position: vec3<f32>,
+        };
+
+        // wgsl_bindgen: name=lights
+        @group(1) @binding(0) var<storage> light_buffer: array<vec4<f32>>;
+    "#};
+
+    let mut docs = WgslDocs::default();
+    docs.add_source("types", source).unwrap();
+
+    assert_eq!(
+      Some(&WgslAnnotation {
+        step_mode: Some(wgpu::VertexStepMode::Instance),
+        ..Default::default()
+      }),
+      docs.item_annotation(&RustItemPath::new("types".into(), "InstanceInput".into()))
+    );
+    assert_eq!(
+      Some("lights"),
+      docs
+        .item_annotation(&RustItemPath::new("types".into(), "light_buffer".into()))
+        .and_then(|annotation| annotation.name.as_deref())
+    );
+    // Items of other modules with the same name aren't annotated.
+    assert_eq!(
+      None,
+      docs.item_annotation(&RustItemPath::new("".into(), "light_buffer".into()))
+    );
+  }
+
+  #[test]
+  fn rejects_invalid_annotations() {
+    let error = |annotation: &str, declaration: &str| {
+      let source = format!("// wgsl_bindgen: {annotation}\n{declaration}");
+      WgslDocs::from_sources([source.as_str()]).unwrap_err()
+    };
+    let binding = "@group(0) @binding(0) var<uniform> camera: Camera;";
+
+    assert_eq!(
+      InvalidAnnotation {
+        annotation: "name=type".to_owned(),
+        message: "`type` of `name` isn't a valid Rust identifier".to_owned(),
+      },
+      error("name=type", binding)
+    );
+    assert_eq!(
+      "`my camera` of `name` isn't a valid Rust identifier",
+      error("name=my camera", binding).message
+    );
+    assert_eq!(
+      "`2d` of `group` isn't a valid Rust identifier",
+      error("group=2d", binding).message
+    );
+    assert_eq!("`name` needs a value", error("name", binding).message);
+    assert_eq!("unknown key `filtrable`", error("filtrable", binding).message);
+    assert_eq!(
+      "`dynamic_offset` must be `true` or `false`",
+      error("dynamic_offset=yes", binding).message
+    );
+    assert_eq!(
+      "`step_mode` can only be used on structs",
+      error("step_mode=instance", binding).message
+    );
+    assert_eq!(
+      "only `step_mode` can be used on structs",
+      error("dynamic_offset", "struct Camera { position: vec4<f32> };").message
+    );
+    assert_eq!(
+      "annotations can only be written above bindings and structs",
+      error("name=count", "const COUNT: u32 = 4u;").message
+    );
+  }
+}
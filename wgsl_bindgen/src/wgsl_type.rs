@@ -48,6 +48,31 @@ pub enum WgslMatType {
   Mat4x4h,
 }
 
+/// The `WgslScalarType` enum represents the WGSL scalar types.
+/// See [spec](https://www.w3.org/TR/WGSL/#scalar-types)
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, EnumIter)]
+pub enum WgslScalarType {
+  Bool,
+  I32,
+  U32,
+  F32,
+  F64,
+}
+
+impl WgslScalarType {
+  pub(crate) fn from_naga(scalar: &naga::Scalar) -> Option<Self> {
+    use naga::ScalarKind;
+    match (scalar.kind, scalar.width) {
+      (ScalarKind::Bool, _) => Some(Self::Bool),
+      (ScalarKind::Sint, 4) => Some(Self::I32),
+      (ScalarKind::Uint, 4) => Some(Self::U32),
+      (ScalarKind::Float, 4) => Some(Self::F32),
+      (ScalarKind::Float, 8) => Some(Self::F64),
+      _ => None,
+    }
+  }
+}
+
 pub(crate) trait WgslTypeAlignmentAndSize {
   fn alignment_and_size(&self) -> (u8, usize);
 }
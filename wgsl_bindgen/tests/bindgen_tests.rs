@@ -53,6 +53,174 @@ fn test_main_bindgen() -> Result<()> {
   Ok(())
 }
 
+#[test]
+fn test_source_files_include_imports() -> Result<()> {
+  let bindgen = WgslBindgenOptionBuilder::default()
+    .add_entry_point("tests/shaders/basic/main.wgsl")
+    .workspace_root("tests/shaders/additional")
+    .additional_scan_dir((None, "tests/shaders/additional"))
+    .serialization_strategy(WgslTypeSerializeStrategy::Bytemuck)
+    .type_map(GlamWgslTypeMap)
+    .emit_rerun_if_change(false)
+    .build()?;
+
+  let files = bindgen.source_files();
+  assert_eq!(3, files.len());
+  assert!(files.iter().any(|file| file.ends_with("main.wgsl")));
+  assert!(files.iter().any(|file| file.ends_with("bindings.wgsl")));
+  assert!(files.iter().any(|file| file.ends_with("types.wgsl")));
+  Ok(())
+}
+
+#[test]
+fn test_in_memory_entry_point() -> Result<()> {
+  let bindgen = WgslBindgenOptionBuilder::default()
+    .add_entry_point_source(
+      "generated.wgsl",
+      "#import types::{Fp64};\n\n@group(0) @binding(0) var<uniform> value: types::Fp64;\n\n@compute @workgroup_size(1)\nfn main() {}\n",
+    )
+    .workspace_root("tests/shaders/additional")
+    .serialization_strategy(WgslTypeSerializeStrategy::Bytemuck)
+    .type_map(GlamWgslTypeMap)
+    .emit_rerun_if_change(false)
+    .build()?;
+
+  assert_eq!(vec!["tests/shaders/additional/types.wgsl"], bindgen.source_files());
+
+  let output = bindgen.generate_string().into_diagnostic()?;
+  assert!(output.contains("pub mod generated {"));
+  Ok(())
+}
+
+#[test]
+fn test_compose_error_points_to_source() -> Result<()> {
+  let source = "@compute @workgroup_size(1)\nfn main() {\n  let x = ;\n}\n";
+  let bindgen = WgslBindgenOptionBuilder::default()
+    .add_entry_point_source("broken.wgsl", source)
+    .workspace_root("tests/shaders/additional")
+    .serialization_strategy(WgslTypeSerializeStrategy::Bytemuck)
+    .type_map(GlamWgslTypeMap)
+    .emit_rerun_if_change(false)
+    .build()?;
+
+  let Err(WgslBindgenError::NagaModuleComposeError { labels, .. }) =
+    bindgen.generate_string()
+  else {
+    panic!("expected a compose error");
+  };
+  assert!(!labels.is_empty());
+  assert!(labels
+    .iter()
+    .all(|label| label.offset() + label.len() <= source.len()));
+  Ok(())
+}
+
+#[test]
+fn test_validation_capabilities() -> Result<()> {
+  let source = "@group(0) @binding(0) var cubes: texture_cube_array<f32>;\n@group(0) @binding(1) var cube_sampler: sampler;\n\n@fragment\nfn main() -> @location(0) vec4<f32> {\n  return textureSample(cubes, cube_sampler, vec3(1.0), 0);\n}\n";
+  let bindgen = WgslBindgenOptionBuilder::default()
+    .add_entry_point_source("cubes.wgsl", source)
+    .workspace_root("tests/shaders/additional")
+    .serialization_strategy(WgslTypeSerializeStrategy::Bytemuck)
+    .type_map(GlamWgslTypeMap)
+    .validation_capabilities(WgslShaderIRCapabilities::empty())
+    .emit_rerun_if_change(false)
+    .build()?;
+
+  assert!(matches!(
+    bindgen.generate_string(),
+    Err(WgslBindgenError::ShaderValidationError { .. })
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_checked_shader_modules_require_wgpu_0_20() {
+  let result = WgslBindgenOptionBuilder::default()
+    .add_entry_point_spirv("tests/shaders/spirv/compute.spv")
+    .workspace_root("tests/shaders/spirv")
+    .serialization_strategy(WgslTypeSerializeStrategy::Bytemuck)
+    .type_map(GlamWgslTypeMap)
+    .checked_shader_modules(true)
+    .emit_rerun_if_change(false)
+    .build();
+
+  assert!(matches!(
+    result,
+    Err(WgslBindgenError::UnsupportedWgpuVersion {
+      option: "checked_shader_modules",
+      required: WgpuVersion::V0_20,
+    })
+  ));
+}
+
+#[test]
+fn test_naga_ir_requires_wgpu_0_19() {
+  let result = WgslBindgenOptionBuilder::default()
+    .add_entry_point_spirv("tests/shaders/spirv/compute.spv")
+    .workspace_root("tests/shaders/spirv")
+    .serialization_strategy(WgslTypeSerializeStrategy::Bytemuck)
+    .type_map(GlamWgslTypeMap)
+    .shader_source_type(WgslShaderSourceType::NagaIr)
+    .wgpu_edition(WgpuVersion::V22)
+    .emit_rerun_if_change(false)
+    .build();
+
+  assert!(matches!(result, Err(WgslBindgenError::NagaIrWgpuVersion)));
+}
+
+#[test]
+fn test_reuse_modules_of() -> Result<()> {
+  let bindgen = || {
+    WgslBindgenOptionBuilder::default()
+      .add_entry_point("tests/shaders/include/main.wgsl")
+      .workspace_root("tests/shaders/include")
+      .textual_includes(true)
+      .serialization_strategy(WgslTypeSerializeStrategy::Bytemuck)
+      .type_map(GlamWgslTypeMap)
+      .emit_rerun_if_change(false)
+      .build()
+  };
+  let previous = bindgen()?;
+  let expected = previous.generate_string().into_diagnostic()?;
+
+  let mut next = bindgen()?;
+  next.reuse_modules_of(previous);
+
+  assert_eq!(expected, next.generate_string().into_diagnostic()?);
+  Ok(())
+}
+
+#[test]
+fn test_spirv_entry_point() -> Result<()> {
+  let bindgen = WgslBindgenOptionBuilder::default()
+    .add_entry_point_spirv("tests/shaders/spirv/compute.spv")
+    .workspace_root("tests/shaders/spirv")
+    .serialization_strategy(WgslTypeSerializeStrategy::Bytemuck)
+    .type_map(GlamWgslTypeMap)
+    .emit_rerun_if_change(false)
+    .build()?;
+
+  assert_eq!(vec!["tests/shaders/spirv/compute.spv"], bindgen.source_files());
+
+  let reflection = bindgen.reflect().into_diagnostic()?;
+  let entry = &reflection.entries[0];
+  assert_eq!("compute", entry.mod_name);
+  assert_eq!("main", entry.entry_points[0].name);
+  assert_eq!(
+    "data",
+    entry.bind_groups[0].bindings[0]
+      .name
+      .rsplit("::")
+      .next()
+      .unwrap()
+  );
+
+  let output = bindgen.generate_string().into_diagnostic()?;
+  assert!(output.contains("pub mod compute {"));
+  Ok(())
+}
+
 #[test]
 fn test_struct_alignment_minimal() -> Result<()> {
   WgslBindgenOptionBuilder::default()
@@ -75,6 +243,73 @@ fn test_struct_alignment_minimal() -> Result<()> {
   Ok(())
 }
 
+#[test]
+fn test_cache_dir() -> Result<()> {
+  let cache_dir = std::env::temp_dir().join("wgsl_bindgen_test_cache");
+  let _ = std::fs::remove_dir_all(&cache_dir);
+
+  let bindgen = |entry_points: &[&str]| {
+    WgslBindgenOptionBuilder::default()
+      .entry_points(
+        entry_points
+          .iter()
+          .map(|it| it.to_string())
+          .collect::<Vec<_>>(),
+      )
+      .workspace_root("tests/shaders")
+      .serialization_strategy(WgslTypeSerializeStrategy::Bytemuck)
+      .type_map(GlamWgslTypeMap)
+      .emit_rerun_if_change(false)
+      .skip_header_comments(true)
+      .cache_dir(&cache_dir)
+      .build()
+  };
+  let cache_entries = || std::fs::read_dir(&cache_dir).unwrap().count();
+
+  let entry_points = ["tests/shaders/minimal.wgsl", "tests/shaders/padding.wgsl"];
+  let uncached = bindgen(&entry_points)?
+    .generate_string()
+    .into_diagnostic()?;
+  let cached = bindgen(&entry_points)?
+    .generate_string()
+    .into_diagnostic()?;
+  assert_eq!(uncached, cached);
+  assert_eq!(2, cache_entries());
+
+  // The module of the removed entry point is pruned.
+  bindgen(&entry_points[..1])?
+    .generate_string()
+    .into_diagnostic()?;
+  assert_eq!(1, cache_entries());
+  Ok(())
+}
+
+#[test]
+fn test_output_independent_of_entry_point_order() -> Result<()> {
+  let generate = |entry_points: [&str; 2]| -> Result<String> {
+    WgslBindgenOptionBuilder::default()
+      .entry_points(entry_points.map(String::from).to_vec())
+      .workspace_root("tests/shaders")
+      .serialization_strategy(WgslTypeSerializeStrategy::Bytemuck)
+      .type_map(GlamWgslTypeMap)
+      .emit_rerun_if_change(false)
+      .skip_header_comments(true)
+      .build()?
+      .generate_string()
+      .into_diagnostic()
+  };
+
+  let minimal_first =
+    generate(["tests/shaders/minimal.wgsl", "tests/shaders/padding.wgsl"])?;
+  let padding_first =
+    generate(["tests/shaders/padding.wgsl", "tests/shaders/minimal.wgsl"])?;
+  assert_eq!(minimal_first, padding_first);
+  assert!(
+    minimal_first.find("Minimal").unwrap() < minimal_first.find("Padding").unwrap()
+  );
+  Ok(())
+}
+
 #[test]
 fn test_struct_alignment_padding() -> Result<()> {
   WgslBindgenOptionBuilder::default()
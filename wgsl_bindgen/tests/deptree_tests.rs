@@ -58,6 +58,9 @@ fn build_bevy_deptree() -> DependencyTree {
       SourceFilePath::new("tests/shaders/bevy_pbr_wgsl/wireframe.wgsl"),
     ],
     vec![],
+    false,
+    Default::default(),
+    Default::default(),
   )
   .into_diagnostic()
   .expect("build_bevy_deptree error")
@@ -127,6 +130,9 @@ fn test_example_wgsl_dep_tree() {
       "../example/assets/shader/utils/testbed.wgsl",
     )],
     vec![],
+    false,
+    Default::default(),
+    Default::default(),
   )
   .unwrap();
 
@@ -164,3 +170,73 @@ fn test_bevy_mesh_wgsl_dependency_order() {
     ]
   );
 }
+
+#[test]
+fn test_textual_includes() {
+  let deptree = DependencyTree::try_build(
+    "tests/shaders/include".into(),
+    None,
+    vec![SourceFilePath::new("tests/shaders/include/main.wgsl")],
+    vec![],
+    true,
+    Default::default(),
+    Default::default(),
+  )
+  .unwrap();
+
+  assert_eq!(
+    deptree.all_files_including_dependencies(),
+    indexset![
+      SourceFilePath::new("tests/shaders/include/main.wgsl"),
+      SourceFilePath::new("tests/shaders/include/constants.wgsl"),
+      SourceFilePath::new("tests/shaders/include/common.wgsl"),
+    ]
+  );
+
+  let content = &deptree.parsed_files()[0].content;
+  assert!(content.starts_with("const TINT: vec3<f32> = vec3(1.0, 0.5, 0.25);\n"));
+  assert!(content.contains("fn tint() -> vec4<f32> {"));
+  assert!(!content.contains("#include"));
+}
+
+#[test]
+fn test_textual_include_cycle() {
+  let result = DependencyTree::try_build(
+    "tests/shaders/include".into(),
+    None,
+    vec![SourceFilePath::new("tests/shaders/include/cycle_a.wgsl")],
+    vec![],
+    true,
+    Default::default(),
+    Default::default(),
+  );
+
+  assert!(matches!(
+    result,
+    Err(wgsl_bindgen::bevy_util::DependencyTreeError::IncludeCycle { .. })
+  ));
+}
+
+#[test]
+fn test_import_aliases() {
+  let deptree = DependencyTree::try_build(
+    "tests/shaders/alias".into(),
+    None,
+    vec![SourceFilePath::new("tests/shaders/alias/main.wgsl")],
+    vec![],
+    false,
+    [("vendor::colors".to_string(), "tests/shaders/alias/lib/palette.wgsl".into())]
+      .into_iter()
+      .collect(),
+    Default::default(),
+  )
+  .unwrap();
+
+  assert_eq!(
+    deptree.all_files_including_dependencies(),
+    indexset![
+      SourceFilePath::new("tests/shaders/alias/main.wgsl"),
+      SourceFilePath::new("tests/shaders/alias/lib/palette.wgsl"),
+    ]
+  );
+}
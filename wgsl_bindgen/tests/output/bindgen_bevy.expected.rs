@@ -701,14 +701,23 @@ pub mod pbr {
     pub mod bind_groups {
         #[derive(Debug)]
         pub struct WgpuBindGroupLayout0<'a> {
+            /// `@group(0) @binding(0) var<uniform> view: View`
             pub view: wgpu::BufferBinding<'a>,
+            /// `@group(0) @binding(1) var<uniform> lights: Lights`
             pub lights: wgpu::BufferBinding<'a>,
+            /// `@group(0) @binding(6) var<storage, read> point_lights: PointLights`
             pub point_lights: wgpu::BufferBinding<'a>,
+            /// `@group(0) @binding(7) var<storage, read> cluster_light_index_lists: ClusterLightIndexLists`
             pub cluster_light_index_lists: wgpu::BufferBinding<'a>,
+            /// `@group(0) @binding(8) var<storage, read> cluster_offsets_and_counts: ClusterOffsetsAndCounts`
             pub cluster_offsets_and_counts: wgpu::BufferBinding<'a>,
+            /// `@group(0) @binding(2) var point_shadow_textures: texture_depth_cube_array`
             pub point_shadow_textures: &'a wgpu::TextureView,
+            /// `@group(0) @binding(3) var point_shadow_textures_sampler: sampler_comparison`
             pub point_shadow_textures_sampler: &'a wgpu::Sampler,
+            /// `@group(0) @binding(4) var directional_shadow_textures: texture_depth_2d_array`
             pub directional_shadow_textures: &'a wgpu::TextureView,
+            /// `@group(0) @binding(5) var directional_shadow_textures_sampler: sampler_comparison`
             pub directional_shadow_textures_sampler: &'a wgpu::Sampler,
         }
         impl<'a> WgpuBindGroupLayout0<'a> {
@@ -892,6 +901,7 @@ pub mod pbr {
         }
         #[derive(Debug)]
         pub struct WgpuBindGroupLayout1<'a> {
+            /// `@group(1) @binding(0) var<uniform> material: StandardMaterial`
             pub material: wgpu::BufferBinding<'a>,
         }
         impl<'a> WgpuBindGroupLayout1<'a> {
@@ -949,6 +959,7 @@ pub mod pbr {
         }
         #[derive(Debug)]
         pub struct WgpuBindGroupLayout2<'a> {
+            /// `@group(2) @binding(0) var<uniform> mesh: Mesh`
             pub mesh: wgpu::BufferBinding<'a>,
         }
         impl<'a> WgpuBindGroupLayout2<'a> {
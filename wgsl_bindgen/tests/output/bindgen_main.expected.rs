@@ -101,6 +101,7 @@ pub mod main {
     pub mod bind_groups {
         #[derive(Debug)]
         pub struct WgpuBindGroupLayout0<'a> {
+            /// `@group(0) @binding(0) var<storage, read_write> buffer: array<f32>`
             pub buffer: wgpu::BufferBinding<'a>,
         }
         impl<'a> WgpuBindGroupLayout0<'a> {
@@ -160,6 +161,7 @@ pub mod main {
         }
         #[derive(Debug)]
         pub struct WgpuBindGroupLayout1<'a> {
+            /// `@group(1) @binding(11) var<uniform> ONE: f32`
             pub ONE: wgpu::BufferBinding<'a>,
         }
         impl<'a> WgpuBindGroupLayout1<'a> {
@@ -82,6 +82,7 @@ pub mod minimal {
     pub mod bind_groups {
         #[derive(Debug)]
         pub struct WgpuBindGroupLayout0<'a> {
+            /// `@group(0) @binding(0) var<uniform> uniform_buf: Uniforms`
             pub uniform_buf: wgpu::BufferBinding<'a>,
         }
         impl<'a> WgpuBindGroupLayout0<'a> {
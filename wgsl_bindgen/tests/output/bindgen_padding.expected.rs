@@ -85,6 +85,7 @@ pub mod padding {
     pub mod bind_groups {
         #[derive(Debug)]
         pub struct WgpuBindGroupLayout0<'a> {
+            /// `@group(0) @binding(0) var<storage, read> frame: Style`
             pub frame: wgpu::BufferBinding<'a>,
         }
         impl<'a> WgpuBindGroupLayout0<'a> {
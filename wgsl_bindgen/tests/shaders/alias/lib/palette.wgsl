@@ -0,0 +1,3 @@
+#define_import_path vendor::colors
+
+const RED: vec4<f32> = vec4(1.0, 0.0, 0.0, 1.0);
@@ -0,0 +1,6 @@
+#import vendor::colors::{RED}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return RED;
+}
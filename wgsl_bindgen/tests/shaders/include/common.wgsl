@@ -0,0 +1,5 @@
+#include "constants.wgsl"
+
+fn tint() -> vec4<f32> {
+    return vec4(TINT, 1.0);
+}
@@ -0,0 +1 @@
+const TINT: vec3<f32> = vec3(1.0, 0.5, 0.25);
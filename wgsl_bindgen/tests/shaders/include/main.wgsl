@@ -0,0 +1,6 @@
+#include "common.wgsl"
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return tint();
+}
@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use wgsl_bindgen::{
+  GlamWgslTypeMap, NalgebraWgslTypeMap, RustWgslTypeMap, WgslBindgenOptionBuilder,
+  WgslTypeSerializeStrategy,
+};
+
+/// The contents of a `wgsl_bindgen.toml`, mirroring [WgslBindgenOptionBuilder].
+/// Paths are relative to the directory of the configuration file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+  pub workspace_root: PathBuf,
+  #[serde(default)]
+  pub entry_points: Vec<PathBuf>,
+  /// Directories whose `.wgsl` files with a vertex, fragment or compute entry point are added.
+  #[serde(default)]
+  pub entry_point_dirs: Vec<PathBuf>,
+  pub output: PathBuf,
+  #[serde(default)]
+  pub serialization_strategy: SerializationStrategy,
+  #[serde(default)]
+  pub type_map: TypeMap,
+  #[serde(default)]
+  pub derive_serde: bool,
+  #[serde(default)]
+  pub skip_hash_check: bool,
+  #[serde(default)]
+  pub split_output: bool,
+  pub short_constructor: Option<i32>,
+  /// Maps fully qualified WGSL structs, eg: `shader::Camera`, to existing Rust types.
+  #[serde(default)]
+  pub override_struct_paths: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SerializationStrategy {
+  #[default]
+  Encase,
+  Bytemuck,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeMap {
+  #[default]
+  Rust,
+  Glam,
+  Nalgebra,
+}
+
+impl Config {
+  pub fn parse(content: &str) -> Result<Self, toml::de::Error> {
+    toml::from_str(content)
+  }
+
+  /// Creates the builder for this configuration, resolving the paths relative to `base_dir`.
+  pub fn builder(&self, base_dir: &Path) -> WgslBindgenOptionBuilder {
+    let mut builder = WgslBindgenOptionBuilder::default();
+    builder
+      .workspace_root(base_dir.join(&self.workspace_root))
+      .output(base_dir.join(&self.output))
+      .derive_serde(self.derive_serde)
+      .skip_hash_check(self.skip_hash_check)
+      .split_output(self.split_output);
+
+    for entry_point in &self.entry_points {
+      builder.add_entry_point(base_dir.join(entry_point).to_string_lossy());
+    }
+
+    for dir in &self.entry_point_dirs {
+      builder.add_entry_point_dir(base_dir.join(dir));
+    }
+
+    if let Some(short_constructor) = self.short_constructor {
+      builder.short_constructor(short_constructor);
+    }
+
+    for (from, to) in &self.override_struct_paths {
+      builder.override_struct_path(from, to);
+    }
+
+    builder.serialization_strategy(match self.serialization_strategy {
+      SerializationStrategy::Encase => WgslTypeSerializeStrategy::Encase,
+      SerializationStrategy::Bytemuck => WgslTypeSerializeStrategy::Bytemuck,
+    });
+
+    match self.type_map {
+      TypeMap::Rust => builder.type_map(RustWgslTypeMap),
+      TypeMap::Glam => builder.type_map(GlamWgslTypeMap),
+      TypeMap::Nalgebra => builder.type_map(NalgebraWgslTypeMap),
+    };
+
+    builder
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use indoc::indoc;
+
+  use super::*;
+
+  #[test]
+  fn parse_config() {
+    let config = Config::parse(indoc! {r#"
+        workspace_root = "shaders"
+        entry_points = ["shaders/triangle.wgsl"]
+        output = "src/shaders.rs"
+        serialization_strategy = "bytemuck"
+        type_map = "glam"
+        short_constructor = 2
+
+        [override_struct_paths]
+        "triangle::Camera" = "crate::Camera"
+    "#})
+    .unwrap();
+
+    assert_eq!(PathBuf::from("shaders"), config.workspace_root);
+    assert_eq!(vec![PathBuf::from("shaders/triangle.wgsl")], config.entry_points);
+    assert!(matches!(config.serialization_strategy, SerializationStrategy::Bytemuck));
+    assert!(matches!(config.type_map, TypeMap::Glam));
+    assert_eq!(Some(2), config.short_constructor);
+    assert_eq!(
+      Some(&"crate::Camera".to_string()),
+      config.override_struct_paths.get("triangle::Camera")
+    );
+    assert!(!config.derive_serde);
+    assert!(config.entry_point_dirs.is_empty());
+  }
+
+  #[test]
+  fn parse_config_rejects_unknown_fields() {
+    let config = Config::parse(indoc! {r#"
+        workspace_root = "shaders"
+        entry_points = []
+        output = "src/shaders.rs"
+        unknown = true
+    "#});
+
+    assert!(config.is_err());
+  }
+}
@@ -0,0 +1,44 @@
+//! Runs `wgsl_bindgen` outside of a build script, configured with a `wgsl_bindgen.toml`.
+//! This is useful when the build is orchestrated by other tools like bazel, just or make.
+
+mod config;
+
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use miette::{Context, IntoDiagnostic, Result};
+
+use crate::config::Config;
+
+#[derive(Debug, Parser)]
+#[command(name = "wgsl-bindgen", version, about)]
+struct Args {
+  /// The configuration file. Paths in it are relative to its directory.
+  #[arg(short, long, default_value = "wgsl_bindgen.toml")]
+  config: PathBuf,
+
+  /// Keeps running and regenerates the output whenever the shaders change.
+  #[arg(short, long)]
+  watch: bool,
+}
+
+fn main() -> Result<()> {
+  let args = Args::parse();
+
+  let content = std::fs::read_to_string(&args.config)
+    .into_diagnostic()
+    .wrap_err_with(|| format!("Failed to read `{}`", args.config.display()))?;
+  let config = Config::parse(&content)
+    .into_diagnostic()
+    .wrap_err_with(|| format!("Invalid configuration `{}`", args.config.display()))?;
+
+  let base_dir = args.config.parent().unwrap_or(Path::new("."));
+  let bindgen = config.builder(base_dir).build()?;
+  if args.watch {
+    bindgen.watch()?;
+  } else {
+    bindgen.generate()?;
+  }
+
+  Ok(())
+}
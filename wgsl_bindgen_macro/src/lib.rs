@@ -0,0 +1,241 @@
+//! A function-like procedural macro frontend for [wgsl_bindgen](https://docs.rs/wgsl_bindgen),
+//! for small projects which don't need a build script.
+//!
+//! ```ignore
+//! wgsl_bindgen_macro::wgsl_bindgen!("shaders/triangle.wgsl", serialization = bytemuck, type_map = glam);
+//! ```
+//!
+//! The paths are relative to the directory of the crate's `Cargo.toml`.
+//! The options are:
+//! - `serialization = encase | bytemuck`
+//! - `type_map = rust | glam | nalgebra`
+//! - `workspace_root = "shaders"`, which defaults to the directory of the first entry point.
+//! - `derive_serde = true | false`
+//!
+//! The entry points and the shaders they import are tracked for recompilation.
+
+use std::path::{Path, PathBuf};
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, Ident, LitBool, LitStr, Token};
+use wgsl_bindgen::{
+  GlamWgslTypeMap, NalgebraWgslTypeMap, RustWgslTypeMap, WgslBindgenOptionBuilder,
+  WgslTypeSerializeStrategy,
+};
+
+/// Generates the bindings of the given WGSL entry points in place.
+/// See the [crate] documentation for the supported options.
+#[proc_macro]
+pub fn wgsl_bindgen(input: TokenStream) -> TokenStream {
+  let args = parse_macro_input!(input as MacroArgs);
+  match expand(args) {
+    Ok(tokens) => tokens.into(),
+    Err(err) => err.to_compile_error().into(),
+  }
+}
+
+struct MacroArgs {
+  entry_points: Vec<LitStr>,
+  options: Vec<MacroOption>,
+}
+
+enum MacroOption {
+  Serialization(Ident),
+  TypeMap(Ident),
+  WorkspaceRoot(LitStr),
+  DeriveSerde(LitBool),
+}
+
+impl Parse for MacroArgs {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let mut entry_points = Vec::new();
+    while input.peek(LitStr) {
+      entry_points.push(input.parse()?);
+      if input.is_empty() {
+        break;
+      }
+      input.parse::<Token![,]>()?;
+    }
+
+    if entry_points.is_empty() {
+      return Err(input.error("expected at least one WGSL entry point path"));
+    }
+
+    let options = Punctuated::<MacroOption, Token![,]>::parse_terminated(input)?;
+    Ok(Self {
+      entry_points,
+      options: options.into_iter().collect(),
+    })
+  }
+}
+
+impl Parse for MacroOption {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let key: Ident = input.parse()?;
+    input.parse::<Token![=]>()?;
+    match key.to_string().as_str() {
+      "serialization" => Ok(Self::Serialization(input.parse()?)),
+      "type_map" => Ok(Self::TypeMap(input.parse()?)),
+      "workspace_root" => Ok(Self::WorkspaceRoot(input.parse()?)),
+      "derive_serde" => Ok(Self::DeriveSerde(input.parse()?)),
+      _ => Err(syn::Error::new(key.span(), format!("unknown option `{key}`"))),
+    }
+  }
+}
+
+fn expand(args: MacroArgs) -> syn::Result<proc_macro2::TokenStream> {
+  let manifest_dir =
+    PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").map_err(|_| {
+      syn::Error::new(Span::call_site(), "CARGO_MANIFEST_DIR is not set")
+    })?);
+  let entry_paths = args
+    .entry_points
+    .iter()
+    .map(|entry| manifest_dir.join(entry.value()))
+    .collect::<Vec<_>>();
+
+  let Some(first_entry_path) = entry_paths.first() else {
+    return Err(syn::Error::new(
+      Span::call_site(),
+      "expected at least one WGSL entry point path",
+    ));
+  };
+
+  let mut builder = WgslBindgenOptionBuilder::default();
+  let workspace_root = first_entry_path.parent().unwrap_or(manifest_dir.as_path());
+  builder.workspace_root(workspace_root);
+  // There's no build script to rerun, the files are tracked with `include_bytes!` instead.
+  builder.emit_rerun_if_change(false);
+  for entry_path in &entry_paths {
+    builder.add_entry_point(path_str(entry_path));
+  }
+
+  // The type map depends on the serialization strategy, so it's applied last.
+  let mut serialization_strategy = WgslTypeSerializeStrategy::default();
+  let mut type_map = None;
+  for option in args.options {
+    match option {
+      MacroOption::Serialization(ident) => {
+        serialization_strategy = match ident.to_string().as_str() {
+          "encase" => WgslTypeSerializeStrategy::Encase,
+          "bytemuck" => WgslTypeSerializeStrategy::Bytemuck,
+          _ => {
+            return Err(syn::Error::new(ident.span(), "expected `encase` or `bytemuck`"))
+          }
+        }
+      }
+      MacroOption::TypeMap(ident) => match ident.to_string().as_str() {
+        "rust" | "glam" | "nalgebra" => type_map = Some(ident),
+        _ => {
+          return Err(syn::Error::new(
+            ident.span(),
+            "expected `rust`, `glam` or `nalgebra`",
+          ))
+        }
+      },
+      MacroOption::WorkspaceRoot(root) => {
+        builder.workspace_root(manifest_dir.join(root.value()));
+      }
+      MacroOption::DeriveSerde(value) => {
+        builder.derive_serde(value.value());
+      }
+    }
+  }
+
+  builder.serialization_strategy(serialization_strategy);
+  match type_map.map(|ident| ident.to_string()).as_deref() {
+    Some("glam") => builder.type_map(GlamWgslTypeMap),
+    Some("nalgebra") => builder.type_map(NalgebraWgslTypeMap),
+    _ => builder.type_map(RustWgslTypeMap),
+  };
+
+  let to_syn_error = |err: wgsl_bindgen::WgslBindgenError| {
+    syn::Error::new(Span::call_site(), err.to_string())
+  };
+  let bindgen = builder.build().map_err(to_syn_error)?;
+  let output = bindgen.generate_string().map_err(to_syn_error)?;
+
+  // Inner attributes aren't allowed in a macro expansion, so they are applied to each item instead.
+  let mut file = syn::parse_file(&output)?;
+  let attrs = std::mem::take(&mut file.attrs).into_iter().map(|mut attr| {
+    attr.style = syn::AttrStyle::Outer;
+    attr
+  });
+  let attrs = quote!(#(#attrs)*);
+  let items = file.items;
+
+  // Makes the compiler rerun the macro when one of the entry points or their imports changes.
+  let tracked_paths = bindgen.source_files();
+
+  Ok(quote! {
+    #(const _: &[u8] = include_bytes!(#tracked_paths);)*
+    #(#attrs #items)*
+  })
+}
+
+fn path_str(path: &Path) -> String {
+  path.to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn included_files(tokens: proc_macro2::TokenStream) -> Vec<String> {
+    let file: syn::File = syn::parse2(tokens).unwrap();
+    let mut paths: Vec<_> = file
+      .items
+      .iter()
+      .filter_map(|item| match item {
+        syn::Item::Const(item) => match &*item.expr {
+          syn::Expr::Macro(expr) if expr.mac.path.is_ident("include_bytes") => {
+            Some(expr.mac.parse_body::<LitStr>().unwrap().value())
+          }
+          _ => None,
+        },
+        _ => None,
+      })
+      .collect();
+    paths.sort();
+    paths
+  }
+
+  #[test]
+  fn expand_tracks_imported_files() {
+    let args: MacroArgs =
+      syn::parse_quote!("tests/shaders/main.wgsl", serialization = bytemuck);
+    let tokens = expand(args).unwrap();
+
+    let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    let shaders_dir = manifest_dir.join("tests/shaders");
+    let mut expected: Vec<_> = ["constants.wgsl", "main.wgsl"]
+      .into_iter()
+      .map(|file| path_str(&shaders_dir.join(file)))
+      .collect();
+    expected.sort();
+    assert_eq!(expected, included_files(tokens));
+  }
+
+  #[test]
+  fn expand_without_entry_points() {
+    let args = MacroArgs {
+      entry_points: Vec::new(),
+      options: Vec::new(),
+    };
+
+    let err = expand(args).unwrap_err();
+    assert_eq!("expected at least one WGSL entry point path", err.to_string());
+  }
+
+  #[test]
+  fn parse_unknown_option() {
+    let err = syn::parse2::<MacroArgs>(quote!("shader.wgsl", colour = red))
+      .err()
+      .unwrap();
+    assert_eq!("unknown option `colour`", err.to_string());
+  }
+}
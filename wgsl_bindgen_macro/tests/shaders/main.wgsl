@@ -0,0 +1,6 @@
+#import constants;
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4(constants::TINT, 1.0);
+}